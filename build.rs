@@ -3,9 +3,27 @@
 // Build script for scx_horoscope
 // Uses scx_rustland_core for simpler BPF integration
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
-    scx_rustland_core::RustLandBuilder::new()
-        .unwrap()
-        .build()
-        .unwrap();
+    // Diagnostic reports name the build date; SOURCE_DATE_EPOCH keeps builds reproducible
+    let epoch = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()));
+    println!("cargo:rustc-env=SCX_HOROSCOPE_BUILD_EPOCH={epoch}");
+
+    // Without the scheduler there is no BPF to build
+    #[cfg(feature = "bpf")]
+    {
+        scx_rustland_core::RustLandBuilder::new()
+            .unwrap()
+            .build()
+            .unwrap();
+
+        // Our own BPF object for the syscall hooks, kept apart from the rustland backend
+        let mut hooks = scx_utils::BpfBuilder::new().unwrap();
+        hooks.enable_skel("src/bpf/hooks.bpf.c", "hooks");
+        hooks.build().unwrap();
+    }
 }
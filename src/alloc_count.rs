@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Allocation counting
+//
+// The dispatch loop runs for every task the kernel queues, and a trip to the
+// heap there costs more than consulting the stars does. This wraps the system
+// allocator of the test binary, counting allocations per thread, and checks
+// that once the chart is cast and the tasks have been met, dispatching them
+// again doesn't allocate at all.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use clap::Parser;
+
+use crate::bpf::DispatchedTask;
+use crate::golden::{golden_queue, golden_scheduler, golden_time, Queued};
+use crate::retry::Dispatcher;
+use crate::{Kernel, Opts};
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The system allocator, counting what each thread asks of it
+struct CountingAllocator;
+
+impl CountingAllocator {
+    fn count() {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    }
+}
+
+// SAFETY: every call goes straight to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made on this thread so far
+fn allocations() -> u64 {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Takes every task and keeps nothing
+struct NullKernel;
+
+impl Dispatcher for NullKernel {
+    type Error = ();
+
+    fn dispatch(&mut self, _task: &DispatchedTask) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Kernel for NullKernel {
+    fn select_cpu(&mut self, _pid: i32, prev_cpu: i32, _flags: u64) -> i32 {
+        prev_cpu
+    }
+}
+
+#[test]
+fn test_dispatch_does_not_allocate() {
+    let opts = Opts::parse_from(["scx_horoscope"]);
+    let mut sched = golden_scheduler(NullKernel, &opts);
+    let queue: Vec<_> = golden_queue().iter().map(Queued::queued_task).collect();
+    let now = golden_time();
+
+    // Each task through decide and dispatch_decided; batching is left out, as
+    // draining a batch has to allocate it
+    let mut dispatch_all = || {
+        for task in &queue {
+            if let Some((task, comm, decision)) = sched.decide(task.clone(), now) {
+                sched.dispatch_decided(&task, comm.as_str(), &decision, false);
+            }
+        }
+    };
+
+    // The first round casts the chart and meets every PID
+    dispatch_all();
+    let before = allocations();
+    for _ in 0..3 {
+        dispatch_all();
+    }
+    assert_eq!(allocations() - before, 0, "dispatching {} known tasks allocated", queue.len());
+}
@@ -0,0 +1,421 @@
+use super::planets::{Planet, PlanetaryPosition};
+
+/// Angular relationships between two planets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectKind {
+    Conjunction,  // 0°
+    Sextile,      // 60°
+    Square,       // 90°
+    Trine,        // 120°
+    Opposition,   // 180°
+}
+
+impl AspectKind {
+    pub fn all() -> Vec<AspectKind> {
+        ASPECT_KINDS.to_vec()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AspectKind::Conjunction => "conjunct",
+            AspectKind::Sextile => "sextile",
+            AspectKind::Square => "square",
+            AspectKind::Trine => "trine",
+            AspectKind::Opposition => "opposite",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            AspectKind::Conjunction => "☌",
+            AspectKind::Sextile => "⚹",
+            AspectKind::Square => "□",
+            AspectKind::Trine => "△",
+            AspectKind::Opposition => "☍",
+        }
+    }
+
+    /// Exact separation of the aspect in degrees
+    pub fn angle(self) -> f64 {
+        match self {
+            AspectKind::Conjunction => 0.0,
+            AspectKind::Sextile => 60.0,
+            AspectKind::Square => 90.0,
+            AspectKind::Trine => 120.0,
+            AspectKind::Opposition => 180.0,
+        }
+    }
+
+    /// How far from exact the aspect still counts, in degrees
+    pub fn orb(self) -> f64 {
+        match self {
+            AspectKind::Conjunction | AspectKind::Trine | AspectKind::Opposition => 8.0,
+            AspectKind::Square => 7.0,
+            AspectKind::Sextile => 6.0,
+        }
+    }
+
+    /// Priority bonus of an exact aspect (harmonious positive, tense negative)
+    pub fn modifier(self) -> f64 {
+        match self {
+            AspectKind::Trine => 0.15,
+            AspectKind::Sextile => 0.10,
+            AspectKind::Conjunction => 0.05,
+            AspectKind::Square => -0.10,
+            AspectKind::Opposition => -0.15,
+        }
+    }
+}
+
+/// An aspect the ruling planet makes to another planet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aspect {
+    pub kind: AspectKind,
+    pub other: Planet,
+    pub strength: f64,  // 1.0 when exact, fading to 0.0 at the orb limit
+}
+
+/// Bounds on the combined aspect modifier
+const MIN_ASPECT_MODIFIER: f64 = 0.5;
+const MAX_ASPECT_MODIFIER: f64 = 1.5;
+
+/// Shortest angle between two ecliptic longitudes, 0-180°
+pub fn angular_separation(a: f64, b: f64) -> f64 {
+    let delta = (a - b).rem_euclid(360.0);
+    delta.min(360.0 - delta)
+}
+
+/// How exact an aspect is: 1.0 at 0° orb, falling linearly to 0.0 at the orb limit
+pub fn aspect_strength(exact_angle: f64, actual: f64, orb: f64) -> f64 {
+    if orb <= 0.0 {
+        return 0.0;
+    }
+    let deviation = (actual - exact_angle).abs();
+    (1.0 - deviation / orb).clamp(0.0, 1.0)
+}
+
+/// Every kind of aspect
+const ASPECT_KINDS: [AspectKind; 5] =
+    [AspectKind::Conjunction, AspectKind::Sextile, AspectKind::Square, AspectKind::Trine, AspectKind::Opposition];
+
+/// The in-orb aspects between `ruler` and the other planets, found as they are asked for
+pub fn aspects_to<'p>(ruler: &'p PlanetaryPosition, positions: &'p [PlanetaryPosition]) -> impl Iterator<Item = Aspect> + 'p {
+    positions.iter().filter(|pos| pos.planet != ruler.planet).flat_map(move |other| {
+        let separation = angular_separation(ruler.longitude, other.longitude);
+        ASPECT_KINDS.into_iter().filter_map(move |kind| {
+            let strength = aspect_strength(kind.angle(), separation, kind.orb());
+            (strength > 0.0).then_some(Aspect { kind, other: other.planet, strength })
+        })
+    })
+}
+
+/// All in-orb aspects between `ruler` and the other planets
+pub fn find_aspects(ruler: &PlanetaryPosition, positions: &[PlanetaryPosition]) -> Vec<Aspect> {
+    aspects_to(ruler, positions).collect()
+}
+
+/// Combined priority multiplier from a set of aspects, each scaled by its strength
+pub fn aspect_modifier(aspects: impl IntoIterator<Item = Aspect>) -> f64 {
+    let bonus: f64 = aspects.into_iter().map(|aspect| aspect.kind.modifier() * aspect.strength).sum();
+    (1.0 + bonus).clamp(MIN_ASPECT_MODIFIER, MAX_ASPECT_MODIFIER)
+}
+
+/// Whether the Moon is void of course: it will make no further aspect before leaving its sign
+///
+/// The other planets are taken as standing still, which over the two and a
+/// half days the Moon spends in a sign is close enough: it outruns them all.
+pub fn moon_void_of_course(positions: &[PlanetaryPosition]) -> bool {
+    let Some(moon) = positions.iter().find(|pos| pos.planet == Planet::Moon) else {
+        return false;
+    };
+    let left_in_sign = 30.0 - moon.degree_in_sign;
+    !positions.iter().filter(|pos| pos.planet != Planet::Moon).any(|other| {
+        AspectKind::all().into_iter().any(|kind| {
+            // The aspect perfects with the Moon on either side of the other planet
+            [other.longitude + kind.angle(), other.longitude - kind.angle()]
+                .into_iter()
+                .any(|exact| (exact - moon.longitude).rem_euclid(360.0) < left_in_sign)
+        })
+    })
+}
+
+/// Orb used by `AspectCalculator` until asked for another, in degrees
+pub const DEFAULT_ASPECT_ORB: f64 = 8.0;
+
+/// An aspect between two planets of a chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanetaryAspect {
+    pub first: Planet,
+    pub second: Planet,
+    pub kind: AspectKind,
+    pub orb: f64,       // Degrees from exact
+    pub strength: f64,  // 1.0 when exact, fading to 0.0 at the orb limit
+}
+
+impl PlanetaryAspect {
+    pub fn involves(&self, planet: Planet) -> bool {
+        self.first == planet || self.second == planet
+    }
+}
+
+/// How tense a chart is, 0 to 100: the share of its aspect strength in squares and oppositions
+///
+/// A chart without any aspect in orb is taken as calm.
+pub fn tension_score(positions: &[PlanetaryPosition]) -> u32 {
+    let (hard, total) = chart_aspects(positions, DEFAULT_ASPECT_ORB).iter().fold((0.0, 0.0), |(hard, total), aspect| {
+        match aspect.kind {
+            AspectKind::Square | AspectKind::Opposition => (hard + aspect.strength, total + aspect.strength),
+            _ => (hard, total + aspect.strength),
+        }
+    });
+    if total <= 0.0 {
+        return 0;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let score = (100.0 * hard / total).round() as u32;
+    score
+}
+
+/// Cheap fingerprint of a chart: the longitudes summed bit for bit
+fn positions_hash(positions: &[PlanetaryPosition]) -> u64 {
+    positions.iter().fold(0, |sum: u64, pos| sum.wrapping_add(pos.longitude.to_bits()))
+}
+
+/// Every aspect between every pair of planets within `orb` degrees
+fn chart_aspects(positions: &[PlanetaryPosition], orb: f64) -> Vec<PlanetaryAspect> {
+    let mut aspects = Vec::new();
+    for (i, a) in positions.iter().enumerate() {
+        for b in &positions[i + 1..] {
+            let separation = angular_separation(a.longitude, b.longitude);
+            for kind in AspectKind::all() {
+                let strength = aspect_strength(kind.angle(), separation, orb);
+                if strength > 0.0 {
+                    let orb = (separation - kind.angle()).abs();
+                    aspects.push(PlanetaryAspect { first: a.planet, second: b.planet, kind, orb, strength });
+                }
+            }
+        }
+    }
+    aspects
+}
+
+/// The aspects of a whole chart, worked out once and reused until the chart moves
+///
+/// Unlike `find_aspects`, which looks at one ruler with each aspect's own
+/// orb, every pair of planets is considered with the same orb.
+pub struct AspectCalculator {
+    positions: Vec<PlanetaryPosition>,
+    aspects: Option<Vec<PlanetaryAspect>>,
+    last_positions_hash: u64,
+    orb: f64,  // Orb the cached aspects were found with
+}
+
+impl AspectCalculator {
+    pub fn new(positions: Vec<PlanetaryPosition>) -> Self {
+        let last_positions_hash = positions_hash(&positions);
+        Self { positions, aspects: None, last_positions_hash, orb: DEFAULT_ASPECT_ORB }
+    }
+
+    /// Swap in a new chart, keeping the cached aspects if no longitude changed
+    pub fn set_positions(&mut self, positions: Vec<PlanetaryPosition>) {
+        let hash = positions_hash(&positions);
+        if hash != self.last_positions_hash {
+            self.aspects = None;
+            self.last_positions_hash = hash;
+        }
+        self.positions = positions;
+    }
+
+    /// All aspects within `orb` degrees, computed on first use
+    pub fn get_aspects(&mut self, orb: f64) -> &[PlanetaryAspect] {
+        if orb.to_bits() != self.orb.to_bits() {
+            self.aspects = None;
+            self.orb = orb;
+        }
+        let positions = &self.positions;
+        self.aspects.get_or_insert_with(|| chart_aspects(positions, orb))
+    }
+
+    /// Aspects `planet` makes, with the orb last asked for
+    pub fn get_aspects_involving(&mut self, planet: Planet) -> Vec<&PlanetaryAspect> {
+        let orb = self.orb;
+        self.get_aspects(orb).iter().filter(|aspect| aspect.involves(planet)).collect()
+    }
+
+    /// The tightest aspect `planet` makes, if any
+    pub fn get_strongest_aspect(&mut self, planet: Planet) -> Option<&PlanetaryAspect> {
+        let orb = self.orb;
+        self.get_aspects(orb)
+            .iter()
+            .filter(|aspect| aspect.involves(planet))
+            .min_by(|a, b| a.orb.total_cmp(&b.orb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::planets::ZodiacSign;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_aspect_strength_falloff() {
+        assert!((aspect_strength(120.0, 120.0, 8.0) - 1.0).abs() < 1e-9);
+        assert!((aspect_strength(120.0, 124.0, 8.0) - 0.5).abs() < 1e-9);
+        assert!((aspect_strength(120.0, 116.0, 8.0) - 0.5).abs() < 1e-9);
+        assert!(aspect_strength(120.0, 128.0, 8.0).abs() < 1e-9);
+        assert!(aspect_strength(120.0, 127.999, 8.0) < 0.001);
+        assert!(aspect_strength(120.0, 140.0, 8.0).abs() < 1e-9);
+        assert!(aspect_strength(120.0, 120.0, 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_moon_void_of_course() {
+        // Moon at 25° Aries: a Sun at 28° Aries will still be met by conjunction
+        let sun = position(Planet::Sun, 28.0);
+        assert!(!moon_void_of_course(&[position(Planet::Moon, 25.0), sun.clone()]));
+        // Past it, and nothing else ahead before Taurus
+        assert!(moon_void_of_course(&[position(Planet::Moon, 28.5), sun.clone()]));
+        // Saturn at 29.5° Capricorn is squared at 29.5° Aries, just in time
+        let saturn = position(Planet::Saturn, 299.5);
+        assert!(!moon_void_of_course(&[position(Planet::Moon, 28.5), sun, saturn]));
+        // No Moon in the chart, nothing to be void
+        assert!(!moon_void_of_course(&[position(Planet::Mars, 10.0)]));
+    }
+
+    #[test]
+    fn test_angular_separation_wraps() {
+        assert!((angular_separation(350.0, 10.0) - 20.0).abs() < 1e-9);
+        assert!((angular_separation(10.0, 350.0) - 20.0).abs() < 1e-9);
+        assert!((angular_separation(0.0, 180.0) - 180.0).abs() < 1e-9);
+        assert!((angular_separation(90.0, 330.0) - 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_find_aspects_scales_by_orb() {
+        let mars = position(Planet::Mars, 10.0);
+        let positions = vec![
+            mars.clone(),
+            position(Planet::Jupiter, 130.0),  // Exact trine
+            position(Planet::Saturn, 284.0),   // Square, 4° of a 7° orb
+            position(Planet::Venus, 45.0),     // Nothing
+        ];
+
+        let aspects = find_aspects(&mars, &positions);
+        assert_eq!(aspects.len(), 2);
+        assert_eq!(aspects[0].kind, AspectKind::Trine);
+        assert_eq!(aspects[0].other, Planet::Jupiter);
+        assert!((aspects[0].strength - 1.0).abs() < 1e-9);
+        assert_eq!(aspects[1].kind, AspectKind::Square);
+        assert!((aspects[1].strength - 3.0 / 7.0).abs() < 1e-9);
+
+        let expected = 1.0 + 0.15 - 0.10 * 3.0 / 7.0;
+        assert!((aspect_modifier(aspects.iter().copied()) - expected).abs() < 1e-9);
+        assert!((aspect_modifier([]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tension_score() {
+        // Sun square Mars, Mars opposite Saturn, Sun square Saturn: all hard
+        let tense = [position(Planet::Sun, 0.0), position(Planet::Mars, 90.0), position(Planet::Saturn, 270.0)];
+        assert_eq!(tension_score(&tense), 100);
+        // Trines all round
+        let easy = [position(Planet::Sun, 0.0), position(Planet::Mars, 120.0), position(Planet::Saturn, 240.0)];
+        assert_eq!(tension_score(&easy), 0);
+        // One exact square, one exact trine
+        let mixed = [position(Planet::Sun, 0.0), position(Planet::Mars, 90.0), position(Planet::Saturn, 210.0)];
+        assert_eq!(tension_score(&mixed), 50);
+        assert_eq!(tension_score(&[position(Planet::Sun, 0.0), position(Planet::Mars, 45.0)]), 0);
+    }
+
+    #[test]
+    fn test_aspect_calculator() {
+        let mut calculator = AspectCalculator::new(vec![
+            position(Planet::Mars, 10.0),
+            position(Planet::Jupiter, 130.0),  // Exact trine to Mars
+            position(Planet::Saturn, 284.0),   // Square to Mars, 4° off
+            position(Planet::Sun, 290.0),      // Conjunct Saturn, 6° off
+            position(Planet::Venus, 330.0),    // Nothing
+        ]);
+
+        let aspects = calculator.get_aspects(DEFAULT_ASPECT_ORB).to_vec();
+        assert_eq!(aspects.len(), 3);
+        assert_eq!(
+            (aspects[0].first, aspects[0].second, aspects[0].kind),
+            (Planet::Mars, Planet::Jupiter, AspectKind::Trine)
+        );
+
+        let mars = calculator.get_aspects_involving(Planet::Mars);
+        assert_eq!(mars.len(), 2);
+        assert!(mars.iter().all(|aspect| aspect.involves(Planet::Mars)));
+        assert!(calculator.get_aspects_involving(Planet::Venus).is_empty());
+        assert!(calculator.get_strongest_aspect(Planet::Venus).is_none());
+
+        // Saturn's tightest is the square to Mars, not the wider conjunction with the Sun
+        let strongest = *calculator.get_strongest_aspect(Planet::Saturn).unwrap();
+        assert_eq!((strongest.first, strongest.kind), (Planet::Mars, AspectKind::Square));
+        assert!((strongest.orb - 4.0).abs() < 1e-9);
+
+        // A tighter orb drops the looser aspects
+        assert_eq!(calculator.get_aspects(5.0).len(), 2);
+        assert_eq!(calculator.get_aspects_involving(Planet::Jupiter).len(), 1);
+    }
+
+    #[test]
+    fn test_aspect_cache_follows_positions() {
+        let chart = |saturn: f64| vec![position(Planet::Mars, 10.0), position(Planet::Saturn, saturn)];
+        let mut calculator = AspectCalculator::new(chart(100.0));
+        let first = calculator.get_aspects(DEFAULT_ASPECT_ORB).as_ptr();
+        assert_eq!(calculator.get_aspects(DEFAULT_ASPECT_ORB).as_ptr(), first);
+
+        // The same chart again keeps the cache; a moved planet drops it
+        calculator.set_positions(chart(100.0));
+        assert_eq!(calculator.get_aspects(DEFAULT_ASPECT_ORB).as_ptr(), first);
+        assert_eq!(calculator.get_aspects(DEFAULT_ASPECT_ORB)[0].kind, AspectKind::Square);
+        calculator.set_positions(chart(190.0));
+        assert_eq!(calculator.get_aspects(DEFAULT_ASPECT_ORB)[0].kind, AspectKind::Opposition);
+    }
+
+    /// Run with `cargo test --release bench_ -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_cached_aspects_involving() {
+        use chrono::{TimeZone, Utc};
+        use std::time::Instant;
+
+        const ROUNDS: u32 = 100_000;
+        let positions = crate::astrology::calculate_planetary_positions(Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap()).unwrap();
+
+        let start = Instant::now();
+        let mut uncached = 0;
+        for _ in 0..ROUNDS {
+            uncached += AspectCalculator::new(positions.clone()).get_aspects_involving(Planet::Mars).len();
+        }
+        let uncached_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut calculator = AspectCalculator::new(positions.clone());
+        let mut cached = 0;
+        for _ in 0..ROUNDS {
+            cached += calculator.get_aspects_involving(Planet::Mars).len();
+        }
+        let cached_time = start.elapsed();
+
+        assert_eq!(cached, uncached);
+        println!(
+            "get_aspects_involving x{ROUNDS}: uncached {uncached_time:?}, cached {cached_time:?} ({:.0}x faster)",
+            uncached_time.as_secs_f64() / cached_time.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+
+use super::aspects::{AspectCalculator, DEFAULT_ASPECT_ORB};
+use super::ephemeris::Ephemeris;
+use super::error::AstroError;
+use super::planets::{julian_day, normalize_longitude, MoonPhase, Planet, PlanetaryPosition, ZodiacSign};
+
+/// Where the planets stood when a process started
+#[derive(Debug, Clone)]
+pub struct NatalChart {
+    pub positions: Vec<PlanetaryPosition>,
+}
+
+impl NatalChart {
+    pub fn new(positions: Vec<PlanetaryPosition>) -> Self {
+        Self { positions }
+    }
+
+    /// Cast the chart for a process started at `birth` with `ephemeris`
+    pub fn cast(ephemeris: &dyn Ephemeris, birth: DateTime<Utc>) -> Result<Self, AstroError> {
+        Ok(Self::new(ephemeris.positions(julian_day(birth)?)?))
+    }
+}
+
+/// Midpoint of two ecliptic longitudes, on the shorter arc between them
+///
+/// Exactly opposite longitudes have two; the one 90° ahead of `a` is taken.
+pub fn calculate_midpoint(a: f64, b: f64) -> f64 {
+    let arc = (b - a).rem_euclid(360.0);
+    if arc <= 180.0 {
+        normalize_longitude(a + arc / 2.0)
+    } else {
+        normalize_longitude(a - (360.0 - arc) / 2.0)
+    }
+}
+
+/// The relationship between two processes, as one chart of midpoints
+#[derive(Debug, Clone)]
+pub struct CompositeChart {
+    pub positions: Vec<PlanetaryPosition>,
+}
+
+impl CompositeChart {
+    /// Midpoint of each planet in `a` with the same planet in `b`
+    ///
+    /// Planets missing from either chart are left out. A composite doesn't
+    /// move, so nothing in it is retrograde.
+    pub fn new(a: &NatalChart, b: &NatalChart) -> Self {
+        let mut positions: Vec<PlanetaryPosition> = a
+            .positions
+            .iter()
+            .filter_map(|pa| {
+                let pb = b.positions.iter().find(|pb| pb.planet == pa.planet)?;
+                let longitude = calculate_midpoint(pa.longitude, pb.longitude);
+                Some(PlanetaryPosition {
+                    planet: pa.planet,
+                    longitude,
+                    latitude: f64::midpoint(pa.latitude, pb.latitude),
+                    sign: ZodiacSign::from_longitude(longitude),
+                    degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                    retrograde: false,
+                    moon_phase: None,
+                })
+            })
+            .collect();
+
+        let sun = positions.iter().find(|pos| pos.planet == Planet::Sun).map(|pos| pos.longitude);
+        if let (Some(sun), Some(moon)) = (sun, positions.iter_mut().find(|pos| pos.planet == Planet::Moon)) {
+            moon.moon_phase = Some(MoonPhase::from_angle(moon.longitude - sun));
+        }
+        Self { positions }
+    }
+
+    /// How well the two processes get along, from 0.0 to 2.0
+    ///
+    /// Half comes from the share of composite planets in an element compatible
+    /// with the composite Sun's, half from the balance of harmonious against
+    /// tense aspects between them (each weighted by its strength). With no
+    /// aspects at all the balance counts as neutral.
+    pub fn compatibility_score(&self) -> f64 {
+        let element_share = match self.positions.iter().find(|pos| pos.planet == Planet::Sun) {
+            Some(sun) => {
+                let compatible = self
+                    .positions
+                    .iter()
+                    .filter(|pos| pos.sign.element().is_compatible(sun.sign.element()))
+                    .count();
+                #[allow(clippy::cast_precision_loss)]
+                let share = compatible as f64 / self.positions.len() as f64;
+                share
+            }
+            None => 0.5,
+        };
+
+        let mut calculator = AspectCalculator::new(self.positions.clone());
+        let (harmony, tension) = calculator.get_aspects(DEFAULT_ASPECT_ORB).iter().fold(
+            (0.0, 0.0),
+            |(harmony, tension), aspect| {
+                if aspect.kind.modifier() > 0.0 {
+                    (harmony + aspect.strength, tension)
+                } else {
+                    (harmony, tension + aspect.strength)
+                }
+            },
+        );
+        let balance = if harmony + tension > 0.0 { (harmony - tension) / (harmony + tension) } else { 0.0 };
+
+        element_share + 0.5 * (1.0 + balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    fn natal(positions: &[(Planet, f64)]) -> NatalChart {
+        NatalChart::new(positions.iter().map(|&(planet, longitude)| position(planet, longitude)).collect())
+    }
+
+    #[test]
+    fn test_calculate_midpoint() {
+        assert!((calculate_midpoint(10.0, 50.0) - 30.0).abs() < 1e-9);
+        assert!((calculate_midpoint(50.0, 10.0) - 30.0).abs() < 1e-9);
+        // Across 0° Aries, not the long way round
+        assert!((calculate_midpoint(350.0, 20.0) - 5.0).abs() < 1e-9);
+        assert!((calculate_midpoint(20.0, 350.0) - 5.0).abs() < 1e-9);
+        assert!((calculate_midpoint(0.0, 180.0) - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_composite_midpoints() {
+        let a = natal(&[(Planet::Sun, 10.0), (Planet::Moon, 100.0), (Planet::Mars, 200.0)]);
+        let b = natal(&[(Planet::Sun, 350.0), (Planet::Moon, 140.0)]);
+        let composite = CompositeChart::new(&a, &b);
+
+        // Mars has no partner in the second chart
+        assert_eq!(composite.positions.len(), 2);
+        let sun = &composite.positions[0];
+        assert_eq!(sun.planet, Planet::Sun);
+        assert!(sun.longitude.abs() < 1e-9);
+        assert_eq!(sun.sign, ZodiacSign::Aries);
+        let moon = &composite.positions[1];
+        assert!((moon.longitude - 120.0).abs() < 1e-9);
+        assert_eq!(moon.moon_phase, Some(MoonPhase::from_angle(120.0)));
+    }
+
+    #[test]
+    fn test_compatibility_score() {
+        // All in Fire, all in trine: as good as it gets
+        let harmonious = natal(&[(Planet::Sun, 0.0), (Planet::Moon, 120.0), (Planet::Mars, 240.0)]);
+        let score = CompositeChart::new(&harmonious, &harmonious).compatibility_score();
+        assert!((score - 2.0).abs() < 1e-9, "{score}");
+
+        // Squares and an opposition, and only the Sun in its own element
+        let tense = natal(&[(Planet::Sun, 0.0), (Planet::Moon, 90.0), (Planet::Mars, 270.0)]);
+        let score = CompositeChart::new(&tense, &tense).compatibility_score();
+        assert!((score - 1.0 / 3.0).abs() < 1e-9, "{score}");
+
+        // Nothing in aspect, half the elements compatible: right in the middle
+        let mixed = natal(&[(Planet::Sun, 0.0), (Planet::Moon, 35.0)]);
+        let score = CompositeChart::new(&mixed, &mixed).compatibility_score();
+        assert!((score - 1.0).abs() < 1e-9, "{score}");
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use super::error::AstroError;
+use super::nodes::mean_north_node;
+use super::planets::{calculate_planetary_positions_for_jd, calculate_position_for_jd, julian_day, Planet, PlanetaryPosition};
+
+/// Where planetary positions come from
+///
+/// The scheduler only ever asks for a chart at a Julian day, so a Swiss
+/// Ephemeris binding, a precomputed table or a test stub can stand in for the
+/// `astro` crate.
+pub trait Ephemeris {
+    fn positions(&self, jd: f64) -> Result<Vec<PlanetaryPosition>, AstroError>;
+
+    /// Positions of just these planets, for backends that can skip the rest
+    fn positions_of(&self, jd: f64, planets: &[Planet]) -> Result<Vec<PlanetaryPosition>, AstroError> {
+        Ok(self.positions(jd)?.into_iter().filter(|pos| planets.contains(&pos.planet)).collect())
+    }
+
+    /// Longitude of the North lunar node, if the backend knows it
+    fn north_node(&self, jd: f64) -> Option<f64> {
+        Some(mean_north_node(jd))
+    }
+}
+
+/// The default backend, computed with the `astro` crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AstroEphemeris;
+
+impl Ephemeris for AstroEphemeris {
+    fn positions(&self, jd: f64) -> Result<Vec<PlanetaryPosition>, AstroError> {
+        calculate_planetary_positions_for_jd(jd)
+    }
+
+    fn positions_of(&self, jd: f64, planets: &[Planet]) -> Result<Vec<PlanetaryPosition>, AstroError> {
+        planets.iter().map(|&planet| calculate_position_for_jd(planet, jd)).collect()
+    }
+}
+
+/// The same chart whatever the date, for tests and reproducible runs
+///
+/// A fixed chart has no lunar nodes unless given one.
+#[derive(Debug, Clone)]
+pub struct FixedEphemeris {
+    positions: Vec<PlanetaryPosition>,
+    north_node: Option<f64>,
+}
+
+impl FixedEphemeris {
+    pub fn new(positions: Vec<PlanetaryPosition>) -> Self {
+        Self { positions, north_node: None }
+    }
+
+    #[must_use]
+    pub fn with_north_node(mut self, longitude: f64) -> Self {
+        self.north_node = Some(longitude.rem_euclid(360.0));
+        self
+    }
+}
+
+impl Ephemeris for FixedEphemeris {
+    fn positions(&self, _jd: f64) -> Result<Vec<PlanetaryPosition>, AstroError> {
+        Ok(self.positions.clone())
+    }
+
+    fn north_node(&self, _jd: f64) -> Option<f64> {
+        self.north_node
+    }
+}
+
+/// Planetary positions and lunar node at one moment
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub time: DateTime<Utc>,
+    pub positions: Vec<PlanetaryPosition>,
+    pub north_node: Option<f64>,
+}
+
+/// Computes charts with a scheduler's ephemeris, on whatever thread it is sent to
+#[derive(Clone)]
+pub struct ChartSource {
+    ephemeris: Arc<dyn Ephemeris + Send + Sync>,
+}
+
+impl ChartSource {
+    pub fn new(ephemeris: Arc<dyn Ephemeris + Send + Sync>) -> Self {
+        Self { ephemeris }
+    }
+
+    pub fn compute(&self, time: DateTime<Utc>) -> Result<Chart, AstroError> {
+        let jd = julian_day(time)?;
+        let positions = self.ephemeris.positions(jd)?;
+        Ok(Chart { time, positions, north_node: self.ephemeris.north_node(jd) })
+    }
+}
@@ -0,0 +1,46 @@
+use super::planets::Planet;
+use chrono::NaiveDate;
+use std::fmt;
+
+/// Errors that can occur while consulting the heavens
+#[derive(Debug, Clone, PartialEq)]
+pub enum AstroError {
+    /// A planet needed for a decision is missing from the chart
+    MissingPlanet(Planet),
+    /// The ephemeris produced a NaN or infinite longitude
+    NonFiniteLongitude { planet: Planet, value: f64 },
+    /// The ephemeris could not be evaluated for the requested moment
+    EphemerisFailure(String),
+    /// The ascendant cannot be determined (e.g. inside the polar circles)
+    UndefinedAscendant { latitude: f64 },
+    /// The Sun neither rises nor sets on this date (polar day or night)
+    NoSunrise { latitude: f64, date: NaiveDate },
+    /// Planetary hours were asked for without an observer location
+    MissingLocation,
+    /// A classifier ruleset could not be loaded
+    InvalidClassifier(String),
+}
+
+impl fmt::Display for AstroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AstroError::MissingPlanet(planet) => {
+                write!(f, "{} is missing from the planetary chart", planet.name())
+            }
+            AstroError::NonFiniteLongitude { planet, value } => {
+                write!(f, "{} has a non-finite longitude ({value})", planet.name())
+            }
+            AstroError::EphemerisFailure(reason) => write!(f, "ephemeris failure: {reason}"),
+            AstroError::UndefinedAscendant { latitude } => {
+                write!(f, "ascendant is undefined at latitude {latitude}°")
+            }
+            AstroError::NoSunrise { latitude, date } => {
+                write!(f, "the Sun neither rises nor sets at latitude {latitude}° on {date}")
+            }
+            AstroError::MissingLocation => write!(f, "planetary hours need an observer latitude and longitude"),
+            AstroError::InvalidClassifier(reason) => write!(f, "invalid classifier ruleset: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for AstroError {}
@@ -0,0 +1,160 @@
+use super::aspects::angular_separation;
+use super::planets::{normalize_longitude, Planet, PlanetaryPosition};
+
+/// How close a planet must stand to a fixed star to be conjunct it, in degrees
+pub const FIXED_STAR_ORB: f64 = 1.0;
+
+/// Julian day of the J2000.0 epoch the star longitudes are given for
+const J2000: f64 = 2_451_545.0;
+
+/// General precession in longitude, in degrees a year (50.29″)
+const PRECESSION: f64 = 50.29 / 3600.0;
+
+/// What a star does to the planets it touches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StarNature {
+    Benefic,
+    Malefic,
+    Mixed,
+}
+
+impl StarNature {
+    pub fn name(self) -> &'static str {
+        match self {
+            StarNature::Benefic => "benefic",
+            StarNature::Malefic => "malefic",
+            StarNature::Mixed => "mixed",
+        }
+    }
+}
+
+/// A fixed star, drifting slowly along the zodiac with the precession of the equinoxes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedStar {
+    pub name: &'static str,
+    pub longitude_2000: f64,     // Tropical longitude at J2000.0
+    pub annual_precession: f64,  // Degrees a year
+    pub nature: StarNature,
+    pub scheduling_bonus: f64,   // Priority multiplier for tasks whose ruler is conjunct the star
+}
+
+impl FixedStar {
+    const fn new(name: &'static str, longitude_2000: f64, nature: StarNature, scheduling_bonus: f64) -> Self {
+        Self { name, longitude_2000, annual_precession: PRECESSION, nature, scheduling_bonus }
+    }
+
+    /// Tropical longitude of the star on Julian day `jd`
+    pub fn longitude_at(&self, jd: f64) -> f64 {
+        normalize_longitude(self.longitude_2000 + self.annual_precession * (jd - J2000) / 365.25)
+    }
+}
+
+/// The fifteen Behenian stars of medieval magic, in zodiacal order
+///
+/// Regulus, the royal star, gives the planet on it a kingly boost; Algol,
+/// the Demon Star, is the most unfortunate in the sky.
+pub const BEHENIAN_STARS: [FixedStar; 15] = [
+    FixedStar::new("Algol", 56.17, StarNature::Malefic, 0.5),
+    FixedStar::new("Pleiades", 60.00, StarNature::Malefic, 0.9),
+    FixedStar::new("Aldebaran", 69.79, StarNature::Mixed, 1.2),
+    FixedStar::new("Capella", 81.85, StarNature::Benefic, 1.1),
+    FixedStar::new("Sirius", 104.08, StarNature::Mixed, 1.2),
+    FixedStar::new("Procyon", 115.79, StarNature::Mixed, 0.9),
+    FixedStar::new("Regulus", 149.83, StarNature::Benefic, 1.5),
+    FixedStar::new("Alkaid", 176.93, StarNature::Malefic, 0.8),
+    FixedStar::new("Algorab", 193.45, StarNature::Malefic, 0.8),
+    FixedStar::new("Spica", 203.84, StarNature::Benefic, 1.3),
+    FixedStar::new("Arcturus", 204.23, StarNature::Benefic, 1.2),
+    FixedStar::new("Alphecca", 222.30, StarNature::Benefic, 1.1),
+    FixedStar::new("Antares", 249.76, StarNature::Malefic, 0.8),
+    FixedStar::new("Vega", 285.32, StarNature::Benefic, 1.1),
+    FixedStar::new("Deneb Algedi", 323.55, StarNature::Mixed, 1.0),
+];
+
+/// The Behenian stars `planet` is conjunct on Julian day `jd`, within `FIXED_STAR_ORB`
+pub fn detect_fixed_star_conjunctions(planet: &PlanetaryPosition, jd: f64) -> Vec<&'static FixedStar> {
+    BEHENIAN_STARS
+        .iter()
+        .filter(|star| angular_separation(planet.longitude, star.longitude_at(jd)) <= FIXED_STAR_ORB)
+        .collect()
+}
+
+/// A planet on a fixed star
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedStarCondition {
+    pub planet: Planet,
+    pub star: &'static FixedStar,
+}
+
+/// Every conjunction between a planet of the chart and a Behenian star on Julian day `jd`
+pub fn get_fixed_star_conditions(positions: &[PlanetaryPosition], jd: f64) -> Vec<FixedStarCondition> {
+    positions
+        .iter()
+        .flat_map(|pos| {
+            detect_fixed_star_conjunctions(pos, jd).into_iter().map(|star| FixedStarCondition { planet: pos.planet, star })
+        })
+        .collect()
+}
+
+/// Priority multiplier for tasks ruled by `planet`, from every star it is conjunct
+pub fn fixed_star_factor(conditions: &[FixedStarCondition], planet: Planet) -> f64 {
+    conditions
+        .iter()
+        .filter(|condition| condition.planet == planet)
+        .map(|condition| condition.star.scheduling_bonus)
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::ZodiacSign;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    fn star(name: &str) -> &'static FixedStar {
+        BEHENIAN_STARS.iter().find(|star| star.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_precession() {
+        // Regulus left Leo for Virgo in late 2011
+        let jd_2012 = J2000 + 12.0 * 365.25;
+        assert!(star("Regulus").longitude_at(J2000) < 150.0);
+        assert!((star("Regulus").longitude_at(jd_2012) - 150.0).abs() < 0.05);
+        // A degree every 72 years or so
+        assert!((star("Spica").longitude_at(J2000 + 72.0 * 365.25) - star("Spica").longitude_2000 - 1.0).abs() < 0.01);
+        assert_eq!(BEHENIAN_STARS.len(), 15);
+    }
+
+    #[test]
+    fn test_conjunctions_and_factors() {
+        let jd = J2000 + 25.0 * 365.25;
+        let regulus = star("Regulus").longitude_at(jd);
+        let algol = star("Algol").longitude_at(jd);
+
+        let mars = position(Planet::Mars, regulus + 0.5);
+        assert_eq!(detect_fixed_star_conjunctions(&mars, jd), [star("Regulus")]);
+        assert!(detect_fixed_star_conjunctions(&position(Planet::Mars, regulus + 1.5), jd).is_empty());
+        // Spica and Arcturus stand less than half a degree apart: a planet between them has both
+        let between = (star("Spica").longitude_at(jd) + star("Arcturus").longitude_at(jd)) / 2.0;
+        assert_eq!(detect_fixed_star_conjunctions(&position(Planet::Venus, between), jd).len(), 2);
+
+        let chart = [mars, position(Planet::Saturn, algol - 0.3), position(Planet::Sun, 0.0)];
+        let conditions = get_fixed_star_conditions(&chart, jd);
+        assert_eq!(conditions.len(), 2);
+        assert!((fixed_star_factor(&conditions, Planet::Mars) - 1.5).abs() < 1e-9);
+        assert!((fixed_star_factor(&conditions, Planet::Saturn) - 0.5).abs() < 1e-9);
+        assert!((fixed_star_factor(&conditions, Planet::Sun) - 1.0).abs() < 1e-9);
+    }
+}
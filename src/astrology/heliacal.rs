@@ -0,0 +1,119 @@
+use super::ephemeris::{AstroEphemeris, Ephemeris};
+use super::planets::{find_position, Planet, PlanetaryPosition, ZodiacSign};
+use super::solar::elongation;
+
+/// Influence multiplier for a planet in the chart cycle after its heliacal rising
+pub const HELIACAL_BONUS: f64 = 1.3;
+
+/// A planet reappearing at dawn after its time lost in the Sun's glare
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeliacalRising {
+    pub jd: f64,
+    pub sign: ZodiacSign,
+}
+
+/// How far west of the Sun `planet` must be to be seen rising before it
+///
+/// None for the Sun itself and for the Moon, whose first crescent is an
+/// evening affair.
+pub fn visibility_threshold(planet: Planet) -> Option<f64> {
+    match planet {
+        Planet::Sun | Planet::Moon => None,
+        Planet::Mercury => Some(10.0),
+        Planet::Venus => Some(5.0),
+        Planet::Mars | Planet::Jupiter | Planet::Saturn | Planet::Uranus => Some(15.0),
+    }
+}
+
+/// Julian day `planet` cleared its visibility threshold west of the Sun between two elongations
+///
+/// Elongations are as from `elongation`, negative west of the Sun. The
+/// moment is interpolated between `(jd_before, before)` and `(jd_after, after)`,
+/// which is close enough over a day or so. None unless the planet was
+/// invisible at the first and visible at the second.
+pub fn heliacal_crossing(planet: Planet, (jd_before, before): (f64, f64), (jd_after, after): (f64, f64)) -> Option<f64> {
+    let threshold = visibility_threshold(planet)?;
+    if before.abs() >= threshold || after > -threshold {
+        return None;
+    }
+    let fraction = (threshold - before.abs()) / (after.abs() - before.abs());
+    Some(jd_before + (jd_after - jd_before) * fraction)
+}
+
+/// Elongation from the Sun of `planet` in a chart, if the chart has both
+pub fn elongation_in(positions: &[PlanetaryPosition], planet: Planet) -> Option<f64> {
+    let sun = find_position(positions, Planet::Sun).ok()?;
+    let pos = find_position(positions, planet).ok()?;
+    Some(elongation(pos.longitude, sun.longitude))
+}
+
+/// The heliacal rising `planet` made in the day up to `jd`, with `ephemeris`
+pub fn heliacal_rising_with(ephemeris: &dyn Ephemeris, planet: Planet, jd: f64) -> Option<HeliacalRising> {
+    visibility_threshold(planet)?;
+    let elongation_at = |jd: f64| {
+        let positions = ephemeris.positions_of(jd, &[planet, Planet::Sun]).ok()?;
+        elongation_in(&positions, planet)
+    };
+    let rising = heliacal_crossing(planet, (jd - 1.0, elongation_at(jd - 1.0)?), (jd, elongation_at(jd)?))?;
+    let positions = ephemeris.positions_of(rising, &[planet]).ok()?;
+    let sign = find_position(&positions, planet).ok()?.sign;
+    Some(HeliacalRising { jd: rising, sign })
+}
+
+/// The heliacal rising `planet` made in the day up to `jd`, if it made one
+pub fn calculate_heliacal_rising(planet: Planet, jd: f64) -> Option<HeliacalRising> {
+    heliacal_rising_with(&AstroEphemeris, planet, jd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::julian_day;
+    use chrono::{TimeZone, Utc};
+
+    /// Every heliacal rising of `planet` in the `days` from midnight on `(year, month, day)`
+    fn risings(planet: Planet, (year, month, day): (i32, u32, u32), days: u32) -> Vec<HeliacalRising> {
+        let start = julian_day(Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()).unwrap();
+        (1..=days).filter_map(|day| calculate_heliacal_rising(planet, start + f64::from(day))).collect()
+    }
+
+    fn date(jd: f64) -> String {
+        crate::astrology::jd_to_datetime(jd).unwrap().format("%Y-%m-%d").to_string()
+    }
+
+    #[test]
+    fn test_crossing() {
+        // Invisible, then 5° west of the Sun: Venus is up, Mercury is not yet
+        assert_eq!(heliacal_crossing(Planet::Venus, (100.0, -3.0), (101.0, -7.0)), Some(100.5));
+        assert_eq!(heliacal_crossing(Planet::Mercury, (100.0, -3.0), (101.0, -7.0)), None);
+        // Clearing the threshold east of the Sun is an evening first, not a rising
+        assert_eq!(heliacal_crossing(Planet::Venus, (100.0, 3.0), (101.0, 7.0)), None);
+        // Nor is staying visible, or sinking back into the glare
+        assert_eq!(heliacal_crossing(Planet::Venus, (100.0, -6.0), (101.0, -7.0)), None);
+        assert_eq!(heliacal_crossing(Planet::Venus, (100.0, -7.0), (101.0, -3.0)), None);
+        assert_eq!(heliacal_crossing(Planet::Sun, (100.0, 0.0), (101.0, -20.0)), None);
+    }
+
+    #[test]
+    fn test_known_heliacal_risings() {
+        // Venus and Mercury both passed inferior conjunction in late March 2025
+        let venus = risings(Planet::Venus, (2025, 3, 20), 30);
+        assert_eq!(venus.len(), 1);
+        assert_eq!(date(venus[0].jd), "2025-03-26");
+        assert_eq!(venus[0].sign, ZodiacSign::Aries);
+
+        let mercury = risings(Planet::Mercury, (2025, 3, 20), 30);
+        assert_eq!(mercury.len(), 1);
+        assert_eq!(date(mercury[0].jd), "2025-03-30");
+        assert_eq!(mercury[0].sign, ZodiacSign::Pisces);
+
+        // Jupiter, three weeks after its conjunction of 24 June 2025
+        let jupiter = risings(Planet::Jupiter, (2025, 6, 24), 40);
+        assert_eq!(jupiter.len(), 1);
+        assert_eq!(date(jupiter[0].jd), "2025-07-15");
+        assert_eq!(jupiter[0].sign, ZodiacSign::Cancer);
+
+        // Nothing while Venus is a bright evening star
+        assert!(risings(Planet::Venus, (2025, 1, 1), 30).is_empty());
+    }
+}
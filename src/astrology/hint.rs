@@ -0,0 +1,217 @@
+use super::planets::Element;
+use super::scheduler::SchedulingDecision;
+
+/// Version written into every packed hint
+pub const HINT_VERSION: u8 = 1;
+
+/// Largest kernel weight a packed hint can carry (14 bits)
+pub const MAX_HINT_WEIGHT: u32 = (1 << 14) - 1;
+
+/// Largest element code a packed hint can carry (3 bits)
+pub const MAX_HINT_ELEMENT: u8 = 7;
+
+/// A scheduling decision boiled down to what a kernel-side consumer could use
+///
+/// Packed layout (128 bits, little-endian):
+///
+/// | bits    | field          |
+/// |---------|----------------|
+/// | 0-3     | version        |
+/// | 4       | retrograde     |
+/// | 5-7     | element        |
+/// | 8-15    | `latency_nice` |
+/// | 16-29   | weight         |
+/// | 30-31   | reserved       |
+/// | 32-63   | priority       |
+/// | 64-127  | `slice_ns`     |
+///
+/// New fields may only take reserved bits; existing ones never move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoroscopeSchedHint {
+    pub priority: u32,
+    pub slice_ns: u64,     // 0 = let the dispatcher pick
+    pub weight: u32,       // Kernel weight, saturates at MAX_HINT_WEIGHT when packed
+    pub latency_nice: i8,  // -20 (most urgent) to 19
+    pub element: u8,       // 0 = none, then Fire, Earth, Air, Water
+    pub retrograde: bool,
+}
+
+impl HoroscopeSchedHint {
+    /// Element code for the hint's `element` field
+    pub fn element_code(element: Option<Element>) -> u8 {
+        match element {
+            None => 0,
+            Some(Element::Fire) => 1,
+            Some(Element::Earth) => 2,
+            Some(Element::Air) => 3,
+            Some(Element::Water) => 4,
+        }
+    }
+
+    pub fn element(&self) -> Option<Element> {
+        match self.element {
+            1 => Some(Element::Fire),
+            2 => Some(Element::Earth),
+            3 => Some(Element::Air),
+            4 => Some(Element::Water),
+            _ => None,
+        }
+    }
+
+    pub fn pack(&self) -> [u8; 16] {
+        let bits = u128::from(HINT_VERSION & 0xf)
+            | u128::from(self.retrograde) << 4
+            | u128::from(self.element.min(MAX_HINT_ELEMENT)) << 5
+            | u128::from(self.latency_nice.to_le_bytes()[0]) << 8
+            | u128::from(self.weight.min(MAX_HINT_WEIGHT)) << 16
+            | u128::from(self.priority) << 32
+            | u128::from(self.slice_ns) << 64;
+        bits.to_le_bytes()
+    }
+
+    /// Decode a packed hint; fields of any version are read from their fixed bits
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn unpack(bytes: [u8; 16]) -> Self {
+        let bits = u128::from_le_bytes(bytes);
+        Self {
+            priority: (bits >> 32) as u32,
+            slice_ns: (bits >> 64) as u64,
+            weight: (bits >> 16) as u32 & MAX_HINT_WEIGHT,
+            latency_nice: i8::from_le_bytes([(bits >> 8) as u8]),
+            element: (bits >> 5) as u8 & MAX_HINT_ELEMENT,
+            retrograde: bits & (1 << 4) != 0,
+        }
+    }
+
+    /// Version of a packed hint
+    pub fn version(bytes: [u8; 16]) -> u8 {
+        bytes[0] & 0xf
+    }
+}
+
+/// The stars' latency verdict: priority 1000 and up is -20, priority 0 is 19
+fn latency_nice(priority: u32) -> i8 {
+    let scaled = i64::from(priority.min(1000)) * 39 / 1000;
+    i8::try_from(19 - scaled).unwrap_or(0)
+}
+
+impl From<SchedulingDecision> for HoroscopeSchedHint {
+    fn from(decision: SchedulingDecision) -> Self {
+        Self {
+            priority: decision.priority,
+            slice_ns: 0,  // The time slice is picked at dispatch time
+            weight: u32::try_from(decision.weight).unwrap_or(u32::MAX),
+            latency_nice: latency_nice(decision.priority),
+            element: Self::element_code(decision.ruling_element),
+            retrograde: decision.planetary_influence < 0.0,
+        }
+    }
+}
+
+/// Compact 64-bit form for BPF map values
+///
+/// Same low 30 bits as the packed form; then the priority saturated to 16
+/// bits (30-45) and the slice in microseconds saturated to 18 bits (46-63).
+impl From<HoroscopeSchedHint> for u64 {
+    fn from(hint: HoroscopeSchedHint) -> Self {
+        let low = u64::from_le_bytes(hint.pack()[..8].try_into().unwrap_or_default()) & ((1 << 30) - 1);
+        let priority = u64::from(hint.priority.min(0xffff));
+        let slice_us = (hint.slice_ns / 1000).min((1 << 18) - 1);
+        low | priority << 30 | slice_us << 46
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_round_trip_boundaries() {
+        let mut checked = 0;
+        for priority in [0, 1, 1000, u32::MAX] {
+            for slice_ns in [0, 1, 5_000_000, u64::MAX] {
+                for weight in [0, 1, 100, 10_000, MAX_HINT_WEIGHT] {
+                    for latency_nice in [i8::MIN, -20, 0, 19, i8::MAX] {
+                        for element in 0..=MAX_HINT_ELEMENT {
+                            for retrograde in [false, true] {
+                                let hint = HoroscopeSchedHint { priority, slice_ns, weight, latency_nice, element, retrograde };
+                                let packed = hint.pack();
+                                assert_eq!(HoroscopeSchedHint::unpack(packed), hint);
+                                assert_eq!(HoroscopeSchedHint::version(packed), HINT_VERSION);
+                                checked += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(checked, 4 * 4 * 5 * 5 * 8 * 2);
+    }
+
+    #[test]
+    fn test_pack_saturates_out_of_range_fields() {
+        let hint = HoroscopeSchedHint { priority: 7, slice_ns: 9, weight: 100_000, latency_nice: 0, element: 200, retrograde: false };
+        let unpacked = HoroscopeSchedHint::unpack(hint.pack());
+        assert_eq!(unpacked.weight, MAX_HINT_WEIGHT);
+        assert_eq!(unpacked.element, MAX_HINT_ELEMENT);
+        assert_eq!(unpacked.priority, 7);
+    }
+
+    #[test]
+    fn test_unpack_fuzz() {
+        // xorshift keeps the corpus reproducible
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..10_000 {
+            let mut bytes = [0u8; 16];
+            bytes[..8].copy_from_slice(&next().to_le_bytes());
+            bytes[8..].copy_from_slice(&next().to_le_bytes());
+
+            let hint = HoroscopeSchedHint::unpack(bytes);
+            assert!(hint.weight <= MAX_HINT_WEIGHT);
+            assert!(hint.element <= MAX_HINT_ELEMENT);
+            // Whatever came in, the decoded hint survives a re-pack unchanged
+            assert_eq!(HoroscopeSchedHint::unpack(hint.pack()), hint);
+        }
+    }
+
+    #[test]
+    fn test_from_decision() {
+        use super::super::scheduler::{AstrologicalScheduler, DEFAULT_TASK_WEIGHT};
+        use chrono::Utc;
+
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let decision = scheduler.schedule_task("init", 1, DEFAULT_TASK_WEIGHT, Utc::now()).unwrap();
+        let hint = HoroscopeSchedHint::from(decision);
+        assert_eq!(hint.priority, 1000);
+        assert_eq!(hint.weight, 100);
+        assert_eq!(hint.latency_nice, -20);
+        assert_eq!(hint.element(), None);
+        assert!(!hint.retrograde);
+
+        let decision = scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, Utc::now()).unwrap();
+        let element = decision.ruling_element;
+        let hint = HoroscopeSchedHint::from(decision);
+        assert_eq!(hint.element(), element);
+        assert!((-20..=19).contains(&hint.latency_nice));
+    }
+
+    #[test]
+    fn test_compact_u64() {
+        let hint = HoroscopeSchedHint { priority: 70_000, slice_ns: 5_000_000, weight: 100, latency_nice: -5, element: 3, retrograde: true };
+        let compact = u64::from(hint);
+        assert_eq!(compact & 0xf, u64::from(HINT_VERSION));
+        assert_eq!(compact >> 4 & 1, 1);
+        assert_eq!(compact >> 5 & 0x7, 3);
+        assert_eq!(compact >> 8 & 0xff, 0xfb);
+        assert_eq!(compact >> 16 & 0x3fff, 100);
+        assert_eq!(compact >> 30 & 0xffff, 0xffff);
+        assert_eq!(compact >> 46, 5000);
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+
+use super::error::AstroError;
+use super::planets::Planet;
+
+/// Classical planets from slowest to fastest, the order the hours cycle through
+pub const CHALDEAN_ORDER: [Planet; 7] = [
+    Planet::Saturn,
+    Planet::Jupiter,
+    Planet::Mars,
+    Planet::Sun,
+    Planet::Venus,
+    Planet::Mercury,
+    Planet::Moon,
+];
+
+/// Planetary hours in a day and in a night
+pub const HOURS_PER_HALF: i32 = 12;
+
+/// Solar altitude at sunrise and sunset: refraction plus the solar disc
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+/// Planet ruling a weekday, and with it the first hour after sunrise
+pub fn day_ruler(weekday: Weekday) -> Planet {
+    match weekday {
+        Weekday::Sun => Planet::Sun,
+        Weekday::Mon => Planet::Moon,
+        Weekday::Tue => Planet::Mars,
+        Weekday::Wed => Planet::Mercury,
+        Weekday::Thu => Planet::Jupiter,
+        Weekday::Fri => Planet::Venus,
+        Weekday::Sat => Planet::Saturn,
+    }
+}
+
+/// One planetary hour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanetaryHour {
+    pub ruler: Planet,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub night: bool,
+}
+
+/// Sunrise and sunset on a date at a location (longitude east-positive)
+///
+/// Uses the sunrise equation, good to a minute or two away from the poles.
+/// When the Sun never rises or never sets there is no planetary day.
+pub fn sunrise_sunset(date: NaiveDate, latitude: f64, longitude: f64) -> Result<(DateTime<Utc>, DateTime<Utc>), AstroError> {
+    const J2000: f64 = 2_451_545.0;
+    const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+
+    let j2000_date = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default();
+    #[allow(clippy::cast_precision_loss)]
+    let days = (date - j2000_date).num_days() as f64;
+    let mean_solar_noon = days - longitude / 360.0;
+
+    let anomaly = (357.5291 + 0.985_600_28 * mean_solar_noon).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * anomaly.sin() + 0.0200 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372).rem_euclid(360.0).to_radians();
+    let transit = J2000 + mean_solar_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let phi = latitude.to_radians();
+    let cos_hour_angle = (SUNRISE_ALTITUDE.to_radians().sin() - phi.sin() * declination.sin()) / (phi.cos() * declination.cos());
+    if !cos_hour_angle.is_finite() || cos_hour_angle.abs() > 1.0 {
+        return Err(AstroError::NoSunrise { latitude, date });
+    }
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+
+    let to_datetime = |jd: f64| {
+        #[allow(clippy::cast_possible_truncation)]
+        let millis = ((jd - UNIX_EPOCH_JD) * 86_400_000.0).round() as i64;
+        DateTime::from_timestamp_millis(millis).ok_or(AstroError::NoSunrise { latitude, date })
+    };
+    Ok((to_datetime(transit - half_day)?, to_datetime(transit + half_day)?))
+}
+
+/// The 24 planetary hours from sunrise on `date` to sunrise the next day
+///
+/// Day (sunrise to sunset) and night (sunset to the next sunrise) are each
+/// split into twelve equal hours, ruled in the Chaldean order starting with
+/// the planet of the weekday.
+pub fn planetary_hours(date: NaiveDate, latitude: f64, longitude: f64) -> Result<Vec<PlanetaryHour>, AstroError> {
+    let (sunrise, sunset) = sunrise_sunset(date, latitude, longitude)?;
+    let (next_sunrise, _) = sunrise_sunset(date.succ_opt().unwrap_or(date), latitude, longitude)?;
+
+    let first = CHALDEAN_ORDER.iter().position(|&planet| planet == day_ruler(date.weekday())).unwrap_or(0);
+    let mut hours = Vec::with_capacity(2 * HOURS_PER_HALF as usize);
+    for (night, from, to) in [(false, sunrise, sunset), (true, sunset, next_sunrise)] {
+        // Boundaries are interpolated so consecutive hours meet exactly
+        let length = (to - from).num_milliseconds();
+        let boundary = |k: i32| from + Duration::milliseconds(length * i64::from(k) / i64::from(HOURS_PER_HALF));
+        for k in 0..HOURS_PER_HALF {
+            hours.push(PlanetaryHour {
+                ruler: CHALDEAN_ORDER[(first + hours.len()) % CHALDEAN_ORDER.len()],
+                start: boundary(k),
+                end: boundary(k + 1),
+                night,
+            });
+        }
+    }
+    Ok(hours)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const LONDON: (f64, f64) = (51.5074, -0.1278);
+
+    #[test]
+    fn test_london_midsummer_sunrise() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+        let (sunrise, sunset) = sunrise_sunset(date, LONDON.0, LONDON.1).unwrap();
+
+        // Published times: 03:43 and 20:21 UTC
+        let expected_rise = Utc.with_ymd_and_hms(2025, 6, 21, 3, 43, 0).unwrap();
+        let expected_set = Utc.with_ymd_and_hms(2025, 6, 21, 20, 21, 0).unwrap();
+        assert!((sunrise - expected_rise).num_minutes().abs() <= 3, "sunrise {sunrise}");
+        assert!((sunset - expected_set).num_minutes().abs() <= 3, "sunset {sunset}");
+    }
+
+    #[test]
+    fn test_no_sunrise_in_polar_night() {
+        let date = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+        assert!(matches!(sunrise_sunset(date, 78.2, 15.6), Err(AstroError::NoSunrise { .. })));
+        assert!(planetary_hours(date, 78.2, 15.6).is_err());
+    }
+
+    #[test]
+    fn test_hours_cover_the_day_without_gaps() {
+        for (day, latitude, longitude) in [(20, LONDON.0, LONDON.1), (21, -33.87, 151.21), (22, 0.0, 0.0)] {
+            let date = NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+            let hours = planetary_hours(date, latitude, longitude).unwrap();
+            assert_eq!(hours.len(), 24);
+            assert_eq!(hours.iter().filter(|hour| hour.night).count(), 12);
+
+            let (sunrise, sunset) = sunrise_sunset(date, latitude, longitude).unwrap();
+            assert_eq!(hours[0].start, sunrise);
+            assert_eq!(hours[11].end, sunset);
+            assert!(hours.windows(2).all(|w| w[0].end == w[1].start));
+            assert!(hours.iter().all(|hour| hour.start < hour.end));
+        }
+    }
+
+    #[test]
+    fn test_chaldean_sequence() {
+        // 2025-03-20 was a Thursday: Jupiter rules the first hour
+        let date = NaiveDate::from_ymd_opt(2025, 3, 20).unwrap();
+        let hours = planetary_hours(date, LONDON.0, LONDON.1).unwrap();
+        let rulers: Vec<_> = hours.iter().take(8).map(|hour| hour.ruler).collect();
+        assert_eq!(
+            rulers,
+            [Planet::Jupiter, Planet::Mars, Planet::Sun, Planet::Venus, Planet::Mercury, Planet::Moon, Planet::Saturn, Planet::Jupiter]
+        );
+
+        // ...and the 25th hour would open Friday with Venus
+        let next = CHALDEAN_ORDER.iter().position(|&planet| planet == hours[23].ruler).unwrap();
+        assert_eq!(CHALDEAN_ORDER[(next + 1) % 7], day_ruler(Weekday::Fri));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use super::planets::{Element, Planet, PlanetaryPosition, ZodiacSign};
+
+/// A planet leaving one sign for the next, as seen between two charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngressEvent {
+    pub planet: Planet,
+    pub from_sign: ZodiacSign,
+    pub to_sign: ZodiacSign,
+    pub from_element: Element,
+    pub to_element: Element,
+    pub timestamp: DateTime<Utc>,  // When the chart that saw it was cast
+}
+
+impl IngressEvent {
+    /// True when the planet has also changed element
+    pub fn changes_element(&self) -> bool {
+        self.from_element != self.to_element
+    }
+}
+
+impl fmt::Display for IngressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} enters {} from {}", self.planet.name(), self.to_sign.name(), self.from_sign.name())?;
+        if self.changes_element() {
+            write!(f, " ({} → {})", self.from_element.name(), self.to_element.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// Something to do when a planet changes sign
+pub type IngressCallback = Arc<dyn Fn(&IngressEvent) + Send + Sync>;
+
+/// Hands every ingress to the callbacks registered for it
+///
+/// Cloning shares the callbacks rather than copying them.
+#[derive(Clone, Default)]
+pub struct IngressNotifier {
+    callbacks: Vec<IngressCallback>,
+    element_callbacks: Vec<(Element, Element, IngressCallback)>,  // Only for a move between these elements
+}
+
+impl IngressNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `cb` with every ingress
+    pub fn register_callback(&mut self, cb: Box<dyn Fn(&IngressEvent) + Send + Sync>) {
+        self.callbacks.push(Arc::from(cb));
+    }
+
+    /// Call `cb` with the ingresses that take a planet from element `from` to element `to`
+    pub fn register_element_change_callback(&mut self, from: Element, to: Element, cb: Box<dyn Fn(&IngressEvent) + Send + Sync>) {
+        self.element_callbacks.push((from, to, Arc::from(cb)));
+    }
+
+    /// Deliver one event to every callback registered for it
+    pub fn fire(&self, event: &IngressEvent) {
+        for cb in &self.callbacks {
+            cb(event);
+        }
+        for (from, to, cb) in &self.element_callbacks {
+            if event.from_element == *from && event.to_element == *to {
+                cb(event);
+            }
+        }
+    }
+
+    /// Compare a new chart with the previous one and fire every ingress between them, returning them
+    pub fn notify(&self, old: &[PlanetaryPosition], new: &[PlanetaryPosition], timestamp: DateTime<Utc>) -> Vec<IngressEvent> {
+        let events = ingresses(old, new, timestamp);
+        for event in &events {
+            self.fire(event);
+        }
+        events
+    }
+}
+
+/// Planets whose sign differs between two charts; planets missing from either are ignored
+pub fn ingresses(old: &[PlanetaryPosition], new: &[PlanetaryPosition], timestamp: DateTime<Utc>) -> Vec<IngressEvent> {
+    new.iter()
+        .filter_map(|now| {
+            let before = old.iter().find(|pos| pos.planet == now.planet)?;
+            (before.sign != now.sign).then(|| IngressEvent {
+                planet: now.planet,
+                from_sign: before.sign,
+                to_sign: now.sign,
+                from_element: before.sign.element(),
+                to_element: now.sign.element(),
+                timestamp,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::mpsc;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_callbacks_receive_ingresses() {
+        let timestamp = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        // The Moon crosses from Cancer into Leo, Mars from Aries into Taurus; the Sun stays in Pisces
+        let old = [position(Planet::Sun, 355.0), position(Planet::Moon, 119.5), position(Planet::Mars, 29.9)];
+        let new = [position(Planet::Sun, 356.0), position(Planet::Moon, 120.5), position(Planet::Mars, 30.1)];
+
+        let (sender, receiver) = mpsc::channel();
+        let (water_sender, water_receiver) = mpsc::channel();
+        let mut notifier = IngressNotifier::new();
+        notifier.register_callback(Box::new(move |event| sender.send(*event).unwrap()));
+        notifier.register_element_change_callback(
+            Element::Water,
+            Element::Fire,
+            Box::new(move |event| water_sender.send(event.planet).unwrap()),
+        );
+
+        // Clones share the callbacks
+        let events = notifier.clone().notify(&old, &new, timestamp);
+        assert_eq!(events.len(), 2);
+        let received: Vec<IngressEvent> = receiver.try_iter().collect();
+        assert_eq!(received, events);
+        assert_eq!(
+            received[0],
+            IngressEvent {
+                planet: Planet::Moon,
+                from_sign: ZodiacSign::Cancer,
+                to_sign: ZodiacSign::Leo,
+                from_element: Element::Water,
+                to_element: Element::Fire,
+                timestamp,
+            }
+        );
+        assert_eq!(received[1].to_string(), "Mars enters Taurus from Aries (Fire → Earth)");
+        assert_eq!(water_receiver.try_iter().collect::<Vec<_>>(), vec![Planet::Moon]);
+
+        // Nothing moved: nothing to say
+        assert!(notifier.notify(&new, &new, timestamp).is_empty());
+        assert_eq!(receiver.try_iter().count(), 0);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fs;
+
+use super::tasks::TaskType;
+
+/// Words of each task type's planet and signs, and of the work it rules
+///
+/// Matched against the words of a task's command line, so a script run by an
+/// interpreter can be told apart by its name: `python3 run_network_monitor.py`
+/// is Mercury's business. A word belongs to one task type only.
+pub const KEYWORD_TABLE: &[(TaskType, &[&str])] = &[
+    // Mars: speed, fire and force
+    (TaskType::CpuIntensive, &[
+        "mars", "ares", "aries", "leo", "sagittarius", "fire", "flame", "blaze",
+        "burn", "ignite", "forge", "furnace", "heat", "speed", "fast", "turbo",
+        "rocket", "sprint", "race", "warrior", "war", "battle", "sword", "attack",
+        "force", "hero", "champion", "athlete", "iron", "compile", "compiler",
+        "build", "render", "renderer", "encode", "encoder", "transcode", "compress",
+        "crunch", "hash", "miner", "mining", "solver", "simulate", "simulation",
+        "benchmark", "stress", "train", "training", "tensor", "fft", "physics",
+        "math",
+    ]),
+    // Mercury: messages, wings and roads
+    (TaskType::Network, &[
+        "mercury", "hermes", "gemini", "twins", "wings", "messenger", "courier",
+        "communication", "communicate", "message", "mail", "email", "post", "chat",
+        "talk", "speak", "voice", "send", "receive", "net", "network", "socket",
+        "http", "https", "web", "api", "grpc", "rpc", "proxy", "gateway", "router",
+        "relay", "bridge", "broadcast", "signal", "dns", "sync", "download",
+        "upload", "fetch", "crawl", "crawler", "spider", "scrape", "scraper",
+        "stream", "feed", "rss", "ping", "telegram", "irc", "bot", "webhook",
+        "client",
+    ]),
+    // Venus: beauty, art and harmony
+    (TaskType::Desktop, &[
+        "venus", "aphrodite", "libra", "taurus", "beauty", "beautiful", "love",
+        "art", "artist", "paint", "painter", "draw", "canvas", "color", "colour",
+        "palette", "design", "designer", "style", "theme", "font", "image", "photo",
+        "picture", "gallery", "view", "viewer", "display", "window", "desktop",
+        "panel", "dock", "wallpaper", "music", "song", "player", "audio", "sound",
+        "video", "media", "movie", "harmony", "garden", "flower", "rose", "mirror",
+        "ui", "gui", "widget", "icon",
+    ]),
+    // Jupiter: expansion, abundance and deep waters
+    (TaskType::MemoryHeavy, &[
+        "jupiter", "zeus", "pisces", "scorpio", "ocean", "sea", "water", "expansion",
+        "expand", "abundance", "wealth", "treasure", "vault", "store", "storage",
+        "cache", "memory", "memo", "remember", "recall", "database", "db", "sql",
+        "index", "indexer", "search", "warehouse", "lake", "datalake", "bigdata",
+        "dataset", "data", "etl", "aggregate", "analytics", "analyze", "report",
+        "pandas", "spark", "hadoop", "hive", "jvm", "heap", "loader", "library",
+        "catalog", "harvest", "feast",
+    ]),
+    // Saturn: structure, time and order
+    (TaskType::System, &[
+        "saturn", "kronos", "chronos", "capricorn", "structure", "order", "law",
+        "rule", "boundary", "wall", "foundation", "root", "kernel", "daemon",
+        "service", "init", "boot", "mount", "disk", "backup", "archive", "log",
+        "logger", "journal", "audit", "guard", "watchdog", "cron", "clock", "time",
+        "discipline", "maintenance", "cleanup", "clean", "housekeeping", "health",
+        "healthcheck", "check", "update", "upgrade", "install", "package", "config",
+        "setup", "admin", "supervisor", "rotate", "quota", "permission", "policy",
+    ]),
+    // The Moon: moods, tides and the hearth
+    (TaskType::Interactive, &[
+        "moon", "luna", "selene", "cancer", "home", "mother", "mood", "feeling",
+        "emotion", "dream", "night", "tide", "crescent", "terminal", "console",
+        "repl", "prompt", "editor", "edit", "notebook", "jupyter", "ipython",
+        "interactive", "input", "keyboard", "mouse", "touch", "game", "play",
+        "quiz", "tutor", "tui", "cli", "menu", "pager", "reader", "notes", "diary",
+        "calendar", "todo", "shell", "lullaby",
+    ]),
+    // Uranus: other worlds, sudden change and the sky
+    (TaskType::Virtualization, &[
+        "uranus", "ouranos", "aquarius", "prometheus", "rebel", "revolution",
+        "innovation", "invent", "future", "sky", "heaven", "lightning", "thunder",
+        "electric", "shock", "sudden", "awaken", "freedom", "liberty", "alien",
+        "utopia", "world", "universe", "cosmos", "multiverse", "dimension", "portal",
+        "realm", "sandbox", "virtual", "virt", "vm", "guest", "hypervisor",
+        "emulator", "emulate", "container", "pod", "jail", "chroot", "namespace",
+        "cloud", "instance", "kube", "kubelet", "k8s", "lxc", "wine", "proton",
+    ]),
+];
+
+/// Classifies tasks by the words of their command line
+pub struct KeywordClassifier {
+    keywords: HashMap<&'static str, TaskType>,
+}
+
+impl KeywordClassifier {
+    pub fn new() -> Self {
+        let keywords = KEYWORD_TABLE
+            .iter()
+            .flat_map(|&(task_type, words)| words.iter().map(move |&word| (word, task_type)))
+            .collect();
+        Self { keywords }
+    }
+
+    /// How many keywords there are
+    pub fn len(&self) -> usize {
+        self.keywords.len()
+    }
+
+    /// Whether there are no keywords at all
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+    }
+
+    /// The task type of the first keyword in a NUL-separated command line
+    pub fn classify_cmdline(&self, cmdline: &[u8]) -> Option<TaskType> {
+        tokenize(cmdline).find_map(|token| self.keywords.get(token.as_str()).copied())
+    }
+
+    /// Classify a running task by `/proc/<pid>/cmdline`; `None` once it has gone, or for kernel threads
+    pub fn classify_from_cmdline(&self, pid: i32) -> Option<TaskType> {
+        let cmdline = fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+        self.classify_cmdline(&cmdline)
+    }
+}
+
+impl Default for KeywordClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The lowercase words of a command line, argument by argument
+///
+/// Only the last path component of an argument counts, so a script under
+/// `/home` is not taken for the Moon's; options (`--log-level`) are skipped.
+pub fn tokenize(cmdline: &[u8]) -> impl Iterator<Item = String> + '_ {
+    cmdline
+        .split(|&byte| byte == 0)
+        .map(String::from_utf8_lossy)
+        .filter(|arg| !arg.starts_with('-'))
+        .flat_map(|arg| {
+            let name = arg.rsplit('/').next().unwrap_or_default().to_lowercase();
+            name.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_keyword_table() {
+        let words: Vec<&str> = KEYWORD_TABLE.iter().flat_map(|(_, words)| words.iter().copied()).collect();
+        assert!(words.len() >= 200, "{} keywords", words.len());
+        let distinct: HashSet<&str> = words.iter().copied().collect();
+        assert_eq!(distinct.len(), words.len(), "a keyword belongs to one task type");
+        assert!(words.iter().all(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())));
+        assert_eq!(KeywordClassifier::new().len(), words.len());
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens: Vec<String> = tokenize(b"/usr/bin/python3\0--log-level=debug\0/home/me/Run_Network-Monitor.py\0").collect();
+        assert_eq!(tokens, vec!["python3", "run", "network", "monitor", "py"]);
+        assert_eq!(tokenize(b"").count(), 0);
+    }
+
+    #[test]
+    fn test_classify_cmdline_fixtures() {
+        let keywords = KeywordClassifier::new();
+        let fixtures: &[(&[u8], Option<TaskType>)] = &[
+            (b"python3\0run_network_monitor.py\0", Some(TaskType::Network)),
+            (b"/usr/bin/python3\0-u\0/opt/jobs/render_frames.py\0--fast\0", Some(TaskType::CpuIntensive)),
+            (b"node\0/srv/app/cache-warmer.js\0", Some(TaskType::MemoryHeavy)),
+            (b"python3\0/usr/local/bin/nightly_backup.py\0", Some(TaskType::System)),
+            (b"ruby\0bin/photo_gallery.rb\0", Some(TaskType::Desktop)),
+            (b"python3\0-m\0jupyter\0notebook\0", Some(TaskType::Interactive)),
+            (b"/usr/libexec/sandbox-helper\0", Some(TaskType::Virtualization)),
+            // The first keyword decides; paths and options don't count
+            (b"perl\0sync_then_compile.pl\0", Some(TaskType::Network)),
+            (b"python3\0/home/me/script.py\0--log\0", None),
+            (b"", None),
+        ];
+        for &(cmdline, expected) in fixtures {
+            assert_eq!(keywords.classify_cmdline(cmdline), expected, "{}", String::from_utf8_lossy(cmdline));
+        }
+        // PIDs that don't exist have no command line
+        assert_eq!(keywords.classify_from_cmdline(-1), None);
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+
+use super::aspects::angular_separation;
+use super::error::AstroError;
+use super::planets::{calculate_ascendant, find_position, Planet, PlanetaryPosition, ZodiacSign};
+
+/// How close a ruler must be to the Part of Fortune to conjunct it, in degrees
+pub const FORTUNE_ORB: f64 = 3.0;
+
+/// Priority multiplier for a task whose ruler conjuncts the Part of Fortune
+pub const FORTUNE_BONUS: f64 = 1.1;
+
+/// True when the Sun is above the horizon: a day chart
+///
+/// The half of the ecliptic from the ascendant onward through the houses
+/// lies below the horizon; the other half, from the descendant back round
+/// to the ascendant, is above it.
+pub fn is_day_chart(ascendant: f64, sun: f64) -> bool {
+    (sun - ascendant).rem_euclid(360.0) >= 180.0
+}
+
+/// Longitude of the Part of Fortune
+///
+/// By day it is as far from the ascendant as the Moon is from the Sun
+/// (Ascendant + Moon − Sun); by night the arc is reversed (Ascendant + Sun − Moon).
+pub fn part_of_fortune(ascendant: f64, sun: f64, moon: f64, day: bool) -> f64 {
+    let arc = if day { moon - sun } else { sun - moon };
+    (ascendant + arc).rem_euclid(360.0)
+}
+
+/// The Part of Fortune of a chart cast for a place
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PartOfFortune {
+    pub longitude: f64,
+    pub sign: ZodiacSign,
+    pub day: bool,  // Worked out with the day formula
+}
+
+impl PartOfFortune {
+    pub fn new(ascendant: f64, positions: &[PlanetaryPosition]) -> Result<Self, AstroError> {
+        let sun = find_position(positions, Planet::Sun)?.longitude;
+        let moon = find_position(positions, Planet::Moon)?.longitude;
+        let day = is_day_chart(ascendant, sun);
+        let longitude = part_of_fortune(ascendant, sun, moon, day);
+        Ok(Self { longitude, sign: ZodiacSign::from_longitude(longitude), day })
+    }
+
+    /// The Part of Fortune of the chart `positions` for `time` at a place
+    pub fn cast(
+        time: DateTime<Utc>,
+        latitude: f64,
+        longitude: f64,
+        positions: &[PlanetaryPosition],
+    ) -> Result<Self, AstroError> {
+        Self::new(calculate_ascendant(time, latitude, longitude)?, positions)
+    }
+
+    /// Whether a planet at `longitude` conjuncts the Part
+    pub fn conjuncts(self, longitude: f64) -> bool {
+        angular_separation(self.longitude, longitude) <= FORTUNE_ORB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_sect() {
+        // Cancer rising: a Sun in Aries or Capricorn is up, one in Leo is down
+        assert!(is_day_chart(100.0, 10.0));
+        assert!(is_day_chart(100.0, 280.0));
+        assert!(!is_day_chart(100.0, 100.0 + 45.0));
+        assert!(!is_day_chart(350.0, 20.0));
+        assert!(is_day_chart(350.0, 200.0));
+    }
+
+    #[test]
+    fn test_part_of_fortune() {
+        // Cancer rising, the Sun in Libra below the horizon, the Moon in Taurus: a night chart
+        let chart = [position(Planet::Sun, 200.0), position(Planet::Moon, 50.0)];
+        let fortune = PartOfFortune::new(100.0, &chart).unwrap();
+        assert!(!fortune.day);
+        assert!((fortune.longitude - 250.0).abs() < 1e-9);
+        assert_eq!(fortune.sign, ZodiacSign::Sagittarius);
+
+        // The day formula would have put it in Aquarius instead
+        let by_day = part_of_fortune(100.0, 200.0, 50.0, true);
+        assert!((by_day - 310.0).abs() < 1e-9);
+        assert_eq!(ZodiacSign::from_longitude(by_day), ZodiacSign::Aquarius);
+
+        // At a New Moon both formulas put it on the ascendant
+        assert!((part_of_fortune(100.0, 30.0, 30.0, true) - part_of_fortune(100.0, 30.0, 30.0, false)).abs() < 1e-9);
+
+        assert!(fortune.conjuncts(252.5));
+        assert!(!fortune.conjuncts(254.0));
+        assert!(PartOfFortune::new(100.0, &chart[..1]).is_err());
+    }
+}
@@ -1,11 +1,77 @@
+pub mod aspects;
+pub mod composite;
+pub mod ephemeris;
+pub mod error;
+pub mod fixed_stars;
+pub mod heliacal;
+pub mod hours;
+pub mod ingress_notifier;
+pub mod keywords;
+pub mod lots;
+pub mod mundane;
+pub mod nodes;
 pub mod planets;
+pub mod relocation;
+pub mod return_charts;
+pub mod rng;
+pub mod sabian;
+pub mod solar;
+pub mod stations;
+pub mod synergy;
 pub mod tasks;
+pub mod venus;
 pub mod scheduler;
+pub mod solar_arc;
+pub mod hint;
 
 // Public API re-exports for external use
 #[allow(unused_imports)]
-pub use planets::{Planet, ZodiacSign, Element, PlanetaryPosition, MoonPhase, calculate_planetary_positions};
+pub use error::AstroError;
 #[allow(unused_imports)]
-pub use tasks::{TaskType, TaskClassifier};
+pub use planets::{Planet, ZodiacSign, Element, PlanetaryPosition, MoonPhase, calculate_planetary_positions, calculate_planetary_positions_for_jd, find_position};
 #[allow(unused_imports)]
-pub use scheduler::{AstrologicalScheduler, SchedulingDecision};
+pub use aspects::{AspectCalculator, AspectKind, PlanetaryAspect, DEFAULT_ASPECT_ORB, moon_void_of_course, tension_score};
+#[allow(unused_imports)]
+pub use composite::{CompositeChart, NatalChart, calculate_midpoint};
+#[allow(unused_imports)]
+pub use ephemeris::{Ephemeris, AstroEphemeris, FixedEphemeris, Chart, ChartSource};
+#[allow(unused_imports)]
+pub use fixed_stars::{detect_fixed_star_conjunctions, get_fixed_star_conditions, FixedStar, FixedStarCondition, StarNature, BEHENIAN_STARS, FIXED_STAR_ORB};
+#[allow(unused_imports)]
+pub use ingress_notifier::{IngressCallback, IngressEvent, IngressNotifier, ingresses};
+#[allow(unused_imports)]
+pub use keywords::{KeywordClassifier, KEYWORD_TABLE};
+#[allow(unused_imports)]
+pub use lots::{PartOfFortune, FORTUNE_BONUS, FORTUNE_ORB};
+#[allow(unused_imports)]
+pub use mundane::{detect_great_conjunction, detect_saturn_jupiter_square, MundaneEvent, MundaneWatch, MUNDANE_ORB, RECESSION_FACTOR, RECESSION_HOURS};
+#[allow(unused_imports)]
+pub use nodes::{mean_north_node, is_at_bending};
+#[allow(unused_imports)]
+pub use relocation::{calculate_angular_cpus, cpu_longitude, AngularCpus, ANGULAR_ORB};
+#[allow(unused_imports)]
+pub use return_charts::{calculate_next_lunar_return, calculate_next_solar_return, ReturnType, LUNAR_RETURN_DAYS, SOLAR_RETURN_DAYS};
+#[allow(unused_imports)]
+pub use rng::CosmicRng;
+#[allow(unused_imports)]
+pub use sabian::{get_sabian_symbol, sabian_degree, SABIAN_SYMBOLS};
+#[allow(unused_imports)]
+pub use stations::{datetime_to_jd, jd_to_datetime, retrograde_calendar, retrograde_window_jd, STATION_SCAN_DAYS};
+#[allow(unused_imports)]
+pub use solar_arc::{LifecycleEvent, ProgressedState, SolarArcProgressor, PROGRESSION_ORB};
+#[allow(unused_imports)]
+pub use synergy::TaskTypeSynergy;
+#[allow(unused_imports)]
+pub use hours::{PlanetaryHour, planetary_hours, sunrise_sunset};
+#[allow(unused_imports)]
+pub use tasks::{TaskType, TaskClassifier, RulershipMap, Provenance, CriticalPolicy, CriticalReason, TASK_FLAG_RT};
+#[allow(unused_imports)]
+pub use scheduler::{AstrologicalScheduler, SchedulingDecision, ConditionComparison, ElementForecastPoint, ForecastHour, DEFAULT_TASK_WEIGHT};
+#[allow(unused_imports)]
+pub use venus::{venus_elongation, venus_phase_modifier, VenusPhase, PROMINENT_ELONGATION};
+#[allow(unused_imports)]
+pub use heliacal::{calculate_heliacal_rising, HeliacalRising, HELIACAL_BONUS};
+#[allow(unused_imports)]
+pub use solar::{elongation, solar_condition, SolarCondition, CAZIMI_ORB, COMBUST_ORB, UNDER_BEAMS_ORB};
+#[allow(unused_imports)]
+pub use hint::HoroscopeSchedHint;
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::aspects::angular_separation;
+use super::planets::{find_position, Planet, PlanetaryPosition};
+
+/// How close Jupiter and Saturn must be for a great conjunction or square, in degrees
+pub const MUNDANE_ORB: f64 = 5.0;
+
+/// Priority multiplier for `MemoryHeavy` tasks during a recession
+pub const RECESSION_FACTOR: f64 = 0.5;
+
+/// How long a recession lasts once a Jupiter–Saturn square begins, in hours
+pub const RECESSION_HOURS: i64 = 24;
+
+/// How far apart Jupiter and Saturn stand in a chart, if it has both
+fn jupiter_saturn_separation(positions: &[PlanetaryPosition]) -> Option<f64> {
+    let jupiter = find_position(positions, Planet::Jupiter).ok()?;
+    let saturn = find_position(positions, Planet::Saturn).ok()?;
+    Some(angular_separation(jupiter.longitude, saturn.longitude))
+}
+
+/// The orb of a great conjunction of Jupiter and Saturn, if one is within `MUNDANE_ORB`
+///
+/// Every twenty years or so the two slowest planets of the old sky meet,
+/// and mundane astrology reads the start of a new era in the world.
+pub fn detect_great_conjunction(positions: &[PlanetaryPosition]) -> Option<f64> {
+    jupiter_saturn_separation(positions).filter(|&separation| separation <= MUNDANE_ORB)
+}
+
+/// Whether Jupiter and Saturn are square within `MUNDANE_ORB`
+///
+/// The squares mark the quarters of the twenty-year cycle, read as the
+/// cycle's economic crises.
+pub fn detect_saturn_jupiter_square(positions: &[PlanetaryPosition]) -> bool {
+    jupiter_saturn_separation(positions).is_some_and(|separation| (separation - 90.0).abs() <= MUNDANE_ORB)
+}
+
+/// A world event read from the chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MundaneEvent {
+    /// Jupiter and Saturn have come within `orb` degrees of each other
+    GreatConjunction { orb: f64 },
+    /// Jupiter and Saturn have come square, and `MemoryHeavy` tasks are cut back until `until`
+    Recession { until: DateTime<Utc> },
+}
+
+impl MundaneEvent {
+    /// A banner announcing the event, and what it does to scheduling
+    pub fn banner(&self) -> String {
+        match self {
+            Self::GreatConjunction { orb } => format!(
+                "🪐 GREAT CONJUNCTION: Jupiter meets Saturn ({orb:.1}° orb) — a new era begins, every birth chart and classification is cast anew"
+            ),
+            Self::Recession { until } => format!(
+                "📉 RECESSION: Jupiter squares Saturn — MemoryHeavy tasks tighten their belts to {:.0}% priority until {}",
+                RECESSION_FACTOR * 100.0,
+                until.format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+}
+
+/// Watches successive charts for world events
+///
+/// Each event fires once as Jupiter and Saturn come into orb, and again only
+/// after they have separated and come back.
+#[derive(Debug, Clone, Default)]
+pub struct MundaneWatch {
+    conjunct: bool,  // Within orb of the great conjunction at the last check
+    square: bool,    // Within orb of the square at the last check
+    recession_until: Option<DateTime<Utc>>,
+}
+
+impl MundaneWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The events begun in the chart cast at `now`
+    pub fn check(&mut self, positions: &[PlanetaryPosition], now: DateTime<Utc>) -> Vec<MundaneEvent> {
+        let mut events = Vec::new();
+        let conjunction = detect_great_conjunction(positions);
+        if let Some(orb) = conjunction.filter(|_| !self.conjunct) {
+            events.push(MundaneEvent::GreatConjunction { orb });
+        }
+        self.conjunct = conjunction.is_some();
+
+        let square = detect_saturn_jupiter_square(positions);
+        if square && !self.square {
+            let until = now + Duration::hours(RECESSION_HOURS);
+            self.recession_until = Some(until);
+            events.push(MundaneEvent::Recession { until });
+        }
+        self.square = square;
+        events
+    }
+
+    /// Whether a recession is on at `now`
+    pub fn in_recession(&self, now: DateTime<Utc>) -> bool {
+        self.recession_until.is_some_and(|until| now < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::{calculate_planetary_positions, ZodiacSign};
+    use chrono::TimeZone;
+
+    /// `positions` with Jupiter and Saturn moved to the given longitudes
+    fn with_jupiter_saturn(mut positions: Vec<PlanetaryPosition>, jupiter: f64, saturn: f64) -> Vec<PlanetaryPosition> {
+        for pos in &mut positions {
+            let longitude = match pos.planet {
+                Planet::Jupiter => jupiter,
+                Planet::Saturn => saturn,
+                _ => continue,
+            };
+            pos.longitude = longitude;
+            pos.sign = ZodiacSign::from_longitude(longitude);
+            pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+        }
+        positions
+    }
+
+    #[test]
+    fn test_great_conjunction_of_2020() {
+        // Exact on 21 December 2020 at 0° Aquarius, a tenth of a degree apart
+        let positions = calculate_planetary_positions(Utc.with_ymd_and_hms(2020, 12, 21, 18, 0, 0).unwrap()).unwrap();
+        let orb = detect_great_conjunction(&positions).expect("no great conjunction on 2020-12-21");
+        assert!(orb < 1.0, "{orb}");
+        assert!(!detect_saturn_jupiter_square(&positions));
+
+        // A year on, Jupiter has pulled well ahead
+        let positions = calculate_planetary_positions(Utc.with_ymd_and_hms(2021, 12, 21, 0, 0, 0).unwrap()).unwrap();
+        assert_eq!(detect_great_conjunction(&positions), None);
+    }
+
+    #[test]
+    fn test_square_either_way_round() {
+        let positions = calculate_planetary_positions(Utc.with_ymd_and_hms(2020, 12, 21, 0, 0, 0).unwrap()).unwrap();
+        assert!(detect_saturn_jupiter_square(&with_jupiter_saturn(positions.clone(), 93.0, 0.0)));
+        assert!(detect_saturn_jupiter_square(&with_jupiter_saturn(positions.clone(), 358.0, 90.0)));
+        assert!(!detect_saturn_jupiter_square(&with_jupiter_saturn(positions.clone(), 100.0, 0.0)));
+        assert_eq!(detect_great_conjunction(&with_jupiter_saturn(positions, 358.0, 2.0)), Some(4.0));
+        assert_eq!(detect_great_conjunction(&[]), None);
+    }
+
+    #[test]
+    fn test_watch_fires_once_per_passage() {
+        let now = Utc.with_ymd_and_hms(2020, 12, 21, 0, 0, 0).unwrap();
+        let positions = calculate_planetary_positions(now).unwrap();
+        let mut watch = MundaneWatch::new();
+
+        let conjunct = with_jupiter_saturn(positions.clone(), 300.0, 302.0);
+        assert_eq!(watch.check(&conjunct, now), [MundaneEvent::GreatConjunction { orb: 2.0 }]);
+        assert!(watch.check(&conjunct, now + Duration::hours(1)).is_empty());
+
+        // The square opens a day's recession, once
+        let square = with_jupiter_saturn(positions.clone(), 30.0, 300.0);
+        let until = now + Duration::hours(RECESSION_HOURS);
+        assert_eq!(watch.check(&square, now), [MundaneEvent::Recession { until }]);
+        assert!(watch.in_recession(now + Duration::hours(23)));
+        assert!(watch.check(&square, now + Duration::hours(2)).is_empty());
+        assert!(!watch.in_recession(until));
+
+        // Separating re-arms the watch
+        let apart = with_jupiter_saturn(positions, 60.0, 300.0);
+        assert!(watch.check(&apart, now).is_empty());
+        assert_eq!(watch.check(&conjunct, now).len(), 1);
+    }
+}
@@ -0,0 +1,55 @@
+use super::aspects::angular_separation;
+
+/// How far from an exact square to the nodal axis a planet still counts as at the bending
+pub const BENDING_ORB: f64 = 3.0;
+
+/// Priority multiplier for a task whose ruler is at a bending
+pub const BENDING_PENALTY: f64 = 0.9;
+
+/// Ecliptic longitude of the mean ascending (North) lunar node, in degrees
+///
+/// Meeus, Astronomical Algorithms, eq. 47.7. The node regresses through the
+/// zodiac once every 18.6 years; the South node is always opposite.
+pub fn mean_north_node(jd: f64) -> f64 {
+    let t = (jd - 2_451_545.0) / 36_525.0;
+    (125.044_547_9 - 1_934.136_289_1 * t + 0.002_075_4 * t * t).rem_euclid(360.0)
+}
+
+/// True when a longitude squares the nodal axis, halfway between the nodes
+pub fn is_at_bending(longitude: f64, north_node: f64) -> bool {
+    (angular_separation(longitude, north_node) - 90.0).abs() <= BENDING_ORB
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_north_node() {
+        // J2000: 125.04°, in Leo
+        assert!((mean_north_node(2_451_545.0) - 125.04).abs() < 0.01);
+
+        // 2025-01-01: the node was leaving Aries for Pisces
+        let node = mean_north_node(2_460_676.5);
+        assert!(angular_separation(node, 0.0) < 3.0, "node {node}");
+
+        // Half a nodal cycle later it has moved to the opposite point
+        let half_cycle = 18.6 * 365.25 / 2.0;
+        let moved = angular_separation(mean_north_node(2_451_545.0), mean_north_node(2_451_545.0 + half_cycle));
+        assert!((moved - 180.0).abs() < 1.0, "moved {moved}");
+    }
+
+    #[test]
+    fn test_bendings() {
+        // Both bendings, either side of either node
+        for longitude in [100.0, 280.0, 97.5, 282.9] {
+            assert!(is_at_bending(longitude, 10.0), "{longitude}");
+        }
+        // On the nodes themselves, or out of orb
+        for longitude in [10.0, 190.0, 96.0, 104.0, 55.0] {
+            assert!(!is_at_bending(longitude, 10.0), "{longitude}");
+        }
+        // Wraps around 0° Aries
+        assert!(is_at_bending(5.0, 275.0));
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use super::aspects::angular_separation;
+use super::composite::NatalChart;
+use super::planets::{calculate_ascendant, calculate_midheaven, normalize_longitude, Planet};
+
+/// How close to one of a CPU's angles a natal planet must stand to be angular there, in degrees
+pub const ANGULAR_ORB: f64 = 10.0;
+
+/// CPUs on which each planet is angular
+pub type AngularCpus = HashMap<Planet, Vec<u32>>;
+
+/// The longitude CPU `cpu` of `cpu_count` stands in for, the CPUs spread evenly round the globe
+pub fn cpu_longitude(cpu: u32, cpu_count: u32) -> f64 {
+    f64::from(cpu) * 360.0 / f64::from(cpu_count)
+}
+
+/// The ascendant, midheaven, descendant and IC at `now` on the equator at `longitude`
+pub fn angles_at(now: DateTime<Utc>, longitude: f64) -> [f64; 4] {
+    let midheaven = calculate_midheaven(now, longitude);
+    // The ascendant is only undefined inside the polar circles, far from the equator
+    let ascendant = calculate_ascendant(now, 0.0, longitude).unwrap_or(normalize_longitude(midheaven + 90.0));
+    [ascendant, midheaven, normalize_longitude(ascendant + 180.0), normalize_longitude(midheaven + 180.0)]
+}
+
+/// The CPUs on which each planet of `natal` is angular at `now`
+///
+/// Astrocartography draws the lines on the globe where a planet of the chart
+/// would have stood on an angle; here each CPU is a place on the equator,
+/// at `cpu_longitude`, and a planet within `ANGULAR_ORB` of one of that place's
+/// angles is angular on the CPU. Planets angular nowhere are left out.
+pub fn calculate_angular_cpus(natal: &NatalChart, now: DateTime<Utc>, cpu_count: u32) -> AngularCpus {
+    let mut angular = AngularCpus::new();
+    for cpu in 0..cpu_count {
+        let angles = angles_at(now, cpu_longitude(cpu, cpu_count));
+        for pos in &natal.positions {
+            if angles.iter().any(|&angle| angular_separation(pos.longitude, angle) <= ANGULAR_ORB) {
+                angular.entry(pos.planet).or_default().push(cpu);
+            }
+        }
+    }
+    angular
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::{PlanetaryPosition, ZodiacSign};
+    use chrono::TimeZone;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_angles_at_the_equator() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let [ascendant, midheaven, descendant, ic] = angles_at(now, 0.0);
+        // On the equator the ascendant stays within a few degrees of a quadrant past the midheaven
+        assert!((angular_separation(ascendant, midheaven) - 90.0).abs() < 3.0);
+        assert!((angular_separation(ascendant, descendant) - 180.0).abs() < 1e-9);
+        assert!((angular_separation(midheaven, ic) - 180.0).abs() < 1e-9);
+        // A quarter of the way round the globe, the midheaven has moved on a quadrant too
+        assert!((angular_separation(angles_at(now, 90.0)[1], midheaven) - 90.0).abs() < 3.0);
+    }
+
+    #[test]
+    fn test_angular_cpus_on_four_cpus() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        assert_eq!((0..4).map(|cpu| cpu_longitude(cpu, 4)).collect::<Vec<_>>(), [0.0, 90.0, 180.0, 270.0]);
+
+        // Mars on CPU 1's midheaven, Venus just inside the orb of CPU 0's ascendant,
+        // Saturn halfway between the angles
+        let [ascendant, midheaven, ..] = angles_at(now, 0.0);
+        let cpu1_midheaven = angles_at(now, cpu_longitude(1, 4))[1];
+        let natal = NatalChart::new(vec![
+            position(Planet::Mars, cpu1_midheaven),
+            position(Planet::Venus, normalize_longitude(ascendant + ANGULAR_ORB - 1.0)),
+            position(Planet::Saturn, normalize_longitude(midheaven + 45.0)),
+        ]);
+        let angular = calculate_angular_cpus(&natal, now, 4);
+
+        // Four CPUs a quadrant apart share their angles between them, each's
+        // midheaven near another's ascendant, so a planet on one is angular on all
+        assert_eq!(angular.get(&Planet::Mars), Some(&vec![0, 1, 2, 3]));
+        assert!(angular[&Planet::Venus].contains(&0));
+        assert!(!angular.contains_key(&Planet::Saturn));
+
+        // With no CPUs there is nowhere to be angular
+        assert!(calculate_angular_cpus(&natal, now, 0).is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+
+use super::ephemeris::{AstroEphemeris, Ephemeris};
+use super::error::AstroError;
+use super::planets::{find_position, normalize_longitude, Planet};
+use super::stations::{datetime_to_jd, jd_to_datetime};
+
+/// Mean days for the Moon to come back to the same longitude (the tropical month)
+///
+/// Not the 29.5-day synodic month, which brings back the same phase.
+pub const LUNAR_RETURN_DAYS: f64 = 27.321_582;
+
+/// Mean days for the Sun to come back to the same longitude (the tropical year)
+pub const SOLAR_RETURN_DAYS: f64 = 365.242_19;
+
+/// How close to the natal longitude a return has to land, in degrees (under a second of time for the Moon)
+const RETURN_TOLERANCE: f64 = 1e-5;
+
+/// Newton steps allowed before the search is given up on
+const MAX_ITERATIONS: u32 = 20;
+
+/// Which cycle a process comes back round to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReturnType {
+    Solar,  // Yearly, the Sun back where it was at birth
+    Lunar,  // Monthly, the Moon back where it was at birth
+}
+
+impl ReturnType {
+    pub fn planet(self) -> Planet {
+        match self {
+            ReturnType::Solar => Planet::Sun,
+            ReturnType::Lunar => Planet::Moon,
+        }
+    }
+
+    /// Mean days between two returns
+    pub fn period_days(self) -> f64 {
+        match self {
+            ReturnType::Solar => SOLAR_RETURN_DAYS,
+            ReturnType::Lunar => LUNAR_RETURN_DAYS,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReturnType::Solar => "Solar return",
+            ReturnType::Lunar => "Lunar return",
+        }
+    }
+}
+
+/// Degrees from `from` forward or back to `to`, in (-180, 180]
+fn signed_difference(to: f64, from: f64) -> f64 {
+    let ahead = normalize_longitude(to - from);
+    if ahead > 180.0 { ahead - 360.0 } else { ahead }
+}
+
+/// The first moment after `from` that the luminary of `return_type` is back at `natal_longitude`, with `ephemeris`
+///
+/// Newton-Raphson from the guess the mean motion gives: each step moves by the
+/// distance still to go over the luminary's speed at that moment, measured
+/// an hour either side.
+pub fn next_return_with(
+    ephemeris: &dyn Ephemeris,
+    return_type: ReturnType,
+    natal_longitude: f64,
+    from: DateTime<Utc>,
+) -> Result<DateTime<Utc>, AstroError> {
+    let planet = return_type.planet();
+    let longitude_at = |jd: f64| -> Result<f64, AstroError> {
+        Ok(find_position(&ephemeris.positions_of(jd, &[planet])?, planet)?.longitude)
+    };
+    let daily_motion = 360.0 / return_type.period_days();
+
+    let start = datetime_to_jd(from);
+    let mut to_go = normalize_longitude(natal_longitude - longitude_at(start)?);
+    loop {
+        // Coming right back to the natal longitude is this return, not the next
+        if to_go < RETURN_TOLERANCE {
+            to_go += 360.0;
+        }
+        let mut jd = start + to_go / daily_motion;
+        for _ in 0..MAX_ITERATIONS {
+            let error = signed_difference(natal_longitude, longitude_at(jd)?);
+            if error.abs() < RETURN_TOLERANCE {
+                break;
+            }
+            let hour = 1.0 / 24.0;
+            let speed = signed_difference(longitude_at(jd + hour)?, longitude_at(jd - hour)?) / (2.0 * hour);
+            if speed <= 0.0 {
+                return Err(AstroError::EphemerisFailure(format!("{} is not moving forward", planet.name())));
+            }
+            jd += error / speed;
+        }
+        if signed_difference(natal_longitude, longitude_at(jd)?).abs() >= RETURN_TOLERANCE {
+            return Err(AstroError::EphemerisFailure(format!("no {} found", return_type.name().to_lowercase())));
+        }
+        if jd > start {
+            return jd_to_datetime(jd).ok_or_else(|| AstroError::EphemerisFailure(format!("invalid Julian day {jd}")));
+        }
+        // Converged on the return just before `from`: go round once more
+        to_go += 360.0;
+    }
+}
+
+/// When the Moon next comes back to `natal_moon_longitude` after `from`
+pub fn calculate_next_lunar_return(natal_moon_longitude: f64, from: DateTime<Utc>) -> Result<DateTime<Utc>, AstroError> {
+    next_return_with(&AstroEphemeris, ReturnType::Lunar, natal_moon_longitude, from)
+}
+
+/// When the Sun next comes back to `natal_sun_longitude` after `from`
+pub fn calculate_next_solar_return(natal_sun_longitude: f64, from: DateTime<Utc>) -> Result<DateTime<Utc>, AstroError> {
+    next_return_with(&AstroEphemeris, ReturnType::Solar, natal_sun_longitude, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::calculate_planetary_positions_for_jd;
+    use chrono::{Duration, TimeZone};
+
+    fn longitude(planet: Planet, at: DateTime<Utc>) -> f64 {
+        find_position(&calculate_planetary_positions_for_jd(datetime_to_jd(at)).unwrap(), planet).unwrap().longitude
+    }
+
+    #[test]
+    fn test_lunar_returns_come_monthly() {
+        let birth = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let natal_moon = longitude(Planet::Moon, birth);
+
+        let mut previous = birth;
+        for _ in 0..3 {
+            let next = calculate_next_lunar_return(natal_moon, previous).unwrap();
+            let hours = (next - previous).num_hours() - (LUNAR_RETURN_DAYS * 24.0).round() as i64;
+            assert!(hours.abs() <= 24, "{hours}h off the mean, from {previous} to {next}");
+            assert!(signed_difference(longitude(Planet::Moon, next), natal_moon).abs() < 0.001);
+            previous = next;
+        }
+
+        // Part way round, the next return is the rest of the way
+        let halfway = birth + Duration::days(14);
+        let next = calculate_next_lunar_return(natal_moon, halfway).unwrap();
+        assert!((next - calculate_next_lunar_return(natal_moon, birth).unwrap()).num_seconds().abs() < 1);
+    }
+
+    #[test]
+    fn test_solar_return() {
+        // Born at the March equinox, so the Sun comes back to 0° Aries a year on
+        let birth = Utc.with_ymd_and_hms(2025, 3, 20, 9, 1, 0).unwrap();
+        let next = calculate_next_solar_return(longitude(Planet::Sun, birth), birth).unwrap();
+        let equinox = Utc.with_ymd_and_hms(2026, 3, 20, 14, 46, 0).unwrap();
+        assert!((next - equinox).num_minutes().abs() < 60, "{next}");
+        assert_eq!(ReturnType::Solar.planet(), Planet::Sun);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Increment of the splitmix64 stream, the golden ratio in 64 bits
+const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// splitmix64's finalizer: every input bit stirs every output bit
+pub fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(GAMMA);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// The one source of every random choice the scheduler makes
+///
+/// A splitmix64 stream, plus draws keyed by the seed alone for choices that
+/// must hold still however many numbers have gone before (a PID's jitter for
+/// the day, the order of a tie). Two generators with the same seed make the
+/// same choices, so with `--seed` a run can be repeated.
+#[derive(Debug, Clone)]
+pub struct CosmicRng {
+    seed: u64,
+    state: u64,
+}
+
+impl CosmicRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Seeded differently every time, from the clock and the standard library's hash keys
+    pub fn from_entropy() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos());
+        Self::seeded(RandomState::new().hash_one((nanos, std::process::id())))
+    }
+
+    /// Seeded by `seed`, or from entropy without one
+    pub fn new(seed: Option<u64>) -> Self {
+        seed.map_or_else(Self::from_entropy, Self::seeded)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The next number in the stream
+    pub fn next_u64(&mut self) -> u64 {
+        let x = self.state;
+        self.state = x.wrapping_add(GAMMA);
+        mix(x)
+    }
+
+    /// The next number in the stream, in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let unit = (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+        unit
+    }
+
+    /// A number fixed by the seed and `key` alone
+    pub fn keyed(&self, key: u64) -> u64 {
+        mix(self.seed ^ mix(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_choices() {
+        let (mut a, mut b) = (CosmicRng::seeded(42), CosmicRng::seeded(42));
+        let stream: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(stream, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(stream, (0..8).map(|_| CosmicRng::seeded(43).next_u64()).collect::<Vec<_>>());
+
+        // Keyed draws don't move with the stream
+        assert_eq!(a.keyed(7), CosmicRng::seeded(42).keyed(7));
+        assert_ne!(a.keyed(7), a.keyed(8));
+
+        for _ in 0..1000 {
+            assert!((0.0..1.0).contains(&a.next_f64()));
+        }
+        assert_eq!(CosmicRng::new(Some(5)).seed(), 5);
+    }
+}
@@ -0,0 +1,431 @@
+/// The Sabian symbols, one image for each degree of the zodiac
+///
+/// After Marc Edmund Jones and Elsie Wheeler, 1925. Entry 0 is the symbol of the
+/// first degree of Aries, which covers longitudes from 0° up to 1°.
+pub const SABIAN_SYMBOLS: [&str; 360] = [
+    // Aries
+    "A woman has risen from the ocean",
+    "A comedian entertaining a group",
+    "A cameo profile of a man in the outline of his country",
+    "Two lovers strolling through a secluded walk",
+    "A triangle with wings",
+    "A square brightly lighted on one side",
+    "A man successfully expressing himself in two realms at once",
+    "A large hat with streamers flying, facing east",
+    "A crystal gazer",
+    "A man teaching new forms for old symbols",
+    "The ruler of a nation",
+    "A flock of wild geese",
+    "An unsuccessful bomb explosion",
+    "A serpent coiling near a man and a woman",
+    "An Indian weaving a blanket",
+    "Brownies dancing in the setting sun",
+    "Two prim spinsters",
+    "An empty hammock",
+    "The magic carpet",
+    "A young girl feeding birds in winter",
+    "A pugilist entering the ring",
+    "The gate to the garden of desire",
+    "A woman in pastel colors carrying a heavy and valuable but veiled load",
+    "An open window and a net curtain blowing into a cornucopia",
+    "A double promise",
+    "A man possessed of more gifts than he can hold",
+    "Lost opportunity regained in the imagination",
+    "A large disappointed audience",
+    "The music of the spheres",
+    "A duck pond and its young brood",
+    // Taurus
+    "A clear mountain stream",
+    "An electrical storm",
+    "Steps up to a lawn blooming with clover",
+    "The rainbow's pot of gold",
+    "A widow at an open grave",
+    "A bridge being built across a gorge",
+    "A woman of Samaria",
+    "A sleigh without snow",
+    "A Christmas tree decorated",
+    "A Red Cross nurse",
+    "A woman sprinkling flowers",
+    "Window-shoppers",
+    "A man handling baggage",
+    "Shellfish groping and children playing",
+    "A man muffled up, with a rakish silk hat",
+    "An old man attempting vainly to reveal the Mysteries",
+    "A battle between the swords and the torches",
+    "A woman holding a bag out of a window",
+    "A newly formed continent",
+    "Wisps of clouds, like wings, streaming across the sky",
+    "A finger pointing in an open book",
+    "A white dove over troubled waters",
+    "A jewelry shop",
+    "A mounted Indian with scalp locks",
+    "A large well-kept public park",
+    "A Spaniard serenading his señorita",
+    "An old Indian woman selling beads",
+    "A woman pursued by mature romance",
+    "Two cobblers working at a table",
+    "A peacock parading on an ancient lawn",
+    // Gemini
+    "A glass-bottomed boat in still water",
+    "Santa Claus filling stockings furtively",
+    "The garden of the Tuileries",
+    "Holly and mistletoe reawaken old memories of Christmas",
+    "A radical magazine",
+    "Drilling for oil",
+    "An old-fashioned well",
+    "An industrial strike",
+    "A quiver filled with arrows",
+    "An airplane falling",
+    "Newly opened lands offer the pioneer new opportunities",
+    "A young girl saucily asserting herself",
+    "A great musician at his piano",
+    "A conversation by telepathy",
+    "Two Dutch children talking",
+    "A woman suffragist orating",
+    "The head of a robust youth changes into that of a mature thinker",
+    "Two men talking in their own tongue in a foreign crowd",
+    "A large archaic volume",
+    "A cafeteria",
+    "A labor demonstration",
+    "A barn dance",
+    "Three fledglings in a nest high in a tree",
+    "Children skating on ice",
+    "A man trimming palms",
+    "Winter frost in the woods",
+    "A gypsy coming out of the forest",
+    "A man declared bankrupt",
+    "The first mockingbird of spring",
+    "Bathing beauties",
+    // Cancer
+    "On a ship the sailors lower an old flag and raise a new one",
+    "A man on a magic carpet hovers over an area of great beauty",
+    "A man bundled in fur leading a shaggy deer",
+    "A cat arguing with a mouse",
+    "An automobile wrecked by a train",
+    "Game birds feathering their nests",
+    "Two fairies on a moonlit night",
+    "Rabbits dressed in clothes and on parade",
+    "A tiny nude miss reaching in the water for a fish",
+    "A large diamond not completely cut",
+    "A clown making grimaces",
+    "A woman nursing a baby whose aura reveals him to be a great teacher reborn",
+    "One hand slightly flexed with a very prominent thumb",
+    "A very old man facing a vast dark space to the northeast",
+    "A group of people who have overeaten and enjoyed it",
+    "A man studying a mandala in front of him",
+    "The germ grows into knowledge and life",
+    "A hen scratching for her chicks",
+    "A priest performing a marriage ceremony",
+    "Gondoliers in a serenade",
+    "A prima donna singing",
+    "A woman awaiting a sailboat",
+    "The meeting of a literary society",
+    "A woman and two men castaways on a small island of the South Seas",
+    "A leader of men wrapped in an invisible mantle of power",
+    "Guests are reading in the library of a luxurious home",
+    "A violent storm in a canyon filled with expensive homes",
+    "A modern Pocahontas",
+    "A muse weighing twins",
+    "A Daughter of the American Revolution",
+    // Leo
+    "Under emotional stress, blood rushes to a man's head",
+    "An epidemic of mumps",
+    "A mature woman, keeping up with the times, having her hair bobbed",
+    "A man formally dressed stands near trophies he brought back from a hunting expedition",
+    "Rock formations tower over a deep canyon",
+    "An old-fashioned woman is confronted by an up-to-date girl",
+    "The constellations in the sky",
+    "A Bolshevik propagandist",
+    "Glass blowers",
+    "Early morning dew",
+    "Children on a swing in a huge oak tree",
+    "An evening party of adults on a lawn illumined by fancy lanterns",
+    "An old sea captain rocking",
+    "The human soul awaiting opportunities for expression",
+    "A pageant",
+    "Brilliant sunshine just after a storm",
+    "A non-vested church choir",
+    "A teacher of chemistry",
+    "A houseboat party",
+    "The Zuni sun worshippers",
+    "Chickens intoxicated",
+    "A carrier pigeon",
+    "A bareback rider",
+    "An untidy, unkempt man",
+    "A large camel crossing the desert",
+    "A rainbow",
+    "Daybreak",
+    "Many little birds on a limb of a large tree",
+    "A mermaid",
+    "An unsealed letter",
+    // Virgo
+    "A man's head",
+    "A large white cross upraised",
+    "Two angels bringing protection",
+    "Black and white children playing together happily",
+    "A man dreaming of fairies",
+    "A merry-go-round",
+    "A harem",
+    "First dancing instruction",
+    "A man making a futurist drawing",
+    "Two heads looking out and beyond the shadows",
+    "A boy molded in his mother's aspirations for him",
+    "A bride with her veil snatched away",
+    "A strong hand supplanting political hysteria",
+    "A family tree",
+    "An ornamental handkerchief",
+    "An orangutan",
+    "A volcanic eruption",
+    "An Ouija board",
+    "A swimming race",
+    "An automobile caravan",
+    "A girls' basketball team",
+    "A royal coat of arms",
+    "An animal trainer",
+    "Mary and her white lamb",
+    "A flag at half-mast",
+    "A boy with a censer",
+    "Grande dames at tea",
+    "A bald-headed man",
+    "A man gaining secret knowledge from an ancient scroll he is reading",
+    "A false call unheard in attention to immediate service",
+    // Libra
+    "A butterfly made perfect by a dart through it",
+    "The light of the sixth race transmuted to the seventh",
+    "The dawn of a new day reveals everything changed",
+    "A group around a campfire",
+    "A man teaching the true inner knowledge",
+    "The ideals of a man abundantly crystallized",
+    "A woman feeding chickens and protecting them from the hawks",
+    "A blazing fireplace in a deserted home",
+    "Three old masters hanging in an art gallery",
+    "A canoe approaching safety through dangerous waters",
+    "A professor peering over his glasses",
+    "Miners emerging from a mine",
+    "Children blowing soap bubbles",
+    "A noon siesta",
+    "Circular paths",
+    "A boat landing washed away",
+    "A retired sea captain",
+    "Two men placed under arrest",
+    "A gang of robbers in hiding",
+    "A rabbi performing his duties",
+    "A crowd upon the beach",
+    "A child giving birds a drink at a fountain",
+    "Chanticleer",
+    "A third wing on the left side of a butterfly",
+    "The sight of an autumn leaf brings a pilgrim the revelation of the mystery of life and death",
+    "An eagle and a large white dove turning one into the other",
+    "An airplane hovering overhead",
+    "A man in the midst of brightening influences",
+    "Humanity seeking to bridge the span of knowledge",
+    "Three mounds of knowledge on a philosopher's head",
+    // Scorpio
+    "A sight-seeing bus",
+    "A broken bottle and spilled perfume",
+    "A house-raising",
+    "A youth holding a lighted candle",
+    "A massive rocky shore",
+    "A gold rush",
+    "Deep-sea divers",
+    "The moon shining across a lake",
+    "Dental work",
+    "A fellowship supper",
+    "A drowning man rescued",
+    "An embassy ball",
+    "An inventor experimenting",
+    "Telephone linemen at work",
+    "Children playing around five mounds of sand",
+    "A girl's face breaking into a smile",
+    "A woman the father of her own child",
+    "A woods rich in autumn coloring",
+    "A parrot listening and then talking",
+    "A woman drawing two dark curtains aside",
+    "A soldier derelict in duty",
+    "Hunters starting out for ducks",
+    "A bunny metamorphosed into a fairy",
+    "Crowds coming down the mountain to listen to one man",
+    "An X-ray",
+    "Indians making camp",
+    "A military band on the march",
+    "The king of the fairies approaching his domain",
+    "A mother pleading with the chief for the lives of her children",
+    "The Halloween jester",
+    // Sagittarius
+    "A Grand Army of the Republic campfire",
+    "The ocean covered with whitecaps",
+    "Two men playing chess",
+    "A little child learning to walk",
+    "An old owl up in a tree",
+    "A game of cricket",
+    "Cupid knocking at the door",
+    "Rocks and things forming therein",
+    "A mother with her children on stairs",
+    "A golden-haired goddess of opportunity",
+    "The lamp of physical enlightenment at the left temple",
+    "A flag that turns into an eagle that crows",
+    "A widow's past brought to light",
+    "The pyramids and the Sphinx",
+    "The groundhog looking for its shadow",
+    "Sea gulls watching a ship",
+    "An Easter sunrise service",
+    "Tiny children in sunbonnets",
+    "Pelicans moving their habitat",
+    "Men cutting through ice",
+    "A child and a dog with borrowed eyeglasses",
+    "A Chinese laundry",
+    "Immigrants entering",
+    "A bluebird standing at the door of the house",
+    "A chubby boy on a hobby-horse",
+    "A flag-bearer",
+    "A sculptor",
+    "An old bridge over a beautiful stream",
+    "A fat boy mowing the lawn",
+    "The Pope blessing the faithful",
+    // Capricorn
+    "An Indian chief demanding recognition",
+    "Three stained-glass windows, one damaged by bombardment",
+    "The human soul receptive to growth and understanding",
+    "A party entering a large canoe",
+    "Indians rowing a canoe and dancing a war dance",
+    "A dark archway and ten logs at the bottom",
+    "A veiled prophet of power",
+    "Birds in the house singing happily",
+    "An angel carrying a harp",
+    "An albatross feeding from the hand",
+    "A large group of pheasants",
+    "A student of nature lecturing",
+    "A fire worshipper",
+    "An ancient bas-relief carved in granite",
+    "Many toys in the children's ward of a hospital",
+    "Boys and girls in gymnasium suits",
+    "A girl surreptitiously bathing in the nude",
+    "The Union Jack flag",
+    "A child of about five with a huge shopping bag",
+    "A hidden choir singing",
+    "A relay race",
+    "A general accepting defeat gracefully",
+    "Two awards for bravery in war",
+    "A woman entering a convent",
+    "An oriental rug dealer",
+    "A water sprite",
+    "A mountain pilgrimage",
+    "A large aviary",
+    "A woman reading tea leaves",
+    "A secret business conference",
+    // Aquarius
+    "An old adobe mission",
+    "An unexpected thunderstorm",
+    "A deserter from the navy",
+    "A Hindu healer",
+    "A council of ancestors",
+    "A performer of a mystery play",
+    "A child born of an eggshell",
+    "Beautifully gowned wax figures",
+    "A flag turned into an eagle",
+    "A popularity that proves ephemeral",
+    "Man tête-à-tête with his inspiration",
+    "People on a vast staircase, graduated upwards",
+    "A barometer",
+    "A train entering a tunnel",
+    "Two lovebirds singing on a fence",
+    "A big businessman at his desk",
+    "A watchdog standing guard",
+    "A man unmasked",
+    "A forest fire quenched",
+    "A big white dove, a message bearer",
+    "A woman disappointed and disillusioned",
+    "A rug placed on the floor for children to play",
+    "A big bear sitting down and waving all its paws",
+    "A man turning his back on his passions and teaching from his experience",
+    "A butterfly with the right wing more perfectly formed",
+    "A hydrometer",
+    "An ancient pottery bowl filled with violets",
+    "A tree felled and sawed",
+    "A butterfly emerging from a chrysalis",
+    "The field of Ardath in bloom",
+    // Pisces
+    "A public market",
+    "A squirrel hiding from hunters",
+    "A petrified forest",
+    "Heavy traffic on a narrow isthmus",
+    "A church bazaar",
+    "Officers on dress parade",
+    "A cross lying on rocks",
+    "A girl blowing a bugle",
+    "A jockey",
+    "An aviator in the clouds",
+    "Men seeking illumination",
+    "An examination of initiates",
+    "A sword in a museum",
+    "A lady in fox fur",
+    "An officer preparing to drill his men",
+    "The flow of inspiration",
+    "An Easter promenade",
+    "A gigantic tent",
+    "A master instructing his pupil",
+    "A table set for an evening meal",
+    "A little white lamb, a child and a servant",
+    "A man bringing down the new law from Sinai",
+    "Spiritist phenomena",
+    "An inhabited island",
+    "The purging of the priesthood",
+    "A new moon that divides its influences",
+    "A harvest moon",
+    "A fertile garden under the full moon",
+    "A prism",
+    "The Great Stone Face",
+];
+
+/// Index into `SABIAN_SYMBOLS` of the degree a longitude falls in
+fn sabian_index(longitude: f64) -> usize {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let index = longitude.rem_euclid(360.0).floor() as usize;
+    index.min(SABIAN_SYMBOLS.len() - 1)  // rem_euclid rounds a tiny negative up to 360.0
+}
+
+/// The Sabian degree of a longitude within its sign, 1 to 30
+///
+/// Sabian degrees count from one: 7°12′ Scorpio is the eighth degree.
+pub fn sabian_degree(longitude: f64) -> u32 {
+    #[allow(clippy::cast_possible_truncation)]
+    let degree = (sabian_index(longitude) % 30) as u32;
+    degree + 1
+}
+
+/// The Sabian symbol of the degree a longitude falls in
+pub fn get_sabian_symbol(longitude: f64) -> &'static str {
+    SABIAN_SYMBOLS[sabian_index(longitude)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_degree_has_a_symbol() {
+        assert!(SABIAN_SYMBOLS.iter().all(|symbol| !symbol.trim().is_empty()));
+        let mut distinct = SABIAN_SYMBOLS.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 360);
+    }
+
+    #[test]
+    fn test_sabian_lookup() {
+        assert_eq!(get_sabian_symbol(0.0), "A woman has risen from the ocean");
+        assert_eq!(sabian_degree(0.0), 1);
+        assert_eq!(get_sabian_symbol(0.99), get_sabian_symbol(0.0));
+
+        // 7°12′ Libra is the eighth degree of Libra
+        assert_eq!(get_sabian_symbol(187.2), "A blazing fireplace in a deserted home");
+        assert_eq!(sabian_degree(187.2), 8);
+
+        // The last degree of Pisces, and round again
+        assert_eq!(get_sabian_symbol(359.5), "The Great Stone Face");
+        assert_eq!(sabian_degree(359.5), 30);
+        assert_eq!(get_sabian_symbol(360.0), get_sabian_symbol(0.0));
+        assert_eq!(get_sabian_symbol(-0.5), "The Great Stone Face");
+        assert_eq!(get_sabian_symbol(-1e-15), "The Great Stone Face");
+    }
+}
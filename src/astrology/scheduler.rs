@@ -1,62 +1,875 @@
-use super::planets::{Planet, Element, PlanetaryPosition, MoonPhase, calculate_planetary_positions};
-use super::tasks::{TaskType, TaskClassifier};
+use super::aspects::{Aspect, aspect_modifier, aspects_to, moon_void_of_course, tension_score};
+use super::composite::{CompositeChart, NatalChart};
+use super::ephemeris::{AstroEphemeris, Chart, ChartSource, Ephemeris};
+use super::error::AstroError;
+use super::fixed_stars::{fixed_star_factor, get_fixed_star_conditions, FixedStarCondition};
+use super::heliacal::{elongation_in, heliacal_crossing, HeliacalRising, HELIACAL_BONUS};
+use super::hours::{planetary_hours, PlanetaryHour};
+use super::ingress_notifier::IngressNotifier;
+use super::lots::{PartOfFortune, FORTUNE_BONUS};
+use super::mundane::{MundaneEvent, MundaneWatch, RECESSION_FACTOR};
+use super::nodes::{is_at_bending, BENDING_PENALTY};
+use super::relocation::{calculate_angular_cpus, AngularCpus};
+use super::return_charts::{next_return_with, ReturnType};
+use super::rng::CosmicRng;
+use super::sabian::{get_sabian_symbol, sabian_degree};
+use super::stations::{jd_to_datetime, retrograde_window_jd};
+use super::solar_arc::{LifecycleEvent, ProgressedState, SolarArcProgressor};
+use super::solar::{solar_condition, SolarCondition};
+use super::synergy::TaskTypeSynergy;
+use super::venus::{venus_elongation, venus_phase_modifier, VenusPhase};
+use super::planets::{Element, Planet, PlanetaryPosition, MoonPhase, ZodiacSign, find_position, julian_day, sun_sign_at};
+use super::tasks::{TaskType, TaskClassifier, RulershipMap, Provenance, CriticalPolicy, CriticalReason};
 use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// Kernel weight of a task at nice 0
+pub const DEFAULT_TASK_WEIGHT: u64 = 100;
+
+/// Priority multiplier when the ruler stands in the element of the task's own sun sign
+const SUN_SIGN_AFFINITY: f64 = 1.1;
+
+/// Sun signs remembered before the cache starts over (PIDs get recycled)
+const MAX_SUN_SIGNS: usize = 4096;
+
+/// Natal charts kept, one per minute of birth, before the cache starts over
+const MAX_NATAL_CHARTS: usize = 1024;
+
+/// Process pair affinities remembered before the cache starts over
+const MAX_AFFINITIES: usize = 4096;
+
+/// Influence added (direct) or taken away (retrograde) by a ruler on a critical degree
+const CRITICAL_DEGREE_MODIFIER: f64 = 0.2;
+
+/// Influence added by a direct ruler standing in its own Egyptian term
+const TERM_DIGNITY_BONUS: f64 = 0.1;
+
+/// How far a ruler's aspects must move its priority before the weather report mentions them
+const ASPECT_NOTE_THRESHOLD: f64 = 0.05;
 
 /// Scheduling decision with astrological reasoning
 #[derive(Debug, Clone)]
 pub struct SchedulingDecision {
+    pub task_type: TaskType,
+    pub critical: bool,             // Exempt from every cosmic penalty, including retrograde slice cuts
+    pub ruling_element: Option<Element>,  // Element of the ruling planet's sign (None for Critical)
     pub priority: u32,
+    pub astro_priority: u32,        // Priority before blending in the kernel weight
+    pub weight: u64,                // Kernel weight the decision was made with
+    pub weight_factor: f64,         // Multiplier derived from the task's nice level
     pub reasoning: String,
     pub planetary_influence: f64,  // -1.0 to 1.0
-    #[allow(dead_code)]  // Used internally in calculations, not accessed externally
     pub element_boost: f64,         // Multiplier (includes moon phase for Interactive tasks)
 }
 
+/// How a chart treats one task type
+struct TypeAssessment<'a> {
+    planet_pos: &'a PlanetaryPosition,
+    planetary_influence: f64,
+    element_boost: f64,
+    strongest_aspect: Option<Aspect>,
+    solar: SolarCondition,
+    newborn: bool,  // The ruler rose heliacally since the previous chart
+    priority: u32,
+}
+
+/// Priorities the sky would hand out at one moment
+#[derive(Debug, Clone)]
+pub struct ForecastHour {
+    pub time: DateTime<Utc>,
+    pub priorities: Vec<(TaskType, u32)>,  // Every type the stars can influence
+    pub retrograde: Vec<Planet>,
+}
+
+/// One hour of a forecast for a single element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementForecastPoint {
+    pub timestamp: DateTime<Utc>,
+    pub element_count: usize,  // Of the element's planets, how many stand in its signs
+    pub planet_in_own_sign: bool,
+    pub retrograde_count: usize,
+    pub recommended_priority: u32,  // For the element's task type, before aspects and per-task modifiers
+}
+
+/// The task type an element's forecast is for, and the planets that decide it
+///
+/// Jupiter and Saturn watch over Earth and Water, Mercury and Mars over Fire
+/// and Air; the task type's usual ruler comes first.
+pub fn element_forecast_subject(element: Element) -> (TaskType, [Planet; 2]) {
+    match element {
+        Element::Fire => (TaskType::CpuIntensive, [Planet::Mars, Planet::Mercury]),
+        Element::Air => (TaskType::Network, [Planet::Mercury, Planet::Mars]),
+        Element::Earth => (TaskType::System, [Planet::Saturn, Planet::Jupiter]),
+        Element::Water => (TaskType::MemoryHeavy, [Planet::Jupiter, Planet::Saturn]),
+    }
+}
+
+/// Side-by-side comparison of the astrological conditions at two moments
+#[derive(Debug, Clone)]
+pub struct ConditionComparison {
+    pub better_time: DateTime<Utc>,
+    pub worse_time: DateTime<Utc>,
+    pub advantage_by_type: HashMap<TaskType, f64>,  // Priority at `a` minus priority at `b`
+    pub retrograde_count_a: usize,
+    pub retrograde_count_b: usize,
+    pub overall_score_a: f64,
+    pub overall_score_b: f64,
+}
+
+impl ConditionComparison {
+    /// True when neither moment is favored by the stars
+    pub fn is_tie(&self) -> bool {
+        (self.overall_score_a - self.overall_score_b).abs() < f64::EPSILON
+    }
+}
+
+impl fmt::Display for ConditionComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // better_time is always `a` unless `b` scored strictly higher
+        let b_won = self.overall_score_b > self.overall_score_a;
+        let (time_a, time_b) = if b_won {
+            (self.worse_time, self.better_time)
+        } else {
+            (self.better_time, self.worse_time)
+        };
+
+        writeln!(f, "🔭 SCHEDULING WINDOW COMPARISON 🔭")?;
+        writeln!(f, "  A: {}", time_a.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(f, "  B: {}", time_b.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(f)?;
+        writeln!(f, "  {:<16} {:>12}", "Task type", "A - B")?;
+        for task_type in TaskType::all() {
+            if let Some(advantage) = self.advantage_by_type.get(&task_type) {
+                writeln!(f, "  {:<16} {:>+12.1}", task_type.name(), advantage)?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(f, "  {:<16} {:>12.1} {:>12.1}", "Overall score", self.overall_score_a, self.overall_score_b)?;
+        writeln!(f, "  {:<16} {:>12} {:>12}", "Retrogrades", self.retrograde_count_a, self.retrograde_count_b)?;
+        writeln!(f)?;
+
+        if self.is_tie() {
+            write!(f, "⚖️  The cosmos is indifferent - both windows are equally blessed")
+        } else {
+            let winner = if b_won { "B" } else { "A" };
+            write!(f, "✨ Window {winner} is favored by the stars")
+        }
+    }
+}
+
 /// The main astrological scheduler
+#[allow(clippy::struct_excessive_bools)]  // Independent feature switches
 pub struct AstrologicalScheduler {
+    ephemeris: Arc<dyn Ephemeris + Send + Sync>,
     classifier: TaskClassifier,
     planetary_cache: Option<(DateTime<Utc>, Vec<PlanetaryPosition>)>,
-    cache_duration_secs: i64,
+    north_node: Option<f64>,    // Refreshed along with the planetary cache
+    fortune: Option<PartOfFortune>,  // Likewise, when there is a location to cast it for
+    fixed_stars: bool,          // Let planets on Behenian stars take on their nature
+    fixed_star_conditions: Vec<FixedStarCondition>,  // Planets on a Behenian star in the cached chart
+    cache_duration_secs: i64,   // Update interval the chart is refreshed on
+    update_jitter: f64,         // Fraction of the update interval each refresh may come early or late by
+    refresh_after_secs: i64,    // Age at which the current chart is due for a refresh
+    rng: CosmicRng,             // Behind every random choice
+    charts_computed: u64,       // Charts computed on the calling thread
+    weight_blend: f64,
+    rulership: RulershipMap,
+    critical: CriticalPolicy,
+    base_priorities: HashMap<TaskType, u32>,  // Overrides of default_base_priority
+    influence_baseline: f64,    // Influence of a direct planet in the weakest element
+    retrograde_influence: f64,  // Influence of a retrograde planet (-1.0 = full penalty)
+    planet_retrograde_influences: HashMap<Planet, f64>,  // Overrides of retrograde_influence
+    retrograde_exempt: HashSet<TaskType>,  // Task types whose retrograde rulers count as direct
+    out_of_bounds_jitter: bool,
+    aspects: bool,              // Scale priority by the ruler's aspects to other planets
+    sun_sign_affinity: bool,    // Favor tasks whose ruler shares the element of their sun sign
+    sun_signs: HashMap<i32, ZodiacSign>,  // Sign the Sun was in when each PID started
+    births: HashMap<i32, DateTime<Utc>>,  // When each PID started
+    natal_charts: HashMap<i64, NatalChart>,  // By minute of birth: processes born together share one
+    affinities: HashMap<(i32, i32), f64>,  // Composite scores, lower PID first
+    angular_cpus: Option<(i64, HashMap<i64, AngularCpus>)>,  // By minute of birth, for the minute drawn
+    progressor: Option<SolarArcProgressor>,  // Progress natal charts by solar arc
+    progressions: HashMap<i32, ProgressedState>,  // Where each PID's progressed chart stood at the last check
+    location: Option<(f64, f64)>,  // Observer latitude and longitude for planetary hours
+    unknown_moon: bool,         // Let unclassified (default Interactive) tasks ride the Moon phase
+    retrograde_windows: HashMap<Planet, (DateTime<Utc>, DateTime<Utc>)>,  // Stations found so far
+    fast_path: Option<(DateTime<Utc>, HashMap<TaskType, SchedulingDecision>)>,  // Per-type decisions for the cached chart
+    synergy: Option<(DateTime<Utc>, TaskTypeSynergy)>,  // Between task types, for the cached chart
+    running_types: HashSet<TaskType>,  // Task types scheduled since the chart was cast
+    co_running: Vec<TaskType>,  // Task types scheduled under the previous chart
+    reasoning: bool,            // Spell out each decision in SchedulingDecision::reasoning
+    revision: u64,              // Bumped by every change to how tasks are judged, other than the chart
+    warmup_remaining: u64,      // Decisions still to be made at base priority, before the stars count
+    ingress: IngressNotifier,   // Told of every sign change between one chart and the next
+    anonymous_tasks: u64,       // Decisions made for tasks with an empty name
+    elongations: Option<(f64, Vec<(Planet, f64)>)>,  // From the Sun at the last heliacal check, by Julian day
+    newborn: HashSet<Planet>,   // Risen heliacally since the previous chart
+    mundane: Option<MundaneWatch>,  // Watching the charts for world events
 }
 
 impl AstrologicalScheduler {
     pub fn new(cache_duration_secs: i64) -> Self {
         Self {
+            ephemeris: Arc::new(AstroEphemeris),
             classifier: TaskClassifier::new(),
             planetary_cache: None,
+            north_node: None,
+            fortune: None,
+            fixed_stars: true,
+            fixed_star_conditions: Vec::new(),
             cache_duration_secs,
+            update_jitter: 0.0,
+            refresh_after_secs: cache_duration_secs,
+            rng: CosmicRng::seeded(0),
+            charts_computed: 0,
+            weight_blend: 0.5,
+            rulership: RulershipMap::default(),
+            critical: CriticalPolicy::default(),
+            base_priorities: HashMap::new(),
+            influence_baseline: 1.0,
+            retrograde_influence: -1.0,
+            planet_retrograde_influences: HashMap::new(),
+            retrograde_exempt: HashSet::new(),
+            out_of_bounds_jitter: true,
+            aspects: true,
+            sun_sign_affinity: false,
+            sun_signs: HashMap::new(),
+            births: HashMap::new(),
+            natal_charts: HashMap::new(),
+            affinities: HashMap::new(),
+            angular_cpus: None,
+            progressor: None,
+            progressions: HashMap::new(),
+            location: None,
+            unknown_moon: true,
+            retrograde_windows: HashMap::new(),
+            fast_path: None,
+            synergy: None,
+            running_types: HashSet::new(),
+            co_running: Vec::new(),
+            reasoning: true,
+            revision: 0,
+            warmup_remaining: 0,
+            ingress: IngressNotifier::new(),
+            anonymous_tasks: 0,
+            elongations: None,
+            newborn: HashSet::new(),
+            mundane: None,
+        }
+    }
+
+    /// Treat a PID as `task_type` from now on, whatever its name says
+    pub fn learn_task_type(&mut self, pid: i32, task_type: TaskType) {
+        self.classifier.learn(pid, task_type);
+        self.revision += 1;
+    }
+
+    /// Stop treating a PID as `task_type`, if that is what was learned
+    pub fn unlearn_task_type(&mut self, pid: i32, task_type: TaskType) {
+        self.classifier.unlearn(pid, task_type);
+        self.revision += 1;
+    }
+
+    /// Where to register callbacks for planets changing sign between one chart and the next
+    pub fn ingress_notifier_mut(&mut self) -> &mut IngressNotifier {
+        &mut self.ingress
+    }
+
+    /// The classifier deciding task types from names
+    pub fn classifier(&self) -> &TaskClassifier {
+        &self.classifier
+    }
+
+    /// Classify tasks named like `pattern` as `task_type` from now on
+    pub fn add_classifier_rule(&mut self, pattern: &str, task_type: TaskType) -> Result<(), AstroError> {
+        self.revision += 1;
+        self.classifier.add_rule(pattern, task_type)
+    }
+
+    /// The planet ruling a task type
+    pub fn ruler(&self, task_type: TaskType) -> Planet {
+        self.rulership.ruler(task_type)
+    }
+
+    /// Changes whenever the classifier or base priorities do
+    ///
+    /// Together with the chart epoch, an unchanged revision means a task
+    /// scheduled again with the same name, weight and flags gets the same
+    /// decision.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The planetary positions decisions are currently made with
+    pub fn positions(&mut self, now: DateTime<Utc>) -> Result<Vec<PlanetaryPosition>, AstroError> {
+        self.ensure_chart(now)?;
+        Ok(self.cached_positions()?.to_vec())
+    }
+
+    /// How strongly each planet acts in the current chart, in chart order
+    ///
+    /// The influence a task ruled by the planet starts from: its element,
+    /// term dignity, critical degrees, closeness to the Sun and retrograde
+    /// motion all count.
+    pub fn dignity_scores(&mut self, now: DateTime<Utc>) -> Result<Vec<(Planet, f64)>, AstroError> {
+        self.ensure_chart(now)?;
+        let positions = self.cached_positions()?;
+        let sun = find_position(positions, Planet::Sun).ok();
+        Ok(positions.iter().map(|pos| (pos.planet, self.calculate_planetary_influence(pos, sun))).collect())
+    }
+
+    /// Birth charts cast for processes and not yet dropped
+    pub fn birth_chart_count(&self) -> usize {
+        self.natal_charts.len()
+    }
+
+    /// Whether the Moon is void of course in the current chart
+    pub fn moon_void_of_course(&mut self, now: DateTime<Utc>) -> Result<bool, AstroError> {
+        self.ensure_chart(now)?;
+        Ok(moon_void_of_course(self.cached_positions()?))
+    }
+
+    /// How tense the current chart is, 0 to 100
+    pub fn tension_score(&mut self, now: DateTime<Utc>) -> Result<u32, AstroError> {
+        self.ensure_chart(now)?;
+        Ok(tension_score(self.cached_positions()?))
+    }
+
+    /// Forget anything learned about a PID
+    pub fn forget_task(&mut self, pid: i32) {
+        self.classifier.forget(pid);
+        self.sun_signs.remove(&pid);
+        if self.births.remove(&pid).is_some() {
+            self.affinities.retain(|&(a, b), _| a != pid && b != pid);
+            self.progressions.remove(&pid);
+        }
+    }
+
+    /// Whether the sun sign of a PID is already known
+    pub fn knows_sun_sign(&self, pid: i32) -> bool {
+        self.sun_signs.contains_key(&pid)
+    }
+
+    /// Record when a PID started, fixing its sun sign
+    pub fn note_task_start(&mut self, pid: i32, start: DateTime<Utc>) {
+        if self.sun_signs.len() >= MAX_SUN_SIGNS {
+            self.sun_signs.clear();
+            self.births.clear();
+            self.affinities.clear();
+            self.progressions.clear();
+        }
+        self.sun_signs.insert(pid, sun_sign_at(start));
+        self.births.insert(pid, start);
+    }
+
+    /// The chart of a process born at `birth`, to the minute
+    fn natal_chart(&mut self, birth: DateTime<Utc>) -> Option<&NatalChart> {
+        let minute = birth.timestamp().div_euclid(60);
+        if !self.natal_charts.contains_key(&minute) {
+            if self.natal_charts.len() >= MAX_NATAL_CHARTS {
+                self.natal_charts.clear();
+            }
+            let birth = DateTime::from_timestamp(minute * 60, 0)?;
+            let chart = NatalChart::cast(self.ephemeris.as_ref(), birth).ok()?;
+            self.natal_charts.insert(minute, chart);
+        }
+        self.natal_charts.get(&minute)
+    }
+
+    /// How well two processes get along, from 0.0 to 2.0, by their composite chart
+    ///
+    /// None unless both start times were noted, and not after `now`.
+    pub fn compute_process_affinity(&mut self, pid_a: i32, pid_b: i32, now: DateTime<Utc>) -> Option<f64> {
+        let key = (pid_a.min(pid_b), pid_a.max(pid_b));
+        if let Some(&score) = self.affinities.get(&key) {
+            return Some(score);
+        }
+        let (birth_a, birth_b) = (*self.births.get(&pid_a)?, *self.births.get(&pid_b)?);
+        if birth_a > now || birth_b > now {
+            return None;
+        }
+        let a = self.natal_chart(birth_a)?.clone();
+        let b = self.natal_chart(birth_b)?;
+        let score = CompositeChart::new(&a, b).compatibility_score();
+        if self.affinities.len() >= MAX_AFFINITIES {
+            self.affinities.clear();
         }
+        self.affinities.insert(key, score);
+        Some(score)
     }
 
-    fn get_planetary_positions(&mut self, now: DateTime<Utc>) -> &Vec<PlanetaryPosition> {
-        let needs_refresh = match &self.planetary_cache {
-            None => true,
-            Some((cached_time, _)) => {
-                now.timestamp() - cached_time.timestamp() > self.cache_duration_secs
+    /// The CPUs of `cpu_count` on which the planet ruling `task_type` is angular in a PID's natal chart, at `now`
+    ///
+    /// The angles turn with the sky, so the CPUs are drawn again each minute.
+    /// None unless the PID's start time was noted.
+    pub fn angular_cpus(&mut self, pid: i32, task_type: TaskType, now: DateTime<Utc>, cpu_count: u32) -> Option<Vec<u32>> {
+        let birth = *self.births.get(&pid)?;
+        let minute = now.timestamp().div_euclid(60);
+        if self.angular_cpus.as_ref().is_none_or(|(drawn, _)| *drawn != minute) {
+            self.angular_cpus = Some((minute, HashMap::new()));
+        }
+        let birth_minute = birth.timestamp().div_euclid(60);
+        let drawn = self.angular_cpus.as_ref().is_some_and(|(_, charts)| charts.contains_key(&birth_minute));
+        if !drawn {
+            let angular = calculate_angular_cpus(self.natal_chart(birth)?, now, cpu_count);
+            let (_, charts) = self.angular_cpus.as_mut()?;
+            if charts.len() >= MAX_NATAL_CHARTS {
+                charts.clear();
             }
+            charts.insert(birth_minute, angular);
+        }
+        let (_, charts) = self.angular_cpus.as_ref()?;
+        Some(charts.get(&birth_minute)?.get(&self.rulership.ruler(task_type)).cloned().unwrap_or_default())
+    }
+
+    /// When the Sun or Moon next comes back to where it stood as a PID started
+    ///
+    /// None unless the PID's start time was noted.
+    pub fn get_next_process_return(&mut self, pid: i32, return_type: ReturnType, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let birth = *self.births.get(&pid)?;
+        let planet = return_type.planet();
+        let natal_longitude = find_position(&self.natal_chart(birth)?.positions, planet).ok()?.longitude;
+        next_return_with(self.ephemeris.as_ref(), return_type, natal_longitude, now).ok()
+    }
+
+    /// PIDs whose progressions `check_progressions` can follow
+    pub fn progressing_pids(&self) -> Vec<i32> {
+        if self.progressor.is_none() {
+            return Vec::new();
+        }
+        self.births.keys().copied().collect()
+    }
+
+    /// Progress a PID's natal chart to `now`, returning what has happened since the last check
+    ///
+    /// The first check only notes where the chart stands. Nothing comes back
+    /// unless progressions are enabled and the PID's start time was noted.
+    pub fn check_progressions(&mut self, pid: i32, now: DateTime<Utc>) -> Vec<LifecycleEvent> {
+        let (Some(progressor), Some(&birth)) = (self.progressor, self.births.get(&pid)) else {
+            return Vec::new();
+        };
+        let Ok(elapsed) = u64::try_from((now - birth).num_seconds()) else {
+            return Vec::new();
         };
+        let Some(natal) = self.natal_chart(birth) else {
+            return Vec::new();
+        };
+        let state = ProgressedState::new(&progressor.advance(natal, elapsed), natal);
+        let events = self.progressions.get(&pid).map(|earlier| state.events_since(earlier)).unwrap_or_default();
+        self.progressions.insert(pid, state);
+        events
+    }
 
-        if needs_refresh {
-            let positions = calculate_planetary_positions(now);
-            self.planetary_cache = Some((now, positions));
+    /// Priority multiplier for a task born under `sun_sign` whose ruler is in `ruling_element`
+    pub fn sun_sign_boost(sun_sign: ZodiacSign, ruling_element: Element) -> f64 {
+        if sun_sign.element() == ruling_element {
+            SUN_SIGN_AFFINITY
+        } else {
+            1.0
         }
+    }
 
-        &self.planetary_cache.as_ref().unwrap().1
+    /// Favor tasks whose ruling planet stands in the element of their sun sign
+    #[must_use]
+    pub fn with_sun_sign_affinity(mut self, enabled: bool) -> Self {
+        self.sun_sign_affinity = enabled;
+        self
     }
 
-    fn calculate_planetary_influence(position: &PlanetaryPosition) -> f64 {
-        // Retrograde planets have NEGATIVE influence (causes time slice penalty)
-        if position.retrograde {
-            return -1.0;
+    /// Follow the solar-arc progressions of processes whose start time is noted
+    ///
+    /// `day_unit_secs` of runtime count as one day of solar motion.
+    #[must_use]
+    pub fn with_progressions(mut self, day_unit_secs: u64) -> Self {
+        self.progressor = Some(SolarArcProgressor::new(day_unit_secs));
+        self
+    }
+
+    /// Observer location (degrees, longitude east-positive) for sunrise-based planetary hours
+    #[must_use]
+    pub fn with_location(mut self, latitude: f64, longitude: f64) -> Self {
+        self.location = Some((latitude, longitude));
+        self
+    }
+
+    /// Whether tasks that matched no classifier rule get the Moon phase boost
+    ///
+    /// Unknown tasks fall back to Interactive, which the Moon rules; without
+    /// this they ride the full moon just like real shells.
+    #[must_use]
+    pub fn with_unknown_moon(mut self, enabled: bool) -> Self {
+        self.unknown_moon = enabled;
+        self
+    }
+
+    /// Take planetary positions from another backend
+    #[must_use]
+    pub fn with_ephemeris(mut self, ephemeris: Box<dyn Ephemeris + Send + Sync>) -> Self {
+        self.ephemeris = Arc::from(ephemeris);
+        self.planetary_cache = None;
+        self.north_node = None;
+        self.fortune = None;
+        self
+    }
+
+    /// Use a custom task-type-to-planet rulership map
+    #[must_use]
+    pub fn with_rulership(mut self, rulership: RulershipMap) -> Self {
+        self.rulership = rulership;
+        self
+    }
+
+    /// Classify tasks with a custom ruleset
+    #[must_use]
+    pub fn with_classifier(mut self, classifier: TaskClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// Classify names no rule matches by the closest pattern within `max_edit_distance` edits
+    #[must_use]
+    pub fn with_fuzzy_match_distance(mut self, max_edit_distance: usize) -> Self {
+        self.classifier.set_fuzzy_distance(max_edit_distance);
+        self
+    }
+
+    /// Make the first `dispatches` decisions at base priority, without casting a chart
+    ///
+    /// A cold start has no chart yet and nothing learned about any task; this
+    /// gets the system running before the first chart is paid for.
+    #[must_use]
+    pub fn with_warmup_dispatches(mut self, dispatches: u64) -> Self {
+        self.warmup_remaining = dispatches;
+        self
+    }
+
+    /// Classify tasks no rule matches by the astrological keywords in their command line
+    #[must_use]
+    pub fn with_keyword_classify(mut self, enabled: bool) -> Self {
+        self.classifier.set_keyword_classify(enabled);
+        self
+    }
+
+    /// Use a custom policy for which tasks are critical
+    #[must_use]
+    pub fn with_critical_policy(mut self, critical: CriticalPolicy) -> Self {
+        self.critical = critical;
+        self
+    }
+
+    /// Set the influence of a direct planet before its element bonus
+    #[must_use]
+    pub fn with_influence_baseline(mut self, baseline: f64) -> Self {
+        self.influence_baseline = baseline.max(0.0);
+        self
+    }
+
+    /// Set the influence of a retrograde planet
+    ///
+    /// -1.0 is the classic full penalty. Values closer to zero soften it, and
+    /// positive values treat retrograde planets like direct ones of that
+    /// strength (no slice penalty).
+    #[must_use]
+    pub fn with_retrograde_influence(mut self, influence: f64) -> Self {
+        self.retrograde_influence = influence.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Set the influence of one planet when retrograde, in place of `with_retrograde_influence`'s
+    ///
+    /// A Mercury retrograde can scramble Network work harder than a Saturn
+    /// retrograde troubles System work, say.
+    #[must_use]
+    pub fn with_planet_retrograde_influence(mut self, planet: Planet, influence: f64) -> Self {
+        self.planet_retrograde_influences.insert(planet, influence.clamp(-1.0, 1.0));
+        self
+    }
+
+    /// Influence of `planet` when retrograde
+    pub fn retrograde_influence_of(&self, planet: Planet) -> f64 {
+        self.planet_retrograde_influences.get(&planet).copied().unwrap_or(self.retrograde_influence)
+    }
+
+    /// Spare these task types the retrograde penalty: their ruler counts as direct
+    #[must_use]
+    pub fn with_retrograde_exemptions(mut self, task_types: impl IntoIterator<Item = TaskType>) -> Self {
+        self.retrograde_exempt = task_types.into_iter().collect();
+        self
+    }
+
+    /// Draw every random choice from `rng`, instead of a generator seeded with 0
+    #[must_use]
+    pub fn with_rng(mut self, rng: CosmicRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// The source of every random choice, for those made outside the scheduler
+    pub fn rng(&self) -> &CosmicRng {
+        &self.rng
+    }
+
+    /// Refresh each chart up to `fraction` of the update interval early or late, at random
+    #[must_use]
+    pub fn with_update_jitter(mut self, fraction: f64) -> Self {
+        self.update_jitter = fraction;
+        self
+    }
+
+    /// Enable or disable the priority jitter from out-of-bounds ruling planets
+    #[must_use]
+    pub fn with_out_of_bounds_jitter(mut self, enabled: bool) -> Self {
+        self.out_of_bounds_jitter = enabled;
+        self
+    }
+
+    /// Enable or disable aspect modifiers
+    #[must_use]
+    pub fn with_aspects(mut self, enabled: bool) -> Self {
+        self.aspects = enabled;
+        self
+    }
+
+    /// Enable or disable the Behenian fixed stars
+    #[must_use]
+    pub fn with_fixed_stars(mut self, enabled: bool) -> Self {
+        self.fixed_stars = enabled;
+        self
+    }
+
+    /// Watch the charts for great conjunctions and recessions
+    #[must_use]
+    pub fn with_mundane_astrology(mut self, enabled: bool) -> Self {
+        self.mundane = enabled.then(MundaneWatch::new);
+        self
+    }
+
+    /// Enable or disable the reasoning attached to each decision
+    ///
+    /// Without it `reasoning` is left empty, and scheduling a task that isn't
+    /// the first under a new chart allocates nothing. `explain_task` always
+    /// gives the reasoning.
+    #[must_use]
+    pub fn with_reasoning(mut self, enabled: bool) -> Self {
+        self.reasoning = enabled;
+        self
+    }
+
+    /// Set how much the kernel task weight (nice level) counts against the stars
+    ///
+    /// 0.0 ignores the weight entirely, 1.0 scales the astrological priority
+    /// linearly with it. Nice 0 tasks are unaffected by the blend.
+    #[must_use]
+    pub fn with_weight_blend(mut self, blend: f64) -> Self {
+        self.weight_blend = blend.clamp(0.0, 1.0);
+        self
+    }
+
+    fn weight_factor(&self, weight: u64) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let relative = weight.max(1) as f64 / DEFAULT_TASK_WEIGHT as f64;
+        relative.powf(self.weight_blend)
+    }
+
+    /// Something that computes charts like this scheduler does, to run elsewhere
+    pub fn chart_source(&self) -> ChartSource {
+        ChartSource::new(Arc::clone(&self.ephemeris))
+    }
+
+    /// Make decisions with `chart` from now on
+    pub fn install_chart(&mut self, chart: Chart) {
+        if let Some((_, previous)) = &self.planetary_cache {
+            self.ingress.notify(previous, &chart.positions, chart.time);
+        }
+        self.north_node = chart.north_node;
+        // No ascendant inside the polar circles, so no Part of Fortune either
+        self.fortune = self
+            .location
+            .and_then(|(latitude, longitude)| PartOfFortune::cast(chart.time, latitude, longitude, &chart.positions).ok());
+        self.fixed_star_conditions = match julian_day(chart.time) {
+            Ok(jd) if self.fixed_stars => get_fixed_star_conditions(&chart.positions, jd),
+            _ => Vec::new(),
+        };
+        let time = chart.time;
+        self.planetary_cache = Some((time, chart.positions));
+        self.refresh_after_secs = self.draw_update_interval();
+        self.check_heliacal_events(time);
+        self.check_mundane_events(time);
+    }
+
+    /// Age at which the next chart is due, the update interval give or take the update jitter
+    fn draw_update_interval(&mut self) -> i64 {
+        if self.update_jitter <= 0.0 {
+            return self.cache_duration_secs;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let interval = self.cache_duration_secs as f64;
+        let offset = (self.rng.next_f64() * 2.0 - 1.0) * self.update_jitter * interval;
+        #[allow(clippy::cast_possible_truncation)]
+        let jittered = (interval + offset).round() as i64;
+        jittered.max(0)
+    }
+
+    /// Age at which the current chart is due for a refresh, in seconds
+    pub fn refresh_after_secs(&self) -> i64 {
+        self.refresh_after_secs
+    }
+
+    /// Find the planets that rose heliacally since the last check, and crown them newborn
+    ///
+    /// Compares their elongations in the cached chart, cast at `now`, with
+    /// those at the previous check, so no ephemeris work is done. The newborn
+    /// bonus lasts until the next check.
+    pub fn check_heliacal_events(&mut self, now: DateTime<Utc>) -> Vec<(Planet, HeliacalRising)> {
+        self.newborn.clear();
+        let (Ok(jd), Some((_, positions))) = (julian_day(now), &self.planetary_cache) else {
+            return Vec::new();
+        };
+        let current: Vec<(&PlanetaryPosition, f64)> = positions
+            .iter()
+            .filter_map(|pos| elongation_in(positions, pos.planet).map(|elongation| (pos, elongation)))
+            .collect();
+
+        let mut risings = Vec::new();
+        if let Some((since, before)) = &self.elongations {
+            for &(pos, after) in &current {
+                let Some(&(_, before)) = before.iter().find(|(planet, _)| *planet == pos.planet) else {
+                    continue;
+                };
+                if let Some(rising) = heliacal_crossing(pos.planet, (*since, before), (jd, after)) {
+                    log::info!("☀️ {} makes its heliacal rising!", pos.planet.name());
+                    risings.push((pos.planet, HeliacalRising { jd: rising, sign: pos.sign }));
+                }
+            }
+        }
+        self.newborn.extend(risings.iter().map(|(planet, _)| *planet));
+        self.elongations = Some((jd, current.iter().map(|(pos, elongation)| (pos.planet, *elongation)).collect()));
+        risings
+    }
+
+    /// Look for world events begun in the cached chart, cast at `now`, and act on them
+    ///
+    /// A great conjunction rebalances every CPU once; a square opens a
+    /// recession for `MemoryHeavy` tasks. Nothing without mundane astrology.
+    pub fn check_mundane_events(&mut self, now: DateTime<Utc>) -> Vec<MundaneEvent> {
+        let (Some(watch), Some((_, positions))) = (self.mundane.as_mut(), &self.planetary_cache) else {
+            return Vec::new();
+        };
+        let events = watch.check(positions, now);
+        for event in &events {
+            log::info!("{}", event.banner());
+            if let MundaneEvent::GreatConjunction { .. } = event {
+                self.rebalance_all_cpus();
+            }
+        }
+        events
+    }
+
+    /// Forget every birth chart and classification, as if each task were new
+    ///
+    /// Births and sun signs are noted again as tasks come by; classes learned
+    /// from probes have to be learned afresh.
+    pub fn rebalance_all_cpus(&mut self) {
+        self.sun_signs.clear();
+        self.births.clear();
+        self.natal_charts.clear();
+        self.affinities.clear();
+        self.angular_cpus = None;
+        self.progressions.clear();
+        self.classifier.forget_all();
+        self.revision += 1;
+    }
+
+    /// Whether a recession is on under the cached chart
+    fn in_recession(&self) -> bool {
+        let epoch = self.chart_epoch();
+        self.mundane.as_ref().zip(epoch).is_some_and(|(watch, epoch)| watch.in_recession(epoch))
+    }
+
+    /// Recompute the chart for `now` on this thread and install it
+    pub fn refresh(&mut self, now: DateTime<Utc>) -> Result<(), AstroError> {
+        let chart = self.chart_source().compute(now)?;
+        self.charts_computed += 1;
+        self.install_chart(chart);
+        Ok(())
+    }
+
+    /// True once the chart is older than the update interval (give or take the jitter), or missing
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.chart_epoch()
+            .is_none_or(|epoch| now.timestamp() - epoch.timestamp() > self.refresh_after_secs)
+    }
+
+    /// When the current chart was cast
+    pub fn chart_epoch(&self) -> Option<DateTime<Utc>> {
+        self.planetary_cache.as_ref().map(|(time, _)| *time)
+    }
+
+    /// How many charts have been computed on the calling thread
+    pub fn charts_computed(&self) -> u64 {
+        self.charts_computed
+    }
+
+    /// How many decisions were made for tasks without a name
+    pub fn anonymous_tasks(&self) -> u64 {
+        self.anonymous_tasks
+    }
+
+    /// Cast a first chart if there is none yet
+    ///
+    /// A chart that is merely old is left alone: keeping it fresh is the job
+    /// of whoever calls `refresh` or `install_chart`, off the hot path.
+    fn ensure_chart(&mut self, now: DateTime<Utc>) -> Result<(), AstroError> {
+        if self.planetary_cache.is_none() {
+            self.refresh(now)?;
+        }
+        Ok(())
+    }
+
+    fn cached_positions(&self) -> Result<&[PlanetaryPosition], AstroError> {
+        match &self.planetary_cache {
+            Some((_, positions)) => Ok(positions),
+            None => Err(AstroError::EphemerisFailure("planetary cache is empty".to_string())),
         }
+    }
+
+    /// How strongly a planet acts, given the Sun's position if the chart has it
+    fn calculate_planetary_influence(&self, position: &PlanetaryPosition, sun: Option<&PlanetaryPosition>) -> f64 {
+        // Critical degrees sharpen whatever the planet is already doing
+        let critical = if position.is_in_critical_degree() { CRITICAL_DEGREE_MODIFIER } else { 0.0 };
 
-        // Apply element modifier to planetary influence when direct
-        match position.sign.element() {
-            Element::Fire => 1.3,
-            Element::Earth => 1.1,
-            Element::Air => 1.2,
-            Element::Water => 1.0,
+        // Too close to the Sun burns a planet up, unless it is right in its heart
+        let solar = sun.map_or(0.0, |sun| solar_condition(position, sun).influence_modifier());
+
+        // Retrograde planets have NEGATIVE influence by default (causes time slice penalty)
+        if position.retrograde {
+            return (self.retrograde_influence_of(position.planet) - critical + solar).max(-1.0);
         }
+
+        // Apply element modifier on top of the baseline when direct
+        let element_bonus = match position.sign.element() {
+            Element::Fire => 0.3,
+            Element::Earth => 0.1,
+            Element::Air => 0.2,
+            Element::Water => 0.0,
+        };
+        let term = if position.term_ruler() == position.planet { TERM_DIGNITY_BONUS } else { 0.0 };
+        self.influence_baseline + element_bonus + critical + term + solar
+    }
+
+    /// Priority jitter in percent (-20..=19) for a task under an out-of-bounds planet
+    ///
+    /// Unpredictable, but fixed for a given PID and day by the random seed.
+    pub fn out_of_bounds_jitter_percent(&self, pid: i32, now: DateTime<Utc>) -> i32 {
+        // The Julian day, which turns over at noon UTC
+        let day = (now.timestamp() + 43_200).div_euclid(86_400);
+        #[allow(clippy::cast_sign_loss)]
+        let key = (u64::from(pid as u32) << 32) ^ day as u64;
+        #[allow(clippy::cast_possible_truncation)]
+        let percent = (self.rng.keyed(key) % 40) as i32;
+        percent - 20
     }
 
     fn moon_phase_modifier(phase: MoonPhase) -> f64 {
@@ -76,107 +889,530 @@ impl AstrologicalScheduler {
         }
     }
 
-    fn calculate_element_boost(positions: &[PlanetaryPosition], task_type: TaskType) -> f64 {
-        let ruling_planet = task_type.ruling_planet();
-
-        let planet_pos = positions.iter()
-            .find(|p| p.planet == ruling_planet)
-            .expect("Ruling planet should always be present");
-
-        let element = planet_pos.sign.element();
+    /// Venus's elongation from the Sun, when she is the ruler of a Desktop task
+    fn desktop_venus_elongation(positions: &[PlanetaryPosition], ruler: &PlanetaryPosition, task_type: TaskType) -> Option<f64> {
+        if task_type != TaskType::Desktop || ruler.planet != Planet::Venus {
+            return None;
+        }
+        let sun = find_position(positions, Planet::Sun).ok()?;
+        Some(venus_elongation(ruler.longitude, sun.longitude))
+    }
 
+    fn calculate_element_boost(element: Element, task_type: TaskType) -> f64 {
         // Strong boost for matching elements, DEBUFF for opposing elements!
         match (element, task_type) {
             // Perfect matches - BOOSTED
             (Element::Fire, TaskType::CpuIntensive) | (Element::Air, TaskType::Network) => 1.5,
             (Element::Earth, TaskType::System) => 1.4,
-            (Element::Water, TaskType::MemoryHeavy) | (Element::Air | Element::Water, TaskType::Desktop) => 1.3,
+            (Element::Water, TaskType::MemoryHeavy)
+            | (Element::Air | Element::Water, TaskType::Desktop)
+            | (Element::Air, TaskType::Virtualization) => 1.3,
 
             // Opposing elements - DEBUFFED (Fire opposes Water, Earth opposes Air)
             (Element::Water, TaskType::CpuIntensive) | (Element::Earth, TaskType::Network) => 0.6,
-            (Element::Air, TaskType::System) | (Element::Fire, TaskType::MemoryHeavy) => 0.7,
+            (Element::Air, TaskType::System)
+            | (Element::Fire, TaskType::MemoryHeavy)
+            | (Element::Earth, TaskType::Virtualization) => 0.7,
 
             // Neutral combinations
             _ => 1.0,
         }
     }
 
-    pub fn schedule_task(
-        &mut self,
-        comm: &str,
-        pid: i32,
-        now: DateTime<Utc>,
-    ) -> SchedulingDecision {
-        if TaskClassifier::is_critical(pid) {
-            return SchedulingDecision {
-                priority: 1000,
-                reasoning: format!("☀️ Sun rules all - PID {pid} is CRITICAL (init)"),
-                planetary_influence: 1.0,
-                element_boost: 2.0,
-            };
-        }
-
-        let task_type = self.classifier.classify(comm);
-        let ruling_planet = task_type.ruling_planet();
-
-        let positions = self.get_planetary_positions(now);
-
-        let planet_pos = positions.iter()
-            .find(|p| p.planet == ruling_planet)
-            .expect("Ruling planet should always be present");
-
-        let planetary_influence = Self::calculate_planetary_influence(planet_pos);
-        let mut element_boost = Self::calculate_element_boost(positions, task_type);
-
-        // Apply moon phase boost for Interactive tasks (Moon's domain)
-        if task_type == TaskType::Interactive {
-            if let Some(moon_pos) = positions.iter().find(|p| p.planet == Planet::Moon) {
-                if let Some(phase) = moon_pos.moon_phase {
-                    element_boost *= Self::moon_phase_modifier(phase);
-                }
-            }
-        }
-
-        let base_priority = match task_type {
+    /// Priority a task type starts from before the sky has its say
+    pub fn default_base_priority(task_type: TaskType) -> u32 {
+        match task_type {
             TaskType::Critical => 1000,
             TaskType::System => 200,
             TaskType::Interactive => 150,
             TaskType::Desktop => 120,
             TaskType::CpuIntensive | TaskType::Network => 100,
-            TaskType::MemoryHeavy => 80,
-        };
+            TaskType::MemoryHeavy | TaskType::Virtualization => 80,
+        }
+    }
+
+    pub fn base_priority(&self, task_type: TaskType) -> u32 {
+        self.base_priorities
+            .get(&task_type)
+            .copied()
+            .unwrap_or_else(|| Self::default_base_priority(task_type))
+    }
+
+    /// Override the base priority of a task type
+    pub fn set_base_priority(&mut self, task_type: TaskType, priority: u32) {
+        self.revision += 1;
+        self.base_priorities.insert(task_type, priority);
+    }
 
-        let influenced_priority = if planetary_influence >= 0.0 {
+    /// A base priority under a planet's influence
+    ///
+    /// `boost` applies while the planet is direct, `retrograde_boost` while it
+    /// is retrograde, where the element no longer helps.
+    fn influenced_priority(base_priority: u32, planetary_influence: f64, boost: f64, retrograde_boost: f64) -> u32 {
+        if planetary_influence >= 0.0 {
             #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let result = (f64::from(base_priority) * planetary_influence * element_boost) as u32;
+            let result = (f64::from(base_priority) * planetary_influence * boost) as u32;
             result
         } else {
+            // Full retrograde (-1.0) cuts priority to 30%, softer values cut less
             #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let result = (f64::from(base_priority) * 0.3) as u32;
+            let result = (f64::from(base_priority) * (1.0 + 0.7 * planetary_influence) * retrograde_boost) as u32;
             result
+        }
+    }
+
+    /// Work out how the given chart treats a task type
+    fn assess<'p>(&self, positions: &'p [PlanetaryPosition], task_type: TaskType) -> Result<TypeAssessment<'p>, AstroError> {
+        self.assess_with_moon(positions, task_type, true)
+    }
+
+    /// Like `assess`, optionally leaving out the Moon phase
+    fn assess_with_moon<'p>(
+        &self,
+        positions: &'p [PlanetaryPosition],
+        task_type: TaskType,
+        moon_phase: bool,
+    ) -> Result<TypeAssessment<'p>, AstroError> {
+        let planet_pos = find_position(positions, self.rulership.ruler(task_type))?;
+        let sun = find_position(positions, Planet::Sun).ok();
+
+        let planetary_influence = if planet_pos.retrograde && self.retrograde_exempt.contains(&task_type) {
+            self.calculate_planetary_influence(&PlanetaryPosition { retrograde: false, ..planet_pos.clone() }, sun)
+        } else {
+            self.calculate_planetary_influence(planet_pos, sun)
         };
+        let solar = sun.map_or(SolarCondition::Free, |sun| solar_condition(planet_pos, sun));
 
-        let reasoning = Self::create_reasoning(
-            task_type,
+        // Fresh out of the Sun's glare, a planet acts at its most direct
+        let newborn = planetary_influence > 0.0 && self.newborn.contains(&planet_pos.planet);
+        let planetary_influence = if newborn { planetary_influence * HELIACAL_BONUS } else { planetary_influence };
+
+        let mut element_boost = Self::calculate_element_boost(planet_pos.sign.element(), task_type);
+
+        // Apply moon phase boost for Moon-ruled tasks (Interactive by default)
+        if let Some(phase) = planet_pos.moon_phase.filter(|_| moon_phase) {
+            element_boost *= Self::moon_phase_modifier(phase);
+        }
+        // And Venus's distance from the Sun for Desktop tasks she rules, whatever the Moon is doing
+        if let Some(elongation) = Self::desktop_venus_elongation(positions, planet_pos, task_type) {
+            element_boost *= venus_phase_modifier(elongation);
+        }
+
+        let base_priority = self.base_priority(task_type);
+
+        // Aspects to the ruler count in proportion to how exact they are
+        let aspects = || aspects_to(planet_pos, positions).filter(|_| self.aspects);
+        let aspect_factor = aspect_modifier(aspects());
+        let strongest_aspect = aspects()
+            .max_by(|a, b| (a.kind.modifier().abs() * a.strength).total_cmp(&(b.kind.modifier().abs() * b.strength)));
+
+        let mut influenced_priority =
+            Self::influenced_priority(base_priority, planetary_influence, element_boost * aspect_factor, aspect_factor);
+
+        // A ruler on a fixed star takes on its nature
+        let fixed_star = fixed_star_factor(&self.fixed_star_conditions, planet_pos.planet);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let starred = (f64::from(influenced_priority) * fixed_star) as u32;
+        influenced_priority = starred;
+
+        // Memory is the first thing cut back in hard times
+        if task_type == TaskType::MemoryHeavy && self.in_recession() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let austere = (f64::from(influenced_priority) * RECESSION_FACTOR) as u32;
+            influenced_priority = austere;
+        }
+
+        Ok(TypeAssessment {
             planet_pos,
             planetary_influence,
             element_boost,
-        );
+            strongest_aspect,
+            solar,
+            newborn,
+            priority: influenced_priority,
+        })
+    }
 
-        SchedulingDecision {
-            priority: influenced_priority.max(1),
-            reasoning,
-            planetary_influence,
-            element_boost,
-        }
+    pub fn schedule_task(
+        &mut self,
+        comm: &str,
+        pid: i32,
+        weight: u64,
+        now: DateTime<Utc>,
+    ) -> Result<SchedulingDecision, AstroError> {
+        self.schedule_task_with_flags(comm, pid, weight, false, 0, now)
     }
 
-    fn create_reasoning(
-        task_type: TaskType,
-        planet_pos: &PlanetaryPosition,
-        influence: f64,
-        boost: f64,
-    ) -> String {
+    /// Schedule a task as `schedule_task` does, always with the reasoning
+    pub fn explain_task(
+        &mut self,
+        comm: &str,
+        pid: i32,
+        weight: u64,
+        now: DateTime<Utc>,
+    ) -> Result<SchedulingDecision, AstroError> {
+        let reasoning = std::mem::replace(&mut self.reasoning, true);
+        let decision = self.schedule_task(comm, pid, weight, now);
+        self.reasoning = reasoning;
+        decision
+    }
+
+    /// Schedule a task, with kernel-side facts the critical policy may need
+    ///
+    /// `flags` takes `TASK_FLAG_*` bits gathered by the caller.
+    #[allow(clippy::too_many_lines)]
+    pub fn schedule_task_with_flags(
+        &mut self,
+        comm: &str,
+        pid: i32,
+        weight: u64,
+        is_kthread: bool,
+        flags: u32,
+        now: DateTime<Utc>,
+    ) -> Result<SchedulingDecision, AstroError> {
+        use std::fmt::Write;
+
+        if let Some(reason) = self.critical.critical_reason(pid, comm, is_kthread, flags) {
+            return Ok(self.critical_decision(comm, pid, weight, reason));
+        }
+        if self.take_warmup() {
+            return Ok(self.neutral_decision(comm, pid, weight));
+        }
+
+        let (task_type, provenance) = self.classifier.classify_pid(pid, comm);
+        if provenance == Provenance::Anonymous {
+            self.anonymous_tasks += 1;
+        }
+        self.ensure_chart(now)?;
+        let synergy = if self.aspects { self.synergy_with_running(task_type)? } else { 1.0 };
+        let positions = self.cached_positions()?;
+        let moon_phase = self.unknown_moon || !matches!(provenance, Provenance::Default | Provenance::Anonymous);
+        let assessment = self.assess_with_moon(positions, task_type, moon_phase)?;
+        let ruling_element = assessment.planet_pos.sign.element();
+        let out_of_bounds = self.out_of_bounds_jitter && assessment.planet_pos.is_out_of_bounds();
+        let at_bending = self.north_node.is_some_and(|node| is_at_bending(assessment.planet_pos.longitude, node));
+        let fortune = self.fortune.filter(|fortune| fortune.conjuncts(assessment.planet_pos.longitude));
+        let sun_sign = self.sun_signs.get(&pid).copied().filter(|_| self.sun_sign_affinity);
+        let sun_sign_boost = sun_sign.map_or(1.0, |sign| Self::sun_sign_boost(sign, ruling_element));
+
+        let mut reasoning = String::new();
+        if self.reasoning {
+            reasoning = Self::create_reasoning(
+                task_type,
+                assessment.planet_pos,
+                assessment.planetary_influence,
+                assessment.element_boost,
+            );
+            let planet = assessment.planet_pos.planet.name();
+            if let Some(aspect) = assessment.strongest_aspect {
+                let _ = write!(
+                    reasoning,
+                    " | {} {planet} {} {} ({:.0}%)",
+                    aspect.kind.symbol(),
+                    aspect.kind.name(),
+                    aspect.other.name(),
+                    aspect.strength * 100.0
+                );
+            }
+            if assessment.planet_pos.is_in_critical_degree() {
+                let _ = write!(
+                    reasoning,
+                    " | ⚡ {planet} at critical {:.0}° {}",
+                    assessment.planet_pos.degree_in_sign.floor(),
+                    assessment.planet_pos.sign.name()
+                );
+            }
+            match assessment.solar {
+                SolarCondition::Cazimi => {
+                    let _ = write!(reasoning, " | ☀️ {planet} cazimi — {} tasks in the heart of the Sun", task_type.name());
+                }
+                SolarCondition::Combust => {
+                    let _ = write!(reasoning, " | 🔥 {planet} combust — {} tasks burned up", task_type.name());
+                }
+                SolarCondition::UnderBeams => {
+                    let _ = write!(reasoning, " | 🌫️ {planet} {}", SolarCondition::UnderBeams.name());
+                }
+                SolarCondition::Free => {}
+            }
+            if assessment.newborn {
+                let _ = write!(reasoning, " | 🌅 {planet} newly risen from the Sun's glare");
+            }
+            if !assessment.planet_pos.retrograde && assessment.planet_pos.term_ruler() == assessment.planet_pos.planet {
+                let _ = write!(reasoning, " | 🏛️ {planet} in its own term");
+            }
+            if assessment.planet_pos.retrograde && self.retrograde_exempt.contains(&task_type) {
+                let _ = write!(reasoning, " | 🛡️ {planet} retrograde, {} exempt", task_type.name());
+            }
+            match provenance {
+                Provenance::Learned => reasoning.push_str(" | 🔬 learned"),
+                Provenance::Keyword => reasoning.push_str(" | 🔑 command-line keyword"),
+                Provenance::Fuzzy => reasoning.push_str(" | 🔍 fuzzy match"),
+                Provenance::Anonymous => {
+                    let _ = write!(reasoning, " | 👤 unnamed task PID {pid}");
+                }
+                Provenance::Name | Provenance::Default => {}
+            }
+            if !moon_phase && assessment.planet_pos.moon_phase.is_some() {
+                reasoning.push_str(" | ❔ unclassified, Moon phase ignored");
+            }
+            if out_of_bounds {
+                let jitter = self.out_of_bounds_jitter_percent(pid, now);
+                let _ = write!(reasoning, " | ✈️ {planet} out of bounds ({jitter:+}%)");
+            }
+            for condition in self.fixed_star_conditions.iter().filter(|condition| condition.planet == assessment.planet_pos.planet) {
+                let star = condition.star;
+                let _ = write!(
+                    reasoning,
+                    " | ⭐ {planet} on {} ({}, ×{:.2})",
+                    star.name,
+                    star.nature.name(),
+                    star.scheduling_bonus
+                );
+            }
+            if task_type == TaskType::MemoryHeavy && self.in_recession() {
+                let _ = write!(reasoning, " | 📉 recession ({:+.0}%)", (RECESSION_FACTOR - 1.0) * 100.0);
+            }
+            if at_bending {
+                let _ = write!(
+                    reasoning,
+                    " | 🐉 {planet} at the bending — {} tasks face karmic friction",
+                    task_type.name()
+                );
+            }
+            if let Some(fortune) = fortune {
+                let _ = write!(reasoning, " | 🍀 {planet} on the Part of Fortune in {}", fortune.sign.name());
+            }
+            if let Some(sign) = sun_sign.filter(|_| sun_sign_boost > 1.0) {
+                let _ = write!(reasoning, " | ☉ born in {} ({:+.0}%)", sign.name(), (sun_sign_boost - 1.0) * 100.0);
+            }
+            if synergy > 1.0 {
+                let _ = write!(reasoning, " | 🤝 flowing with other tasks ({:+.0}%)", (synergy - 1.0) * 100.0);
+            } else if synergy < 1.0 {
+                let _ = write!(reasoning, " | ⚙️ friction with other tasks ({:+.0}%)", (synergy - 1.0) * 100.0);
+            }
+        }
+        let TypeAssessment { planetary_influence, element_boost, priority: mut influenced_priority, .. } = assessment;
+
+        // Task types whose rulers are in aspect help or hinder each other
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let cooperative = (f64::from(influenced_priority) * synergy) as u32;
+        influenced_priority = cooperative;
+
+        // A ruler in the task's own element feels like home
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let boosted = (f64::from(influenced_priority) * sun_sign_boost) as u32;
+        influenced_priority = boosted;
+
+        // Squaring the nodal axis strains the ruler
+        if at_bending {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let strained = (f64::from(influenced_priority) * BENDING_PENALTY) as u32;
+            influenced_priority = strained;
+        }
+
+        // A ruler on the Part of Fortune brings its tasks luck
+        if fortune.is_some() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let lucky = (f64::from(influenced_priority) * FORTUNE_BONUS) as u32;
+            influenced_priority = lucky;
+        }
+
+        // Out-of-bounds rulers make their tasks unpredictable
+        if out_of_bounds {
+            let jitter = f64::from(self.out_of_bounds_jitter_percent(pid, now));
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let jittered = (f64::from(influenced_priority) * (1.0 + jitter / 100.0)) as u32;
+            influenced_priority = jittered;
+        }
+
+        // Blend in the kernel weight so user-set nice values still mean something
+        let astro_priority = influenced_priority.max(1);
+        let weight_factor = self.weight_factor(weight);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let priority = (f64::from(astro_priority) * weight_factor) as u32;
+
+        Ok(SchedulingDecision {
+            task_type,
+            critical: false,
+            ruling_element: Some(ruling_element),
+            priority: priority.max(1),
+            astro_priority,
+            weight,
+            weight_factor,
+            reasoning,
+            planetary_influence,
+            element_boost,
+        })
+    }
+
+    /// The Sun's verdict on a task the critical policy protects
+    fn critical_decision(&self, comm: &str, pid: i32, weight: u64, reason: CriticalReason) -> SchedulingDecision {
+        let reasoning = match reason {
+            _ if !self.reasoning => String::new(),
+            CriticalReason::Init => format!("☀️ Sun rules all - PID {pid} is CRITICAL (init)"),
+            _ => format!("☀️ Sun shields {comm} - PID {pid} is CRITICAL ({})", reason.name()),
+        };
+        SchedulingDecision {
+            task_type: TaskType::Critical,
+            critical: true,
+            ruling_element: None,
+            priority: 1000,
+            astro_priority: 1000,
+            weight,
+            weight_factor: 1.0,
+            reasoning,
+            planetary_influence: 1.0,
+            element_boost: 2.0,
+        }
+    }
+
+    /// Count off one warm-up decision, if any are left
+    ///
+    /// The revision moves on with the last one, so no neutral decision is
+    /// remembered past the warm-up.
+    fn take_warmup(&mut self) -> bool {
+        if self.warmup_remaining == 0 {
+            return false;
+        }
+        self.warmup_remaining -= 1;
+        if self.warmup_remaining == 0 {
+            self.revision += 1;
+        }
+        true
+    }
+
+    /// Decision for a task during the warm-up: its base priority, and the kernel weight
+    fn neutral_decision(&self, comm: &str, pid: i32, weight: u64) -> SchedulingDecision {
+        let (task_type, _) = self.classifier.classify_pid(pid, comm);
+        let astro_priority = self.base_priority(task_type);
+        let weight_factor = self.weight_factor(weight);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let priority = (f64::from(astro_priority) * weight_factor) as u32;
+        let reasoning = if self.reasoning {
+            format!("🌱 Warming up - {} at base priority, the stars not yet consulted", task_type.name())
+        } else {
+            String::new()
+        };
+        SchedulingDecision {
+            task_type,
+            critical: false,
+            ruling_element: None,
+            priority: priority.max(1),
+            astro_priority,
+            weight,
+            weight_factor,
+            reasoning,
+            planetary_influence: 1.0,
+            element_boost: 1.0,
+        }
+    }
+
+    /// Schedule a task from the per-type table, for when the queue is backing up
+    ///
+    /// The critical policy, the classifier and the kernel weight still count;
+    /// everything per-PID (sun signs, out-of-bounds jitter, bendings, the Part of
+    /// Fortune) and the
+    /// detailed reasoning are skipped. The table is worked out once per chart.
+    pub fn schedule_task_fast(
+        &mut self,
+        comm: &str,
+        pid: i32,
+        weight: u64,
+        is_kthread: bool,
+        flags: u32,
+        now: DateTime<Utc>,
+    ) -> Result<SchedulingDecision, AstroError> {
+        if let Some(reason) = self.critical.critical_reason(pid, comm, is_kthread, flags) {
+            return Ok(self.critical_decision(comm, pid, weight, reason));
+        }
+        if self.take_warmup() {
+            return Ok(self.neutral_decision(comm, pid, weight));
+        }
+
+        let (task_type, provenance) = self.classifier.classify_pid(pid, comm);
+        if provenance == Provenance::Anonymous {
+            self.anonymous_tasks += 1;
+        }
+        self.ensure_chart(now)?;
+        self.refresh_fast_path()?;
+        let table = self.fast_path.as_ref().map(|(_, table)| table);
+        let template = table.and_then(|table| table.get(&task_type)).ok_or_else(|| {
+            AstroError::EphemerisFailure(format!("no fast-path entry for {}", task_type.name()))
+        })?;
+
+        let weight_factor = self.weight_factor(weight);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let priority = (f64::from(template.astro_priority) * weight_factor) as u32;
+        Ok(SchedulingDecision { priority: priority.max(1), weight, weight_factor, ..template.clone() })
+    }
+
+    /// Synergy multiplier for `task_type` with the other types that are running, and count it as running
+    ///
+    /// The matrix is worked out once per chart. The types that count as
+    /// running are those scheduled under the previous chart, so every task of
+    /// a type gets the same multiplier until the next one, whatever order
+    /// tasks come in.
+    fn synergy_with_running(&mut self, task_type: TaskType) -> Result<f64, AstroError> {
+        let Some((epoch, positions)) = &self.planetary_cache else {
+            return Err(AstroError::EphemerisFailure("planetary cache is empty".to_string()));
+        };
+        if self.synergy.as_ref().is_none_or(|(made, _)| made != epoch) {
+            self.synergy = Some((*epoch, TaskTypeSynergy::new(positions, &self.rulership)));
+            self.co_running = TaskType::all().into_iter().filter(|t| self.running_types.contains(t)).collect();
+            self.running_types.clear();
+        }
+        self.running_types.insert(task_type);
+        Ok(self
+            .synergy
+            .as_ref()
+            .map_or(1.0, |(_, synergy)| synergy.combined(task_type, self.co_running.iter().copied())))
+    }
+
+    /// Rebuild the fast-path table if the chart has moved on since it was made
+    fn refresh_fast_path(&mut self) -> Result<(), AstroError> {
+        let Some((epoch, positions)) = &self.planetary_cache else {
+            return Err(AstroError::EphemerisFailure("planetary cache is empty".to_string()));
+        };
+        if self.fast_path.as_ref().is_some_and(|(made, _)| made == epoch) {
+            return Ok(());
+        }
+
+        let mut table = HashMap::new();
+        for task_type in TaskType::all().into_iter().filter(|&t| t != TaskType::Critical) {
+            let assessment = self.assess(positions, task_type)?;
+            let astro_priority = assessment.priority.max(1);
+            table.insert(task_type, SchedulingDecision {
+                task_type,
+                critical: false,
+                ruling_element: Some(assessment.planet_pos.sign.element()),
+                priority: astro_priority,
+                astro_priority,
+                weight: DEFAULT_TASK_WEIGHT,
+                weight_factor: 1.0,
+                reasoning: if self.reasoning {
+                    format!(
+                        "⏩ {} in {} - fast path, {} tasks get the table",
+                        assessment.planet_pos.planet.name(),
+                        assessment.planet_pos.sign.name(),
+                        task_type.name()
+                    )
+                } else {
+                    String::new()
+                },
+                planetary_influence: assessment.planetary_influence,
+                element_boost: assessment.element_boost,
+            });
+        }
+        self.fast_path = Some((*epoch, table));
+        Ok(())
+    }
+
+    fn create_reasoning(
+        task_type: TaskType,
+        planet_pos: &PlanetaryPosition,
+        influence: f64,
+        boost: f64,
+    ) -> String {
         let planet_name = planet_pos.planet.name();
         let sign_name = planet_pos.sign.name();
         let element_name = planet_pos.sign.element().name();
@@ -198,6 +1434,7 @@ impl AstrologicalScheduler {
                 (Element::Earth, TaskType::Network) => "🪨 Earth blocks air",
                 (Element::Air, TaskType::System) => "💨 Air disrupts earth",
                 (Element::Fire, TaskType::MemoryHeavy) => "🔥 Fire evaporates water",
+                (Element::Earth, TaskType::Virtualization) => "🪨 Earth resists disruption",
                 _ => "⚔️ Elemental opposition",
             };
             format!(
@@ -238,10 +1475,205 @@ impl AstrologicalScheduler {
         }
     }
 
+    /// Compare the astrological conditions at two moments
+    ///
+    /// The overall score is the mean priority across all task types the stars
+    /// can influence. On a tie, `a` is reported as the better time.
+    pub fn compare_conditions(&self, a: DateTime<Utc>, b: DateTime<Utc>) -> Result<ConditionComparison, AstroError> {
+        let positions_a = self.ephemeris.positions(julian_day(a)?)?;
+        let positions_b = self.ephemeris.positions(julian_day(b)?)?;
+        self.compare_charts(a, &positions_a, b, &positions_b)
+    }
+
+    /// Base priority of every task type, hour by hour from `from`
+    ///
+    /// Like `compare_conditions`, this leaves out per-task modifiers (nice
+    /// level, sun sign, out-of-bounds jitter).
+    pub fn forecast(&self, from: DateTime<Utc>, hours: u32) -> Result<Vec<ForecastHour>, AstroError> {
+        let task_types: Vec<_> = TaskType::all().into_iter().filter(|&t| t != TaskType::Critical).collect();
+        (0..hours)
+            .map(|hour| {
+                let time = from + chrono::Duration::hours(i64::from(hour));
+                let positions = self.ephemeris.positions(julian_day(time)?)?;
+                let priorities = task_types
+                    .iter()
+                    .map(|&task_type| Ok((task_type, self.assess(&positions, task_type)?.priority)))
+                    .collect::<Result<_, AstroError>>()?;
+                let retrograde = positions.iter().filter(|pos| pos.retrograde).map(|pos| pos.planet).collect();
+                Ok(ForecastHour { time, priorities, retrograde })
+            })
+            .collect()
+    }
+
+    /// One element's outlook, hour by hour from `from`
+    ///
+    /// Only the element's two planets (see `element_forecast_subject`) are
+    /// cast, which makes this much cheaper than `forecast`; without the Sun,
+    /// combustion is left out. The recommended
+    /// priority averages what each of them would give the element's task type.
+    pub fn get_element_forecast_from(
+        &self,
+        element: Element,
+        from: DateTime<Utc>,
+        hours: u8,
+    ) -> Result<Vec<ElementForecastPoint>, AstroError> {
+        let (task_type, planets) = element_forecast_subject(element);
+        let base_priority = self.base_priority(task_type);
+        (0..hours)
+            .map(|hour| {
+                let timestamp = from + chrono::Duration::hours(i64::from(hour));
+                let positions = self.ephemeris.positions_of(julian_day(timestamp)?, &planets)?;
+                let priorities: Vec<u32> = planets
+                    .iter()
+                    .map(|&planet| {
+                        let pos = find_position(&positions, planet)?;
+                        let influence = if pos.retrograde && self.retrograde_exempt.contains(&task_type) {
+                            self.calculate_planetary_influence(&PlanetaryPosition { retrograde: false, ..pos.clone() }, None)
+                        } else {
+                            self.calculate_planetary_influence(pos, None)
+                        };
+                        let boost = Self::calculate_element_boost(pos.sign.element(), task_type);
+                        Ok(Self::influenced_priority(base_priority, influence, boost, 1.0))
+                    })
+                    .collect::<Result<_, AstroError>>()?;
+                #[allow(clippy::cast_possible_truncation)]
+                let recommended_priority = priorities.iter().sum::<u32>() / priorities.len() as u32;
+                Ok(ElementForecastPoint {
+                    timestamp,
+                    element_count: positions.iter().filter(|pos| pos.sign.element() == element).count(),
+                    planet_in_own_sign: positions.iter().any(|pos| pos.planet.is_domiciled_in(pos.sign)),
+                    retrograde_count: positions.iter().filter(|pos| pos.retrograde).count(),
+                    recommended_priority,
+                })
+            })
+            .collect()
+    }
+
+    /// `get_element_forecast_from` for the coming `hours`, starting now
+    pub fn get_element_forecast(&self, element: Element, hours: u8) -> Result<Vec<ElementForecastPoint>, AstroError> {
+        self.get_element_forecast_from(element, Utc::now(), hours)
+    }
+
+    /// An element's forecast as a table, one line per hour
+    pub fn element_forecast_reference(
+        &self,
+        element: Element,
+        from: DateTime<Utc>,
+        hours: u8,
+    ) -> Result<String, AstroError> {
+        use std::fmt::Write;
+        let (task_type, [first, second]) = element_forecast_subject(element);
+        let mut reference = format!(
+            "🔭 {} FORECAST: {} tasks, read from {} and {} 🔭\n",
+            element.name().to_uppercase(),
+            task_type.name(),
+            first.name(),
+            second.name()
+        );
+        for point in self.get_element_forecast_from(element, from, hours)? {
+            let line = format!(
+                "  {} UTC  priority {:>4}  in {}: {}/2  retrograde: {}  {}",
+                point.timestamp.format("%Y-%m-%d %H:%M"),
+                point.recommended_priority,
+                element.name(),
+                point.element_count,
+                point.retrograde_count,
+                if point.planet_in_own_sign { "🏠 at home" } else { "" }
+            );
+            let _ = writeln!(reference, "{}", line.trim_end());
+        }
+        Ok(reference)
+    }
+
+    fn compare_charts(
+        &self,
+        a: DateTime<Utc>,
+        positions_a: &[PlanetaryPosition],
+        b: DateTime<Utc>,
+        positions_b: &[PlanetaryPosition],
+    ) -> Result<ConditionComparison, AstroError> {
+        let mut advantage_by_type = HashMap::new();
+        let mut total_a = 0.0;
+        let mut total_b = 0.0;
+
+        let task_types: Vec<_> = TaskType::all()
+            .into_iter()
+            .filter(|&t| t != TaskType::Critical)  // Critical ignores the sky
+            .collect();
+
+        for &task_type in &task_types {
+            let priority_a = f64::from(self.assess(positions_a, task_type)?.priority);
+            let priority_b = f64::from(self.assess(positions_b, task_type)?.priority);
+            advantage_by_type.insert(task_type, priority_a - priority_b);
+            total_a += priority_a;
+            total_b += priority_b;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let type_count = task_types.len() as f64;
+        let overall_score_a = total_a / type_count;
+        let overall_score_b = total_b / type_count;
+
+        let (better_time, worse_time) = if overall_score_b > overall_score_a {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        Ok(ConditionComparison {
+            better_time,
+            worse_time,
+            advantage_by_type,
+            retrograde_count_a: positions_a.iter().filter(|p| p.retrograde).count(),
+            retrograde_count_b: positions_b.iter().filter(|p| p.retrograde).count(),
+            overall_score_a,
+            overall_score_b,
+        })
+    }
+
+    /// When a retrograde `planet` stationed retrograde, and when it stations direct
+    ///
+    /// None while the planet is direct. The scan is bounded by
+    /// `STATION_SCAN_DAYS` each way, and a window found once is reused until
+    /// `now` leaves it.
+    pub fn retrograde_window(&mut self, planet: Planet, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if let Some(&(start, end)) = self.retrograde_windows.get(&planet) {
+            if start <= now && now < end {
+                return Some((start, end));
+            }
+        }
+        let jd = julian_day(now).ok()?;
+        let (start, end) = retrograde_window_jd(self.ephemeris.as_ref(), planet, jd)?;
+        let window = (jd_to_datetime(start)?, jd_to_datetime(end)?);
+        self.retrograde_windows.insert(planet, window);
+        Some(window)
+    }
+
+    /// The Sabian symbol of the Sun's degree: the scheduler's message for the day
+    pub fn get_daily_cosmic_message(&mut self, now: DateTime<Utc>) -> Result<&'static str, AstroError> {
+        self.ensure_chart(now)?;
+        Ok(get_sabian_symbol(find_position(self.cached_positions()?, Planet::Sun)?.longitude))
+    }
+
+    /// The daily cosmic message with the Sun's Sabian degree, e.g. ☉ at 8° Libra: '…'
+    pub fn daily_cosmic_message_line(&mut self, now: DateTime<Utc>) -> Result<String, AstroError> {
+        let message = self.get_daily_cosmic_message(now)?;
+        let sun = find_position(self.cached_positions()?, Planet::Sun)?;
+        Ok(format!("☉ at {}° {}: '{message}'", sabian_degree(sun.longitude), sun.sign.name()))
+    }
+
     /// Get a summary of current astrological conditions
-    pub fn get_cosmic_weather(&mut self, now: DateTime<Utc>) -> String {
+    #[allow(clippy::too_many_lines)]
+    pub fn get_cosmic_weather(&mut self, now: DateTime<Utc>) -> Result<String, AstroError> {
         use std::fmt::Write;
-        let positions = self.get_planetary_positions(now);
+        self.ensure_chart(now)?;
+        let retrograde: Vec<Planet> = self.cached_positions()?.iter().filter(|pos| pos.retrograde).map(|pos| pos.planet).collect();
+        let windows: HashMap<Planet, _> = retrograde
+            .into_iter()
+            .filter_map(|planet| Some((planet, self.retrograde_window(planet, now)?)))
+            .collect();
+        let positions = self.cached_positions()?;
+        let void_moon = moon_void_of_course(positions);
 
         let mut report = String::from("🌌 COSMIC WEATHER REPORT 🌌\n");
         let _ = writeln!(report, "Current time: {}", now.format("%Y-%m-%d %H:%M:%S UTC"));
@@ -249,19 +1681,47 @@ impl AstrologicalScheduler {
 
         for pos in positions {
             let phase_info = if let Some(phase) = pos.moon_phase {
-                format!(" [{}]", phase.name())
+                format!(" [{}]{}", phase.name(), if void_moon { " void of course" } else { "" })
+            } else {
+                String::new()
+            };
+            let oob_info = if pos.is_out_of_bounds() {
+                format!(" ✈️ OUT OF BOUNDS ({:+.1}°)", pos.latitude)
             } else {
                 String::new()
             };
+            let critical_info = if pos.is_in_critical_degree() { " ⚡ CRITICAL DEGREE" } else { "" };
+            let retrograde_info = match windows.get(&pos.planet) {
+                Some((start, end)) => format!(" ℞ since {}, direct {}", start.format("%b %-d"), end.format("%b %-d")),
+                None if pos.retrograde => " ℞".to_string(),
+                None => String::new(),
+            };
             #[allow(clippy::cast_possible_truncation)]
             let longitude_deg = pos.longitude as i32;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let (degree, minute) = (pos.degree_in_sign as u32, ZodiacSign::minute_in_sign(pos.longitude) as u32);
             let _ = writeln!(
                 report,
-                "{} in {} ({longitude_deg}°) - {}{}",
+                "{} in {} {degree}°{minute:02}′ ({longitude_deg}°) - {}{}{}{}{}",
                 pos.planet.name(),
                 pos.sign.name(),
                 pos.sign.element().name(),
-                phase_info
+                retrograde_info,
+                phase_info,
+                oob_info,
+                critical_info
+            );
+        }
+
+        if let Some(fortune) = self.fortune {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let (degree, minute) =
+                (ZodiacSign::degree_in_sign(fortune.longitude) as u32, ZodiacSign::minute_in_sign(fortune.longitude) as u32);
+            let _ = writeln!(
+                report,
+                "🍀 Part of Fortune in {} {degree}°{minute:02}′ ({} chart)",
+                fortune.sign.name(),
+                if fortune.day { "day" } else { "night" }
             );
         }
 
@@ -280,51 +1740,111 @@ impl AstrologicalScheduler {
 
         report.push_str("\n💫 ASTROLOGICAL GUIDANCE 💫\n\n");
 
-        // Helper to generate status for each task type
-        let task_status = |planet: Planet, ideal: Element, opposed: Element, clash: bool,
+        // Helper to generate status for each task type: the ruler's element
+        // sets the verdict, its motion and aspects qualify it
+        let task_status = |pos: &PlanetaryPosition, task_type: TaskType, ideal: Element, opposed: Element, clash: bool,
                           boosted_msg: &str, contested_msg: &str, debuffed_msg: &str| -> String {
-            let pos = positions.iter().find(|p| p.planet == planet).unwrap();
             let element = pos.sign.element();
-            match element {
+            let planet = pos.planet.name();
+            let retrograde = pos.retrograde && !self.retrograde_exempt.contains(&task_type);
+            let mut status = match element {
+                e if e == ideal && retrograde => {
+                    format!("🌗 BOOSTED but {planet} RETROGRADE 🌗 - Delays likely despite {}!", e.name())
+                }
                 e if e == ideal && clash => format!("⚔️ BOOSTED but CONTESTED ⚔️ - {contested_msg}"),
                 e if e == ideal => format!("✨ BOOSTED ✨ - {boosted_msg}"),
+                e if e == opposed && retrograde => format!("⚠️  DEBUFFED ⚠️  - {debuffed_msg} And {planet} is retrograde!"),
                 e if e == opposed => format!("⚠️  DEBUFFED ⚠️  - {debuffed_msg}"),
+                _ if retrograde => format!("Neutral but {planet} retrograde - Expect delays"),
                 _ => "Neutral - Normal operations".to_string(),
+            };
+
+            let aspects = || aspects_to(pos, positions).filter(|_| self.aspects);
+            let factor = aspect_modifier(aspects());
+            let strongest = aspects()
+                .max_by(|a, b| (a.kind.modifier().abs() * a.strength).total_cmp(&(b.kind.modifier().abs() * b.strength)));
+            if let Some(aspect) = strongest.filter(|_| (factor - 1.0).abs() >= ASPECT_NOTE_THRESHOLD) {
+                let _ = write!(
+                    status,
+                    " ({} {} {} {})",
+                    aspect.kind.symbol(),
+                    aspect.kind.name(),
+                    aspect.other.name(),
+                    if factor > 1.0 { "helps" } else { "hinders" }
+                );
             }
+            status
         };
 
-        let mars_pos = positions.iter().find(|p| p.planet == Planet::Mars).unwrap();
-        let _ = writeln!(report, "🔥 CPU-Intensive Tasks (Mars in {}): {}",
+        let mars_pos = find_position(positions, self.rulership.ruler(TaskType::CpuIntensive))?;
+        let _ = writeln!(report, "🔥 CPU-Intensive Tasks ({} in {}): {}",
+            mars_pos.planet.name(),
             mars_pos.sign.name(),
-            task_status(Planet::Mars, Element::Fire, Element::Water, fire_water_clash,
+            task_status(mars_pos, TaskType::CpuIntensive, Element::Fire, Element::Water, fire_water_clash,
                 "Compilations and calculations favored!",
                 "Fire powers CPU but Water planets oppose!",
                 "Water dampens the CPU fires!"));
 
-        let merc_pos = positions.iter().find(|p| p.planet == Planet::Mercury).unwrap();
-        let _ = writeln!(report, "💬 Network Tasks (Mercury in {}): {}",
+        let merc_pos = find_position(positions, self.rulership.ruler(TaskType::Network))?;
+        let _ = writeln!(report, "💬 Network Tasks ({} in {}): {}",
+            merc_pos.planet.name(),
             merc_pos.sign.name(),
-            task_status(Planet::Mercury, Element::Air, Element::Earth, earth_air_clash,
+            task_status(merc_pos, TaskType::Network, Element::Air, Element::Earth, earth_air_clash,
                 "Network communications flow freely!",
                 "Air speeds networks but Earth planets oppose!",
                 "Earth blocks network packets!"));
 
-        let jup_pos = positions.iter().find(|p| p.planet == Planet::Jupiter).unwrap();
-        let _ = writeln!(report, "💾 Memory-Heavy Tasks (Jupiter in {}): {}",
+        let jup_pos = find_position(positions, self.rulership.ruler(TaskType::MemoryHeavy))?;
+        let _ = writeln!(report, "💾 Memory-Heavy Tasks ({} in {}): {}",
+            jup_pos.planet.name(),
             jup_pos.sign.name(),
-            task_status(Planet::Jupiter, Element::Water, Element::Fire, fire_water_clash,
+            task_status(jup_pos, TaskType::MemoryHeavy, Element::Water, Element::Fire, fire_water_clash,
                 "Databases and caches optimized!",
                 "Water fills memory but Fire planets oppose!",
                 "Fire evaporates memory pools!"));
 
-        let sat_pos = positions.iter().find(|p| p.planet == Planet::Saturn).unwrap();
-        let _ = writeln!(report, "⚙️  System Tasks (Saturn in {}): {}",
+        let sat_pos = find_position(positions, self.rulership.ruler(TaskType::System))?;
+        let _ = writeln!(report, "⚙️  System Tasks ({} in {}): {}",
+            sat_pos.planet.name(),
             sat_pos.sign.name(),
-            task_status(Planet::Saturn, Element::Earth, Element::Air, earth_air_clash,
+            task_status(sat_pos, TaskType::System, Element::Earth, Element::Air, earth_air_clash,
                 "System operations rock solid!",
                 "Earth stabilizes systems but Air planets oppose!",
                 "Air disrupts system stability!"));
 
+        // Desktop has no element against it, so the ideal element doubles as the opposed one
+        let venus_pos = find_position(positions, self.rulership.ruler(TaskType::Desktop))?;
+        let desktop_element = match venus_pos.sign.element() {
+            e @ (Element::Air | Element::Water) => e,
+            _ => Element::Air,
+        };
+        let venus_phase = Self::desktop_venus_elongation(positions, venus_pos, TaskType::Desktop)
+            .map(|elongation| {
+                format!(
+                    " | 🌟 {}, {:.0}° from the Sun ({:.2}x)",
+                    VenusPhase::from_elongation(elongation).name(),
+                    elongation.abs(),
+                    venus_phase_modifier(elongation)
+                )
+            })
+            .unwrap_or_default();
+        let _ = writeln!(report, "🖥️  Desktop Tasks ({} in {}): {}{venus_phase}",
+            venus_pos.planet.name(),
+            venus_pos.sign.name(),
+            task_status(venus_pos, TaskType::Desktop, desktop_element, desktop_element, false,
+                "Windows glide and cursors flow in harmony!",
+                "",
+                ""));
+
+        let uranus_pos = find_position(positions, self.rulership.ruler(TaskType::Virtualization))?;
+        let _ = writeln!(report, "🛸 Virtualization Tasks ({} in {}): {}",
+            uranus_pos.planet.name(),
+            uranus_pos.sign.name(),
+            task_status(uranus_pos, TaskType::Virtualization, Element::Air, Element::Earth, earth_air_clash,
+                "Guests boot in a flash of insight!",
+                "Air frees the hypervisor but Earth planets oppose!",
+                "Earth pins the guests to bare metal!"));
+
         // Element summary
         report.push_str("\n📊 Elemental Balance:\n");
 
@@ -351,7 +1871,149 @@ impl AstrologicalScheduler {
             report.push_str("   ✌️  The elements are at peace (for now).\n");
         }
 
-        report
+        Ok(report)
+    }
+
+    /// Planetary hours of the day starting at sunrise on `date`'s date
+    fn hours_today(&self, date: DateTime<Utc>) -> Result<Vec<PlanetaryHour>, AstroError> {
+        let (latitude, longitude) = self.location.ok_or(AstroError::MissingLocation)?;
+        planetary_hours(date.date_naive(), latitude, longitude)
+    }
+
+    /// Whether an hour ruled by `ruler` is bad for `task_type`: Saturn and
+    /// Mars hours, unless the task type is ruled by that planet itself
+    fn is_unlucky(&self, task_type: TaskType, ruler: Planet) -> bool {
+        matches!(ruler, Planet::Saturn | Planet::Mars) && ruler != self.rulership.ruler(task_type)
+    }
+
+    /// Today's planetary hours ruled by the task type's planet
+    ///
+    /// These are the windows to aim heavy work of that type at. Empty without
+    /// a location or when the Sun does not rise.
+    pub fn get_lucky_hours_today(&self, task_type: TaskType, date: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let ruler = self.rulership.ruler(task_type);
+        self.hours_today(date)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hour| hour.ruler == ruler)
+            .map(|hour| (hour.start, hour.end))
+            .collect()
+    }
+
+    /// Today's Saturn and Mars hours, for task types those planets don't rule
+    pub fn get_unlucky_hours_today(&self, task_type: TaskType, date: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        self.hours_today(date)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hour| self.is_unlucky(task_type, hour.ruler))
+            .map(|hour| (hour.start, hour.end))
+            .collect()
+    }
+
+    /// Today's planetary hours for a task type, lucky and unlucky ones marked
+    pub fn lucky_hours_reference(&self, task_type: TaskType, date: DateTime<Utc>) -> Result<String, AstroError> {
+        use std::fmt::Write;
+        let hours = self.hours_today(date)?;
+        let ruler = self.rulership.ruler(task_type);
+        let lucky = self.get_lucky_hours_today(task_type, date);
+        let unlucky = self.get_unlucky_hours_today(task_type, date);
+
+        let mut reference = format!(
+            "🍀 LUCKY HOURS: {} (ruled by {}) on {} 🍀\n",
+            task_type.name(),
+            ruler.name(),
+            date.format("%A %Y-%m-%d")
+        );
+        for hour in hours {
+            let window = (hour.start, hour.end);
+            let mark = if lucky.contains(&window) {
+                "✨ lucky"
+            } else if unlucky.contains(&window) {
+                "⚠️  unlucky"
+            } else {
+                ""
+            };
+            let line = format!(
+                "  {}–{} UTC {} {:<8} {mark}",
+                hour.start.format("%H:%M"),
+                hour.end.format("%H:%M"),
+                if hour.night { "🌙" } else { "☀️ " },
+                hour.ruler.name()
+            );
+            let _ = writeln!(reference, "{}", line.trim_end());
+        }
+        Ok(reference)
+    }
+
+    /// The next solar and lunar returns of a PID whose start time was noted
+    pub fn return_reference(&mut self, pid: i32, now: DateTime<Utc>) -> Option<String> {
+        use std::fmt::Write;
+        let birth = *self.births.get(&pid)?;
+        let mut reference = format!("🔁 RETURNS: PID {pid}, started {} 🔁\n", birth.format("%Y-%m-%d %H:%M:%S UTC"));
+        for (return_type, symbol) in [(ReturnType::Lunar, "🌙"), (ReturnType::Solar, "☀️ ")] {
+            let next = self.get_next_process_return(pid, return_type, now)?;
+            let natal = find_position(&self.natal_chart(birth)?.positions, return_type.planet()).ok()?;
+            let _ = writeln!(
+                reference,
+                "  {symbol} {:<13} {} ({} back at {:.1}° {}, in {} days)",
+                return_type.name(),
+                next.format("%Y-%m-%d %H:%M UTC"),
+                return_type.planet().name(),
+                natal.degree_in_sign,
+                natal.sign.name(),
+                (next - now).num_minutes() / 1440
+            );
+        }
+        Some(reference)
+    }
+
+    /// Reference card of the zodiac signs, their elements and the moon phases
+    pub fn sign_reference() -> String {
+        use std::fmt::Write;
+        let mut reference = String::from("♈ ZODIAC SIGNS ♈\n");
+        for sign in ZodiacSign::all() {
+            let _ = writeln!(reference, "  {:<12} {}", sign.name(), sign.element().name());
+        }
+
+        reference.push_str("\n🌙 MOON PHASES (Interactive task modifier) 🌙\n");
+        for phase in MoonPhase::all() {
+            let _ = writeln!(reference, "  {:<16} {:.1}x", phase.name(), Self::moon_phase_modifier(phase));
+        }
+        reference
+    }
+
+    /// Reference card of the planets, their domains and the task types they rule
+    pub fn planet_reference(&self) -> String {
+        use std::fmt::Write;
+        let mut reference = String::from("🪐 PLANETS 🪐\n");
+        for planet in Planet::all() {
+            let _ = writeln!(reference, "  {:<8} {}", planet.name(), planet.domain());
+
+            let ruled: Vec<_> = TaskType::all()
+                .into_iter()
+                .filter(|&task_type| self.rulership.ruler(task_type) == planet)
+                .collect();
+            if ruled.is_empty() {
+                reference.push_str("           rules nothing\n");
+            }
+            for task_type in ruled {
+                let _ = write!(reference, "           rules {}", task_type.name());
+                if let Some(element) = task_type.favored_element() {
+                    let _ = write!(reference, " (favors {})", element.name());
+                }
+                let affinities: Vec<_> = Element::all()
+                    .into_iter()
+                    .map(|element| (element, Self::calculate_element_boost(element, task_type)))
+                    .filter(|&(_, boost)| (boost - 1.0).abs() > f64::EPSILON)
+                    .map(|(element, boost)| format!("{} {boost:.1}x", element.name()))
+                    .collect();
+                if !affinities.is_empty() {
+                    let _ = write!(reference, " | {}", affinities.join(", "));
+                }
+                reference.push('\n');
+            }
+        }
+        reference
     }
 }
 
@@ -364,6 +2026,7 @@ impl Default for AstrologicalScheduler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::astrology::planets::calculate_planetary_positions;
 
     #[test]
     fn test_scheduler_creation() {
@@ -376,54 +2039,272 @@ mod tests {
         let mut scheduler = AstrologicalScheduler::new(300);
         let now = Utc::now();
 
-        let decision = scheduler.schedule_task("init", 1, now);
+        let decision = scheduler.schedule_task("init", 1, DEFAULT_TASK_WEIGHT, now).unwrap();
 
         assert_eq!(decision.priority, 1000);
         assert!(decision.reasoning.contains("CRITICAL"));
     }
 
+    #[test]
+    fn test_critical_kernel_thread() {
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let now = Utc::now();
+
+        let decision = scheduler.schedule_task_with_flags("ksoftirqd/2", 24, DEFAULT_TASK_WEIGHT, true, 0, now).unwrap();
+        assert!(decision.critical);
+        assert_eq!(decision.task_type, TaskType::Critical);
+        assert_eq!(decision.priority, 1000);
+        assert!(decision.reasoning.contains("kernel infrastructure"));
+
+        let decision = scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert!(!decision.critical);
+    }
+
+    #[test]
+    fn test_fast_path_decisions() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use crate::astrology::tasks::TASK_FLAG_RT;
+        let now = Utc::now();
+        let mut scheduler = AstrologicalScheduler::new(300)
+            .with_ephemeris(Box::new(FixedEphemeris::new(retrograde_mars_chart())));
+
+        // The critical policy still shields init and kernel threads
+        for (comm, pid, is_kthread) in [("systemd", 1, false), ("ksoftirqd/2", 24, true)] {
+            let decision = scheduler.schedule_task_fast(comm, pid, DEFAULT_TASK_WEIGHT, is_kthread, 0, now).unwrap();
+            assert!(decision.critical, "{comm}");
+            assert_eq!(decision.task_type, TaskType::Critical);
+            assert_eq!(decision.priority, 1000);
+        }
+        let rt = scheduler.schedule_task_fast("pipewire", 4000, DEFAULT_TASK_WEIGHT, false, TASK_FLAG_RT, now).unwrap();
+        assert!(rt.critical);
+
+        // Everyone else gets the table: same type and chart priority as a full reading, weight still blended
+        let full = scheduler.schedule_task_with_flags("rustc", 5678, 400, false, 0, now).unwrap();
+        let fast = scheduler.schedule_task_fast("rustc", 5678, 400, false, 0, now).unwrap();
+        assert_eq!(fast.task_type, TaskType::CpuIntensive);
+        assert!(!fast.critical);
+        assert_eq!(fast.astro_priority, full.astro_priority);
+        assert_eq!(fast.priority, full.priority);
+        assert!(fast.planetary_influence < 0.0);
+        assert!(fast.reasoning.contains("fast path"));
+    }
+
+    #[test]
+    fn test_reasoning_can_be_left_out() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        let now = Utc::now();
+        let chart = || Box::new(FixedEphemeris::new(retrograde_mars_chart()));
+        let mut wordy = AstrologicalScheduler::new(300).with_ephemeris(chart());
+        let mut quiet = AstrologicalScheduler::new(300).with_ephemeris(chart()).with_reasoning(false);
+
+        // Same decisions, without the words
+        for (comm, pid) in [("rustc", 5678), ("systemd", 1), ("firefox", 4242)] {
+            let said = wordy.schedule_task(comm, pid, DEFAULT_TASK_WEIGHT, now).unwrap();
+            let unsaid = quiet.schedule_task(comm, pid, DEFAULT_TASK_WEIGHT, now).unwrap();
+            assert!(!said.reasoning.is_empty(), "{comm}");
+            assert!(unsaid.reasoning.is_empty(), "{comm}");
+            assert_eq!((unsaid.task_type, unsaid.priority), (said.task_type, said.priority), "{comm}");
+        }
+        assert!(quiet.schedule_task_fast("rustc", 5678, DEFAULT_TASK_WEIGHT, false, 0, now).unwrap().reasoning.is_empty());
+
+        // Asking for an explanation still gets one, and leaves the setting alone
+        let explained = quiet.explain_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert!(explained.reasoning.contains("RETROGRADE"), "{}", explained.reasoning);
+        assert!(quiet.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap().reasoning.is_empty());
+    }
+
+    #[test]
+    fn test_user_critical_comm_overrides_debuff() {
+        use chrono::{Duration, TimeZone};
+        let mut plain = AstrologicalScheduler::new(0);
+        let mut shielded = AstrologicalScheduler::new(0)
+            .with_critical_policy(CriticalPolicy::new().with_comm("rustc"));
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        let mut debuffed_days = 0;
+        for day in (0..730).step_by(5) {
+            let now = start + Duration::days(day);
+            plain.refresh(now).unwrap();
+            let decision = plain.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+            if decision.element_boost < 1.0 || decision.planetary_influence < 0.0 {
+                debuffed_days += 1;
+                let decision = shielded.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+                assert!(decision.critical);
+                assert_eq!(decision.priority, 1000);
+                assert!(decision.planetary_influence > 0.0);
+                assert!(decision.reasoning.contains("user-marked"));
+            }
+        }
+        assert!(debuffed_days > 0, "Mars never troubled rustc in two years");
+    }
+
+    #[test]
+    fn test_base_priority_override() {
+        let mut scheduler = AstrologicalScheduler::new(300).with_out_of_bounds_jitter(false);
+        let now = Utc::now();
+        assert_eq!(scheduler.base_priority(TaskType::MemoryHeavy), 80);
+
+        let before = scheduler.schedule_task("postgres", 4242, DEFAULT_TASK_WEIGHT, now).unwrap();
+        scheduler.set_base_priority(TaskType::MemoryHeavy, 160);
+        let after = scheduler.schedule_task("postgres", 4242, DEFAULT_TASK_WEIGHT, now).unwrap();
+
+        assert_eq!(scheduler.base_priority(TaskType::MemoryHeavy), 160);
+        assert!(after.astro_priority >= before.astro_priority * 2 - 1);
+        assert_eq!(scheduler.base_priority(TaskType::System), 200);
+    }
+
+    #[test]
+    fn test_planet_reference() {
+        let reference = AstrologicalScheduler::new(300).planet_reference();
+        let mars = reference.lines().skip_while(|line| !line.contains("Mars")).nth(1).unwrap();
+        assert!(mars.contains("rules CPU-Intensive"));
+        assert!(mars.contains("favors Fire"));
+        assert!(mars.contains("Fire 1.5x"));
+        assert!(mars.contains("Water 0.6x"));
+        assert!(reference.contains("Uranus"));
+
+        // Reassigned rulerships show up under their new planet
+        let mut rulership = RulershipMap::default();
+        rulership.reassign(TaskType::System, Planet::Sun);
+        let reference = AstrologicalScheduler::new(300).with_rulership(rulership).planet_reference();
+        let saturn = reference.lines().skip_while(|line| !line.contains("Saturn")).nth(1).unwrap();
+        assert!(saturn.contains("rules nothing"));
+    }
+
+    #[test]
+    fn test_sign_reference() {
+        let reference = AstrologicalScheduler::sign_reference();
+        assert!(reference.lines().any(|line| line.contains("Scorpio") && line.contains("Water")));
+        assert!(reference.lines().any(|line| line.contains("Full Moon") && line.contains("1.4x")));
+        assert_eq!(reference.lines().filter(|line| line.contains(" Fire")).count(), 3);
+    }
+
     #[test]
     fn test_task_scheduling() {
         let mut scheduler = AstrologicalScheduler::new(300);
         let now = Utc::now();
 
         // Test various task types
-        let firefox_decision = scheduler.schedule_task("firefox", 1234, now);
+        let firefox_decision = scheduler.schedule_task("firefox", 1234, DEFAULT_TASK_WEIGHT, now).unwrap();
         assert!(firefox_decision.priority > 0);
         assert!(!firefox_decision.reasoning.is_empty());
 
-        let rustc_decision = scheduler.schedule_task("rustc", 5678, now);
+        let rustc_decision = scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
         assert!(rustc_decision.priority > 0);
 
-        let systemd_decision = scheduler.schedule_task("systemd", 100, now);
+        let systemd_decision = scheduler.schedule_task("systemd", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
         assert!(systemd_decision.priority > 0);
     }
 
+    #[test]
+    fn test_nice_weight_blending() {
+        use chrono::{Duration, TimeZone};
+        // Out-of-bounds jitter differs per PID; keep it out of the comparison
+        let mut scheduler = AstrologicalScheduler::new(0).with_out_of_bounds_jitter(false);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        // Nice 19 (weight 1) vs nice -10 (weight ~930) across a year of skies
+        for day in (0..365).step_by(7) {
+            let now = start + Duration::days(day);
+            scheduler.refresh(now).unwrap();
+            for comm in ["rustc", "sshd", "postgres", "systemd", "bash", "gnome-shell"] {
+                let low = scheduler.schedule_task(comm, 2000, 1, now).unwrap();
+                let high = scheduler.schedule_task(comm, 2001, 930, now).unwrap();
+                assert_eq!(low.astro_priority, high.astro_priority);
+                assert!(low.priority <= high.priority,
+                        "{comm}: nice 19 ({}) out-prioritized nice -10 ({}) on day {day}",
+                        low.priority, high.priority);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weight_blend_factor() {
+        let now = Utc::now();
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let nice0 = scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert_eq!(nice0.priority, nice0.astro_priority);
+
+        let mut pure_astro = AstrologicalScheduler::new(300).with_weight_blend(0.0);
+        let ignored = pure_astro.schedule_task("rustc", 5678, 10_000, now).unwrap();
+        assert_eq!(ignored.priority, ignored.astro_priority);
+
+        let mut blended = AstrologicalScheduler::new(300);
+        let heavy = blended.schedule_task("rustc", 5678, 400, now).unwrap();
+        assert!((heavy.weight_factor - 2.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_planetary_caching() {
         let mut scheduler = AstrologicalScheduler::new(300);
         let now = Utc::now();
 
         // First call should populate cache
-        scheduler.schedule_task("bash", 1000, now);
+        scheduler.schedule_task("bash", 1000, DEFAULT_TASK_WEIGHT, now).unwrap();
         assert!(scheduler.planetary_cache.is_some());
 
         let cached_time = scheduler.planetary_cache.as_ref().unwrap().0;
 
         // Second call within cache window should reuse cache
-        scheduler.schedule_task("vim", 1001, now);
+        scheduler.schedule_task("vim", 1001, DEFAULT_TASK_WEIGHT, now).unwrap();
         let still_cached_time = scheduler.planetary_cache.as_ref().unwrap().0;
 
         assert_eq!(cached_time, still_cached_time);
     }
 
 
+    #[test]
+    fn test_refresh_advances_chart_epoch() {
+        use chrono::{Duration, TimeZone};
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let start = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        assert!(scheduler.needs_refresh(start));
+
+        scheduler.refresh(start).unwrap();
+        assert_eq!(scheduler.chart_epoch(), Some(start));
+        assert!(!scheduler.needs_refresh(start + Duration::seconds(300)));
+        assert!(scheduler.needs_refresh(start + Duration::seconds(301)));
+
+        // A chart computed elsewhere is swapped in as is
+        let later = start + Duration::hours(6);
+        let chart = scheduler.chart_source().compute(later).unwrap();
+        let moon = |positions: &[PlanetaryPosition]| find_position(positions, Planet::Moon).unwrap().longitude;
+        let moon_later = moon(&chart.positions);
+        scheduler.install_chart(chart);
+        assert_eq!(scheduler.chart_epoch(), Some(later));
+        assert!((moon(&scheduler.positions(later).unwrap()) - moon_later).abs() < 1e-9);
+        assert_eq!(scheduler.charts_computed(), 1);
+    }
+
+    #[test]
+    fn test_schedule_task_never_recomputes() {
+        use chrono::{Duration, TimeZone};
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let start = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+
+        // The first decision has nothing to read, so it casts the first chart
+        scheduler.schedule_task("bash", 1000, DEFAULT_TASK_WEIGHT, start).unwrap();
+        assert_eq!(scheduler.charts_computed(), 1);
+
+        // Long after the chart has gone stale, decisions still read it
+        for hours in 1..48 {
+            let now = start + Duration::hours(hours);
+            scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, now).unwrap();
+            scheduler.schedule_task_fast("rustc", 5678, DEFAULT_TASK_WEIGHT, false, 0, now).unwrap();
+        }
+        assert_eq!(scheduler.charts_computed(), 1);
+        assert_eq!(scheduler.chart_epoch(), Some(start));
+
+        scheduler.refresh(start + Duration::hours(48)).unwrap();
+        assert_eq!(scheduler.charts_computed(), 2);
+    }
+
     #[test]
     fn test_cosmic_weather_report() {
         let mut scheduler = AstrologicalScheduler::new(300);
         let now = Utc::now();
 
-        let report = scheduler.get_cosmic_weather(now);
+        let report = scheduler.get_cosmic_weather(now).unwrap();
 
         assert!(report.contains("COSMIC WEATHER"));
         assert!(report.contains("Sun"));
@@ -431,35 +2312,1191 @@ mod tests {
         assert!(report.contains("ASTROLOGICAL GUIDANCE"));
     }
 
+    #[test]
+    fn test_weather_guidance_mixed_verdicts() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // Mars in Aries, with everything but Jupiter bunched up out of aspect to it
+        let chart = |mars_retrograde: bool, jupiter: f64| {
+            let mut positions = calculate_planetary_positions(now).unwrap();
+            for (i, pos) in positions.iter_mut().enumerate() {
+                #[allow(clippy::cast_precision_loss)]
+                let longitude = match pos.planet {
+                    Planet::Mars => 15.0,
+                    Planet::Jupiter => jupiter,
+                    _ => 35.0 + 3.0 * i as f64,
+                };
+                pos.longitude = longitude;
+                pos.latitude = 0.0;
+                pos.sign = ZodiacSign::from_longitude(longitude);
+                pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                pos.retrograde = pos.planet == Planet::Mars && mars_retrograde;
+            }
+            AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(positions)))
+        };
+        let cpu_line = |mut scheduler: AstrologicalScheduler| {
+            let report = scheduler.get_cosmic_weather(now).unwrap();
+            report.lines().find(|line| line.contains("CPU-Intensive")).unwrap().to_string()
+        };
+
+        let direct = cpu_line(chart(false, 160.0));
+        assert!(direct.contains("✨ BOOSTED ✨"), "{direct}");
+        assert!(!direct.contains("helps") && !direct.contains("hinders"), "{direct}");
+
+        // Fire still boosts a retrograde Mars, but not without a warning
+        let retrograde = cpu_line(chart(true, 160.0));
+        assert!(retrograde.contains("BOOSTED but Mars RETROGRADE"), "{retrograde}");
+        assert!(retrograde.contains("Delays likely despite Fire"), "{retrograde}");
+
+        // An exact trine from Jupiter is worth a mention, an exact square too
+        assert!(cpu_line(chart(false, 135.0)).contains("△ trine Jupiter helps"));
+        assert!(cpu_line(chart(false, 105.0)).contains("□ square Jupiter hinders"));
+    }
+
+    #[test]
+    fn test_part_of_fortune_luck() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use crate::astrology::lots::PartOfFortune;
+        use crate::astrology::planets::calculate_ascendant;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let (latitude, longitude) = (51.5074, -0.1278);
+
+        // Mars put on the Part of Fortune of the London chart
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        let ascendant = calculate_ascendant(now, latitude, longitude).unwrap();
+        let fortune = PartOfFortune::new(ascendant, &positions).unwrap();
+        for pos in positions.iter_mut().filter(|pos| pos.planet == Planet::Mars) {
+            pos.longitude = fortune.longitude;
+            pos.latitude = 0.0;
+            pos.sign = fortune.sign;
+            pos.degree_in_sign = ZodiacSign::degree_in_sign(fortune.longitude);
+            pos.retrograde = false;
+        }
+        let scheduler = || {
+            AstrologicalScheduler::new(300).with_aspects(false).with_ephemeris(Box::new(FixedEphemeris::new(positions.clone())))
+        };
+
+        // Without a location there is no ascendant to cast it from
+        let mut nowhere = scheduler();
+        let mut london = scheduler().with_location(latitude, longitude);
+        let plain = nowhere.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let lucky = london.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert!(!plain.reasoning.contains("🍀"));
+        let on_fortune = format!("🍀 Mars on the Part of Fortune in {}", fortune.sign.name());
+        assert!(lucky.reasoning.contains(&on_fortune), "{}", lucky.reasoning);
+        let ratio = f64::from(lucky.astro_priority) / f64::from(plain.astro_priority);
+        assert!((ratio - FORTUNE_BONUS).abs() < 0.02, "{ratio}");
+
+        let report = london.get_cosmic_weather(now).unwrap();
+        assert!(report.contains(&format!("🍀 Part of Fortune in {}", fortune.sign.name())), "{report}");
+        assert!(!nowhere.get_cosmic_weather(now).unwrap().contains("Part of Fortune"));
+    }
+
+    #[test]
+    fn test_warmup_dispatches_are_neutral() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let mut scheduler = AstrologicalScheduler::new(300).with_warmup_dispatches(3);
+        let revision = scheduler.revision();
+
+        // Critical tasks stay critical, without using up the warm-up
+        assert!(scheduler.schedule_task("systemd", 1, DEFAULT_TASK_WEIGHT, now).unwrap().critical);
+        for pid in 100..103 {
+            let decision = scheduler.schedule_task("rustc", pid, DEFAULT_TASK_WEIGHT, now).unwrap();
+            assert_eq!(decision.task_type, TaskType::CpuIntensive);
+            assert_eq!(decision.priority, AstrologicalScheduler::default_base_priority(TaskType::CpuIntensive));
+            assert_eq!(decision.ruling_element, None);
+            assert!(decision.reasoning.starts_with("🌱 Warming up"), "{}", decision.reasoning);
+        }
+        // No chart was cast for them, and memoized neutral decisions go stale
+        assert_eq!(scheduler.charts_computed(), 0);
+        assert_ne!(scheduler.revision(), revision);
+
+        // The fourth decision is the stars'
+        let decision = scheduler.schedule_task("rustc", 103, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert!(!decision.reasoning.contains("Warming up"), "{}", decision.reasoning);
+        let mars = find_position(&scheduler.positions(now).unwrap(), Planet::Mars).unwrap().sign.element();
+        assert_eq!(decision.ruling_element, Some(mars));
+        assert_eq!(scheduler.charts_computed(), 1);
+    }
+
+    #[test]
+    fn test_daily_cosmic_message() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // The Sun put at 7°12′ Libra, the eighth Sabian degree
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in positions.iter_mut().filter(|pos| pos.planet == Planet::Sun) {
+            pos.longitude = 187.2;
+            pos.sign = ZodiacSign::Libra;
+            pos.degree_in_sign = ZodiacSign::degree_in_sign(187.2);
+        }
+        let mut scheduler = AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(positions)));
+        assert_eq!(scheduler.get_daily_cosmic_message(now).unwrap(), "A blazing fireplace in a deserted home");
+        assert_eq!(
+            scheduler.daily_cosmic_message_line(now).unwrap(),
+            "☉ at 8° Libra: 'A blazing fireplace in a deserted home'"
+        );
+    }
+
+    #[test]
+    fn test_unnamed_task() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        // An all-NUL comm, trimmed at the first NUL as the BPF side's names are
+        let raw = [0_u8; 16];
+        let comm = std::str::from_utf8(&raw[..raw.iter().position(|&c| c == 0).unwrap_or(raw.len())]).unwrap();
+
+        let mut scheduler = AstrologicalScheduler::new(300).with_fuzzy_match_distance(2);
+        let decision = scheduler.schedule_task_with_flags(comm, 42, DEFAULT_TASK_WEIGHT, false, 0, now).unwrap();
+        assert_eq!(decision.task_type, TaskType::Interactive);
+        assert!(!decision.critical);
+        assert!(decision.priority >= 1);
+        assert!(decision.reasoning.contains("unnamed task PID 42"), "{}", decision.reasoning);
+        assert!(scheduler.schedule_task_fast(comm, 43, DEFAULT_TASK_WEIGHT, false, 0, now).is_ok());
+        assert_eq!(scheduler.anonymous_tasks(), 2);
+
+        // A named task is not counted
+        scheduler.schedule_task_with_flags("bash", 44, DEFAULT_TASK_WEIGHT, false, 0, now).unwrap();
+        assert_eq!(scheduler.anonymous_tasks(), 2);
+    }
+
+    #[test]
+    fn test_install_chart_fires_ingresses() {
+        use chrono::TimeZone;
+        use std::sync::mpsc;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let first = calculate_planetary_positions(now).unwrap();
+        let mut second = first.clone();
+        for pos in second.iter_mut().filter(|pos| pos.planet == Planet::Moon) {
+            pos.longitude = (pos.longitude + 30.0).rem_euclid(360.0);
+            pos.sign = ZodiacSign::from_longitude(pos.longitude);
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let mut scheduler = AstrologicalScheduler::new(300);
+        scheduler.ingress_notifier_mut().register_callback(Box::new(move |event| sender.send(*event).unwrap()));
+        // The first chart has nothing to compare with
+        scheduler.install_chart(Chart { time: now, positions: first.clone(), north_node: None });
+        assert_eq!(receiver.try_iter().count(), 0);
+        scheduler.install_chart(Chart { time: now, positions: second, north_node: None });
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].planet, Planet::Moon);
+        assert_eq!(events[0].from_sign, find_position(&first, Planet::Moon).unwrap().sign);
+    }
+
+    #[test]
+    fn test_retrograde_window_brackets_mid_retrograde() {
+        use chrono::TimeZone;
+        let mut scheduler = AstrologicalScheduler::new(300);
+
+        // Mercury stationed retrograde on November 9, 2025 and direct on the 29th
+        let now = Utc.with_ymd_and_hms(2025, 11, 19, 12, 0, 0).unwrap();
+        let (start, end) = scheduler.retrograde_window(Planet::Mercury, now).unwrap();
+        assert!(start <= now && now < end, "{start} - {end}");
+        assert!((end - start).num_days() < 30, "{start} - {end}");
+        assert_eq!(scheduler.retrograde_window(Planet::Mercury, now + chrono::Duration::days(2)), Some((start, end)));
+
+        // Nothing to report for a direct planet
+        assert_eq!(scheduler.retrograde_window(Planet::Sun, now), None);
+
+        let report = scheduler.get_cosmic_weather(now).unwrap();
+        let since = format!("℞ since {}, direct {}", start.format("%b %-d"), end.format("%b %-d"));
+        assert!(report.lines().any(|line| line.starts_with("Mercury") && line.contains(&since)), "{report}");
+    }
+
     #[test]
     fn test_element_boost() {
         let now = Utc::now();
-        let positions = calculate_planetary_positions(now);
+        let positions = calculate_planetary_positions(now).unwrap();
+        let mars = find_position(&positions, Planet::Mars).unwrap();
+        let mercury = find_position(&positions, Planet::Mercury).unwrap();
 
         // Test that boosts are calculated
-        let cpu_boost = AstrologicalScheduler::calculate_element_boost(&positions, TaskType::CpuIntensive);
-        let net_boost = AstrologicalScheduler::calculate_element_boost(&positions, TaskType::Network);
+        let cpu_boost = AstrologicalScheduler::calculate_element_boost(mars.sign.element(), TaskType::CpuIntensive);
+        let net_boost = AstrologicalScheduler::calculate_element_boost(mercury.sign.element(), TaskType::Network);
 
         assert!(cpu_boost > 0.0);
         assert!(net_boost > 0.0);
     }
 
+    #[test]
+    fn test_assess_missing_ruling_planet() {
+        let now = Utc::now();
+        let positions: Vec<_> = calculate_planetary_positions(now).unwrap()
+            .into_iter()
+            .filter(|p| p.planet != Planet::Mars)
+            .collect();
+
+        let scheduler = AstrologicalScheduler::new(300);
+        assert!(matches!(
+            scheduler.assess(&positions, TaskType::CpuIntensive),
+            Err(AstroError::MissingPlanet(Planet::Mars))
+        ));
+    }
+
+    #[test]
+    fn test_rulership_remap_changes_driving_planet() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in &mut positions {
+            pos.retrograde = false;
+            match pos.planet {
+                // Saturn in Air debuffs System tasks, the Sun in Earth blesses them
+                Planet::Saturn => pos.sign = ZodiacSign::Gemini,
+                Planet::Sun => pos.sign = ZodiacSign::Capricorn,
+                _ => {}
+            }
+        }
+
+        let classic = AstrologicalScheduler::new(300);
+        let saturn_ruled = classic.assess(&positions, TaskType::System).unwrap();
+        assert_eq!(saturn_ruled.planet_pos.planet, Planet::Saturn);
+
+        let mut rulership = RulershipMap::default();
+        rulership.reassign(TaskType::System, Planet::Sun);
+        let solar = AstrologicalScheduler::new(300).with_rulership(rulership);
+        let sun_ruled = solar.assess(&positions, TaskType::System).unwrap();
+        assert_eq!(sun_ruled.planet_pos.planet, Planet::Sun);
+        assert!(sun_ruled.priority > saturn_ruled.priority);
+
+        // Other task types keep their classic rulers
+        let cpu = solar.assess(&positions, TaskType::CpuIntensive).unwrap();
+        assert_eq!(cpu.planet_pos.planet, Planet::Mars);
+    }
+
+    #[test]
+    fn test_schedule_task_propagates_ephemeris_failure() {
+        use chrono::TimeZone;
+        let mut scheduler = AstrologicalScheduler::new(300);
+        let far_future = Utc.with_ymd_and_hms(40_000, 1, 1, 0, 0, 0).unwrap();
+
+        assert!(matches!(
+            scheduler.schedule_task("rustc", 5678, DEFAULT_TASK_WEIGHT, far_future),
+            Err(AstroError::EphemerisFailure(_))
+        ));
+        assert!(scheduler.planetary_cache.is_none());
+    }
+
+    #[test]
+    fn test_compare_conditions_prefers_direct_sky() {
+        use chrono::{Duration, TimeZone};
+        let a = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let b = a + Duration::hours(12);
+
+        let mut direct = calculate_planetary_positions(a).unwrap();
+        for pos in &mut direct {
+            pos.retrograde = false;
+        }
+        let mut chaotic = direct.clone();
+        for pos in &mut chaotic {
+            if matches!(pos.planet, Planet::Mercury | Planet::Mars | Planet::Saturn) {
+                pos.retrograde = true;
+            }
+        }
+
+        // Argument order must not matter for the verdict
+        let scheduler = AstrologicalScheduler::new(300);
+        let cmp = scheduler.compare_charts(b, &chaotic, a, &direct).unwrap();
+        assert_eq!(cmp.better_time, a);
+        assert_eq!(cmp.worse_time, b);
+        assert_eq!(cmp.retrograde_count_a, 3);
+        assert_eq!(cmp.retrograde_count_b, 0);
+        assert!(cmp.overall_score_b > cmp.overall_score_a);
+        assert!(cmp.advantage_by_type[&TaskType::Network] < 0.0);
+        assert!(cmp.advantage_by_type[&TaskType::CpuIntensive] < 0.0);
+        assert!(cmp.advantage_by_type[&TaskType::System] < 0.0);
+        assert!(!cmp.advantage_by_type.contains_key(&TaskType::Critical));
+        assert!(!cmp.is_tie());
+    }
+
+    #[test]
+    fn test_compare_conditions_tie() {
+        use chrono::TimeZone;
+        let a = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let cmp = AstrologicalScheduler::new(300).compare_conditions(a, a).unwrap();
+
+        assert!(cmp.is_tie());
+        assert_eq!(cmp.better_time, a);
+        assert!(cmp.advantage_by_type.values().all(|&adv| adv == 0.0));
+        assert!(cmp.to_string().contains("equally blessed"));
+    }
+
     #[test]
     fn test_planetary_influence() {
         let now = Utc::now();
-        let positions = calculate_planetary_positions(now);
+        let positions = calculate_planetary_positions(now).unwrap();
+        let scheduler = AstrologicalScheduler::new(300);
 
         for pos in positions {
-            let influence = AstrologicalScheduler::calculate_planetary_influence(&pos);
+            let influence = scheduler.calculate_planetary_influence(&pos, None);
 
             if pos.retrograde {
                 // Retrograde planets have negative influence
                 assert_eq!(influence, -1.0, "{} is retrograde and should have -1.0 influence", pos.planet.name());
+            } else if pos.is_in_critical_degree() {
+                assert!((1.2..=1.5).contains(&influence), "{} influence should be between 1.2 and 1.5", pos.planet.name());
             } else {
                 // Direct planets have positive influence based on element
                 assert!(influence > 0.0, "{} is direct and should have positive influence", pos.planet.name());
-                assert!(influence >= 1.0 && influence <= 1.3, "{} influence should be between 1.0 and 1.3", pos.planet.name());
+                assert!((1.0..=1.3).contains(&influence), "{} influence should be between 1.0 and 1.3", pos.planet.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_sun_sign_affinity() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let mut scheduler = AstrologicalScheduler::new(300).with_sun_sign_affinity(true);
+        let positions = calculate_planetary_positions(now).unwrap();
+        let mars_element = find_position(&positions, Planet::Mars).unwrap().sign.element();
+
+        // One process born under Mars's current element, one born in a different month under another
+        let births: Vec<_> = (1..=12).map(|month| Utc.with_ymd_and_hms(2020, month, 5, 0, 0, 0).unwrap()).collect();
+        let at_home = *births.iter().find(|&&b| sun_sign_at(b).element() == mars_element).unwrap();
+        let abroad = *births.iter().find(|&&b| sun_sign_at(b).element() != mars_element).unwrap();
+        assert_ne!(sun_sign_at(at_home), sun_sign_at(abroad));
+        assert!((AstrologicalScheduler::sun_sign_boost(sun_sign_at(at_home), mars_element) - 1.1).abs() < 1e-9);
+        assert!((AstrologicalScheduler::sun_sign_boost(sun_sign_at(abroad), mars_element) - 1.0).abs() < 1e-9);
+
+        scheduler.note_task_start(100, at_home);
+        scheduler.note_task_start(200, abroad);
+        assert!(scheduler.knows_sun_sign(100));
+        let home = scheduler.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let away = scheduler.schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert!(home.priority > away.priority);
+        assert!(home.reasoning.contains("☉ born in"));
+        assert!(!away.reasoning.contains("☉ born in"));
+
+        // Off by default
+        let mut plain = AstrologicalScheduler::new(300);
+        plain.note_task_start(100, at_home);
+        assert_eq!(plain.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap().priority, away.priority);
+
+        scheduler.forget_task(100);
+        assert!(!scheduler.knows_sun_sign(100));
+    }
+
+    #[test]
+    fn test_task_type_synergy() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        let now = Utc::now();
+
+        // Mercury trine Mars, exact, with nothing else in aspect to either
+        let positions: Vec<_> = [(Planet::Mercury, 10.0), (Planet::Mars, 130.0), (Planet::Saturn, 45.0)]
+            .into_iter()
+            .map(|(planet, longitude)| PlanetaryPosition {
+                planet,
+                longitude,
+                latitude: 0.0,
+                sign: ZodiacSign::from_longitude(longitude),
+                degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                retrograde: false,
+                moon_phase: None,
+            })
+            .collect();
+        let scheduler = || AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(positions.clone())));
+
+        // On their own, each type gets only its ruler's aspects
+        let network_alone = scheduler().schedule_task("curl", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let cpu_alone = scheduler().schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert_eq!(network_alone.task_type, TaskType::Network);
+        assert_eq!(cpu_alone.task_type, TaskType::CpuIntensive);
+
+        // Once they have been running side by side, both flow from the next chart on
+        let mut together = scheduler();
+        together.schedule_task("curl", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let cpu = together.schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, now).unwrap();
+        assert_eq!(cpu.priority, cpu_alone.priority);
+        let later = now + chrono::Duration::minutes(10);
+        together.refresh(later).unwrap();
+        let network = together.schedule_task("curl", 100, DEFAULT_TASK_WEIGHT, later).unwrap();
+        let cpu = together.schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, later).unwrap();
+        assert!(cpu.priority > cpu_alone.priority, "{} vs {}", cpu.priority, cpu_alone.priority);
+        assert!(network.priority > network_alone.priority, "{} vs {}", network.priority, network_alone.priority);
+        assert!(cpu.reasoning.contains("🤝 flowing with other tasks (+15%)"), "{}", cpu.reasoning);
+
+        // Without aspects there is no synergy either
+        let mut plain = scheduler().with_aspects(false);
+        plain.schedule_task("curl", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        plain.schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, now).unwrap();
+        plain.refresh(later).unwrap();
+        assert!(!plain.schedule_task("rustc", 200, DEFAULT_TASK_WEIGHT, later).unwrap().reasoning.contains("🤝"));
+    }
+
+    #[test]
+    fn test_process_affinity() {
+        use chrono::TimeZone;
+
+        /// Born in 2020: Sun, Moon and Mars in a grand trine; born since, squared and opposed
+        struct Births;
+        impl Ephemeris for Births {
+            fn positions(&self, jd: f64) -> Result<Vec<PlanetaryPosition>, AstroError> {
+                let longitudes = if jd < 2_459_215.5 { [0.0, 120.0, 240.0] } else { [0.0, 90.0, 270.0] };
+                Ok([Planet::Sun, Planet::Moon, Planet::Mars]
+                    .into_iter()
+                    .zip(longitudes)
+                    .map(|(planet, longitude)| PlanetaryPosition {
+                        planet,
+                        longitude,
+                        latitude: 0.0,
+                        sign: ZodiacSign::from_longitude(longitude),
+                        degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                        retrograde: false,
+                        moon_phase: None,
+                    })
+                    .collect())
+            }
+        }
+
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let mut scheduler = AstrologicalScheduler::new(300).with_ephemeris(Box::new(Births));
+        scheduler.note_task_start(100, Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0).unwrap());
+        scheduler.note_task_start(101, Utc.with_ymd_and_hms(2020, 9, 1, 0, 0, 0).unwrap());
+        scheduler.note_task_start(200, Utc.with_ymd_and_hms(2022, 3, 1, 0, 0, 0).unwrap());
+        scheduler.note_task_start(201, Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap());
+
+        let kindred = scheduler.compute_process_affinity(100, 101, now).unwrap();
+        let mixed = scheduler.compute_process_affinity(100, 200, now).unwrap();
+        let strained = scheduler.compute_process_affinity(200, 201, now).unwrap();
+        assert!((kindred - 2.0).abs() < 1e-9, "{kindred}");
+        assert!(kindred > mixed && mixed > strained, "{kindred} {mixed} {strained}");
+        assert!((0.0..=2.0).contains(&strained));
+        assert_eq!(scheduler.compute_process_affinity(200, 100, now), Some(mixed));
+
+        // Unknown PIDs, or a start time ahead of the clock, have no affinity
+        assert_eq!(scheduler.compute_process_affinity(100, 999, now), None);
+        scheduler.note_task_start(300, now + chrono::Duration::hours(1));
+        assert_eq!(scheduler.compute_process_affinity(100, 300, now), None);
+        scheduler.forget_task(101);
+        assert_eq!(scheduler.compute_process_affinity(100, 101, now), None);
+    }
+
+    #[test]
+    fn test_check_progressions() {
+        use crate::astrology::aspects::AspectKind;
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::Duration;
+
+        // Mars 2° short of Gemini and 4° short of squaring Saturn; an hour of runtime is a day of solar motion
+        let chart = [(Planet::Sun, 10.0), (Planet::Mars, 58.0), (Planet::Saturn, 152.0)]
+            .into_iter()
+            .map(|(planet, longitude)| PlanetaryPosition {
+                planet,
+                longitude,
+                latitude: 0.0,
+                sign: ZodiacSign::from_longitude(longitude),
+                degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                retrograde: false,
+                moon_phase: None,
+            })
+            .collect();
+        let born = Utc::now() - Duration::days(1);
+        let mut scheduler = AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(chart)));
+        scheduler.note_task_start(4242, born);
+        assert!(scheduler.progressing_pids().is_empty());
+
+        let mut scheduler = scheduler.with_progressions(3600);
+        assert_eq!(scheduler.progressing_pids(), vec![4242]);
+        assert!(scheduler.check_progressions(4242, born + Duration::hours(1)).is_empty());
+        assert_eq!(
+            scheduler.check_progressions(4242, born + Duration::hours(3)),
+            vec![LifecycleEvent::Ingress { planet: Planet::Mars, sign: ZodiacSign::Gemini }]
+        );
+        assert_eq!(
+            scheduler.check_progressions(4242, born + Duration::hours(4)),
+            vec![LifecycleEvent::Aspect { planet: Planet::Mars, kind: AspectKind::Square, natal: Planet::Saturn }]
+        );
+        assert!(scheduler.check_progressions(4242, born + Duration::hours(4)).is_empty());
+
+        // Unknown or departed PIDs have no progressions
+        assert!(scheduler.check_progressions(999, born + Duration::hours(5)).is_empty());
+        scheduler.forget_task(4242);
+        assert!(scheduler.progressing_pids().is_empty());
+    }
+
+    #[test]
+    fn test_critical_degree_influence() {
+        let scheduler = AstrologicalScheduler::new(300).with_retrograde_influence(-0.5);
+        let position = |longitude: f64, retrograde: bool| PlanetaryPosition {
+            planet: Planet::Mars,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde,
+            moon_phase: None,
+        };
+
+        // Aries (Fire, +0.3): 13° is critical, 14° is not
+        let critical = scheduler.calculate_planetary_influence(&position(13.5, false), None);
+        let ordinary = scheduler.calculate_planetary_influence(&position(14.5, false), None);
+        assert!((critical - 1.5).abs() < 1e-9);
+        assert!((ordinary - 1.3).abs() < 1e-9);
+
+        let critical = scheduler.calculate_planetary_influence(&position(13.5, true), None);
+        let ordinary = scheduler.calculate_planetary_influence(&position(14.5, true), None);
+        assert!((critical + 0.7).abs() < 1e-9);
+        assert!((ordinary + 0.5).abs() < 1e-9);
+
+        // Never below the full retrograde penalty
+        let harsh = AstrologicalScheduler::new(300);
+        assert!((harsh.calculate_planetary_influence(&position(13.5, true), None) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_term_dignity_influence() {
+        let scheduler = AstrologicalScheduler::new(300).with_retrograde_influence(-0.5);
+        let position = |longitude: f64, retrograde: bool| PlanetaryPosition {
+            planet: Planet::Mars,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde,
+            moon_phase: None,
+        };
+
+        // Aries 20°-25° is Mars's own term, 12°-20° is Mercury's
+        let own = scheduler.calculate_planetary_influence(&position(22.0, false), None);
+        let other = scheduler.calculate_planetary_influence(&position(18.0, false), None);
+        assert!((own - 1.4).abs() < 1e-9);
+        assert!((other - 1.3).abs() < 1e-9);
+
+        // No dignity for a retrograde planet
+        assert!((scheduler.calculate_planetary_influence(&position(22.0, true), None) + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_combust_and_cazimi_rulers() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // Mercury direct at 10° Gemini, the Sun some distance behind
+        let chart = |separation: f64| {
+            let mut positions = calculate_planetary_positions(now).unwrap();
+            for pos in &mut positions {
+                let longitude = match pos.planet {
+                    Planet::Mercury => 70.0,
+                    Planet::Sun => 70.0 - separation,
+                    _ => continue,
+                };
+                pos.longitude = longitude;
+                pos.sign = ZodiacSign::from_longitude(longitude);
+                pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                pos.retrograde = false;
             }
+            AstrologicalScheduler::new(300).with_aspects(false).with_ephemeris(Box::new(FixedEphemeris::new(positions)))
+        };
+        let network = |separation: f64| {
+            let mut scheduler = chart(separation);
+            scheduler.schedule_task("curl", 4242, 100, now).unwrap()
+        };
+
+        let free = network(40.0);
+        let beams = network(12.0);
+        let combust = network(5.0);
+        let cazimi = network(0.1);
+        assert!(cazimi.priority > free.priority);
+        assert!(free.priority > beams.priority);
+        assert!(beams.priority > combust.priority);
+
+        assert!(combust.reasoning.contains("Mercury combust — Network tasks burned up"), "{}", combust.reasoning);
+        assert!(cazimi.reasoning.contains("Mercury cazimi"), "{}", cazimi.reasoning);
+        assert!(beams.reasoning.contains("Mercury under the beams"), "{}", beams.reasoning);
+        assert!(!free.reasoning.contains("combust") && !free.reasoning.contains("beams"));
+    }
+
+    #[test]
+    fn test_next_process_return() {
+        use crate::astrology::return_charts::ReturnType;
+        use chrono::TimeZone;
+        let birth = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let now = birth + chrono::Duration::days(40);
+        let mut scheduler = AstrologicalScheduler::new(300);
+        assert_eq!(scheduler.get_next_process_return(900_001, ReturnType::Lunar, now), None);
+
+        scheduler.note_task_start(900_001, birth);
+        let lunar = scheduler.get_next_process_return(900_001, ReturnType::Lunar, now).unwrap();
+        assert!(lunar > now && lunar - now < chrono::Duration::days(28), "{lunar}");
+        let solar = scheduler.get_next_process_return(900_001, ReturnType::Solar, now).unwrap();
+        assert!(solar - birth > chrono::Duration::days(364) && solar - birth < chrono::Duration::days(366), "{solar}");
+
+        let reference = scheduler.return_reference(900_001, now).unwrap();
+        assert!(reference.contains("Lunar return") && reference.contains("Solar return"), "{reference}");
+    }
+
+    #[test]
+    fn test_angular_cpus_follow_the_ruler() {
+        use chrono::TimeZone;
+        let birth = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let now = birth + chrono::Duration::hours(5);
+        let mut scheduler = AstrologicalScheduler::new(300);
+        assert_eq!(scheduler.angular_cpus(900_001, TaskType::CpuIntensive, now, 64), None);
+
+        scheduler.note_task_start(900_001, birth);
+        let natal = NatalChart::cast(&AstroEphemeris, birth).unwrap();
+        let expected = calculate_angular_cpus(&natal, now, 64);
+        for task_type in [TaskType::CpuIntensive, TaskType::Network] {
+            let ruler = scheduler.rulership.ruler(task_type);
+            let cpus = scheduler.angular_cpus(900_001, task_type, now, 64).unwrap();
+            assert_eq!(cpus, expected.get(&ruler).cloned().unwrap_or_default(), "{}", ruler.name());
+        }
+        // 64 CPUs less than six degrees apart put every planet on an angle somewhere
+        assert!(!scheduler.angular_cpus(900_001, TaskType::CpuIntensive, now, 64).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mars_on_regulus() {
+        use crate::astrology::ephemeris::Chart;
+        use crate::astrology::fixed_stars::BEHENIAN_STARS;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let regulus = BEHENIAN_STARS.iter().find(|star| star.name == "Regulus").unwrap();
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in positions.iter_mut().filter(|pos| pos.planet == Planet::Mars) {
+            pos.longitude = regulus.longitude_at(julian_day(now).unwrap()) + 0.2;
+            pos.sign = ZodiacSign::from_longitude(pos.longitude);
+            pos.degree_in_sign = ZodiacSign::degree_in_sign(pos.longitude);
         }
+        let chart = Chart { time: now, positions, north_node: None };
+
+        let mut starry = AstrologicalScheduler::new(300);
+        starry.install_chart(chart.clone());
+        let royal = starry.schedule_task("rustc", 900_001, 100, now).unwrap();
+        assert!(royal.reasoning.contains("Mars on Regulus"), "{}", royal.reasoning);
+
+        let mut plain = AstrologicalScheduler::new(300).with_fixed_stars(false);
+        plain.install_chart(chart);
+        let common = plain.schedule_task("rustc", 900_001, 100, now).unwrap();
+        assert!(!common.reasoning.contains("Regulus"));
+        let ratio = f64::from(royal.astro_priority) / f64::from(common.astro_priority);
+        assert!((ratio - 1.5).abs() < 0.02, "{ratio}");
+    }
+
+    #[test]
+    fn test_mundane_rebalance_and_recession() {
+        use crate::astrology::ephemeris::Chart;
+        use crate::astrology::planets::normalize_longitude;
+        use chrono::TimeZone;
+        let conjunction = Utc.with_ymd_and_hms(2020, 12, 21, 18, 0, 0).unwrap();
+        let mut scheduler = AstrologicalScheduler::new(300).with_aspects(false).with_mundane_astrology(true);
+        scheduler.note_task_start(900_001, conjunction - chrono::Duration::days(400));
+        scheduler.learn_task_type(900_001, TaskType::MemoryHeavy);
+        let revision = scheduler.revision();
+
+        // The great conjunction of 2020 wipes the slate clean, once
+        let positions = calculate_planetary_positions(conjunction).unwrap();
+        scheduler.install_chart(Chart { time: conjunction, positions: positions.clone(), north_node: None });
+        assert!(!scheduler.knows_sun_sign(900_001));
+        assert!(scheduler.classifier().learned().is_empty());
+        assert_ne!(scheduler.revision(), revision);
+        assert!(scheduler.check_mundane_events(conjunction).is_empty());
+
+        // A square a quadrant on halves MemoryHeavy tasks for a day
+        let square = |time: DateTime<Utc>| {
+            let mut positions = positions.clone();
+            for pos in &mut positions {
+                if pos.planet == Planet::Jupiter {
+                    pos.longitude = normalize_longitude(pos.longitude + 90.0);
+                    pos.sign = ZodiacSign::from_longitude(pos.longitude);
+                    pos.degree_in_sign = ZodiacSign::degree_in_sign(pos.longitude);
+                }
+            }
+            Chart { time, positions, north_node: None }
+        };
+        let later = conjunction + chrono::Duration::days(1);
+        scheduler.install_chart(square(later));
+        scheduler.learn_task_type(900_001, TaskType::MemoryHeavy);
+        let austere = scheduler.schedule_task("rustc", 900_001, 100, later).unwrap();
+        assert!(austere.reasoning.contains("recession"), "{}", austere.reasoning);
+
+        let recovered = later + chrono::Duration::hours(crate::astrology::mundane::RECESSION_HOURS);
+        scheduler.install_chart(square(recovered));
+        let normal = scheduler.schedule_task("rustc", 900_001, 100, recovered).unwrap();
+        assert!(!normal.reasoning.contains("recession"));
+        assert!(austere.priority < normal.priority);
+    }
+
+    #[test]
+    fn test_heliacal_rising_newborn_bonus() {
+        use crate::astrology::ephemeris::Chart;
+        use chrono::TimeZone;
+        let dawn = Utc.with_ymd_and_hms(2025, 3, 29, 0, 0, 0).unwrap();
+
+        // Mercury direct at 10° Gemini, `west` degrees ahead of the Sun in its daily motion
+        let chart = |time: DateTime<Utc>, west: f64| {
+            let mut positions = calculate_planetary_positions(dawn).unwrap();
+            for pos in &mut positions {
+                let longitude = match pos.planet {
+                    Planet::Mercury => 70.0,
+                    Planet::Sun => 70.0 + west,
+                    _ => continue,
+                };
+                pos.longitude = longitude;
+                pos.sign = ZodiacSign::from_longitude(longitude);
+                pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                pos.retrograde = false;
+            }
+            Chart { time, positions, north_node: None }
+        };
+        let mut scheduler = AstrologicalScheduler::new(300).with_aspects(false);
+
+        // Lost in the glare, then 12° west of the Sun a day later
+        scheduler.install_chart(chart(dawn, 8.0));
+        let hidden = scheduler.schedule_task("curl", 4242, 100, dawn).unwrap();
+        let tomorrow = dawn + chrono::Duration::days(1);
+        scheduler.install_chart(chart(tomorrow, 12.0));
+        assert_eq!(scheduler.newborn, HashSet::from([Planet::Mercury]));
+        let newborn = scheduler.schedule_task("curl", 4242, 100, tomorrow).unwrap();
+        assert!(newborn.reasoning.contains("Mercury newly risen"), "{}", newborn.reasoning);
+
+        // The bonus lasts one chart: the next finds Mercury visible already
+        let later = tomorrow + chrono::Duration::minutes(5);
+        scheduler.install_chart(chart(later, 12.0));
+        assert!(scheduler.newborn.is_empty());
+        let risen = scheduler.schedule_task("curl", 4242, 100, later).unwrap();
+        assert!(newborn.priority > risen.priority);
+        assert!(risen.priority > hidden.priority);
+        assert!(!risen.reasoning.contains("newly risen"));
+
+        // Sinking back into the glare is no rising
+        let dusk = later + chrono::Duration::days(1);
+        scheduler.install_chart(chart(dusk, 3.0));
+        assert!(scheduler.check_heliacal_events(dusk + chrono::Duration::days(1)).is_empty());
+    }
+
+    /// A chart where Mars is retrograde in Water, far from the Sun
+    fn retrograde_mars_chart() -> Vec<PlanetaryPosition> {
+        use chrono::TimeZone;
+        let time = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let mut positions = calculate_planetary_positions(time).unwrap();
+        for pos in &mut positions {
+            if pos.planet == Planet::Mars {
+                pos.longitude = 105.0;
+                pos.sign = ZodiacSign::Cancer;
+                pos.degree_in_sign = 15.0;
+                pos.retrograde = true;
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn test_softened_retrograde_influence() {
+        let positions = retrograde_mars_chart();
+
+        // Aspects to Mars would shift the exact numbers; keep them out
+        let classic = AstrologicalScheduler::new(300).with_aspects(false);
+        let full = classic.assess(&positions, TaskType::CpuIntensive).unwrap();
+        assert!((full.planetary_influence + 1.0).abs() < f64::EPSILON);
+        assert_eq!(full.priority, 30);
+
+        // Halfway: a milder cut, still a penalty
+        let half = AstrologicalScheduler::new(300).with_aspects(false).with_retrograde_influence(-0.5);
+        let half = half.assess(&positions, TaskType::CpuIntensive).unwrap();
+        assert!(half.planetary_influence < 0.0);
+        assert_eq!(half.priority, 65);
+
+        // Positive: retrograde Mars works like a weak direct planet, Water debuff included
+        let soft = AstrologicalScheduler::new(300).with_aspects(false).with_retrograde_influence(0.8);
+        let soft = soft.assess(&positions, TaskType::CpuIntensive).unwrap();
+        assert_eq!(soft.priority, 48);
+    }
+
+    #[test]
+    fn test_retrograde_influence_per_planet() {
+        use chrono::TimeZone;
+        // Mercury retrograde in Cancer and Saturn retrograde in Sagittarius, both far from the Sun
+        // and off any critical degree
+        let time = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let mut positions = calculate_planetary_positions(time).unwrap();
+        for pos in &mut positions {
+            let (longitude, sign) = match pos.planet {
+                Planet::Mercury => (105.0, ZodiacSign::Cancer),
+                Planet::Saturn => (255.0, ZodiacSign::Sagittarius),
+                _ => continue,
+            };
+            pos.longitude = longitude;
+            pos.sign = sign;
+            pos.degree_in_sign = 15.0;
+            pos.retrograde = true;
+        }
+
+        let uniform = AstrologicalScheduler::new(300).with_aspects(false);
+        let tuned = AstrologicalScheduler::new(300)
+            .with_aspects(false)
+            .with_planet_retrograde_influence(Planet::Mercury, -1.0)
+            .with_planet_retrograde_influence(Planet::Saturn, -0.3);
+        assert!((tuned.retrograde_influence_of(Planet::Saturn) + 0.3).abs() < f64::EPSILON);
+        assert!((tuned.retrograde_influence_of(Planet::Mars) + 1.0).abs() < f64::EPSILON);
+
+        // Network still takes Mercury's full hit; System gets off lighter under Saturn
+        let network = tuned.assess(&positions, TaskType::Network).unwrap();
+        let system = tuned.assess(&positions, TaskType::System).unwrap();
+        assert!((network.planetary_influence + 1.0).abs() < f64::EPSILON);
+        assert!((system.planetary_influence + 0.3).abs() < f64::EPSILON);
+        assert_eq!(network.priority, uniform.assess(&positions, TaskType::Network).unwrap().priority);
+        assert!(system.priority > uniform.assess(&positions, TaskType::System).unwrap().priority);
+    }
+
+    #[test]
+    fn test_aspects_scale_priority() {
+        let mut positions = retrograde_mars_chart();
+        for pos in &mut positions {
+            pos.retrograde = false;
+        }
+        let mars = find_position(&positions, Planet::Mars).unwrap().longitude;
+        // Move everyone well clear of Mars, then put Jupiter in an exact trine
+        for pos in &mut positions {
+            if pos.planet != Planet::Mars {
+                pos.longitude = (mars + 35.0).rem_euclid(360.0);
+            }
+            if pos.planet == Planet::Jupiter {
+                pos.longitude = (mars + 120.0).rem_euclid(360.0);
+            }
+        }
+
+        let plain = AstrologicalScheduler::new(300).with_aspects(false);
+        let aspected = AstrologicalScheduler::new(300);
+        let without = plain.assess(&positions, TaskType::CpuIntensive).unwrap();
+        let with = aspected.assess(&positions, TaskType::CpuIntensive).unwrap();
+
+        assert!(without.strongest_aspect.is_none());
+        assert_eq!(with.strongest_aspect.map(|aspect| aspect.other), Some(Planet::Jupiter));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let expected = (f64::from(without.priority) * 1.15) as u32;
+        assert!(with.priority.abs_diff(expected) <= 1);
+    }
+
+    #[test]
+    fn test_influence_baseline() {
+        let positions = retrograde_mars_chart();
+        let direct_saturn = find_position(&positions, Planet::Saturn).unwrap().clone();
+        let direct_saturn = PlanetaryPosition { retrograde: false, ..direct_saturn };
+
+        let classic = AstrologicalScheduler::new(300);
+        let raised = AstrologicalScheduler::new(300).with_influence_baseline(1.5);
+        let bonus = classic.calculate_planetary_influence(&direct_saturn, None) - 1.0;
+        assert!((raised.calculate_planetary_influence(&direct_saturn, None) - (1.5 + bonus)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_out_of_bounds_jitter_range() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let scheduler = AstrologicalScheduler::new(300);
+
+        for pid in [1, 2, 1000, 65_535, i32::MAX] {
+            let jitter = scheduler.out_of_bounds_jitter_percent(pid, now);
+            assert!((-20..20).contains(&jitter), "jitter {jitter} for PID {pid}");
+            assert_eq!(jitter, scheduler.out_of_bounds_jitter_percent(pid, now));
+        }
+    }
+
+    #[test]
+    fn test_out_of_bounds_ruler_jitters_priority() {
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in &mut positions {
+            pos.latitude = 0.0;
+        }
+        let mut oob_positions = positions.clone();
+        for pos in &mut oob_positions {
+            if pos.planet == Planet::Mars {
+                pos.latitude = 6.5;
+            }
+        }
+
+        // The first PID from 4242 with a non-zero jitter on this day
+        let reference = AstrologicalScheduler::new(300);
+        let pid = (4242..5000).find(|&pid| reference.out_of_bounds_jitter_percent(pid, now) != 0).unwrap();
+        let jitter = reference.out_of_bounds_jitter_percent(pid, now);
+
+        let decide = |positions: &Vec<PlanetaryPosition>, jitter_enabled: bool| {
+            let mut scheduler = AstrologicalScheduler::new(300).with_out_of_bounds_jitter(jitter_enabled);
+            scheduler.planetary_cache = Some((now, positions.clone()));
+            scheduler.schedule_task("rustc", pid, DEFAULT_TASK_WEIGHT, now).unwrap()
+        };
+
+        let calm = decide(&positions, true);
+        let wild = decide(&oob_positions, true);
+        let tamed = decide(&oob_positions, false);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let expected = (f64::from(calm.astro_priority) * (1.0 + f64::from(jitter) / 100.0)) as u32;
+        assert_eq!(wild.astro_priority, expected.max(1));
+        assert!(wild.reasoning.contains("out of bounds"));
+        assert_eq!(tamed.astro_priority, calm.astro_priority);
+    }
+
+    #[test]
+    fn test_lucky_hours() {
+        use chrono::TimeZone;
+        // A Thursday in London: day ruled by Jupiter, Mars takes hours 2, 9, 16 and 23
+        let date = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let scheduler = AstrologicalScheduler::new(300).with_location(51.5074, -0.1278);
+        let hours = planetary_hours(date.date_naive(), 51.5074, -0.1278).unwrap();
+
+        let lucky = scheduler.get_lucky_hours_today(TaskType::CpuIntensive, date);
+        let expected: Vec<_> = [1, 8, 15, 22].iter().map(|&i| (hours[i].start, hours[i].end)).collect();
+        assert_eq!(lucky, expected);
+
+        // Mars's own tasks only fear Saturn; everyone else fears both
+        let unlucky = scheduler.get_unlucky_hours_today(TaskType::CpuIntensive, date);
+        assert!(unlucky.iter().all(|window| hours.iter().any(|h| (h.start, h.end) == *window && h.ruler == Planet::Saturn)));
+        let network = scheduler.get_unlucky_hours_today(TaskType::Network, date);
+        assert_eq!(network.len(), 7);
+        assert!(network.iter().all(|window| !scheduler.get_lucky_hours_today(TaskType::Network, date).contains(window)));
+
+        let reference = scheduler.lucky_hours_reference(TaskType::CpuIntensive, date).unwrap();
+        assert_eq!(reference.lines().count(), 25);
+        assert_eq!(reference.matches("lucky").count() - reference.matches("unlucky").count(), 4);
+
+        // No location, no hours
+        let nowhere = AstrologicalScheduler::new(300);
+        assert!(nowhere.get_lucky_hours_today(TaskType::CpuIntensive, date).is_empty());
+        assert_eq!(nowhere.lucky_hours_reference(TaskType::CpuIntensive, date), Err(AstroError::MissingLocation));
+    }
+
+    #[test]
+    fn test_unknown_tasks_skip_the_moon() {
+        use chrono::{Duration, TimeZone};
+        // Find a full moon
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let full_moon = (0..60)
+            .map(|day| start + Duration::days(day))
+            .find(|&t| {
+                let positions = calculate_planetary_positions(t).unwrap();
+                find_position(&positions, Planet::Moon).unwrap().moon_phase == Some(MoonPhase::FullMoon)
+            })
+            .unwrap();
+
+        let mut riding = AstrologicalScheduler::new(300).with_out_of_bounds_jitter(false);
+        let mut grounded = AstrologicalScheduler::new(300).with_out_of_bounds_jitter(false).with_unknown_moon(false);
+
+        // By default an unknown comm rides the full moon exactly like a shell
+        let bash = riding.schedule_task("bash", 100, 100, full_moon).unwrap();
+        let unknown = riding.schedule_task("qzx9", 101, 100, full_moon).unwrap();
+        assert_eq!(unknown.task_type, TaskType::Interactive);
+        assert_eq!(unknown.priority, bash.priority);
+
+        // With the fallback grounded only the real shell gets the 1.4x boost
+        let bash = grounded.schedule_task("bash", 100, 100, full_moon).unwrap();
+        let unknown = grounded.schedule_task("qzx9", 101, 100, full_moon).unwrap();
+        assert!((bash.element_boost / unknown.element_boost - 1.4).abs() < 1e-9);
+        assert!(bash.priority > unknown.priority);
+        assert!(unknown.reasoning.contains("Moon phase ignored"));
+        assert!(!bash.reasoning.contains("Moon phase ignored"));
+    }
+
+    #[test]
+    fn test_decisions_follow_the_ephemeris() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // The same sky, except for where Mars stands and which way it moves
+        let chart = |longitude: f64, retrograde: bool| {
+            let mut positions = calculate_planetary_positions(now).unwrap();
+            for pos in &mut positions {
+                pos.latitude = 0.0;
+                if pos.planet == Planet::Mars {
+                    pos.longitude = longitude;
+                    pos.sign = ZodiacSign::from_longitude(longitude);
+                    pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                    pos.retrograde = retrograde;
+                }
+            }
+            AstrologicalScheduler::new(300).with_aspects(false).with_ephemeris(Box::new(FixedEphemeris::new(positions)))
+        };
+
+        let mut fiery = chart(15.0, false);
+        let mut watery = chart(105.0, false);
+        let mut backwards = chart(15.0, true);
+
+        let fire = fiery.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let water = watery.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let retro = backwards.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+
+        assert_eq!(fire.ruling_element, Some(Element::Fire));
+        assert_eq!(water.ruling_element, Some(Element::Water));
+        assert!(fire.reasoning.contains("Aries"));
+        assert!(fire.priority > water.priority);
+        assert!(retro.planetary_influence < 0.0);
+        assert!(retro.reasoning.contains("RETROGRADE"));
+        assert!(retro.priority < water.priority);
+
+        // Other dates get the same fixed sky
+        let later = fiery.schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now + chrono::Duration::days(400)).unwrap();
+        assert_eq!(later.priority, fire.priority);
+    }
+
+    #[test]
+    fn test_forecast() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in &mut positions {
+            pos.retrograde = pos.planet == Planet::Mars;
+        }
+        let scheduler = AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(positions.clone())));
+
+        let forecast = scheduler.forecast(now, 6).unwrap();
+        assert_eq!(forecast.len(), 6);
+        assert_eq!(forecast[5].time, now + chrono::Duration::hours(5));
+        for hour in &forecast {
+            assert_eq!(hour.retrograde, [Planet::Mars]);
+            assert_eq!(hour.priorities.len(), TaskType::all().len() - 1);
+            let cpu = hour.priorities.iter().find(|(t, _)| *t == TaskType::CpuIntensive).unwrap().1;
+            assert_eq!(cpu, scheduler.assess(&positions, TaskType::CpuIntensive).unwrap().priority);
+        }
+        assert!(scheduler.forecast(now, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_venus_phase_sways_desktop() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use crate::astrology::solar::UNDER_BEAMS_ORB;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // Venus direct at 10° Libra; the Sun 40° behind her, or right on top of her
+        let chart = |sun: f64| {
+            let mut positions = calculate_planetary_positions(now).unwrap();
+            for pos in &mut positions {
+                let longitude = match pos.planet {
+                    Planet::Venus => 190.0,
+                    Planet::Sun => sun,
+                    _ => continue,
+                };
+                pos.longitude = longitude;
+                pos.sign = ZodiacSign::from_longitude(longitude);
+                pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                pos.retrograde = false;
+            }
+            AstrologicalScheduler::new(300).with_aspects(false).with_ephemeris(Box::new(FixedEphemeris::new(positions)))
+        };
+        let desktop = |scheduler: &AstrologicalScheduler| {
+            let positions = scheduler.ephemeris.positions(0.0).unwrap();
+            scheduler.assess(&positions, TaskType::Desktop).unwrap().priority
+        };
+        let evening_star = chart(150.0);
+        let combust = chart(188.0);
+        let plain = f64::from(desktop(&chart(190.0 - UNDER_BEAMS_ORB - 0.5)));
+        assert!(f64::from(desktop(&evening_star)) > plain * 1.15);
+        assert!(f64::from(desktop(&combust)) < plain * 0.85);
+
+        // Only Desktop answers to Venus's phase
+        let network = |scheduler: &AstrologicalScheduler| {
+            let positions = scheduler.ephemeris.positions(0.0).unwrap();
+            scheduler.assess(&positions, TaskType::Network).unwrap().priority
+        };
+        assert_eq!(network(&evening_star), network(&combust));
+
+        let report = chart(150.0).get_cosmic_weather(now).unwrap();
+        let line = report.lines().find(|line| line.contains("Desktop Tasks")).unwrap();
+        assert!(line.contains("Venus in Libra"), "{line}");
+        assert!(line.contains("Evening star, 40° from the Sun (1.20x)"), "{line}");
+    }
+
+    #[test]
+    fn test_element_forecast() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // The real sky: the Water forecast reads Jupiter and Saturn where the full chart has them
+        let positions = calculate_planetary_positions(now).unwrap();
+        let forecast = AstrologicalScheduler::new(300).get_element_forecast_from(Element::Water, now, 3).unwrap();
+        assert_eq!(forecast.len(), 3);
+        assert_eq!(forecast[2].timestamp, now + chrono::Duration::hours(2));
+        let watched = [Planet::Jupiter, Planet::Saturn].map(|planet| find_position(&positions, planet).unwrap());
+        assert_eq!(forecast[0].element_count, watched.iter().filter(|pos| pos.sign.element() == Element::Water).count());
+        assert_eq!(forecast[0].retrograde_count, watched.iter().filter(|pos| pos.retrograde).count());
+
+        // Saturn at 5° Aries throughout; Jupiter at 15° of Cancer or Aries, direct or not
+        let chart = |jupiter: f64, retrograde: bool| {
+            let mut positions = positions.clone();
+            for pos in &mut positions {
+                let longitude = match pos.planet {
+                    Planet::Jupiter => jupiter,
+                    Planet::Saturn => 5.0,
+                    _ => continue,
+                };
+                pos.longitude = longitude;
+                pos.sign = ZodiacSign::from_longitude(longitude);
+                pos.degree_in_sign = ZodiacSign::degree_in_sign(longitude);
+                pos.retrograde = retrograde && pos.planet == Planet::Jupiter;
+            }
+            let scheduler = AstrologicalScheduler::new(300).with_ephemeris(Box::new(FixedEphemeris::new(positions)));
+            scheduler.get_element_forecast_from(Element::Water, now, 1).unwrap().remove(0)
+        };
+        let in_water = chart(105.0, false);
+        assert_eq!(in_water.element_count, 1);
+        assert_eq!(in_water.retrograde_count, 0);
+        assert!(!in_water.planet_in_own_sign);
+
+        // Jupiter direct in a Water sign is worth more than in Fire, or retrograde
+        assert!(in_water.recommended_priority > chart(15.0, false).recommended_priority);
+        assert!(in_water.recommended_priority > chart(105.0, true).recommended_priority);
+        assert_eq!(chart(105.0, true).retrograde_count, 1);
+
+        // At home in Pisces
+        let at_home = chart(338.0, false);
+        assert!(at_home.planet_in_own_sign);
+        assert_eq!(at_home.recommended_priority, in_water.recommended_priority);
+    }
+
+    #[test]
+    fn test_ruler_at_the_bending() {
+        use crate::astrology::ephemeris::FixedEphemeris;
+        use chrono::TimeZone;
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+
+        // Mars at 15° Aries, the North node placed on top of it or 90° away
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in &mut positions {
+            pos.latitude = 0.0;
+            if pos.planet == Planet::Mars {
+                pos.longitude = 15.0;
+                pos.sign = ZodiacSign::Aries;
+                pos.degree_in_sign = 15.0;
+                pos.retrograde = false;
+            }
+        }
+        let chart = |north_node: f64| {
+            let ephemeris = FixedEphemeris::new(positions.clone()).with_north_node(north_node);
+            AstrologicalScheduler::new(300).with_aspects(false).with_ephemeris(Box::new(ephemeris))
+        };
+
+        let calm = chart(15.0).schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let bent = chart(285.0).schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+        let south = chart(106.0).schedule_task("rustc", 100, DEFAULT_TASK_WEIGHT, now).unwrap();
+
+        assert!(!calm.reasoning.contains("bending"));
+        assert!(bent.reasoning.contains("Mars at the bending — CPU"), "{}", bent.reasoning);
+        assert!(south.reasoning.contains("at the bending"));
+
+        // A mild penalty, not a retrograde
+        assert!(bent.planetary_influence > 0.0);
+        assert!(bent.priority < calm.priority);
+        assert_eq!(bent.priority, (f64::from(calm.priority) * BENDING_PENALTY) as u32);
+
+        // Tasks under other rulers don't feel it
+        let mut other = chart(285.0);
+        assert!(!other.schedule_task("postgres", 101, DEFAULT_TASK_WEIGHT, now).unwrap().reasoning.contains("Mars at the bending"));
     }
 }
@@ -0,0 +1,113 @@
+use super::aspects::angular_separation;
+use super::planets::{normalize_longitude, Planet, PlanetaryPosition};
+
+/// Within 17 arcminutes of the Sun a planet is cazimi, in the heart of the Sun
+pub const CAZIMI_ORB: f64 = 17.0 / 60.0;
+
+/// Within this many degrees of the Sun a planet is combust: burnt up and powerless
+pub const COMBUST_ORB: f64 = 8.5;
+
+/// Within this many degrees a planet is under the Sun's beams and hard to see
+pub const UNDER_BEAMS_ORB: f64 = 17.0;
+
+/// How a planet fares for its closeness to the Sun
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolarCondition {
+    Cazimi,
+    Combust,
+    UnderBeams,
+    Free,
+}
+
+impl SolarCondition {
+    pub fn name(self) -> &'static str {
+        match self {
+            SolarCondition::Cazimi => "cazimi",
+            SolarCondition::Combust => "combust",
+            SolarCondition::UnderBeams => "under the beams",
+            SolarCondition::Free => "free of the Sun",
+        }
+    }
+
+    /// Influence added to (or taken from) a planet in this condition
+    pub fn influence_modifier(self) -> f64 {
+        match self {
+            SolarCondition::Cazimi => 0.3,
+            SolarCondition::Combust => -0.3,
+            SolarCondition::UnderBeams => -0.1,
+            SolarCondition::Free => 0.0,
+        }
+    }
+}
+
+/// A planet's angular distance from the Sun in degrees, in (-180, 180]
+///
+/// Positive east of the Sun, where it sets after it; negative west of it,
+/// where it rises before it.
+pub fn elongation(longitude: f64, sun_longitude: f64) -> f64 {
+    let east = normalize_longitude(longitude - sun_longitude);
+    if east > 180.0 { east - 360.0 } else { east }
+}
+
+/// How close `planet_pos` stands to the Sun at `sun_pos`
+///
+/// The Sun is always free of itself.
+pub fn solar_condition(planet_pos: &PlanetaryPosition, sun_pos: &PlanetaryPosition) -> SolarCondition {
+    if planet_pos.planet == Planet::Sun {
+        return SolarCondition::Free;
+    }
+    match angular_separation(planet_pos.longitude, sun_pos.longitude) {
+        d if d <= CAZIMI_ORB => SolarCondition::Cazimi,
+        d if d < COMBUST_ORB => SolarCondition::Combust,
+        d if d < UNDER_BEAMS_ORB => SolarCondition::UnderBeams,
+        _ => SolarCondition::Free,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::ZodiacSign;
+
+    fn position(planet: Planet, longitude: f64) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet,
+            longitude,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+            retrograde: false,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_solar_condition_thresholds() {
+        let sun = position(Planet::Sun, 100.0);
+        let mercury = |separation: f64| solar_condition(&position(Planet::Mercury, 100.0 + separation), &sun);
+
+        // (separation from the Sun, condition), either side of each threshold
+        let cases = [
+            (0.0, SolarCondition::Cazimi),
+            (0.28, SolarCondition::Cazimi),
+            (-0.28, SolarCondition::Cazimi),
+            (0.29, SolarCondition::Combust),
+            (8.4, SolarCondition::Combust),
+            (-8.4, SolarCondition::Combust),
+            (8.5, SolarCondition::UnderBeams),
+            (16.9, SolarCondition::UnderBeams),
+            (17.0, SolarCondition::Free),
+            (-27.0, SolarCondition::Free),
+        ];
+        for (separation, condition) in cases {
+            assert_eq!(mercury(separation), condition, "{separation}° from the Sun");
+        }
+
+        // Across 0° Aries, and never for the Sun itself
+        assert_eq!(solar_condition(&position(Planet::Venus, 358.0), &position(Planet::Sun, 3.0)), SolarCondition::Combust);
+        assert_eq!(solar_condition(&sun, &sun), SolarCondition::Free);
+
+        assert!(SolarCondition::Cazimi.influence_modifier() > 0.0);
+        assert!(SolarCondition::Combust.influence_modifier() < SolarCondition::UnderBeams.influence_modifier());
+    }
+}
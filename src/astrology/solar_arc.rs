@@ -0,0 +1,256 @@
+use super::aspects::{angular_separation, AspectKind};
+use super::composite::NatalChart;
+use super::planets::{normalize_longitude, Planet, PlanetaryPosition, ZodiacSign};
+use super::tasks::TaskType;
+
+/// Mean motion of the Sun in degrees per day
+const SUN_MEAN_DAILY_MOTION: f64 = 360.0 / 365.242_19;
+
+/// How far from exact a progressed planet may be and still aspect a natal one, in degrees
+pub const PROGRESSION_ORB: f64 = 1.0;
+
+/// Aspects hard enough to mark a turn in a process's life
+const HARD_ASPECTS: [AspectKind; 3] = [AspectKind::Conjunction, AspectKind::Square, AspectKind::Opposition];
+
+/// Moves a natal chart forward by solar arc
+///
+/// Every planet advances by the same arc the progressed Sun has covered: a
+/// day's worth of solar motion (just under 1°) for each `day_unit_secs` the
+/// process has been running, where tradition would use a year of life.
+#[derive(Debug, Clone, Copy)]
+pub struct SolarArcProgressor {
+    day_unit_secs: u64,
+}
+
+impl SolarArcProgressor {
+    pub fn new(day_unit_secs: u64) -> Self {
+        Self { day_unit_secs: day_unit_secs.max(1) }
+    }
+
+    /// Degrees every planet has moved after `elapsed_secs` of runtime
+    pub fn arc(self, elapsed_secs: u64) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let days = elapsed_secs as f64 / self.day_unit_secs as f64;
+        days * SUN_MEAN_DAILY_MOTION
+    }
+
+    /// The chart progressed `elapsed_secs` past the natal one
+    ///
+    /// Latitudes, retrograde flags and the Moon phase stay as they were: the
+    /// whole chart turns together, so nothing moves relative to the Sun.
+    pub fn advance(self, natal: &NatalChart, elapsed_secs: u64) -> Vec<PlanetaryPosition> {
+        let arc = self.arc(elapsed_secs);
+        natal
+            .positions
+            .iter()
+            .map(|pos| {
+                let longitude = normalize_longitude(pos.longitude + arc);
+                PlanetaryPosition {
+                    longitude,
+                    sign: ZodiacSign::from_longitude(longitude),
+                    degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                    ..pos.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Hard aspects from progressed planets to other natal planets, within `PROGRESSION_ORB`
+pub fn hard_aspects(progressed: &[PlanetaryPosition], natal: &NatalChart) -> Vec<(Planet, AspectKind, Planet)> {
+    let mut aspects = Vec::new();
+    for moved in progressed {
+        for fixed in natal.positions.iter().filter(|fixed| fixed.planet != moved.planet) {
+            let separation = angular_separation(moved.longitude, fixed.longitude);
+            if let Some(kind) = HARD_ASPECTS.into_iter().find(|kind| (separation - kind.angle()).abs() <= PROGRESSION_ORB) {
+                aspects.push((moved.planet, kind, fixed.planet));
+            }
+        }
+    }
+    aspects
+}
+
+/// Where a process's progressed chart stood when last looked at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressedState {
+    signs: Vec<(Planet, ZodiacSign)>,
+    aspects: Vec<(Planet, AspectKind, Planet)>,
+}
+
+impl ProgressedState {
+    pub fn new(progressed: &[PlanetaryPosition], natal: &NatalChart) -> Self {
+        Self {
+            signs: progressed.iter().map(|pos| (pos.planet, pos.sign)).collect(),
+            aspects: hard_aspects(progressed, natal),
+        }
+    }
+
+    /// Ingresses and newly formed aspects since `earlier`
+    pub fn events_since(&self, earlier: &Self) -> Vec<LifecycleEvent> {
+        let ingresses = self
+            .signs
+            .iter()
+            .filter(|&&(planet, sign)| earlier.signs.iter().any(|&(p, s)| p == planet && s != sign))
+            .map(|&(planet, sign)| LifecycleEvent::Ingress { planet, sign });
+        let aspects = self
+            .aspects
+            .iter()
+            .filter(|aspect| !earlier.aspects.contains(aspect))
+            .map(|&(planet, kind, natal)| LifecycleEvent::Aspect { planet, kind, natal });
+        ingresses.chain(aspects).collect()
+    }
+}
+
+/// A turn in a long-running process's progressed chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// A progressed planet moved into a new sign
+    Ingress { planet: Planet, sign: ZodiacSign },
+    /// A progressed planet came into hard aspect with a natal one
+    Aspect { planet: Planet, kind: AspectKind, natal: Planet },
+}
+
+impl LifecycleEvent {
+    /// The event as news for the process's owner
+    pub fn describe(self, comm: &str, task_type: TaskType) -> String {
+        let work = activity(task_type);
+        match self {
+            LifecycleEvent::Ingress { planet, sign } => format!(
+                "🌅 Your {comm} process has progressed {} into {}—{work} is entering {} phase.",
+                planet.name(),
+                sign.name(),
+                phase(sign)
+            ),
+            LifecycleEvent::Aspect { planet, kind, natal } => format!(
+                "🌅 Your {comm} process has progressed {} {} its natal {}—{work} {}.",
+                planet.name(),
+                kind.name(),
+                natal.name(),
+                match kind {
+                    AspectKind::Conjunction => "is coming back to its roots",
+                    AspectKind::Opposition => "faces what it was started for",
+                    _ => "is under strain",
+                }
+            ),
+        }
+    }
+}
+
+/// What a task type spends its life doing
+fn activity(task_type: TaskType) -> &'static str {
+    match task_type {
+        TaskType::Critical => "keeping the system alive",
+        TaskType::System => "housekeeping",
+        TaskType::Interactive => "the conversation",
+        TaskType::Desktop => "drawing",
+        TaskType::CpuIntensive => "compilation",
+        TaskType::Network => "networking",
+        TaskType::MemoryHeavy => "remembering",
+        TaskType::Virtualization => "hosting",
+    }
+}
+
+/// The character a sign lends whatever passes through it
+fn phase(sign: ZodiacSign) -> &'static str {
+    match sign {
+        ZodiacSign::Aries => "a pioneering",
+        ZodiacSign::Taurus => "a steady",
+        ZodiacSign::Gemini => "a communicative",
+        ZodiacSign::Cancer => "a nurturing",
+        ZodiacSign::Leo => "an expressive",
+        ZodiacSign::Virgo => "a meticulous",
+        ZodiacSign::Libra => "a balanced",
+        ZodiacSign::Scorpio => "a transformative",
+        ZodiacSign::Sagittarius => "an expansive",
+        ZodiacSign::Capricorn => "a disciplined",
+        ZodiacSign::Aquarius => "an inventive",
+        ZodiacSign::Pisces => "a dreamy",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn natal(positions: &[(Planet, f64, bool)]) -> NatalChart {
+        NatalChart::new(
+            positions
+                .iter()
+                .map(|&(planet, longitude, retrograde)| PlanetaryPosition {
+                    planet,
+                    longitude,
+                    latitude: 1.5,
+                    sign: ZodiacSign::from_longitude(longitude),
+                    degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                    retrograde,
+                    moon_phase: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_solar_arc() {
+        // A day of solar motion per unit of runtime
+        let progressor = SolarArcProgressor::new(3600);
+        assert!((progressor.arc(3600) - 0.985_647).abs() < 1e-6);
+        assert!((progressor.arc(36_000) - 9.856_47).abs() < 1e-5);
+        assert!((progressor.arc(1800) - progressor.arc(3600) / 2.0).abs() < 1e-12);
+        assert!((progressor.arc(3600 * 365) - 360.0 * 365.0 / 365.242_19).abs() < 1e-9);
+
+        let chart = natal(&[(Planet::Sun, 10.0, false), (Planet::Saturn, 355.0, true)]);
+        let progressed = progressor.advance(&chart, 36_000);
+        assert!((progressed[0].longitude - 19.856_47).abs() < 1e-5);
+        assert_eq!(progressed[0].sign, ZodiacSign::Aries);
+        // Saturn goes round past 0° Aries, and keeps its latitude and station
+        assert!((progressed[1].longitude - 4.856_47).abs() < 1e-5);
+        assert_eq!(progressed[1].sign, ZodiacSign::Aries);
+        assert!((progressed[1].degree_in_sign - 4.856_47).abs() < 1e-5);
+        assert!(progressed[1].retrograde);
+        assert!((progressed[1].latitude - 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hard_aspects_to_natal() {
+        let chart = natal(&[(Planet::Sun, 10.0, false), (Planet::Mars, 58.0, false), (Planet::Saturn, 152.0, false)]);
+        let progressor = SolarArcProgressor::new(60);
+
+        // Four units in, Mars has come within a degree of squaring natal Saturn
+        assert!(hard_aspects(&progressor.advance(&chart, 180), &chart).is_empty());
+        assert_eq!(
+            hard_aspects(&progressor.advance(&chart, 240), &chart),
+            vec![(Planet::Mars, AspectKind::Square, Planet::Saturn)]
+        );
+    }
+
+    #[test]
+    fn test_events_since() {
+        let chart = natal(&[(Planet::Sun, 10.0, false), (Planet::Mars, 58.0, false), (Planet::Saturn, 152.0, false)]);
+        let progressor = SolarArcProgressor::new(60);
+        let at = |secs| ProgressedState::new(&progressor.advance(&chart, secs), &chart);
+
+        // Mars crosses into Gemini after a couple of units, then squares Saturn
+        assert!(at(120).events_since(&at(60)).is_empty());
+        assert_eq!(
+            at(180).events_since(&at(60)),
+            vec![LifecycleEvent::Ingress { planet: Planet::Mars, sign: ZodiacSign::Gemini }]
+        );
+        assert_eq!(
+            at(240).events_since(&at(180)),
+            vec![LifecycleEvent::Aspect { planet: Planet::Mars, kind: AspectKind::Square, natal: Planet::Saturn }]
+        );
+        // An aspect already there is no news
+        assert!(at(245).events_since(&at(240)).is_empty());
+    }
+
+    #[test]
+    fn test_lifecycle_messages() {
+        let ingress = LifecycleEvent::Ingress { planet: Planet::Mars, sign: ZodiacSign::Gemini };
+        assert_eq!(
+            ingress.describe("rustc", TaskType::CpuIntensive),
+            "🌅 Your rustc process has progressed Mars into Gemini—compilation is entering a communicative phase."
+        );
+        let square = LifecycleEvent::Aspect { planet: Planet::Mars, kind: AspectKind::Square, natal: Planet::Saturn };
+        assert!(square.describe("nginx", TaskType::Network).contains("Mars square its natal Saturn—networking is under strain"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use super::ephemeris::Ephemeris;
+use super::planets::{find_position, Planet};
+
+/// How many days either side of a moment to look for a station
+///
+/// Longer than any retrograde spell of the planets we track (Uranus, the
+/// slowest, spends about 150 days retrograde).
+pub const STATION_SCAN_DAYS: u32 = 160;
+
+/// Julian day of the Unix epoch
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+
+/// The retrograde spell `planet` is in at `jd`, as Julian days
+///
+/// Steps a day at a time back to the day it stationed retrograde and forward
+/// to the day it stations direct, so both ends are good to a day. None when
+/// the planet is direct, missing from the chart, or still retrograde at the
+/// edge of the scan.
+pub fn retrograde_window_jd(ephemeris: &dyn Ephemeris, planet: Planet, jd: f64) -> Option<(f64, f64)> {
+    let retrograde_at = |jd: f64| -> Option<bool> {
+        let positions = ephemeris.positions(jd).ok()?;
+        find_position(&positions, planet).ok().map(|pos| pos.retrograde)
+    };
+    if !retrograde_at(jd)? {
+        return None;
+    }
+
+    let mut start = None;
+    for day in 1..=STATION_SCAN_DAYS {
+        let earlier = jd - f64::from(day);
+        if !retrograde_at(earlier)? {
+            start = Some(earlier + 1.0);
+            break;
+        }
+    }
+    let start = start?;
+
+    for day in 1..=STATION_SCAN_DAYS {
+        let later = jd + f64::from(day);
+        if !retrograde_at(later)? {
+            return Some((start, later));
+        }
+    }
+    None
+}
+
+/// Every retrograde spell under way during the `days` from `jd`, as (planet, start, end) Julian days
+///
+/// Ordered by when each spell began. A spell whose station lies beyond
+/// `STATION_SCAN_DAYS` of where the calendar found it is left out.
+pub fn retrograde_calendar(ephemeris: &dyn Ephemeris, jd: f64, days: u32) -> Vec<(Planet, f64, f64)> {
+    let mut spells = Vec::new();
+    let mut placed = HashSet::new();  // Planets whose current spell is already on the calendar
+    for day in 0..days {
+        let at = jd + f64::from(day);
+        let Ok(positions) = ephemeris.positions(at) else {
+            continue;
+        };
+        for pos in &positions {
+            if !pos.retrograde {
+                placed.remove(&pos.planet);
+            } else if placed.insert(pos.planet) {
+                if let Some((start, end)) = retrograde_window_jd(ephemeris, pos.planet, at) {
+                    spells.push((pos.planet, start, end));
+                }
+            }
+        }
+    }
+    spells.sort_by(|a, b| a.1.total_cmp(&b.1));
+    spells
+}
+
+/// The Julian day of a moment, to the millisecond
+///
+/// Unlike `julian_day`, which only goes by the date.
+pub fn datetime_to_jd(dt: DateTime<Utc>) -> f64 {
+    #[allow(clippy::cast_precision_loss)]
+    let days = dt.timestamp_millis() as f64 / 86_400_000.0;
+    UNIX_EPOCH_JD + days
+}
+
+/// The moment of a Julian day
+pub fn jd_to_datetime(jd: f64) -> Option<DateTime<Utc>> {
+    if !jd.is_finite() {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let millis = ((jd - UNIX_EPOCH_JD) * 86_400_000.0).round() as i64;
+    DateTime::from_timestamp_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::{AstroError, FixedEphemeris, PlanetaryPosition, ZodiacSign};
+
+    fn mars(retrograde: bool) -> Vec<PlanetaryPosition> {
+        vec![PlanetaryPosition {
+            planet: Planet::Mars,
+            longitude: 100.0,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(100.0),
+            degree_in_sign: ZodiacSign::degree_in_sign(100.0),
+            retrograde,
+            moon_phase: None,
+        }]
+    }
+
+    #[test]
+    fn test_no_window_without_a_station() {
+        // Direct, missing from the chart, or retrograde forever
+        let jd = 2_460_999.5;
+        assert_eq!(retrograde_window_jd(&FixedEphemeris::new(mars(false)), Planet::Mars, jd), None);
+        assert_eq!(retrograde_window_jd(&FixedEphemeris::new(mars(true)), Planet::Venus, jd), None);
+        assert_eq!(retrograde_window_jd(&FixedEphemeris::new(mars(true)), Planet::Mars, jd), None);
+    }
+
+    #[test]
+    fn test_retrograde_calendar() {
+        /// Mercury retrograde for 20 days from day 10 and again from day 100; Mars from before the start to day 5
+        struct Spells;
+        impl Ephemeris for Spells {
+            fn positions(&self, jd: f64) -> Result<Vec<PlanetaryPosition>, AstroError> {
+                let day = jd - 2_460_000.5;
+                let mercury = (10.0..30.0).contains(&day) || (100.0..120.0).contains(&day);
+                let mut positions = mars((-40.0..5.0).contains(&day));
+                positions.push(PlanetaryPosition { planet: Planet::Mercury, retrograde: mercury, ..positions[0].clone() });
+                Ok(positions)
+            }
+        }
+
+        let jd = 2_460_000.5;
+        assert_eq!(
+            retrograde_calendar(&Spells, jd, 110),
+            vec![
+                (Planet::Mars, jd - 40.0, jd + 5.0),
+                (Planet::Mercury, jd + 10.0, jd + 30.0),
+                (Planet::Mercury, jd + 100.0, jd + 120.0),
+            ]
+        );
+        // A spell still running at the end of the scan is found whole
+        assert_eq!(retrograde_calendar(&Spells, jd + 20.0, 5), vec![(Planet::Mercury, jd + 10.0, jd + 30.0)]);
+    }
+
+    #[test]
+    fn test_jd_to_datetime() {
+        assert_eq!(jd_to_datetime(UNIX_EPOCH_JD), DateTime::from_timestamp(0, 0));
+        assert_eq!(jd_to_datetime(2_451_545.0), DateTime::from_timestamp(946_728_000, 0));
+        assert_eq!(jd_to_datetime(f64::NAN), None);
+
+        let moment = DateTime::from_timestamp_millis(1_742_461_260_123).unwrap();
+        assert_eq!(jd_to_datetime(datetime_to_jd(moment)), Some(moment));
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use super::aspects::{angular_separation, aspect_strength, AspectKind};
+use super::planets::{find_position, PlanetaryPosition};
+use super::tasks::{RulershipMap, TaskType};
+
+/// How two task types get along while running side by side, by the aspects between their rulers
+///
+/// Each entry is a priority multiplier: a trine or sextile between the rulers
+/// lets both types flow, a square or opposition sets them grinding against
+/// each other, each in proportion to how exact it is. Types with the same
+/// ruler, or whose rulers make no aspect, have no entry.
+#[derive(Debug, Clone, Default)]
+pub struct TaskTypeSynergy {
+    factors: HashMap<(TaskType, TaskType), f64>,
+}
+
+impl TaskTypeSynergy {
+    /// Work out the matrix for a chart
+    pub fn new(positions: &[PlanetaryPosition], rulership: &RulershipMap) -> Self {
+        let task_types: Vec<_> = TaskType::all().into_iter().filter(|&t| t != TaskType::Critical).collect();
+        let mut factors = HashMap::new();
+        for (i, &a) in task_types.iter().enumerate() {
+            for &b in &task_types[i + 1..] {
+                let (Ok(pa), Ok(pb)) = (find_position(positions, rulership.ruler(a)), find_position(positions, rulership.ruler(b)))
+                else {
+                    continue;
+                };
+                if pa.planet == pb.planet {
+                    continue;
+                }
+                let separation = angular_separation(pa.longitude, pb.longitude);
+                let bonus: f64 = AspectKind::all()
+                    .into_iter()
+                    .map(|kind| kind.modifier() * aspect_strength(kind.angle(), separation, kind.orb()))
+                    .sum();
+                if bonus != 0.0 {
+                    factors.insert((a, b), 1.0 + bonus);
+                    factors.insert((b, a), 1.0 + bonus);
+                }
+            }
+        }
+        Self { factors }
+    }
+
+    /// Multiplier for `task_type` running alongside `other`
+    pub fn factor(&self, task_type: TaskType, other: TaskType) -> Option<f64> {
+        self.factors.get(&(task_type, other)).copied()
+    }
+
+    /// Multiplier for `task_type` running alongside all of `others`: the mean of their entries
+    pub fn combined(&self, task_type: TaskType, others: impl IntoIterator<Item = TaskType>) -> f64 {
+        let (sum, count) = others
+            .into_iter()
+            .filter_map(|other| self.factor(task_type, other))
+            .fold((0.0, 0_u32), |(sum, count), factor| (sum + factor, count + 1));
+        if count == 0 {
+            return 1.0;
+        }
+        sum / f64::from(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::planets::{Planet, ZodiacSign};
+
+    fn chart(longitudes: &[(Planet, f64)]) -> Vec<PlanetaryPosition> {
+        longitudes
+            .iter()
+            .map(|&(planet, longitude)| PlanetaryPosition {
+                planet,
+                longitude,
+                latitude: 0.0,
+                sign: ZodiacSign::from_longitude(longitude),
+                degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+                retrograde: false,
+                moon_phase: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_synergy_matrix() {
+        // Mercury trine Mars, exact; Saturn squares Mars from 4° off
+        let positions = chart(&[(Planet::Mercury, 10.0), (Planet::Mars, 130.0), (Planet::Saturn, 224.0)]);
+        let synergy = TaskTypeSynergy::new(&positions, &RulershipMap::default());
+
+        let flow = synergy.factor(TaskType::Network, TaskType::CpuIntensive).unwrap();
+        assert!((flow - 1.15).abs() < 1e-9);
+        assert_eq!(synergy.factor(TaskType::CpuIntensive, TaskType::Network), Some(flow));
+        let friction = synergy.factor(TaskType::System, TaskType::CpuIntensive).unwrap();
+        assert!((friction - (1.0 - 0.10 * (1.0 - 4.0 / 7.0))).abs() < 1e-9);
+
+        // Mercury and Saturn are 146° apart: no aspect, no entry
+        assert_eq!(synergy.factor(TaskType::Network, TaskType::System), None);
+        assert!((synergy.combined(TaskType::CpuIntensive, [TaskType::Network, TaskType::System]) - (flow + friction) / 2.0).abs() < 1e-9);
+        assert!((synergy.combined(TaskType::Network, [TaskType::System]) - 1.0).abs() < 1e-9);
+    }
+}
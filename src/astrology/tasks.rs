@@ -1,5 +1,10 @@
-use super::planets::Planet;
-use std::collections::HashMap;
+use super::error::AstroError;
+use super::keywords::KeywordClassifier;
+use super::planets::{Element, Planet};
+use crate::levenshtein;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 /// Task type classification based on astrological domains
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -10,11 +15,24 @@ pub enum TaskType {
     MemoryHeavy,    // Jupiter - Expansion
     System,         // Saturn - Structure
     Interactive,    // Moon - Emotions/Cycles
-    #[allow(dead_code)]  // Never returned by classify(), only used in is_critical() check
-    Critical,       // Sun - Life Force (only for PID 1/init)
+    Virtualization, // Uranus - Technology/Disruption
+    Critical,       // Sun - Life Force (init, core kernel threads, user-marked tasks)
 }
 
 impl TaskType {
+    pub fn all() -> Vec<TaskType> {
+        vec![
+            TaskType::Network,
+            TaskType::CpuIntensive,
+            TaskType::Desktop,
+            TaskType::MemoryHeavy,
+            TaskType::System,
+            TaskType::Interactive,
+            TaskType::Virtualization,
+            TaskType::Critical,
+        ]
+    }
+
     /// Get the ruling planet for this task type
     pub fn ruling_planet(self) -> Planet {
         match self {
@@ -24,10 +42,22 @@ impl TaskType {
             TaskType::MemoryHeavy => Planet::Jupiter,
             TaskType::System => Planet::Saturn,
             TaskType::Interactive => Planet::Moon,
+            TaskType::Virtualization => Planet::Uranus,
             TaskType::Critical => Planet::Sun,
         }
     }
 
+    /// The element whose signs boost this task type, if any
+    pub fn favored_element(self) -> Option<Element> {
+        match self {
+            TaskType::CpuIntensive => Some(Element::Fire),
+            TaskType::Network | TaskType::Desktop | TaskType::Virtualization => Some(Element::Air),
+            TaskType::MemoryHeavy => Some(Element::Water),
+            TaskType::System => Some(Element::Earth),
+            TaskType::Interactive | TaskType::Critical => None,
+        }
+    }
+
     pub fn name(self) -> &'static str {
         match self {
             TaskType::Network => "Network",
@@ -36,100 +66,377 @@ impl TaskType {
             TaskType::MemoryHeavy => "Memory-Heavy",
             TaskType::System => "System",
             TaskType::Interactive => "Interactive",
+            TaskType::Virtualization => "Virtualization",
             TaskType::Critical => "Critical",
         }
     }
 }
 
+impl FromStr for TaskType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "network" => Ok(TaskType::Network),
+            "cpu" | "cpu-intensive" | "cpuintensive" => Ok(TaskType::CpuIntensive),
+            "desktop" | "desktop/ui" | "ui" => Ok(TaskType::Desktop),
+            "memory" | "memory-heavy" | "memoryheavy" => Ok(TaskType::MemoryHeavy),
+            "system" => Ok(TaskType::System),
+            "interactive" => Ok(TaskType::Interactive),
+            "virtualization" | "vm" => Ok(TaskType::Virtualization),
+            "critical" => Ok(TaskType::Critical),
+            _ => Err(format!("unknown task type '{s}'")),
+        }
+    }
+}
+
+impl Serialize for TaskType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which planet rules which task type
+///
+/// Defaults to the classic rulerships from `TaskType::ruling_planet`, but can be
+/// rearranged to experiment with other cosmologies.
+#[derive(Debug, Clone)]
+pub struct RulershipMap {
+    rulers: HashMap<TaskType, Planet>,
+}
+
+impl RulershipMap {
+    pub fn new() -> Self {
+        let rulers = TaskType::all()
+            .into_iter()
+            .map(|task_type| (task_type, task_type.ruling_planet()))
+            .collect();
+        Self { rulers }
+    }
+
+    /// Get the planet currently ruling a task type
+    pub fn ruler(&self, task_type: TaskType) -> Planet {
+        self.rulers.get(&task_type).copied().unwrap_or_else(|| task_type.ruling_planet())
+    }
+
+    /// Hand a task type over to a different planet
+    pub fn reassign(&mut self, task_type: TaskType, planet: Planet) {
+        self.rulers.insert(task_type, planet);
+    }
+}
+
+impl Default for RulershipMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a task's type was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+    Name,       // Matched from the command name
+    Keyword,    // A keyword in the command line
+    Fuzzy,      // Close enough to a pattern, within the fuzzy match distance
+    Learned,    // Promoted from observed behavior
+    Default,    // Nothing matched; Interactive by default
+    Anonymous,  // No name at all; Interactive by default
+}
+
+/// Classifier tier checked before everything else (e.g. browsers, whatever else their name says)
+pub const TIER_OVERRIDE: u8 = 0;
+
+/// Classifier tier for the ordinary name patterns
+pub const TIER_PATTERN: u8 = 1;
+
+/// Weight of rules added while running, so they beat every other override
+const RUNTIME_RULE_WEIGHT: u32 = u32::MAX;
+
+/// Where a classifier rule came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSource {
+    Builtin,
+    File,
+    Cli,
+}
+
+/// One name pattern and the task type it implies
+///
+/// Lower tiers win. Within a tier an exact match wins, then the substring
+/// match with the highest weight, then the longest pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassifierRule {
+    pub tier: u8,
+    pub pattern: String,
+    pub task_type: TaskType,
+    pub weight: u32,
+    pub source: RuleSource,
+}
+
+/// The effective classifier ruleset, as exported and imported
+#[derive(Debug, Serialize, Deserialize)]
+struct ClassifierRules {
+    rules: Vec<ClassifierRule>,
+}
+
 /// Task classifier - maps process names to task types
 pub struct TaskClassifier {
-    patterns: HashMap<String, TaskType>,
+    rules: Vec<ClassifierRule>,       // Sorted by precedence
+    exact: HashMap<String, usize>,    // Pattern -> index of its highest-precedence rule
+    learned: HashMap<i32, TaskType>,  // Per-PID overrides from runtime probes
+    fuzzy_distance: usize,            // Edits allowed when nothing else matches, 0 for none
+    keywords: Option<KeywordClassifier>,  // Consulted for names no rule matches
 }
 
 impl TaskClassifier {
     pub fn new() -> Self {
-        let mut patterns = HashMap::new();
-
-        for pattern in &[
+        let mut rules = Vec::new();
+        let mut builtin = |tier: u8, patterns: &[&str], task_type: TaskType| {
+            rules.extend(patterns.iter().map(|pattern| ClassifierRule {
+                tier,
+                pattern: (*pattern).to_string(),
+                task_type,
+                weight: 1,
+                source: RuleSource::Builtin,
+            }));
+        };
+
+        builtin(TIER_OVERRIDE, &["firefox", "chrome", "chromium"], TaskType::Network);
+
+        builtin(TIER_PATTERN, &[
             "ssh", "sshd", "curl", "wget", "transmission", "discord", "slack",
             "teams", "zoom", "thunderbird", "evolution", "networkmanager",
             "dhcpcd", "wpa_supplicant", "nginx", "apache", "httpd", "node",
             "npm", "deno",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::Network);
-        }
+        ], TaskType::Network);
 
-        for pattern in &[
+        builtin(TIER_PATTERN, &[
             "cc1", "rustc", "make", "cargo", "gcc", "clang", "g++", "ld",
             "as", "ffmpeg", "blender", "gimp", "inkscape", "handbrake",
             "x264", "x265", "vpxenc", "tar", "gzip", "bzip2", "xz", "zip",
             "7z", "convert", "montage",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::CpuIntensive);
-        }
+        ], TaskType::CpuIntensive);
 
-        for pattern in &[
+        builtin(TIER_PATTERN, &[
             "gnome-shell", "kde", "plasma", "kwin", "xorg", "wayland",
             "pulseaudio", "pipewire", "mutter", "compiz", "enlightenment",
             "xfce4", "lxde", "mate-panel", "cinnamon", "budgie", "polybar",
             "waybar", "dunst", "mako", "rofi", "dmenu",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::Desktop);
-        }
+        ], TaskType::Desktop);
 
-        for pattern in &[
+        builtin(TIER_PATTERN, &[
             "postgres", "postgresql", "mysql", "mariadb", "redis", "memcached",
             "mongodb", "cassandra", "elasticsearch", "java", "electron",
             "idea", "pycharm", "studio", "vscode", "code", "docker",
-            "containerd", "qemu", "virtualbox",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::MemoryHeavy);
-        }
+            "containerd",
+        ], TaskType::MemoryHeavy);
+
+        builtin(TIER_PATTERN, &[
+            "qemu", "kvm", "vhost", "virtualbox", "vboxheadless", "libvirtd",
+            "virtqemud", "firecracker", "crosvm", "cloud-hypervisor", "vmware",
+        ], TaskType::Virtualization);
 
-        for pattern in &[
+        builtin(TIER_PATTERN, &[
             "systemd", "init", "kworker", "kswapd", "kthreadd", "ksoftirqd",
             "migration", "rcu", "watchdog", "irqbalance", "systemd-journald",
             "systemd-udevd", "systemd-logind", "dbus-daemon", "accounts-daemon",
             "polkitd", "rtkit-daemon", "udisksd", "upowerd",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::System);
-        }
+        ], TaskType::System);
 
-        for pattern in &[
+        builtin(TIER_PATTERN, &[
             "bash", "zsh", "fish", "sh", "vim", "nvim", "emacs", "nano",
             "less", "more", "cat", "grep", "awk", "sed", "tmux", "screen",
             "htop", "top", "btop", "glances", "alacritty", "kitty", "konsole",
             "gnome-terminal", "terminator", "yakuake", "st",
-        ] {
-            patterns.insert((*pattern).to_string(), TaskType::Interactive);
+        ], TaskType::Interactive);
+
+        Self::from_rules(rules)
+    }
+
+    fn from_rules(mut rules: Vec<ClassifierRule>) -> Self {
+        rules.sort_by(|a, b| {
+            a.tier
+                .cmp(&b.tier)
+                .then(b.weight.cmp(&a.weight))
+                .then(b.pattern.len().cmp(&a.pattern.len()))
+                .then(a.pattern.cmp(&b.pattern))
+        });
+
+        let mut exact = HashMap::new();
+        for (index, rule) in rules.iter().enumerate() {
+            exact.entry(rule.pattern.clone()).or_insert(index);
         }
 
-        Self { patterns }
+        Self { rules, exact, learned: HashMap::new(), fuzzy_distance: 0, keywords: None }
     }
 
-    /// Classify a task based on its command name
-    pub fn classify(&self, comm: &str) -> TaskType {
-        if comm.contains("firefox") || comm.contains("chrome") || comm.contains("chromium") {
-            return TaskType::Network;
+    /// Build a classifier from a ruleset previously written by `export_json`
+    pub fn from_json(json: &str) -> Result<Self, AstroError> {
+        let ruleset: ClassifierRules =
+            serde_json::from_str(json).map_err(|e| AstroError::InvalidClassifier(e.to_string()))?;
+        if ruleset.rules.iter().any(|rule| rule.pattern.is_empty()) {
+            return Err(AstroError::InvalidClassifier("empty pattern would match every task".to_string()));
+        }
+        Ok(Self::from_rules(ruleset.rules))
+    }
+
+    /// Every rule of the effective ruleset, in precedence order, as JSON
+    pub fn export_json(&self) -> String {
+        let ruleset = ClassifierRules { rules: self.rules.clone() };
+        serde_json::to_string_pretty(&ruleset).unwrap_or_default()
+    }
+
+    /// Classify `pattern` as `task_type` from now on, ahead of every other rule
+    ///
+    /// Adding the same pattern again replaces the earlier runtime rule.
+    pub fn add_rule(&mut self, pattern: &str, task_type: TaskType) -> Result<(), AstroError> {
+        if pattern.is_empty() {
+            return Err(AstroError::InvalidClassifier("empty pattern would match every task".to_string()));
         }
+        let mut rules = std::mem::take(&mut self.rules);
+        rules.retain(|rule| !(rule.source == RuleSource::Cli && rule.pattern == pattern));
+        rules.push(ClassifierRule {
+            tier: TIER_OVERRIDE,
+            pattern: pattern.to_string(),
+            task_type,
+            weight: RUNTIME_RULE_WEIGHT,
+            source: RuleSource::Cli,
+        });
+        let learned = std::mem::take(&mut self.learned);
+        let fuzzy_distance = self.fuzzy_distance;
+        let keywords = self.keywords.take();
+        *self = Self::from_rules(rules);
+        self.learned = learned;
+        self.fuzzy_distance = fuzzy_distance;
+        self.keywords = keywords;
+        Ok(())
+    }
+
+    /// Let `classify_pid` fall back to patterns within `max_edit_distance` of a name
+    ///
+    /// Off (0) by default: short patterns sit a single edit away from many
+    /// unrelated names (`ls` is one from the linker `ld`).
+    pub fn set_fuzzy_distance(&mut self, max_edit_distance: usize) {
+        self.fuzzy_distance = max_edit_distance;
+    }
+
+    /// Let `classify_pid` look for astrological keywords in the command line of a task no rule matches
+    ///
+    /// Between the name rules and fuzzy matching: `python3` says nothing,
+    /// `run_network_monitor.py` does.
+    pub fn set_keyword_classify(&mut self, enabled: bool) {
+        self.keywords = enabled.then(KeywordClassifier::new);
+    }
+
+    /// Remember what a task really is, regardless of its name
+    pub fn learn(&mut self, pid: i32, task_type: TaskType) {
+        self.learned.insert(pid, task_type);
+    }
+
+    /// Everything learned so far, by PID
+    pub fn learned(&self) -> &HashMap<i32, TaskType> {
+        &self.learned
+    }
+
+    /// Drop a learned classification (e.g. the PID has exited)
+    pub fn forget(&mut self, pid: i32) {
+        self.learned.remove(&pid);
+    }
 
-        if let Some(&task_type) = self.patterns.get(comm) {
-            return task_type;
+    /// Drop every learned classification
+    pub fn forget_all(&mut self) {
+        self.learned.clear();
+    }
+
+    /// Drop a learned classification, but only if it is still `task_type`
+    pub fn unlearn(&mut self, pid: i32, task_type: TaskType) {
+        if self.learned.get(&pid) == Some(&task_type) {
+            self.learned.remove(&pid);
         }
+    }
 
-        for (pattern, &task_type) in &self.patterns {
-            if comm.starts_with(pattern) || comm.contains(pattern) {
-                return task_type;
+    /// Classify a task, preferring anything learned about this PID over its name
+    pub fn classify_pid(&self, pid: i32, comm: &str) -> (TaskType, Provenance) {
+        if let Some(&task_type) = self.learned.get(&pid) {
+            return (task_type, Provenance::Learned);
+        }
+        // An empty name would fuzzy-match every short pattern
+        if comm.is_empty() {
+            return (TaskType::Interactive, Provenance::Anonymous);
+        }
+        if let Some(task_type) = self.classify_name(comm) {
+            return (task_type, Provenance::Name);
+        }
+        if let Some(task_type) = self.keywords.as_ref().and_then(|keywords| keywords.classify_from_cmdline(pid)) {
+            return (task_type, Provenance::Keyword);
+        }
+        match self.closest_match(comm, self.fuzzy_distance) {
+            Some((pattern, task_type, distance)) => {
+                log::debug!("{comm}: no rule matches, classified {} by '{pattern}' {distance} edit(s) away", task_type.name());
+                (task_type, Provenance::Fuzzy)
             }
+            None => (TaskType::Interactive, Provenance::Default),
         }
+    }
 
-        TaskType::Interactive
+    /// Classify a task based on its command name, Interactive when nothing matches
+    pub fn classify(&self, comm: &str) -> TaskType {
+        self.classify_name(comm).unwrap_or(TaskType::Interactive)
     }
 
-    /// Check if a task is critical (should always get priority regardless of planets)
-    #[must_use]
-    pub fn is_critical(pid: i32) -> bool {
-        pid == 1
+    /// Classify a task by name, falling back to the closest pattern within `max_edit_distance`
+    ///
+    /// Interactive when nothing matches, not even fuzzily.
+    pub fn fuzzy_classify(&self, comm: &str, max_edit_distance: usize) -> TaskType {
+        self.classify_name(comm)
+            .or_else(|| self.closest_match(comm, max_edit_distance).map(|(_, task_type, _)| task_type))
+            .unwrap_or(TaskType::Interactive)
+    }
+
+    /// Every pattern within `max_distance` edits of a name, closest first
+    ///
+    /// Patterns at the same distance keep their precedence order; a pattern
+    /// with rules in several tiers shows up once, for the rule that wins.
+    pub fn fuzzy_match_all(&self, comm: &str, max_distance: usize) -> Vec<(String, TaskType, usize)> {
+        let mut matches: Vec<_> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|&(index, rule)| self.exact.get(&rule.pattern) == Some(&index))
+            .filter_map(|(_, rule)| {
+                let distance = levenshtein::distance(comm, &rule.pattern);
+                (distance <= max_distance).then(|| (rule.pattern.clone(), rule.task_type, distance))
+            })
+            .collect();
+        matches.sort_by_key(|&(_, _, distance)| distance);
+        matches
+    }
+
+    /// The pattern closest to a name within `max_distance` edits, 0 meaning none
+    fn closest_match(&self, comm: &str, max_distance: usize) -> Option<(String, TaskType, usize)> {
+        if max_distance == 0 {
+            return None;
+        }
+        self.fuzzy_match_all(comm, max_distance).into_iter().next()
+    }
+
+    /// The rule matching a command name, if any
+    fn classify_name(&self, comm: &str) -> Option<TaskType> {
+        let exact = self.exact.get(comm).map(|&index| &self.rules[index]);
+        let substring = self.rules.iter().find(|rule| comm.contains(rule.pattern.as_str()));
+
+        // An exact match wins within its tier, but a lower tier always wins
+        match (exact, substring) {
+            (Some(exact), Some(substring)) if substring.tier < exact.tier => Some(substring.task_type),
+            (Some(rule), _) | (None, Some(rule)) => Some(rule.task_type),
+            (None, None) => None,
+        }
     }
 }
 
@@ -139,6 +446,99 @@ impl Default for TaskClassifier {
     }
 }
 
+/// `is_critical` flag bit: the task runs in a real-time class (`SCHED_FIFO`/`SCHED_RR`)
+pub const TASK_FLAG_RT: u32 = 1 << 0;
+
+/// Kernel threads the cosmos must never punish, matched on the comm before any `/`
+const BUILTIN_CRITICAL_KTHREADS: &[&str] = &[
+    "ksoftirqd", "migration", "rcu_", "rcuc", "rcuog", "rcuop", "cpuhp",
+    "idle_inject", "watchdog", "kthreadd", "irq",
+];
+
+/// Why a task was exempted from the stars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CriticalReason {
+    Init,        // PID 1
+    Kernel,      // Core kernel infrastructure thread
+    RealTime,    // Real-time scheduling class
+    UserMarked,  // Named with --critical-comm
+}
+
+impl CriticalReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            CriticalReason::Init => "init",
+            CriticalReason::Kernel => "kernel infrastructure",
+            CriticalReason::RealTime => "real-time",
+            CriticalReason::UserMarked => "user-marked",
+        }
+    }
+}
+
+/// Which tasks always get full priority, whatever the sky says
+#[derive(Debug, Clone)]
+pub struct CriticalPolicy {
+    kthreads: bool,         // Protect the builtin set of core kernel threads
+    rt_class: bool,         // Protect tasks flagged with TASK_FLAG_RT
+    comms: HashSet<String>, // User-configured additions
+}
+
+impl CriticalPolicy {
+    pub fn new() -> Self {
+        Self { kthreads: true, rt_class: true, comms: HashSet::new() }
+    }
+
+    /// Enable or disable protection of core kernel threads
+    #[must_use]
+    pub fn with_kthreads(mut self, enabled: bool) -> Self {
+        self.kthreads = enabled;
+        self
+    }
+
+    /// Enable or disable protection of real-time tasks
+    #[must_use]
+    pub fn with_rt_class(mut self, enabled: bool) -> Self {
+        self.rt_class = enabled;
+        self
+    }
+
+    /// Always treat tasks with this comm as critical
+    #[must_use]
+    pub fn with_comm(mut self, comm: &str) -> Self {
+        self.comms.insert(comm.to_string());
+        self
+    }
+
+    /// Check if a task is critical (should always get priority regardless of planets)
+    pub fn is_critical(&self, pid: i32, comm: &str, is_kthread: bool, flags: u32) -> bool {
+        self.critical_reason(pid, comm, is_kthread, flags).is_some()
+    }
+
+    /// Like `is_critical`, but says which criterion matched
+    pub fn critical_reason(&self, pid: i32, comm: &str, is_kthread: bool, flags: u32) -> Option<CriticalReason> {
+        // Per-CPU kernel threads are named like "ksoftirqd/3"
+        let base = comm.split('/').next().unwrap_or(comm);
+
+        if pid == 1 {
+            Some(CriticalReason::Init)
+        } else if self.comms.contains(comm) || self.comms.contains(base) {
+            Some(CriticalReason::UserMarked)
+        } else if self.kthreads && is_kthread && BUILTIN_CRITICAL_KTHREADS.iter().any(|name| base.starts_with(name)) {
+            Some(CriticalReason::Kernel)
+        } else if self.rt_class && flags & TASK_FLAG_RT != 0 {
+            Some(CriticalReason::RealTime)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for CriticalPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,6 +581,15 @@ mod tests {
         assert_eq!(classifier.classify("electron"), TaskType::MemoryHeavy);
     }
 
+    #[test]
+    fn test_task_classification_virtualization() {
+        let classifier = TaskClassifier::new();
+
+        assert_eq!(classifier.classify("qemu"), TaskType::Virtualization);
+        assert_eq!(classifier.classify("libvirtd"), TaskType::Virtualization);
+        assert_eq!(classifier.classify("firecracker").ruling_planet(), Planet::Uranus);
+    }
+
     #[test]
     fn test_task_classification_system() {
         let classifier = TaskClassifier::new();
@@ -205,6 +614,10 @@ mod tests {
 
         assert_eq!(classifier.classify("unknown_process"), TaskType::Interactive);
         assert_eq!(classifier.classify("my_custom_app"), TaskType::Interactive);
+
+        // A fallback is told apart from a real match
+        assert_eq!(classifier.classify_pid(1, "qzx9"), (TaskType::Interactive, Provenance::Default));
+        assert_eq!(classifier.classify_pid(1, "bash"), (TaskType::Interactive, Provenance::Name));
     }
 
     #[test]
@@ -216,11 +629,219 @@ mod tests {
         assert_eq!(classifier.classify("systemd-journald"), TaskType::System);
     }
 
+    #[test]
+    fn test_learned_classification() {
+        let mut classifier = TaskClassifier::new();
+        assert_eq!(classifier.classify_pid(4242, "worker"), (TaskType::Interactive, Provenance::Default));
+
+        classifier.learn(4242, TaskType::MemoryHeavy);
+        assert_eq!(classifier.classify_pid(4242, "worker"), (TaskType::MemoryHeavy, Provenance::Learned));
+        assert_eq!(classifier.classify_pid(4243, "worker"), (TaskType::Interactive, Provenance::Default));
+
+        // Another probe's verdict doesn't undo this one
+        classifier.unlearn(4242, TaskType::Network);
+        assert_eq!(classifier.classify_pid(4242, "worker"), (TaskType::MemoryHeavy, Provenance::Learned));
+        classifier.unlearn(4242, TaskType::MemoryHeavy);
+        assert_eq!(classifier.classify_pid(4242, "worker"), (TaskType::Interactive, Provenance::Default));
+
+        classifier.learn(4242, TaskType::Network);
+        classifier.forget(4242);
+        assert_eq!(classifier.classify_pid(4242, "worker"), (TaskType::Interactive, Provenance::Default));
+    }
+
+    #[test]
+    fn test_substring_precedence() {
+        let classifier = TaskClassifier::new();
+
+        // Browsers are Network whatever else their name contains
+        assert_eq!(classifier.classify("chrome-sandbox"), TaskType::Network);
+        // The longest pattern wins among substring matches
+        assert_eq!(classifier.classify("my-systemd-journald-helper"), TaskType::System);
+        assert_eq!(classifier.classify("qemu-system-x86_64"), TaskType::Virtualization);
+    }
+
+    #[test]
+    fn test_fuzzy_truncated_names() {
+        let classifier = TaskClassifier::new();
+
+        // Past 15 bytes the kernel cuts the name short
+        assert_eq!(classifier.classify("cloud-hyperviso"), TaskType::Interactive);
+        assert_eq!(classifier.fuzzy_classify("cloud-hyperviso", 2), TaskType::Virtualization);
+        assert_eq!(classifier.fuzzy_classify("thunderbir", 2), TaskType::Network);
+        assert_eq!(
+            classifier.fuzzy_match_all("cloud-hyperviso", 2),
+            vec![("cloud-hypervisor".to_string(), TaskType::Virtualization, 1)]
+        );
+
+        // Exact and substring matches come first, whatever is nearby
+        assert_eq!(classifier.fuzzy_classify("gcc", 2), TaskType::CpuIntensive);
+        assert_eq!(classifier.fuzzy_classify("kworker/3:1", 2), TaskType::System);
+    }
+
+    #[test]
+    fn test_fuzzy_transpositions() {
+        let classifier = TaskClassifier::new();
+
+        // Two letters swapped are two edits
+        assert_eq!(classifier.fuzzy_classify("rsutc", 1), TaskType::Interactive);
+        assert_eq!(classifier.fuzzy_classify("rsutc", 2), TaskType::CpuIntensive);
+        assert_eq!(classifier.fuzzy_classify("redsi", 2), TaskType::MemoryHeavy);
+
+        // Closest first, ties in precedence order: qemu's swap loses to dmenu
+        let matches = classifier.fuzzy_match_all("qmeu", 3);
+        assert!(matches.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+        let closest: Vec<_> = matches.iter().take_while(|(_, _, distance)| *distance == 2).collect();
+        assert_eq!(closest[0], &("dmenu".to_string(), TaskType::Desktop, 2));
+        assert!(closest.contains(&&("qemu".to_string(), TaskType::Virtualization, 2)));
+    }
+
+    #[test]
+    fn test_fuzzy_off_by_default() {
+        let mut classifier = TaskClassifier::new();
+
+        // One edit turns ls into the linker: fuzzy matching gets it wrong
+        assert_eq!(classifier.fuzzy_classify("ls", 1), TaskType::CpuIntensive);
+        assert!(classifier.fuzzy_match_all("ls", 1).iter().any(|(pattern, _, _)| pattern == "ld"));
+
+        // Which is why classify_pid leaves it alone unless asked
+        assert_eq!(classifier.classify_pid(42, "ls"), (TaskType::Interactive, Provenance::Default));
+        classifier.set_fuzzy_distance(1);
+        assert_eq!(classifier.classify_pid(42, "ls"), (TaskType::CpuIntensive, Provenance::Fuzzy));
+        assert_eq!(classifier.classify_pid(42, "rustc"), (TaskType::CpuIntensive, Provenance::Name));
+        assert_eq!(classifier.classify_pid(42, ""), (TaskType::Interactive, Provenance::Anonymous));
+
+        // Runtime rules keep the setting
+        classifier.add_rule("myapp", TaskType::Network).unwrap();
+        assert_eq!(classifier.classify_pid(42, "ls"), (TaskType::CpuIntensive, Provenance::Fuzzy));
+    }
+
+    #[test]
+    fn test_runtime_rules() {
+        let mut classifier = TaskClassifier::new();
+        classifier.learn(4242, TaskType::Network);
+
+        // Runtime rules beat even the browser override, exact or substring
+        classifier.add_rule("firefox", TaskType::Desktop).unwrap();
+        assert_eq!(classifier.classify("firefox"), TaskType::Desktop);
+        assert_eq!(classifier.classify("firefox-bin"), TaskType::Desktop);
+        assert_eq!(classifier.classify("chrome"), TaskType::Network);
+
+        // Adding the pattern again replaces the rule, and learned PIDs survive
+        classifier.add_rule("firefox", TaskType::MemoryHeavy).unwrap();
+        assert_eq!(classifier.classify("firefox"), TaskType::MemoryHeavy);
+        assert_eq!(classifier.rules.iter().filter(|rule| rule.source == RuleSource::Cli).count(), 1);
+        assert_eq!(classifier.classify_pid(4242, "firefox"), (TaskType::Network, Provenance::Learned));
+
+        assert!(classifier.add_rule("", TaskType::System).is_err());
+    }
+
+    #[test]
+    fn test_classifier_json_round_trip() {
+        let classifier = TaskClassifier::new();
+        let json = classifier.export_json();
+        let imported = TaskClassifier::from_json(&json).unwrap();
+        assert_eq!(imported.export_json(), json);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let firefox = value["rules"].as_array().unwrap().iter().find(|rule| rule["pattern"] == "firefox").unwrap();
+        assert_eq!(firefox["tier"], TIER_OVERRIDE);
+        assert_eq!(firefox["task_type"], "Network");
+        assert_eq!(firefox["source"], "builtin");
+
+        let corpus = classifier.rules.iter().map(|rule| rule.pattern.clone()).chain(
+            [
+                "kworker/0:1", "kworker/1:0H", "systemd-journald", "firefox-bin", "cc1plus",
+                "gnome-terminal-server", "qemu-system-x86", "unknown_process", "my_custom_app",
+            ]
+            .map(String::from),
+        );
+        for comm in corpus {
+            assert_eq!(imported.classify(&comm), classifier.classify(&comm), "{comm}");
+        }
+    }
+
+    #[test]
+    fn test_classifier_json_rejects_bad_rules() {
+        assert!(TaskClassifier::from_json("not json").is_err());
+        let empty = r#"{"rules": [{"tier": 1, "pattern": "", "task_type": "System", "weight": 1, "source": "file"}]}"#;
+        assert!(matches!(TaskClassifier::from_json(empty), Err(AstroError::InvalidClassifier(_))));
+
+        let custom = r#"{"rules": [{"tier": 1, "pattern": "worker", "task_type": "memory", "weight": 5, "source": "file"}]}"#;
+        let classifier = TaskClassifier::from_json(custom).unwrap();
+        assert_eq!(classifier.classify("worker-3"), TaskType::MemoryHeavy);
+        assert_eq!(classifier.classify("bash"), TaskType::Interactive);
+    }
+
     #[test]
     fn test_critical_pid() {
-        assert!(TaskClassifier::is_critical(1));
-        assert!(!TaskClassifier::is_critical(1000));
-        assert!(!TaskClassifier::is_critical(0));
+        let policy = CriticalPolicy::new();
+        assert!(policy.is_critical(1, "systemd", false, 0));
+        assert!(!policy.is_critical(1000, "systemd", false, 0));
+        assert!(!policy.is_critical(0, "swapper", false, 0));
+    }
+
+    #[test]
+    fn test_critical_kernel_threads() {
+        let policy = CriticalPolicy::new();
+        assert_eq!(policy.critical_reason(15, "ksoftirqd/0", true, 0), Some(CriticalReason::Kernel));
+        assert!(policy.is_critical(20, "migration/3", true, 0));
+        assert!(policy.is_critical(16, "rcu_preempt", true, 0));
+        assert!(policy.is_critical(140, "irq/24-nvme0q1", true, 0));
+
+        // Only real kernel threads, not userspace tasks borrowing the name
+        assert!(!policy.is_critical(4242, "ksoftirqd", false, 0));
+        assert!(!policy.is_critical(4242, "irqbalance", false, 0));
+        assert!(!policy.is_critical(88, "kworker/0:1", true, 0));
+
+        let policy = CriticalPolicy::new().with_kthreads(false);
+        assert!(!policy.is_critical(15, "ksoftirqd/0", true, 0));
+    }
+
+    #[test]
+    fn test_critical_rt_class() {
+        let policy = CriticalPolicy::new();
+        assert_eq!(policy.critical_reason(4242, "jackd", false, TASK_FLAG_RT), Some(CriticalReason::RealTime));
+        assert!(!policy.is_critical(4242, "jackd", false, 0));
+
+        let policy = CriticalPolicy::new().with_rt_class(false);
+        assert!(!policy.is_critical(4242, "jackd", false, TASK_FLAG_RT));
+    }
+
+    #[test]
+    fn test_critical_user_comm() {
+        let policy = CriticalPolicy::new().with_comm("postgres").with_comm("nvme-poller");
+        assert_eq!(policy.critical_reason(4242, "postgres", false, 0), Some(CriticalReason::UserMarked));
+        assert!(policy.is_critical(4243, "nvme-poller/2", true, 0));
+        assert!(!policy.is_critical(4244, "postgres-backup", false, 0));
+    }
+
+    #[test]
+    fn test_task_type_from_str() {
+        assert_eq!("cpu".parse::<TaskType>(), Ok(TaskType::CpuIntensive));
+        assert_eq!("System".parse::<TaskType>(), Ok(TaskType::System));
+        assert_eq!("memory-heavy".parse::<TaskType>(), Ok(TaskType::MemoryHeavy));
+        assert!("astral".parse::<TaskType>().is_err());
+    }
+
+    #[test]
+    fn test_rulership_map() {
+        let mut map = RulershipMap::default();
+        for task_type in TaskType::all() {
+            assert_eq!(map.ruler(task_type), task_type.ruling_planet());
+        }
+
+        map.reassign(TaskType::System, Planet::Sun);
+        assert_eq!(map.ruler(TaskType::System), Planet::Sun);
+        assert_eq!(map.ruler(TaskType::Network), Planet::Mercury);
+    }
+
+    #[test]
+    fn test_favored_elements() {
+        assert_eq!(TaskType::CpuIntensive.favored_element(), Some(Element::Fire));
+        assert_eq!(TaskType::Network.favored_element(), Some(Element::Air));
+        assert_eq!(TaskType::System.favored_element(), Some(Element::Earth));
+        assert_eq!(TaskType::MemoryHeavy.favored_element(), Some(Element::Water));
+        assert_eq!(TaskType::Critical.favored_element(), None);
     }
 
     #[test]
@@ -231,6 +852,7 @@ mod tests {
         assert_eq!(TaskType::MemoryHeavy.ruling_planet(), Planet::Jupiter);
         assert_eq!(TaskType::System.ruling_planet(), Planet::Saturn);
         assert_eq!(TaskType::Interactive.ruling_planet(), Planet::Moon);
+        assert_eq!(TaskType::Virtualization.ruling_planet(), Planet::Uranus);
         assert_eq!(TaskType::Critical.ruling_planet(), Planet::Sun);
     }
 }
@@ -0,0 +1,98 @@
+use super::solar::{elongation, COMBUST_ORB, UNDER_BEAMS_ORB};
+
+/// Elongation at which Venus is a prominent morning or evening star
+///
+/// She never strays more than about 47° from the Sun.
+pub const PROMINENT_ELONGATION: f64 = 30.0;
+
+/// Desktop priority multiplier for a combust Venus
+pub const COMBUST_PENALTY: f64 = 0.8;
+
+/// Desktop priority multiplier for Venus under the beams
+pub const UNDER_BEAMS_PENALTY: f64 = 0.95;
+
+/// Desktop priority multiplier for Venus at or beyond `PROMINENT_ELONGATION`
+pub const PROMINENT_BONUS: f64 = 1.2;
+
+/// Where Venus stands relative to the Sun
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VenusPhase {
+    Combust,
+    UnderTheBeams,
+    MorningStar,  // West of the Sun, rising before it
+    EveningStar,  // East of the Sun, setting after it
+}
+
+impl VenusPhase {
+    /// The phase for an elongation from `venus_elongation`
+    pub fn from_elongation(elongation: f64) -> Self {
+        match elongation {
+            e if e.abs() < COMBUST_ORB => VenusPhase::Combust,
+            e if e.abs() < UNDER_BEAMS_ORB => VenusPhase::UnderTheBeams,
+            e if e < 0.0 => VenusPhase::MorningStar,
+            _ => VenusPhase::EveningStar,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VenusPhase::Combust => "Combust",
+            VenusPhase::UnderTheBeams => "Under the beams",
+            VenusPhase::MorningStar => "Morning star",
+            VenusPhase::EveningStar => "Evening star",
+        }
+    }
+}
+
+/// Venus's angular distance from the Sun in degrees, in (-180, 180]
+///
+/// Positive east of the Sun (an evening star), negative west of it (a
+/// morning star).
+pub fn venus_elongation(venus_longitude: f64, sun_longitude: f64) -> f64 {
+    elongation(venus_longitude, sun_longitude)
+}
+
+/// How Venus's elongation sways Desktop tasks
+///
+/// Combust and under the beams she is weakened; clear of the beams she
+/// grows in strength, up to `PROMINENT_BONUS` once prominent.
+pub fn venus_phase_modifier(elongation: f64) -> f64 {
+    match VenusPhase::from_elongation(elongation) {
+        VenusPhase::Combust => COMBUST_PENALTY,
+        VenusPhase::UnderTheBeams => UNDER_BEAMS_PENALTY,
+        VenusPhase::MorningStar | VenusPhase::EveningStar => {
+            let rise = (elongation.abs() - UNDER_BEAMS_ORB) / (PROMINENT_ELONGATION - UNDER_BEAMS_ORB);
+            1.0 + (PROMINENT_BONUS - 1.0) * rise.min(1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_venus_elongation() {
+        assert!((venus_elongation(40.0, 0.0) - 40.0).abs() < 1e-9);
+        assert!((venus_elongation(0.0, 40.0) + 40.0).abs() < 1e-9);
+        // Across 0° Aries
+        assert!((venus_elongation(5.0, 350.0) - 15.0).abs() < 1e-9);
+        assert!((venus_elongation(350.0, 5.0) + 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_venus_phases() {
+        assert_eq!(VenusPhase::from_elongation(45.0), VenusPhase::EveningStar);
+        assert_eq!(VenusPhase::from_elongation(-45.0), VenusPhase::MorningStar);
+        assert_eq!(VenusPhase::from_elongation(-12.0), VenusPhase::UnderTheBeams);
+        assert_eq!(VenusPhase::from_elongation(3.0), VenusPhase::Combust);
+
+        // A wide elongation, either side, earns the full boost; a near-conjunction costs
+        assert!((venus_phase_modifier(45.0) - PROMINENT_BONUS).abs() < 1e-9);
+        assert!((venus_phase_modifier(-30.0) - PROMINENT_BONUS).abs() < 1e-9);
+        assert!(venus_phase_modifier(23.5) > 1.0 && venus_phase_modifier(23.5) < PROMINENT_BONUS);
+        assert!((venus_phase_modifier(2.0) - COMBUST_PENALTY).abs() < 1e-9);
+        assert!(venus_phase_modifier(2.0) < venus_phase_modifier(12.0));
+        assert!(venus_phase_modifier(12.0) < 1.0);
+    }
+}
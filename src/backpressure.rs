@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Backpressure fast path
+//
+// When the queue runs away from us, every chart lookup, line of reasoning and
+// per-PID modifier only digs the hole deeper. Past a threshold the scheduler
+// drops to a fast path: classify the task, look its type up in a table worked
+// out once per chart, dispatch. It only returns to full readings once the
+// queue has fallen to half the threshold, so a queue hovering around the
+// threshold doesn't flip the mode every round.
+
+use std::time::{Duration, Instant};
+
+/// How the scheduler is deciding right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,    // A full reading for every task
+    Degraded,  // Per-type table lookups only
+}
+
+/// Switches to the fast path under load, with hysteresis
+#[derive(Debug, Clone)]
+pub struct Backpressure {
+    high: u64,  // Queue depth that switches to the fast path
+    low: u64,   // Depth the queue must fall to before switching back
+    degraded_since: Option<Instant>,
+    time_degraded: Duration,  // Spells already over
+    spells: u64,
+}
+
+impl Backpressure {
+    pub fn new(threshold: u64) -> Self {
+        Self { high: threshold, low: threshold / 2, degraded_since: None, time_degraded: Duration::ZERO, spells: 0 }
+    }
+
+    /// Queue depth that switches back to full readings
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+
+    /// Take the current queue depth into account, returning the new mode on a switch
+    pub fn update(&mut self, nr_queued: u64, now: Instant) -> Option<Mode> {
+        match self.degraded_since {
+            None if nr_queued > self.high => {
+                self.degraded_since = Some(now);
+                self.spells += 1;
+                Some(Mode::Degraded)
+            }
+            Some(since) if nr_queued <= self.low => {
+                self.time_degraded += now.saturating_duration_since(since);
+                self.degraded_since = None;
+                Some(Mode::Normal)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        if self.degraded_since.is_some() {
+            Mode::Degraded
+        } else {
+            Mode::Normal
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.mode() == Mode::Degraded
+    }
+
+    /// Total time on the fast path up to `now`, the current spell included
+    pub fn time_degraded(&self, now: Instant) -> Duration {
+        self.time_degraded + self.degraded_since.map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+
+    /// How many times the fast path has been taken
+    pub fn spells(&self) -> u64 {
+        self.spells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hysteresis() {
+        let start = Instant::now();
+        let mut backpressure = Backpressure::new(1000);
+        assert_eq!(backpressure.mode(), Mode::Normal);
+
+        // At the threshold is still fine, past it is not
+        assert_eq!(backpressure.update(1000, start), None);
+        assert_eq!(backpressure.update(1001, start), Some(Mode::Degraded));
+        assert!(backpressure.is_degraded());
+
+        // Hovering between the two marks changes nothing either way
+        assert_eq!(backpressure.update(700, start), None);
+        assert_eq!(backpressure.update(1500, start), None);
+        assert_eq!(backpressure.update(501, start), None);
+        assert!(backpressure.is_degraded());
+
+        assert_eq!(backpressure.update(500, start), Some(Mode::Normal));
+        assert_eq!(backpressure.update(900, start), None);
+        assert_eq!(backpressure.mode(), Mode::Normal);
+    }
+
+    #[test]
+    fn test_time_degraded() {
+        let start = Instant::now();
+        let mut backpressure = Backpressure::new(10);
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        backpressure.update(50, at(0));
+        backpressure.update(0, at(200));
+        assert_eq!(backpressure.time_degraded(at(1000)), Duration::from_millis(200));
+
+        // The current spell counts as it runs
+        backpressure.update(11, at(1000));
+        assert_eq!(backpressure.time_degraded(at(1300)), Duration::from_millis(500));
+        assert_eq!(backpressure.spells(), 2);
+    }
+}
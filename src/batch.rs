@@ -0,0 +1,433 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Priority-ordered dispatch batches
+//
+// Dispatching straight off the ring buffer serves tasks first come, first
+// served, so the stars only ever decided how long a task ran. The dispatch
+// loop instead drains a batch, consults the stars for all of it and hands the
+// batch to the kernel Critical tasks first, then by descending priority -- or,
+// for fairness, by ascending virtual runtime, so that priority acts as a
+// weight and a blessed task cannot starve a debuffed one. With
+// --cosmic-tiebreak, tasks the stars rate equally are not left in ring-buffer
+// order either: a shuffle keyed by the scheduler's random source (by --seed,
+// when there is one) and the Julian day reorders them, the same way all day
+// and differently the next.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::astrology::rng::mix;
+use crate::astrology::{CosmicRng, SchedulingDecision};
+use crate::ledger::TaskLedger;
+
+/// How a batch is ordered after Critical tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Fairness {
+    Priority,  // Highest priority first
+    Vruntime,  // Least weighted CPU time first
+}
+
+/// How a drained batch left the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueEnd<E> {
+    More,       // The batch filled up; more tasks may be waiting
+    Empty,
+    Failed(E),
+}
+
+/// Dequeue up to `max` tasks
+pub fn drain<T, E>(mut dequeue: impl FnMut() -> Result<Option<T>, E>, max: usize) -> (Vec<T>, QueueEnd<E>) {
+    let mut tasks = Vec::with_capacity(max);
+    while tasks.len() < max {
+        match dequeue() {
+            Ok(Some(task)) => tasks.push(task),
+            Ok(None) => return (tasks, QueueEnd::Empty),
+            Err(e) => return (tasks, QueueEnd::Failed(e)),
+        }
+    }
+    (tasks, QueueEnd::More)
+}
+
+/// How much of the queue one dispatch iteration may take before yielding
+#[derive(Debug, Clone)]
+pub struct IterationBudget {
+    max_tasks: usize,
+    max_time: Option<Duration>,
+    start: Instant,
+    spent: usize,
+}
+
+impl IterationBudget {
+    pub fn new(max_tasks: usize, max_time: Option<Duration>, start: Instant) -> Self {
+        Self { max_tasks, max_time, start, spent: 0 }
+    }
+
+    /// Size of the next batch: `batch_size`, or whatever is left of the budget
+    pub fn next_batch(&self, batch_size: usize) -> usize {
+        batch_size.min(self.max_tasks.saturating_sub(self.spent))
+    }
+
+    pub fn spend(&mut self, tasks: usize) {
+        self.spent += tasks;
+    }
+
+    /// Tasks drained so far this iteration
+    pub fn spent(&self) -> usize {
+        self.spent
+    }
+
+    pub fn exhausted(&self, now: Instant) -> bool {
+        self.spent >= self.max_tasks
+            || self.max_time.is_some_and(|max_time| now.saturating_duration_since(self.start) >= max_time)
+    }
+}
+
+/// Drain and handle batches until the queue is empty or the budget runs out
+///
+/// `QueueEnd::More` means the budget ran out with tasks possibly still queued.
+pub fn drain_within_budget<S, T, E>(
+    state: &mut S,
+    mut dequeue: impl FnMut(&mut S) -> Result<Option<T>, E>,
+    mut handle: impl FnMut(&mut S, Vec<T>),
+    batch_size: usize,
+    budget: &mut IterationBudget,
+) -> QueueEnd<E> {
+    loop {
+        let (tasks, end) = drain(|| dequeue(state), budget.next_batch(batch_size));
+        budget.spend(tasks.len());
+        handle(state, tasks);
+        match end {
+            QueueEnd::More if budget.exhausted(Instant::now()) => return QueueEnd::More,
+            QueueEnd::More => {}
+            end => return end,
+        }
+    }
+}
+
+/// Order a batch for dispatch: Critical first, then by descending priority
+///
+/// The sort is stable, so equal priorities keep their queue order.
+pub fn sort_by_priority<T>(batch: &mut [T], decision: impl Fn(&T) -> &SchedulingDecision) {
+    batch.sort_by(|a, b| {
+        let (a, b) = (decision(a), decision(b));
+        b.critical.cmp(&a.critical).then(b.priority.cmp(&a.priority))
+    });
+}
+
+/// Seed of the tie-break shuffle for the Julian day `now` falls in, drawn from `rng`
+///
+/// The Julian day turns over at noon UTC.
+pub fn cosmic_seed(rng: &CosmicRng, now: DateTime<Utc>) -> u64 {
+    let julian_day = (now.timestamp() + 43_200).div_euclid(86_400) + 2_440_587;
+    rng.keyed(julian_day.unsigned_abs())
+}
+
+/// Reorder each run of tasks with the same priority (and criticality) by `cosmic_seed`
+///
+/// Meant for a batch already in priority order; the runs stay where they
+/// are, only the tasks within them move.
+pub fn shuffle_ties<T>(batch: &mut [T], cosmic_seed: u64, key: impl Fn(&T) -> (i32, &SchedulingDecision)) {
+    let rank = |item: &T| {
+        let (_, decision) = key(item);
+        (decision.critical, decision.priority)
+    };
+    let mut start = 0;
+    while start < batch.len() {
+        let run = rank(&batch[start]);
+        let len = batch[start..].iter().take_while(|item| rank(item) == run).count();
+        #[allow(clippy::cast_sign_loss)]
+        batch[start..start + len].sort_by_key(|item| mix(cosmic_seed ^ key(item).0 as u64));
+        start += len;
+    }
+}
+
+/// Order a batch for dispatch: Critical first, then by ascending virtual runtime
+///
+/// PIDs the ledger hasn't seen start at its lowest vruntime. Ties keep their
+/// queue order.
+pub fn sort_by_vtime<T>(batch: &mut [T], ledger: &mut TaskLedger, key: impl Fn(&T) -> (i32, &SchedulingDecision)) {
+    for item in batch.iter() {
+        ledger.admit(key(item).0);
+    }
+    batch.sort_by_key(|item| {
+        let (pid, decision) = key(item);
+        (!decision.critical, ledger.vtime(pid))
+    });
+}
+
+/// Sizes of the batches dispatched so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BatchStats {
+    pub batches: u64,
+    pub tasks: u64,
+    pub largest: usize,
+}
+
+impl BatchStats {
+    /// Count one non-empty batch
+    pub fn record(&mut self, size: usize) {
+        if size == 0 {
+            return;
+        }
+        self.batches += 1;
+        self.tasks += size as u64;
+        self.largest = self.largest.max(size);
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.batches == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let average = self.tasks as f64 / self.batches as f64;
+        average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::{AstrologicalScheduler, TASK_FLAG_RT};
+    use chrono::TimeZone;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_drain_overflows_into_next_pass() {
+        let mut queue: VecDeque<i32> = (0..7).collect();
+        let mut dequeue = || Ok::<_, i32>(queue.pop_front());
+
+        let mut passes = Vec::new();
+        loop {
+            let (tasks, end) = drain(&mut dequeue, 3);
+            passes.push(tasks);
+            if end != QueueEnd::More {
+                assert_eq!(end, QueueEnd::Empty);
+                break;
+            }
+        }
+        assert_eq!(passes, [vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+
+        let mut failing = [Ok(Some(1)), Err(-22)].into_iter();
+        assert_eq!(drain(|| failing.next().unwrap(), 8), (vec![1], QueueEnd::Failed(-22)));
+    }
+
+    #[test]
+    fn test_iteration_budget_caps_a_flood() {
+        // A wakeup storm: 5000 tasks queued at once
+        let mut queue: VecDeque<i32> = (0..5000).collect();
+        let mut iterations = Vec::new();
+        loop {
+            let mut budget = IterationBudget::new(256, None, Instant::now());
+            let mut batches = Vec::new();
+            let end = drain_within_budget(
+                &mut queue,
+                |queue| Ok::<_, i32>(queue.pop_front()),
+                |_, tasks| batches.push(tasks.len()),
+                64,
+                &mut budget,
+            );
+            iterations.push((budget.spent(), batches, queue.len()));
+            if end == QueueEnd::Empty {
+                break;
+            }
+            assert_eq!(end, QueueEnd::More);
+        }
+
+        // Each iteration yields after 256 tasks, in batches of 64, leaving the rest queued
+        assert_eq!(iterations.len(), 20);
+        assert_eq!(iterations[0], (256, vec![64; 4], 5000 - 256));
+        assert!(iterations[..19].iter().all(|(spent, batches, _)| *spent == 256 && batches.len() == 4));
+        let (spent, batches, left) = &iterations[19];
+        assert_eq!((*spent, batches.as_slice(), *left), (136, &[64, 64, 8][..], 0));
+
+        // A budget that isn't a multiple of the batch size trims the last batch
+        let mut queue: VecDeque<i32> = (0..1000).collect();
+        let mut budget = IterationBudget::new(100, None, Instant::now());
+        let mut batches = Vec::new();
+        let end = drain_within_budget(&mut queue, |queue| Ok::<_, i32>(queue.pop_front()), |_, tasks| batches.push(tasks.len()), 64, &mut budget);
+        assert_eq!((end, batches, queue.len()), (QueueEnd::More, vec![64, 36], 900));
+    }
+
+    #[test]
+    fn test_iteration_time_budget() {
+        let start = Instant::now();
+        let budget = IterationBudget::new(256, Some(Duration::from_micros(500)), start);
+        assert!(!budget.exhausted(start + Duration::from_micros(499)));
+        assert!(budget.exhausted(start + Duration::from_micros(500)));
+        assert_eq!(budget.next_batch(64), 64);
+
+        // Already out of time: one batch goes out, then the loop yields
+        let mut queue: VecDeque<i32> = (0..1000).collect();
+        let mut budget = IterationBudget::new(usize::MAX, Some(Duration::ZERO), Instant::now());
+        let end = drain_within_budget(&mut queue, |queue| Ok::<_, i32>(queue.pop_front()), |_, _| {}, 64, &mut budget);
+        assert_eq!((end, budget.spent()), (QueueEnd::More, 64));
+    }
+
+    #[test]
+    fn test_batch_dispatch_order() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let mut astro = AstrologicalScheduler::new(60);
+
+        // A mixed queue: (pid, comm, flags)
+        let mut queue: VecDeque<(i32, &str, u32)> = VecDeque::from([
+            (1, "bash", 0),
+            (2, "rustc", 0),
+            (3, "kworker/0:1", 0),
+            (4, "firefox", 0),
+            (5, "postgres", 0),
+            (6, "audio-rt", TASK_FLAG_RT),
+            (7, "rustc", 0),
+            (8, "sshd", 0),
+        ]);
+        let (tasks, end) = drain(|| Ok::<_, i32>(queue.pop_front()), 16);
+        assert_eq!(end, QueueEnd::Empty);
+
+        let mut batch: Vec<_> = tasks
+            .into_iter()
+            .map(|(pid, comm, flags)| (pid, astro.schedule_task_with_flags(comm, pid, 100, false, flags, now).unwrap()))
+            .collect();
+        sort_by_priority(&mut batch, |(_, decision)| decision);
+
+        let order: Vec<i32> = batch.iter().map(|(pid, _)| *pid).collect();
+        let position = |pid| order.iter().position(|&p| p == pid).unwrap();
+
+        // Critical tasks (the real-time one among them) lead, then priorities only fall
+        let critical = batch.iter().take_while(|(_, decision)| decision.critical).count();
+        assert!(position(6) < critical, "Critical goes first: {order:?}");
+        assert!(batch[critical..].iter().all(|(_, decision)| !decision.critical));
+        assert!(batch[critical..].windows(2).all(|w| w[0].1.priority >= w[1].1.priority));
+
+        // The two rustc tasks tie and keep their queue order
+        assert!(position(2) < position(7));
+    }
+
+    #[test]
+    fn test_cosmic_tiebreak() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let mut astro = AstrologicalScheduler::new(60);
+
+        // Eight compilers tie, a shell and a kernel thread don't (PIDs past any real ones)
+        let mut batch: Vec<_> = (900_001..=900_008)
+            .map(|pid| (pid, "rustc"))
+            .chain([(900_009, "bash"), (900_010, "kworker/0:1")])
+            .map(|(pid, comm)| (pid, astro.schedule_task_with_flags(comm, pid, 100, false, 0, now).unwrap()))
+            .collect();
+        sort_by_priority(&mut batch, |(_, decision)| decision);
+        let shuffled = |seed: u64, at: DateTime<Utc>| {
+            let mut batch = batch.clone();
+            shuffle_ties(&mut batch, cosmic_seed(&CosmicRng::seeded(seed), at), |(pid, decision)| (*pid, decision));
+            batch.iter().map(|(pid, _)| *pid).collect::<Vec<_>>()
+        };
+        let order = shuffled(42, now);
+
+        // The same seed on the same day gives the same order
+        assert_eq!(order, shuffled(42, now));
+        assert_eq!(order, shuffled(42, now + chrono::Duration::hours(23)));
+        // Another seed, or another day, reorders the tie
+        assert_ne!(order, shuffled(7, now));
+        assert_ne!(order, shuffled(42, now + chrono::Duration::days(1)));
+
+        // Only the tie moves: the priorities still only fall, and the rustc run stays together
+        let sorted: Vec<i32> = batch.iter().map(|(pid, _)| *pid).collect();
+        let run = |order: &[i32]| order.iter().position(|&pid| pid <= 900_008).unwrap();
+        assert_eq!(run(&order), run(&sorted));
+        let mut tie = order[run(&order)..run(&order) + 8].to_vec();
+        assert_ne!(tie, (900_001..=900_008).collect::<Vec<_>>());
+        tie.sort_unstable();
+        assert_eq!(tie, (900_001..=900_008).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_seed_reproduces_every_random_choice() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        // Jitter for a few PIDs, the tie-break order and the refresh times over a day of charts
+        let run = |seed: u64| {
+            let mut astro = AstrologicalScheduler::new(600).with_rng(CosmicRng::seeded(seed)).with_update_jitter(0.5);
+            let mut choices = Vec::new();
+            let mut now = start;
+            for _ in 0..24 {
+                astro.refresh(now).unwrap();
+                choices.push(i64::from(astro.out_of_bounds_jitter_percent(900_001, now)));
+                choices.push(astro.refresh_after_secs());
+                now += chrono::Duration::seconds(astro.refresh_after_secs() + 1);
+            }
+            let mut batch: Vec<_> = (900_001..=900_008)
+                .map(|pid| (pid, astro.schedule_task_with_flags("rustc", pid, 100, false, 0, start).unwrap()))
+                .collect();
+            shuffle_ties(&mut batch, cosmic_seed(astro.rng(), start), |(pid, decision)| (*pid, decision));
+            choices.extend(batch.iter().map(|(pid, _)| i64::from(*pid)));
+            choices
+        };
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(7));
+
+        // Without jitter the update interval holds
+        let mut steady = AstrologicalScheduler::new(600).with_rng(CosmicRng::seeded(42));
+        steady.refresh(start).unwrap();
+        assert_eq!(steady.refresh_after_secs(), 600);
+    }
+
+    #[test]
+    fn test_batch_stats() {
+        let mut stats = BatchStats::default();
+        assert!(stats.average().abs() < f64::EPSILON);
+        for size in [4, 0, 8, 3] {
+            stats.record(size);
+        }
+        assert_eq!(stats, BatchStats { batches: 3, tasks: 15, largest: 8 });
+        assert!((stats.average() - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_vruntime_shares_cpu_by_priority() {
+        use crate::astrology::TaskType;
+
+        let decision = |priority| SchedulingDecision {
+            task_type: TaskType::CpuIntensive,
+            critical: false,
+            ruling_element: None,
+            priority,
+            astro_priority: priority,
+            weight: 100,
+            weight_factor: 1.0,
+            reasoning: String::new(),
+            planetary_influence: 1.0,
+            element_boost: 1.0,
+        };
+
+        // One CPU, two tasks always runnable: a blessed one and a debuffed one.
+        // Each round only the head of the batch runs, for a slice proportional
+        // to its priority.
+        let tasks = [(1, decision(300)), (2, decision(50))];
+        let run = |fairness: Fairness| {
+            let mut ledger = TaskLedger::new();
+            let mut cpu_ns = [0u64; 2];
+            for _ in 0..7000 {
+                let mut batch = tasks.to_vec();
+                match fairness {
+                    Fairness::Priority => sort_by_priority(&mut batch, |(_, decision)| decision),
+                    Fairness::Vruntime => sort_by_vtime(&mut batch, &mut ledger, |(pid, decision)| (*pid, decision)),
+                }
+                let (pid, decision) = &batch[0];
+                let slice_ns = u64::from(decision.priority) * 10_000;
+                ledger.charge(*pid, slice_ns, decision.priority);
+                cpu_ns[usize::try_from(*pid - 1).unwrap()] += slice_ns;
+            }
+            cpu_ns
+        };
+
+        // Strict priority starves the debuffed task
+        assert_eq!(run(Fairness::Priority)[1], 0);
+
+        // Weighted fairness gives it its 50/350 share of the CPU
+        let [blessed, debuffed] = run(Fairness::Vruntime);
+        #[allow(clippy::cast_precision_loss)]
+        let share = debuffed as f64 / (blessed + debuffed) as f64;
+        assert!((share - 50.0 / 350.0).abs() < 0.01, "debuffed share {share}");
+    }
+}
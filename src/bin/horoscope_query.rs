@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// horoscope-query: ask a running scx_horoscope what the stars are doing
+//
+// Talks to the scheduler's --socket-path. Output is the scheduler's own
+// human-readable rendering, or its JSON with --json.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+
+use scx_horoscope::ipc::{IpcClient, Request, Response, DEFAULT_SOCKET_PATH};
+
+#[derive(Debug, Parser)]
+#[command(name = "horoscope-query", version, about = "Query a running scx_horoscope scheduler")]
+struct Cli {
+    /// Socket the scheduler was started with (--socket-path)
+    #[clap(long, value_name = "PATH", default_value = DEFAULT_SOCKET_PATH, global = true)]
+    socket_path: PathBuf,
+
+    /// Print the answer as JSON
+    #[clap(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Query(Query),
+
+    /// Repeat a query until interrupted, like watch(1)
+    Watch {
+        /// Seconds between refreshes
+        #[clap(long, value_name = "SECONDS", default_value = "2", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        #[command(subcommand)]
+        query: Query,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum Query {
+    /// Current planetary positions
+    Positions,
+    /// The cosmic weather report
+    Weather,
+    /// Dispatch and batch counters
+    Stats,
+    /// Priority of every task type, hour by hour
+    Forecast {
+        #[clap(long, default_value = "24", value_parser = clap::value_parser!(u32).range(1..=168))]
+        hours: u32,
+    },
+    /// How the stars would treat a task with this name
+    Explain { comm: String },
+    /// Classify tasks named like COMM as TYPE from now on
+    AddRule {
+        comm: String,
+        #[clap(value_name = "TYPE")]
+        task_type: String,
+    },
+}
+
+impl From<Query> for Request {
+    fn from(query: Query) -> Self {
+        match query {
+            Query::Positions => Request::Positions,
+            Query::Weather => Request::Weather,
+            Query::Stats => Request::Stats,
+            Query::Forecast { hours } => Request::Forecast { hours },
+            Query::Explain { comm } => Request::Explain { comm },
+            Query::AddRule { comm, task_type } => Request::AddRule { comm, task_type },
+        }
+    }
+}
+
+/// Ask once and print the answer; false if the scheduler said no
+fn ask(cli: &Cli, query: Query) -> Result<bool, String> {
+    let response = IpcClient::connect(&cli.socket_path)
+        .and_then(|client| client.request(&query.into()))
+        .map_err(|e| format!("cannot query the scheduler at {}: {e}", cli.socket_path.display()))?;
+
+    match response {
+        Response::Ok { data, .. } if cli.json => {
+            println!("{}", serde_json::to_string_pretty(&data).unwrap_or_default());
+            Ok(true)
+        }
+        Response::Ok { text, .. } => {
+            println!("{text}");
+            Ok(true)
+        }
+        Response::Error { message } if cli.json => {
+            println!("{}", serde_json::json!({ "error": message }));
+            Ok(false)
+        }
+        Response::Error { message } => {
+            eprintln!("❌ {message}");
+            Ok(false)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        Command::Query(query) => ask(&cli, query.clone()),
+        Command::Watch { interval, query } => loop {
+            if !cli.json {
+                // Clear the screen and home the cursor
+                print!("\x1b[2J\x1b[H");
+                println!("Every {interval}s: {query:?} at {}\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+            }
+            if let Err(e) = ask(&cli, query.clone()) {
+                break Err(e);
+            }
+            thread::sleep(Duration::from_secs(*interval));
+        },
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            ExitCode::from(2)
+        }
+    }
+}
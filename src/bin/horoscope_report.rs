@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// horoscope-report: the scheduler's astrology, without the scheduler
+//
+// Prints what scx_horoscope would see at a given moment -- planetary
+// positions, aspects, the cosmic weather, an hour-by-hour forecast, upcoming
+// retrograde spells and, given a location, the planetary hours -- using only
+// the astrology module. No BPF, no root: fit for demos, classrooms, and CI
+// jobs that want to know whether Mercury will be retrograde on release day.
+
+use std::fmt::Write as _;
+use std::process::ExitCode;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+
+use scx_horoscope::astrology::{
+    self, jd_to_datetime, planetary_hours, retrograde_calendar, AspectCalculator, AstroEphemeris, AstroError,
+    AstrologicalScheduler, MoonPhase, PlanetaryAspect, PlanetaryHour, PlanetaryPosition, DEFAULT_ASPECT_ORB,
+};
+
+/// Days ahead the retrograde calendar looks
+const CALENDAR_DAYS: u32 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "horoscope-report", version, about = "Astrological report from the scx_horoscope engine, no scheduler needed")]
+struct Cli {
+    /// Moment to report on: RFC 3339, or a date for midnight UTC (default: now)
+    #[clap(long, value_name = "DATE", value_parser = parse_date)]
+    date: Option<DateTime<Utc>>,
+
+    /// Hours of priority forecast from DATE, 0 for none
+    #[clap(long, value_name = "HOURS", default_value = "24", value_parser = clap::value_parser!(u32).range(0..=168))]
+    forecast_hours: u32,
+
+    /// List every retrograde spell under way in the year from DATE
+    #[clap(long)]
+    retrograde_calendar: bool,
+
+    #[clap(long, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
+
+    /// Observer latitude in degrees, for the planetary hours
+    #[clap(long, allow_negative_numbers = true, requires = "longitude", value_parser = parse_latitude)]
+    latitude: Option<f64>,
+
+    /// Observer longitude in degrees, east positive
+    #[clap(long, allow_negative_numbers = true, requires = "latitude", value_parser = parse_longitude)]
+    longitude: Option<f64>,
+}
+
+fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+        .ok_or_else(|| format!("'{s}' is neither an RFC 3339 timestamp nor a YYYY-MM-DD date"))
+}
+
+fn parse_latitude(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if (-90.0..=90.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{value} is not a latitude (-90 to 90)"))
+    }
+}
+
+fn parse_longitude(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if (-180.0..=180.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{value} is not a longitude (-180 to 180)"))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PositionReport {
+    planet: &'static str,
+    sign: &'static str,
+    element: &'static str,
+    longitude: f64,
+    degree_in_sign: f64,
+    retrograde: bool,
+    moon_phase: Option<&'static str>,
+}
+
+impl PositionReport {
+    fn new(pos: &PlanetaryPosition) -> Self {
+        Self {
+            planet: pos.planet.name(),
+            sign: pos.sign.name(),
+            element: pos.sign.element().name(),
+            longitude: pos.longitude,
+            degree_in_sign: pos.degree_in_sign,
+            retrograde: pos.retrograde,
+            moon_phase: pos.moon_phase.map(MoonPhase::name),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AspectReport {
+    first: &'static str,
+    aspect: &'static str,
+    second: &'static str,
+    orb: f64,
+    strength: f64,
+}
+
+impl AspectReport {
+    fn new(aspect: &PlanetaryAspect) -> Self {
+        Self {
+            first: aspect.first.name(),
+            aspect: aspect.kind.name(),
+            second: aspect.second.name(),
+            orb: aspect.orb,
+            strength: aspect.strength,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HourReport {
+    ruler: &'static str,
+    start: String,
+    end: String,
+    night: bool,
+}
+
+impl HourReport {
+    fn new(hour: &PlanetaryHour) -> Self {
+        Self {
+            ruler: hour.ruler.name(),
+            start: hour.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            end: hour.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            night: hour.night,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ForecastReport {
+    time: String,
+    priorities: Vec<(&'static str, u32)>,
+    retrograde: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct SpellReport {
+    planet: &'static str,
+    stations_retrograde: String,
+    stations_direct: String,
+}
+
+/// Everything the report says, in the form JSON output takes
+#[derive(Debug, Serialize)]
+struct Report {
+    date: String,
+    positions: Vec<PositionReport>,
+    aspects: Vec<AspectReport>,
+    weather: String,
+    planetary_hours: Option<Vec<HourReport>>,
+    forecast: Vec<ForecastReport>,
+    retrograde_calendar: Option<Vec<SpellReport>>,
+}
+
+impl Report {
+    fn new(cli: &Cli, date: DateTime<Utc>) -> Result<Self, AstroError> {
+        let mut astro = AstrologicalScheduler::new(0);
+        if let (Some(latitude), Some(longitude)) = (cli.latitude, cli.longitude) {
+            astro = astro.with_location(latitude, longitude);
+        }
+        let positions = astro.positions(date)?;
+
+        let mut calculator = AspectCalculator::new(positions.clone());
+        let mut aspects: Vec<_> = calculator.get_aspects(DEFAULT_ASPECT_ORB).iter().map(AspectReport::new).collect();
+        aspects.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+
+        let planetary_hours = match (cli.latitude, cli.longitude) {
+            (Some(latitude), Some(longitude)) => {
+                Some(planetary_hours(date.date_naive(), latitude, longitude)?.iter().map(HourReport::new).collect())
+            }
+            _ => None,
+        };
+
+        let forecast = astro
+            .forecast(date, cli.forecast_hours)?
+            .iter()
+            .map(|hour| ForecastReport {
+                time: hour.time.to_rfc3339(),
+                priorities: hour.priorities.iter().map(|&(task_type, priority)| (task_type.name(), priority)).collect(),
+                retrograde: hour.retrograde.iter().map(|planet| planet.name()).collect(),
+            })
+            .collect();
+
+        let retrograde_calendar = cli.retrograde_calendar.then(|| {
+            let jd = astrology::planets::julian_day(date).unwrap_or_default();
+            retrograde_calendar(&AstroEphemeris, jd, CALENDAR_DAYS)
+                .into_iter()
+                .filter_map(|(planet, start, end)| {
+                    Some(SpellReport {
+                        planet: planet.name(),
+                        stations_retrograde: jd_to_datetime(start)?.format("%Y-%m-%d").to_string(),
+                        stations_direct: jd_to_datetime(end)?.format("%Y-%m-%d").to_string(),
+                    })
+                })
+                .collect()
+        });
+
+        Ok(Self {
+            date: date.to_rfc3339(),
+            positions: positions.iter().map(PositionReport::new).collect(),
+            aspects,
+            weather: astro.get_cosmic_weather(date)?,
+            planetary_hours,
+            forecast,
+            retrograde_calendar,
+        })
+    }
+
+    fn render_text(&self) -> String {
+        let mut text = format!("🔭 HOROSCOPE REPORT for {}\n\n{}\n", self.date, self.weather.trim_end());
+
+        text.push_str("\n✨ ASPECTS\n");
+        if self.aspects.is_empty() {
+            text.push_str("  None within orb\n");
+        }
+        for aspect in &self.aspects {
+            let _ = writeln!(
+                text,
+                "  {:<8} {:<11} {:<8} orb {:.1}° ({:.0}%)",
+                aspect.first,
+                aspect.aspect,
+                aspect.second,
+                aspect.orb,
+                aspect.strength * 100.0
+            );
+        }
+
+        if let Some(hours) = &self.planetary_hours {
+            text.push_str("\n🕐 PLANETARY HOURS\n");
+            for hour in hours {
+                let _ = writeln!(
+                    text,
+                    "  {} {:<8} {} - {}",
+                    if hour.night { "🌙" } else { "☀️" },
+                    hour.ruler,
+                    hour.start,
+                    hour.end
+                );
+            }
+        }
+
+        if let Some(first) = self.forecast.first() {
+            let _ = write!(text, "\n🔮 FORECAST (next {}h)\n{:<26}", self.forecast.len(), "Hour (UTC)");
+            for (task_type, _) in &first.priorities {
+                let _ = write!(text, " {task_type:>14}");
+            }
+            text.push_str("  Retrograde\n");
+            for hour in &self.forecast {
+                let _ = write!(text, "{:<26}", hour.time);
+                for (_, priority) in &hour.priorities {
+                    let _ = write!(text, " {priority:>14}");
+                }
+                let retrograde = if hour.retrograde.is_empty() { "-".to_string() } else { hour.retrograde.join(", ") };
+                let _ = writeln!(text, "  {retrograde}");
+            }
+        }
+
+        if let Some(spells) = &self.retrograde_calendar {
+            let _ = writeln!(text, "\n℞ RETROGRADE CALENDAR (next {CALENDAR_DAYS} days)");
+            if spells.is_empty() {
+                text.push_str("  Every planet stays direct\n");
+            }
+            for spell in spells {
+                let _ = writeln!(text, "  {:<8} {} → {}", spell.planet, spell.stations_retrograde, spell.stations_direct);
+            }
+        }
+        text
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let date = cli.date.unwrap_or_else(Utc::now);
+
+    match Report::new(&cli, date) {
+        Ok(report) => {
+            match cli.output_format {
+                OutputFormat::Text => print!("{}", report.render_text()),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default()),
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("❌ The stars are silent: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/* SPDX-License-Identifier: GPL-2.0 */
+/*
+ * scx_horoscope: syscall hooks
+ *
+ * A kprobe attached to the entry point of each syscall named with
+ * --prio-boost-system-calls marks the calling task in @syscall_boosts. The
+ * user-space scheduler boosts a marked task at its next dispatch and clears
+ * the mark, so every boost lasts a single quantum.
+ *
+ * This object is loaded next to the rustland backend (main.bpf.c), which
+ * scx_rustland_core regenerates on every build and can't carry it.
+ */
+#include <scx/common.bpf.h>
+
+char _license[] SEC("license") = "GPL";
+
+/* Tasks marked at once; the least recently marked make way beyond this */
+#define MAX_BOOSTED_TASKS 8192
+
+/* The scheduler's own syscalls would only ever boost itself */
+const volatile u32 usersched_pid;
+
+struct {
+	__uint(type, BPF_MAP_TYPE_LRU_HASH);
+	__uint(max_entries, MAX_BOOSTED_TASKS);
+	__type(key, u32);	/* pid (thread id) */
+	__type(value, u8);
+} syscall_boosts SEC(".maps");
+
+SEC("kprobe")
+int horoscope_syscall_enter(struct pt_regs *ctx)
+{
+	u64 pid_tgid = bpf_get_current_pid_tgid();
+	u32 pid = (u32)pid_tgid;
+	u8 marked = 1;
+
+	if ((u32)(pid_tgid >> 32) == usersched_pid)
+		return 0;
+
+	bpf_map_update_elem(&syscall_boosts, &pid, &marked, BPF_ANY);
+	return 0;
+}
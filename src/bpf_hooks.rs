@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Syscall priority boosts
+//
+// Some syscalls say more about what a task is doing right now than its name
+// ever will. With --prio-boost-system-calls, a small BPF object
+// (src/bpf/hooks.bpf.c) puts a kprobe on the entry point of each named
+// syscall, and a task making one of those calls is marked in a BPF hash map.
+// At its next dispatch the scheduler raises its priority by
+// --syscall-boost-factor and clears the mark: the boost lasts one quantum.
+//
+// Only syscalls the running kernel actually has may be named; each one is
+// resolved to its entry symbol in /proc/kallsyms before anything is loaded.
+
+use std::collections::HashSet;
+use std::mem::MaybeUninit;
+
+use anyhow::{Context, Result};
+use libbpf_rs::skel::{OpenSkel, SkelBuilder};
+use libbpf_rs::{Link, MapCore, OpenObject};
+
+use crate::bpf_hooks_skel::{HooksSkel, HooksSkelBuilder};
+
+/// Prefixes of syscall entry points across architectures, most specific first
+const SYSCALL_PREFIXES: &[&str] = &["__x64_sys_", "__arm64_sys_", "__riscv_sys_", "__s390x_sys_", "__se_sys_", "sys_"];
+
+/// The kernel symbol a kprobe on syscall `name` attaches to
+///
+/// `kallsyms` is the text of /proc/kallsyms. A name that isn't a syscall of
+/// this kernel is rejected.
+pub fn syscall_symbol(name: &str, kallsyms: &str) -> Result<String, String> {
+    let valid = !name.is_empty() && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if !valid {
+        return Err(format!("'{name}' is not a syscall name"));
+    }
+    let symbols: HashSet<&str> = kallsyms.lines().filter_map(|line| line.split_whitespace().nth(2)).collect();
+    SYSCALL_PREFIXES
+        .iter()
+        .map(|prefix| format!("{prefix}{name}"))
+        .find(|symbol| symbols.contains(symbol.as_str()))
+        .ok_or_else(|| format!("unknown syscall '{name}': this kernel has no entry point for it"))
+}
+
+/// Entry symbols for every name in `names`, or the first name that isn't a syscall
+pub fn syscall_symbols(names: &[String], kallsyms: &str) -> Result<Vec<String>, String> {
+    names.iter().map(|name| syscall_symbol(name, kallsyms)).collect()
+}
+
+/// Priority of a task boosted by one of the hooked syscalls
+pub fn boosted_priority(priority: u32, factor: f64) -> u32 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let boosted = (f64::from(priority) * factor) as u32;
+    boosted.max(1)
+}
+
+/// The loaded hooks, and the tasks they have marked
+pub struct SyscallBoosts<'obj> {
+    skel: HooksSkel<'obj>,
+    _links: Vec<Link>,  // The kprobes stay attached as long as these live
+    applied: u64,
+}
+
+impl<'obj> SyscallBoosts<'obj> {
+    /// Load the hooks and attach a kprobe to each entry symbol
+    pub fn attach(open_object: &'obj mut MaybeUninit<OpenObject>, symbols: &[String]) -> Result<Self> {
+        let skel_builder = HooksSkelBuilder::default();
+        let mut open_skel = skel_builder.open(open_object).context("Failed to open the syscall hooks")?;
+        if let Some(rodata) = open_skel.maps.rodata_data.as_mut() {
+            rodata.usersched_pid = std::process::id();
+        }
+        let skel = open_skel.load().context("Failed to load the syscall hooks")?;
+
+        let links = symbols
+            .iter()
+            .map(|symbol| {
+                skel.progs
+                    .horoscope_syscall_enter
+                    .attach_kprobe(false, symbol)
+                    .with_context(|| format!("Failed to attach a kprobe to {symbol}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { skel, _links: links, applied: 0 })
+    }
+
+    /// Clear the mark on `pid`, returning whether it had one
+    pub fn take(&mut self, pid: i32) -> bool {
+        // One syscall: a mark looked up is a mark spent
+        let key = pid.to_ne_bytes();
+        if !matches!(self.skel.maps.syscall_boosts.lookup_and_delete(&key), Ok(Some(_))) {
+            return false;
+        }
+        self.applied += 1;
+        true
+    }
+
+    /// Boosts handed out since startup
+    pub fn applied(&self) -> u64 {
+        self.applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KALLSYMS: &str = "\
+0000000000000000 T __x64_sys_sendmsg
+0000000000000000 T __x64_sys_recvmsg
+0000000000000000 T __x64_sys_write
+0000000000000000 t __do_sys_write
+0000000000000000 T __ia32_sys_write
+0000000000000000 T tcp_sendmsg
+";
+
+    #[test]
+    fn test_syscall_symbols() {
+        let names = ["sendmsg".to_string(), "recvmsg".to_string(), "write".to_string()];
+        assert_eq!(
+            syscall_symbols(&names, KALLSYMS).unwrap(),
+            ["__x64_sys_sendmsg", "__x64_sys_recvmsg", "__x64_sys_write"]
+        );
+        assert_eq!(syscall_symbol("write", "0 T __arm64_sys_write\n").unwrap(), "__arm64_sys_write");
+    }
+
+    #[test]
+    fn test_rejects_what_the_kernel_lacks() {
+        let err = syscall_symbols(&["sendmsg".to_string(), "frobnicate".to_string()], KALLSYMS).unwrap_err();
+        assert_eq!(err, "unknown syscall 'frobnicate': this kernel has no entry point for it");
+
+        // Only syscall entry points count, and names can't smuggle in other symbols
+        assert!(syscall_symbol("tcp_sendmsg", KALLSYMS).is_err());
+        assert!(syscall_symbol("do_sys_write", KALLSYMS).is_err());
+        assert!(syscall_symbol("", KALLSYMS).is_err());
+        assert_eq!(syscall_symbol("Write", KALLSYMS).unwrap_err(), "'Write' is not a syscall name");
+    }
+
+    #[test]
+    fn test_boosted_priority() {
+        assert_eq!(boosted_priority(500, 1.1), 550);
+        assert_eq!(boosted_priority(0, 1.1), 1);
+    }
+}
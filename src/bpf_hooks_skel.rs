@@ -0,0 +1,3 @@
+// SPDX-License-Identifier: GPL-2.0
+
+include!(concat!(env!("OUT_DIR"), "/hooks_skel.rs"));
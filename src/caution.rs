@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// The stars advise caution
+//
+// Under a tense sky, squares and oppositions everywhere, nothing should be
+// pushed. Once the chart's tension score reaches the threshold the scheduler
+// turns cautious: every slice is cut to three quarters and batches are
+// dispatched in the order they were queued, without sorting by the stars.
+// The caution only lifts once the tension has eased ten points below the
+// threshold, so a chart hovering around it doesn't flip the mode at every
+// refresh.
+
+/// How far below the threshold the tension must fall before caution lifts
+pub const CAUTION_HYSTERESIS: u32 = 10;
+
+/// Share of its normal slice a task gets while the stars advise caution
+pub const CAUTION_SLICE_SCALE: f64 = 0.75;
+
+/// Turns cautious under a tense chart, with hysteresis
+#[derive(Debug, Clone)]
+pub struct Caution {
+    enter: u32,  // Tension score that turns cautious
+    exit: u32,   // Score the tension must fall to before caution lifts
+    active: bool,
+    spells: u64,
+}
+
+impl Caution {
+    pub fn new(threshold: u32) -> Self {
+        Self { enter: threshold, exit: threshold.saturating_sub(CAUTION_HYSTERESIS), active: false, spells: 0 }
+    }
+
+    /// Score that lifts the caution
+    pub fn exit(&self) -> u32 {
+        self.exit
+    }
+
+    /// Take a freshly cast chart's tension score into account, returning the new state on a switch
+    pub fn update(&mut self, score: u32) -> Option<bool> {
+        match self.active {
+            false if score >= self.enter => {
+                self.active = true;
+                self.spells += 1;
+                Some(true)
+            }
+            true if score <= self.exit => {
+                self.active = false;
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// How many times caution has been advised
+    pub fn spells(&self) -> u64 {
+        self.spells
+    }
+
+    /// A slice cut to `CAUTION_SLICE_SCALE` while active, never below `min_ns`
+    pub fn apply(&self, slice_ns: u64, min_ns: u64) -> u64 {
+        if !self.active {
+            return slice_ns;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+        let scaled = (slice_ns as f64 * CAUTION_SLICE_SCALE) as u64;
+        scaled.max(min_ns.min(slice_ns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caution_hysteresis() {
+        let mut caution = Caution::new(60);
+        assert_eq!(caution.exit(), 50);
+
+        // (score, expected switch, active afterwards)
+        let sequence = [
+            (30, None, false),
+            (59, None, false),
+            (60, Some(true), true),
+            (75, None, true),
+            (55, None, true),
+            (51, None, true),
+            (50, Some(false), false),
+            (55, None, false),
+            (90, Some(true), true),
+            (0, Some(false), false),
+        ];
+        for (score, switch, active) in sequence {
+            assert_eq!(caution.update(score), switch, "score {score}");
+            assert_eq!(caution.is_active(), active, "score {score}");
+        }
+        assert_eq!(caution.spells(), 2);
+
+        // A threshold below the hysteresis lifts only at zero
+        assert_eq!(Caution::new(5).exit(), 0);
+    }
+
+    #[test]
+    fn test_caution_slices() {
+        let mut caution = Caution::new(60);
+        assert_eq!(caution.apply(20_000_000, 1_000_000), 20_000_000);
+        caution.update(80);
+        assert_eq!(caution.apply(20_000_000, 1_000_000), 15_000_000);
+        assert_eq!(caution.apply(1_200_000, 1_000_000), 1_000_000);
+        // Slices already under the minimum are not lengthened
+        assert_eq!(caution.apply(800_000, 1_000_000), 800_000);
+    }
+}
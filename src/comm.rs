@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Task names without the heap
+//
+// The BPF side hands us each task's name as a NUL-padded C array. Turning
+// that into a String cost two allocations per dispatched task; a `Comm`
+// keeps the name in a fixed buffer of its own and lends it out as a `&str`.
+
+use std::fmt;
+
+/// Longest task name the kernel keeps, NUL included
+pub const TASK_COMM_LEN: usize = 16;
+
+/// A task's command name, held inline
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Comm {
+    bytes: [u8; TASK_COMM_LEN],
+    len: usize,
+}
+
+impl Comm {
+    /// The name up to the first NUL, with any byte that isn't valid UTF-8 shown as '?'
+    pub fn from_raw(raw: &[libc::c_char]) -> Self {
+        let mut bytes = [0; TASK_COMM_LEN];
+        let mut len = 0;
+        for (byte, &c) in bytes.iter_mut().zip(raw.iter().take_while(|&&c| c != 0)) {
+            #[allow(clippy::cast_sign_loss)]
+            let c = c as u8;
+            *byte = c;
+            len += 1;
+        }
+        if std::str::from_utf8(&bytes[..len]).is_err() {
+            for byte in &mut bytes[..len] {
+                if !byte.is_ascii() {
+                    *byte = b'?';
+                }
+            }
+        }
+        Self { bytes, len }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // from_raw leaves only valid UTF-8 behind
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Comm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Comm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A name as the BPF side would send it
+    #[allow(clippy::cast_possible_wrap)]
+    fn raw(name: &[u8]) -> [libc::c_char; TASK_COMM_LEN] {
+        let mut raw = [0; TASK_COMM_LEN];
+        for (c, &byte) in raw.iter_mut().zip(name) {
+            *c = byte as libc::c_char;
+        }
+        raw
+    }
+
+    #[test]
+    fn test_comm_from_raw() {
+        assert_eq!(Comm::from_raw(&raw(b"rustc")).as_str(), "rustc");
+        assert_eq!(Comm::from_raw(&raw(b"kworker/0:1H-kb")).as_str(), "kworker/0:1H-kb");
+        assert_eq!(Comm::from_raw(&raw(b"")).as_str(), "");
+
+        // A full buffer with no NUL at all
+        let full = raw(b"0123456789abcdef");
+        assert_eq!(Comm::from_raw(&full).as_str(), "0123456789abcdef");
+
+        // Valid UTF-8 survives; a name cut off mid-character doesn't poison the rest
+        assert_eq!(Comm::from_raw(&raw("café".as_bytes())).as_str(), "café");
+        assert_eq!(Comm::from_raw(&raw(&"café".as_bytes()[..4])).as_str(), "caf?");
+        assert_eq!(format!("{}", Comm::from_raw(&raw(b"vim"))), "vim");
+    }
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Kernel compatibility checks
+//
+// sched_ext only exists on Linux 6.12 and later, built with CONFIG_SCHED_CLASS_EXT.
+// Checking up front turns a baffling libbpf failure into a sentence the user
+// can act on.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Oldest kernel with `sched_ext`
+pub const MIN_KERNEL_VERSION: (u32, u32, u32) = (6, 12, 0);
+
+/// Longest name the scheduler may register with the kernel as, in bytes
+pub const MAX_SCHEDULER_NAME_LEN: usize = 16;
+
+const PROC_VERSION: &str = "/proc/version";
+const SCHED_EXT_STATE: &str = "/sys/kernel/sched_ext/state";
+
+/// Why the scheduler cannot run on this kernel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatError {
+    /// A kernel interface could not be read
+    Unreadable { path: String, reason: String },
+    /// The kernel version string could not be parsed
+    UnparsableVersion(String),
+    /// The kernel predates `sched_ext`
+    KernelTooOld { found: (u32, u32, u32), required: (u32, u32, u32) },
+    /// The kernel was built without `sched_ext`
+    NoSchedExt,
+}
+
+impl fmt::Display for CompatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatError::Unreadable { path, reason } => write!(f, "cannot read {path}: {reason}"),
+            CompatError::UnparsableVersion(version) => write!(f, "cannot parse kernel version from '{version}'"),
+            CompatError::KernelTooOld { found, required } => write!(
+                f,
+                "This scheduler requires Linux {}.{} or later; found {}.{}",
+                required.0, required.1, found.0, found.1
+            ),
+            CompatError::NoSchedExt => {
+                write!(f, "This kernel has no sched_ext support (built without CONFIG_SCHED_CLASS_EXT?)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompatError {}
+
+/// Parse `major.minor.patch` out of a `/proc/version` or `uname -r` string
+///
+/// Missing components count as 0 and suffixes like `-rc1` or `-45-generic`
+/// are ignored.
+pub fn parse_kernel_version(version: &str) -> Result<(u32, u32, u32), CompatError> {
+    let unparsable = || CompatError::UnparsableVersion(version.trim().to_string());
+    let release = version
+        .trim()
+        .strip_prefix("Linux version ")
+        .unwrap_or(version.trim())
+        .split_whitespace()
+        .next()
+        .ok_or_else(unparsable)?;
+
+    let mut numbers = release.split('.').map(|part| {
+        let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<u32>().ok()
+    });
+    let major = numbers.next().flatten().ok_or_else(unparsable)?;
+    let minor = numbers.next().flatten().ok_or_else(unparsable)?;
+    let patch = numbers.next().flatten().unwrap_or(0);
+    Ok((major, minor, patch))
+}
+
+fn read(path: &Path) -> Result<String, CompatError> {
+    fs::read_to_string(path).map_err(|e| CompatError::Unreadable {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// The running kernel's version, if it is new enough for `sched_ext`
+pub fn check_kernel_version() -> Result<(u32, u32, u32), CompatError> {
+    let found = parse_kernel_version(&read(Path::new(PROC_VERSION))?)?;
+    require_version(found, MIN_KERNEL_VERSION)
+}
+
+fn require_version(found: (u32, u32, u32), required: (u32, u32, u32)) -> Result<(u32, u32, u32), CompatError> {
+    if found < required {
+        return Err(CompatError::KernelTooOld { found, required });
+    }
+    Ok(found)
+}
+
+/// Check that the kernel exposes `sched_ext`
+pub fn check_bpf_sched_ext_support() -> Result<(), CompatError> {
+    sched_ext_state(Path::new(SCHED_EXT_STATE)).map(|_| ())
+}
+
+/// Current `sched_ext` state (`disabled`, `enabled`, ...)
+fn sched_ext_state(path: &Path) -> Result<String, CompatError> {
+    if !path.exists() {
+        return Err(CompatError::NoSchedExt);
+    }
+    Ok(read(path)?.trim().to_string())
+}
+
+/// Check a name to register the scheduler with the kernel as
+///
+/// It shows up in `/sys/kernel/sched_ext/root/ops` and the kernel log, so it
+/// is kept to letters, digits and underscores.
+pub fn validate_scheduler_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("--scheduler-name must not be empty".to_string());
+    }
+    if name.len() > MAX_SCHEDULER_NAME_LEN {
+        return Err(format!(
+            "--scheduler-name '{name}' is {} bytes long; the most is {MAX_SCHEDULER_NAME_LEN}",
+            name.len()
+        ));
+    }
+    if let Some(c) = name.chars().find(|&c| !c.is_ascii_alphanumeric() && c != '_') {
+        return Err(format!("--scheduler-name '{name}' contains '{c}': only letters, digits and underscores are allowed"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kernel_version() {
+        let cases = [
+            ("Linux version 6.8.0-45-generic (buildd@lcy02-amd64-075) (gcc 13.2.0) #45-Ubuntu SMP", (6, 8, 0)),
+            ("Linux version 6.12.1-arch1-1 (linux@archlinux) #1 SMP PREEMPT_DYNAMIC", (6, 12, 1)),
+            ("Linux version 6.13.0-rc3 (root@build)", (6, 13, 0)),
+            ("6.14.2-300.fc42.x86_64\n", (6, 14, 2)),
+            ("6.12", (6, 12, 0)),
+            ("5.15.167.4-microsoft-standard-WSL2", (5, 15, 167)),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(parse_kernel_version(version), Ok(expected), "{version}");
+        }
+
+        for garbage in ["", "Linux version", "Linux version banana", "6", "six.twelve"] {
+            assert!(matches!(parse_kernel_version(garbage), Err(CompatError::UnparsableVersion(_))), "{garbage}");
+        }
+    }
+
+    #[test]
+    fn test_require_version() {
+        assert_eq!(require_version((6, 12, 0), MIN_KERNEL_VERSION), Ok((6, 12, 0)));
+        assert_eq!(require_version((7, 0, 0), MIN_KERNEL_VERSION), Ok((7, 0, 0)));
+
+        let err = require_version((6, 8, 12), MIN_KERNEL_VERSION).unwrap_err();
+        assert_eq!(err.to_string(), "This scheduler requires Linux 6.12 or later; found 6.8");
+    }
+
+    #[test]
+    fn test_sched_ext_state() {
+        let path = std::env::temp_dir().join(format!("horoscope_sched_ext_state_{}", std::process::id()));
+        assert_eq!(sched_ext_state(&path), Err(CompatError::NoSchedExt));
+
+        fs::write(&path, "disabled\n").unwrap();
+        assert_eq!(sched_ext_state(&path).as_deref(), Ok("disabled"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_validate_scheduler_name() {
+        for name in ["horoscope", "astral_compute", "h0r0sc0pe_v2", "abcdefghijklmnop"] {
+            assert_eq!(validate_scheduler_name(name), Ok(()), "{name}");
+        }
+
+        assert_eq!(validate_scheduler_name(""), Err("--scheduler-name must not be empty".to_string()));
+        let err = validate_scheduler_name("abcdefghijklmnopq").unwrap_err();
+        assert!(err.contains("17 bytes"), "{err}");
+        // Sixteen characters, but not sixteen bytes
+        assert!(validate_scheduler_name("horoscope_♈♈♈").is_err());
+
+        for name in ["astral-compute", "horo scope", "horoscope!", "../horoscope"] {
+            assert!(validate_scheduler_name(name).unwrap_err().contains("only letters"), "{name}");
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Process affinity placement
+//
+// With --enable-process-affinity, each task is weighed against the one
+// dispatched just before it: the midpoints of their two natal charts (cast
+// for when each process started) form a composite chart, scored from 0.0 to
+// 2.0. A pair that scores high enough is kept close, with the second task
+// sent to an idle CPU next to the first one's, where they share caches.
+
+/// Composite score at which two processes are worth keeping on neighboring CPUs
+pub const AFFINITY_THRESHOLD: f64 = 1.5;
+
+/// An idle CPU next to `partner`, lower first
+pub fn neighbor_cpu(partner: i32, idle: &[bool]) -> Option<i32> {
+    if partner < 0 {
+        return None;
+    }
+    [partner - 1, partner + 1]
+        .into_iter()
+        .find(|&cpu| usize::try_from(cpu).ok().and_then(|cpu| idle.get(cpu).copied()).unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbor_cpu() {
+        let idle = [true, false, true, true];
+        assert_eq!(neighbor_cpu(1, &idle), Some(0));
+        assert_eq!(neighbor_cpu(2, &idle), Some(3));
+        assert_eq!(neighbor_cpu(0, &idle), None);
+        // The last CPU has no neighbor above it, and a partner on no CPU in particular has none at all
+        assert_eq!(neighbor_cpu(3, &[true, true, false, true]), None);
+        assert_eq!(neighbor_cpu(-1, &idle), None);
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Where a task goes when select_cpu finds nowhere
+//
+// The kernel's pick fails now and then: every allowed CPU busy, or the task
+// mid-migration. The task then runs on whichever CPU frees up first, unless
+// --cpu-fallback asks for its previous CPU or for an idle one we know of.
+// Element pools and the CPU zodiac narrow down the CPUs a fallback may
+// choose; the policy decides the order they are tried in.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// What to do when `select_cpu` finds no CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CpuFallback {
+    Prev,       // Go back to the CPU the task last ran on
+    Any,        // Run on any CPU
+    IdleFirst,  // An idle CPU if we know of one, the previous one first
+}
+
+/// Where a fallback sent a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackOutcome {
+    Prev,
+    Idle,
+    Any,
+}
+
+/// How often each outcome came up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FallbackStats {
+    pub prev: u64,
+    pub idle: u64,
+    pub any: u64,
+}
+
+impl FallbackStats {
+    pub fn record(&mut self, outcome: FallbackOutcome) {
+        match outcome {
+            FallbackOutcome::Prev => self.prev += 1,
+            FallbackOutcome::Idle => self.idle += 1,
+            FallbackOutcome::Any => self.any += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.prev + self.idle + self.any
+    }
+}
+
+impl CpuFallback {
+    pub fn name(self) -> &'static str {
+        match self {
+            CpuFallback::Prev => "prev",
+            CpuFallback::Any => "any",
+            CpuFallback::IdleFirst => "idle-first",
+        }
+    }
+
+    /// The CPUs to try for a task last seen on `prev`, best first
+    ///
+    /// Only CPUs `allowed` says yes to are candidates; `idle` is what the idle
+    /// tracker believes, and may be empty without one. Nothing left means any
+    /// CPU will do.
+    pub fn candidates<'a, F>(self, prev: i32, allowed: F, idle: &'a [bool]) -> impl Iterator<Item = i32> + 'a
+    where
+        F: Fn(usize) -> bool + Copy + 'a,
+    {
+        let is_idle = |cpu: usize| idle.get(cpu).copied().unwrap_or(false);
+        let prev = usize::try_from(prev).ok().filter(|&cpu| allowed(cpu));
+        let first = match self {
+            CpuFallback::Prev => prev,
+            CpuFallback::Any => None,
+            CpuFallback::IdleFirst => prev.filter(|&cpu| is_idle(cpu)),
+        };
+        let scan = self == CpuFallback::IdleFirst;
+        let others = (0..idle.len()).filter(move |&cpu| scan && is_idle(cpu) && Some(cpu) != first && allowed(cpu));
+        first.into_iter().chain(others).filter_map(|cpu| i32::try_from(cpu).ok())
+    }
+
+    /// The first candidate, `None` for any CPU, and how it was arrived at
+    pub fn pick<F>(self, prev: i32, allowed: F, idle: &[bool]) -> (Option<i32>, FallbackOutcome)
+    where
+        F: Fn(usize) -> bool + Copy,
+    {
+        match self.candidates(prev, allowed, idle).next() {
+            Some(cpu) if cpu == prev => (Some(cpu), FallbackOutcome::Prev),
+            Some(cpu) => (Some(cpu), FallbackOutcome::Idle),
+            None => (None, FallbackOutcome::Any),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(policy: CpuFallback, prev: i32, allowed: &[bool], idle: &[bool]) -> Vec<i32> {
+        policy.candidates(prev, |cpu| allowed.get(cpu).copied().unwrap_or(false), idle).collect()
+    }
+
+    #[test]
+    fn test_candidate_order() {
+        let everywhere = [true; 6];
+        let idle = [false, true, false, true, true, false];
+
+        assert!(order(CpuFallback::Any, 2, &everywhere, &idle).is_empty());
+        assert_eq!(order(CpuFallback::Prev, 2, &everywhere, &idle), vec![2]);
+        // Busy or not: prev only knows one place
+        assert_eq!(order(CpuFallback::Prev, 5, &everywhere, &[]), vec![5]);
+        assert!(order(CpuFallback::Prev, -1, &everywhere, &idle).is_empty());
+
+        // Idle CPUs in order, with the previous one first when it is idle itself
+        assert_eq!(order(CpuFallback::IdleFirst, 2, &everywhere, &idle), vec![1, 3, 4]);
+        assert_eq!(order(CpuFallback::IdleFirst, 3, &everywhere, &idle), vec![3, 1, 4]);
+        assert!(order(CpuFallback::IdleFirst, 3, &everywhere, &[false; 6]).is_empty());
+        assert!(order(CpuFallback::IdleFirst, 3, &everywhere, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_candidates_stay_in_the_allowed_set() {
+        // A pool of CPUs 3-5
+        let pool = [false, false, false, true, true, true];
+        let idle = [true, true, false, false, true, true];
+
+        assert!(order(CpuFallback::Prev, 1, &pool, &idle).is_empty());
+        assert_eq!(order(CpuFallback::Prev, 3, &pool, &idle), vec![3]);
+        assert_eq!(order(CpuFallback::IdleFirst, 1, &pool, &idle), vec![4, 5]);
+        assert_eq!(order(CpuFallback::IdleFirst, 5, &pool, &idle), vec![5, 4]);
+        assert!(order(CpuFallback::Any, 4, &pool, &idle).is_empty());
+    }
+
+    #[test]
+    fn test_pick_outcomes() {
+        let everywhere = |_| true;
+        let idle = [false, true, false];
+        let mut stats = FallbackStats::default();
+
+        let picks = [
+            (CpuFallback::Prev.pick(2, everywhere, &idle), (Some(2), FallbackOutcome::Prev)),
+            (CpuFallback::IdleFirst.pick(1, everywhere, &idle), (Some(1), FallbackOutcome::Prev)),
+            (CpuFallback::IdleFirst.pick(2, everywhere, &idle), (Some(1), FallbackOutcome::Idle)),
+            (CpuFallback::IdleFirst.pick(2, everywhere, &[false; 3]), (None, FallbackOutcome::Any)),
+            (CpuFallback::Any.pick(2, everywhere, &idle), (None, FallbackOutcome::Any)),
+        ];
+        for (picked, expected) in picks {
+            assert_eq!(picked, expected);
+            stats.record(picked.1);
+        }
+        assert_eq!(stats, FallbackStats { prev: 2, idle: 1, any: 2 });
+        assert_eq!(stats.total(), 5);
+    }
+}
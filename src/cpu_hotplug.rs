@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// CPUs coming and going
+//
+// A CPU taken offline while we run leaves element pools, idle masks, NUMA
+// homes and the last affinity partner pointing at a CPU that will never run
+// anything again. The online list is polled from the housekeeping loop; when
+// it changes, offline CPUs are masked out of everything that places tasks,
+// and records naming them are dropped. A dispatch that still targets one is
+// sent to any CPU instead.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::stats::StatsTicker;
+
+const SYSFS_CPU_ONLINE: &str = "/sys/devices/system/cpu/online";
+
+/// How often the online list is read again
+pub const HOTPLUG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Which CPUs went offline and which came back
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HotplugChange {
+    pub offline: Vec<usize>,
+    pub online: Vec<usize>,
+}
+
+/// Parse an online list such as `0-3,6` into a mask over `nr_cpus` CPUs
+///
+/// Parsed by hand rather than as a `Cpumask`, which is sized to the CPUs of
+/// the machine it runs on.
+pub fn parse_online(cpulist: &str, nr_cpus: usize) -> Result<Vec<bool>, String> {
+    let invalid = || format!("invalid CPU list '{}'", cpulist.trim());
+    let mut online = vec![false; nr_cpus];
+    for range in cpulist.trim().split(',').filter(|range| !range.is_empty()) {
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let first: usize = first.parse().map_err(|_| invalid())?;
+        let last: usize = last.parse().map_err(|_| invalid())?;
+        for up in online.iter_mut().take(last.saturating_add(1)).skip(first) {
+            *up = true;
+        }
+    }
+    Ok(online)
+}
+
+/// The CPUs currently online
+pub struct OnlineCpus {
+    path: PathBuf,  // Normally /sys/devices/system/cpu/online
+    poll: StatsTicker,
+    online: Vec<bool>,  // Indexed by CPU
+}
+
+impl OnlineCpus {
+    /// Follow the online list at `path`; every CPU counts as online until it can be read
+    pub fn new(path: impl Into<PathBuf>, nr_cpus: usize, poll_interval: Duration, start: Instant) -> Self {
+        let mut cpus = Self { path: path.into(), poll: StatsTicker::new(poll_interval, start), online: vec![true; nr_cpus] };
+        if let Ok(online) = cpus.read() {
+            cpus.online = online;
+        }
+        cpus
+    }
+
+    pub fn from_sysfs(nr_cpus: usize, start: Instant) -> Self {
+        Self::new(SYSFS_CPU_ONLINE, nr_cpus, HOTPLUG_POLL_INTERVAL, start)
+    }
+
+    fn read(&self) -> Result<Vec<bool>, String> {
+        let cpulist = fs::read_to_string(&self.path).map_err(|e| format!("{}: {e}", self.path.display()))?;
+        parse_online(&cpulist, self.online.len())
+    }
+
+    /// Online mask, indexed by CPU
+    pub fn mask(&self) -> &[bool] {
+        &self.online
+    }
+
+    /// False for CPUs known to be offline; negative CPUs ("any") are not judged
+    pub fn is_online(&self, cpu: i32) -> bool {
+        usize::try_from(cpu).map_or(true, |cpu| self.online.get(cpu).copied().unwrap_or(false))
+    }
+
+    /// Once per poll interval, read the online list again and report what changed
+    pub fn poll(&mut self, now: Instant) -> Result<Option<HotplugChange>, String> {
+        if !self.poll.tick(now) {
+            return Ok(None);
+        }
+        let online = self.read()?;
+        Ok(self.update(online))
+    }
+
+    /// Take a new online mask, returning the difference if there is one
+    pub fn update(&mut self, online: Vec<bool>) -> Option<HotplugChange> {
+        let mut change = HotplugChange::default();
+        for (cpu, (&was, &is)) in self.online.iter().zip(&online).enumerate() {
+            match (was, is) {
+                (true, false) => change.offline.push(cpu),
+                (false, true) => change.online.push(cpu),
+                _ => {}
+            }
+        }
+        self.online = online;
+        (change != HotplugChange::default()).then_some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_online() {
+        assert_eq!(parse_online("0-2,5\n", 6).unwrap(), vec![true, true, true, false, false, true]);
+        assert_eq!(parse_online("0", 2).unwrap(), vec![true, false]);
+        assert_eq!(parse_online("", 2).unwrap(), vec![false, false]);
+        assert!(parse_online("zero", 2).is_err());
+        assert!(parse_online("0-", 2).is_err());
+    }
+
+    #[test]
+    fn test_online_set_shrinks_and_grows() {
+        let path = std::env::temp_dir().join(format!("horoscope_online_{}", std::process::id()));
+        fs::write(&path, "0-3\n").unwrap();
+        let start = Instant::now();
+        let mut cpus = OnlineCpus::new(&path, 4, Duration::from_secs(1), start);
+        assert_eq!(cpus.mask(), &[true; 4]);
+
+        // CPUs 1 and 3 go away: noticed at the next poll, not before
+        fs::write(&path, "0,2\n").unwrap();
+        assert_eq!(cpus.poll(start + Duration::from_millis(500)), Ok(None));
+        let change = cpus.poll(start + Duration::from_secs(1)).unwrap().unwrap();
+        assert_eq!(change, HotplugChange { offline: vec![1, 3], online: vec![] });
+        assert!(cpus.is_online(2));
+        assert!(!cpus.is_online(3));
+        assert!(!cpus.is_online(42));
+        assert!(cpus.is_online(-1));
+
+        // Nothing new
+        assert_eq!(cpus.poll(start + Duration::from_secs(2)), Ok(None));
+
+        // CPU 3 returns
+        assert_eq!(cpus.update(vec![true, false, true, true]), Some(HotplugChange { offline: vec![], online: vec![3] }));
+
+        // An unreadable list is reported and changes nothing
+        fs::remove_file(&path).unwrap();
+        assert!(cpus.poll(start + Duration::from_secs(3)).is_err());
+        assert_eq!(cpus.mask(), &[true, false, true, true]);
+    }
+}
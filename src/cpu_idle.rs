@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Userspace view of which CPUs are idle
+//
+// The BPF side doesn't share its idle mask with us, so a CPU counts as busy
+// until the last slice we handed it should have run out. Rough, but good
+// enough to steer placement.
+
+use std::time::{Duration, Instant};
+
+pub struct CpuIdleTracker {
+    busy_until: Vec<Option<Instant>>,
+    online: Vec<bool>,  // Offline CPUs are never idle
+}
+
+impl CpuIdleTracker {
+    pub fn new(nr_cpus: usize) -> Self {
+        Self { busy_until: vec![None; nr_cpus], online: vec![true; nr_cpus] }
+    }
+
+    /// Count only `online` CPUs as ever idle from now on
+    pub fn set_online(&mut self, online: &[bool]) {
+        for (cpu, up) in self.online.iter_mut().enumerate() {
+            *up = online.get(cpu).copied().unwrap_or(false);
+        }
+    }
+
+    /// Remember that a task was sent to `cpu` for `slice`
+    pub fn note_dispatch(&mut self, cpu: i32, slice: Duration, now: Instant) {
+        if let Some(busy) = usize::try_from(cpu).ok().and_then(|cpu| self.busy_until.get_mut(cpu)) {
+            *busy = Some(now + slice);
+        }
+    }
+
+    /// Which CPUs we believe are idle at `now`
+    pub fn idle_mask(&self, now: Instant) -> Vec<bool> {
+        self.busy_until
+            .iter()
+            .zip(&self.online)
+            .map(|(busy, &online)| online && busy.is_none_or(|until| until <= now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_tracking() {
+        let mut tracker = CpuIdleTracker::new(6);
+        let t0 = Instant::now();
+
+        tracker.note_dispatch(0, Duration::from_millis(5), t0);
+        assert!(!tracker.idle_mask(t0)[0]);
+        assert!(tracker.idle_mask(t0)[4]);
+        assert!(tracker.idle_mask(t0 + Duration::from_millis(10))[0]);
+
+        // Out-of-range CPUs are ignored
+        tracker.note_dispatch(42, Duration::from_millis(5), t0);
+        tracker.note_dispatch(-1, Duration::from_millis(5), t0);
+        assert_eq!(tracker.idle_mask(t0).len(), 6);
+    }
+
+    #[test]
+    fn test_offline_cpus_are_never_idle() {
+        let mut tracker = CpuIdleTracker::new(4);
+        let t0 = Instant::now();
+        tracker.set_online(&[true, true, false, true]);
+        assert_eq!(tracker.idle_mask(t0), vec![true, true, false, true]);
+        tracker.set_online(&[true; 4]);
+        assert_eq!(tracker.idle_mask(t0), vec![true; 4]);
+    }
+}
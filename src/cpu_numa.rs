@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// NUMA homes for Jupiter's children
+//
+// Databases and JVMs keep their memory on the node they first ran on, and
+// pay for every access from the other side of the interconnect. A
+// Memory-Heavy task therefore stays on the node it was last dispatched to: an
+// idle CPU there if there is one, otherwise back to its last CPU to wait.
+// Only when its home node has had no idle CPU for longer than the patience
+// does it spill over to wherever the kernel would put it, and that node
+// becomes its new home.
+
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use scx_utils::Cpumask;
+
+/// Memory-Heavy tasks remembered before the homes start over
+pub const NUMA_HOMES_SIZE: usize = 4096;
+
+/// Which node each CPU belongs to
+pub struct NumaTopology {
+    node_of: Vec<Option<usize>>,  // Indexed by CPU
+    nr_nodes: usize,
+}
+
+impl NumaTopology {
+    /// Build from the CPUs of each node, node 0 first
+    pub fn new(nr_cpus: usize, nodes: &[Vec<usize>]) -> Self {
+        let mut node_of = vec![None; nr_cpus];
+        for (node, cpus) in nodes.iter().enumerate() {
+            for &cpu in cpus.iter().filter(|&&cpu| cpu < nr_cpus) {
+                node_of[cpu] = Some(node);
+            }
+        }
+        Self { node_of, nr_nodes: nodes.len() }
+    }
+
+    /// Read each node's CPUs from sysfs; without NUMA there is a single node or none
+    pub fn from_sysfs(nr_cpus: usize) -> Self {
+        let nodes: Vec<Vec<usize>> = (0..)
+            .map_while(|node| fs::read_to_string(format!("/sys/devices/system/node/node{node}/cpulist")).ok())
+            .map(|cpulist| match Cpumask::from_cpulist(cpulist.trim()) {
+                Ok(mask) => (0..nr_cpus).filter(|&cpu| mask.test_cpu(cpu)).collect(),
+                Err(_) => Vec::new(),  // A memory-only node
+            })
+            .collect();
+        Self::new(nr_cpus, &nodes)
+    }
+
+    /// True with more than one node to choose between
+    pub fn is_numa(&self) -> bool {
+        self.nr_nodes > 1
+    }
+
+    pub fn nr_nodes(&self) -> usize {
+        self.nr_nodes
+    }
+
+    pub fn node_of(&self, cpu: i32) -> Option<usize> {
+        usize::try_from(cpu).ok().and_then(|cpu| self.node_of.get(cpu).copied().flatten())
+    }
+}
+
+/// Where a Memory-Heavy task lives
+#[derive(Debug, Clone, Copy)]
+struct Home {
+    node: usize,
+    last_cpu: i32,
+    busy_since: Option<Instant>,  // When the node was first found without an idle CPU, this spell
+}
+
+/// Keeps Memory-Heavy tasks on their home node, and counts dispatches per node
+pub struct NumaPlacement {
+    topology: NumaTopology,
+    patience: Duration,
+    homes: HashMap<i32, Home>,
+    dispatches: Vec<u64>,  // Per node
+    spills: u64,
+}
+
+impl NumaPlacement {
+    pub fn new(topology: NumaTopology, patience: Duration) -> Self {
+        let dispatches = vec![0; topology.nr_nodes()];
+        Self { topology, patience, homes: HashMap::new(), dispatches, spills: 0 }
+    }
+
+    pub fn topology(&self) -> &NumaTopology {
+        &self.topology
+    }
+
+    /// Pick a CPU for a Memory-Heavy task, given the CPU chosen by `select_cpu`
+    ///
+    /// A task seen for the first time has no home yet and keeps `selected`.
+    /// `None` means dispatch on any CPU.
+    pub fn place(&mut self, pid: i32, selected: i32, idle: &[bool], now: Instant) -> Option<i32> {
+        let keep = (selected >= 0).then_some(selected);
+        let Some(home) = self.homes.get_mut(&pid) else {
+            return keep;
+        };
+        if self.topology.node_of(selected) == Some(home.node) {
+            home.busy_since = None;
+            return keep;
+        }
+
+        let home_idle = idle
+            .iter()
+            .enumerate()
+            .find(|&(cpu, &idle)| idle && self.topology.node_of.get(cpu).copied().flatten() == Some(home.node))
+            .and_then(|(cpu, _)| i32::try_from(cpu).ok());
+        if let Some(cpu) = home_idle {
+            home.busy_since = None;
+            return Some(cpu);
+        }
+
+        // The whole home node is busy: wait there, until patience runs out
+        let busy_since = *home.busy_since.get_or_insert(now);
+        if now.duration_since(busy_since) < self.patience {
+            return Some(home.last_cpu);
+        }
+        home.busy_since = None;
+        self.spills += 1;
+        keep
+    }
+
+    /// Count a dispatch to `cpu`, and make its node a Memory-Heavy task's home
+    pub fn note_dispatch(&mut self, pid: i32, cpu: i32, memory_heavy: bool) {
+        let Some(node) = self.topology.node_of(cpu) else {
+            return;
+        };
+        if let Some(count) = self.dispatches.get_mut(node) {
+            *count += 1;
+        }
+        if !memory_heavy {
+            return;
+        }
+        if self.homes.len() >= NUMA_HOMES_SIZE && !self.homes.contains_key(&pid) {
+            self.homes.clear();
+        }
+        let home = self.homes.entry(pid).or_insert(Home { node, last_cpu: cpu, busy_since: None });
+        if home.node != node {
+            home.busy_since = None;
+        }
+        home.node = node;
+        home.last_cpu = cpu;
+    }
+
+    /// Drop an exited task's home
+    pub fn forget(&mut self, pid: i32) {
+        self.homes.remove(&pid);
+    }
+
+    /// Drop the homes of tasks last dispatched to a CPU that has gone offline
+    pub fn forget_cpus(&mut self, offline: &[usize]) {
+        self.homes.retain(|_, home| usize::try_from(home.last_cpu).map_or(true, |cpu| !offline.contains(&cpu)));
+    }
+
+    /// Dispatches to each node, node 0 first
+    pub fn dispatches(&self) -> &[u64] {
+        &self.dispatches
+    }
+
+    /// Times a Memory-Heavy task gave up on its busy home node
+    pub fn spills(&self) -> u64 {
+        self.spills
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Node 0 has CPUs 0-3, node 1 CPUs 4-7
+    fn two_nodes(patience_ms: u64) -> NumaPlacement {
+        let topology = NumaTopology::new(8, &[vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+        NumaPlacement::new(topology, Duration::from_millis(patience_ms))
+    }
+
+    fn mask(set: &[usize]) -> Vec<bool> {
+        (0..8).map(|cpu| set.contains(&cpu)).collect()
+    }
+
+    #[test]
+    fn test_topology() {
+        let numa = two_nodes(10);
+        assert!(numa.topology().is_numa());
+        assert_eq!(numa.topology().node_of(2), Some(0));
+        assert_eq!(numa.topology().node_of(6), Some(1));
+        assert_eq!(numa.topology().node_of(-1), None);
+        assert_eq!(numa.topology().node_of(42), None);
+        assert!(!NumaTopology::new(4, &[vec![0, 1, 2, 3]]).is_numa());
+    }
+
+    #[test]
+    fn test_memory_heavy_tasks_stay_home() {
+        let mut numa = two_nodes(10);
+        let now = Instant::now();
+        let all = mask(&[0, 1, 2, 3, 4, 5, 6, 7]);
+
+        // No home yet: the kernel's choice stands, and becomes home
+        assert_eq!(numa.place(42, 1, &all, now), Some(1));
+        numa.note_dispatch(42, 1, true);
+
+        // Sent across to node 1: come back to an idle CPU on node 0
+        assert_eq!(numa.place(42, 5, &all, now), Some(0));
+        assert_eq!(numa.place(42, 5, &mask(&[3, 4, 5]), now), Some(3));
+        // Already home: nothing to do
+        assert_eq!(numa.place(42, 2, &all, now), Some(2));
+
+        // Other task types are counted, not steered
+        numa.note_dispatch(7, 6, false);
+        assert_eq!(numa.place(7, 6, &all, now), Some(6));
+        assert_eq!(numa.dispatches(), &[1, 1]);
+    }
+
+    #[test]
+    fn test_spills_after_patience() {
+        let mut numa = two_nodes(10);
+        let now = Instant::now();
+        let node_one_idle = mask(&[4, 5, 6, 7]);
+        numa.note_dispatch(42, 2, true);
+
+        // Node 0 is full: wait on the last CPU while patience lasts
+        assert_eq!(numa.place(42, 5, &node_one_idle, now), Some(2));
+        assert_eq!(numa.place(42, 5, &node_one_idle, now + Duration::from_millis(9)), Some(2));
+        assert_eq!(numa.spills(), 0);
+
+        // Then cross over, and node 1 becomes home
+        assert_eq!(numa.place(42, 5, &node_one_idle, now + Duration::from_millis(10)), Some(5));
+        assert_eq!(numa.spills(), 1);
+        numa.note_dispatch(42, 5, true);
+        assert_eq!(numa.place(42, 1, &mask(&[1, 6]), now + Duration::from_millis(11)), Some(6));
+
+        // An idle CPU at home resets the wait
+        numa.note_dispatch(42, 6, true);
+        assert_eq!(numa.place(42, 1, &mask(&[0, 1]), now + Duration::from_millis(20)), Some(6));
+        assert_eq!(numa.place(42, 1, &mask(&[4]), now + Duration::from_millis(25)), Some(4));
+        assert_eq!(numa.place(42, 1, &mask(&[0, 1]), now + Duration::from_millis(26)), Some(6));
+        assert_eq!(numa.place(42, 1, &mask(&[0, 1]), now + Duration::from_millis(35)), Some(6));
+        assert_eq!(numa.spills(), 1);
+
+        // Forgotten tasks start over
+        numa.forget(42);
+        assert_eq!(numa.place(42, 1, &mask(&[0, 1]), now), Some(1));
+
+        // So do tasks whose last CPU went offline
+        numa.note_dispatch(42, 2, true);
+        numa.note_dispatch(43, 3, true);
+        numa.forget_cpus(&[2]);
+        assert_eq!(numa.place(42, 5, &mask(&[5]), now), Some(5));
+        assert_eq!(numa.place(43, 5, &mask(&[5]), now), Some(3));
+    }
+}
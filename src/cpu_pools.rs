@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Element-based CPU pools
+//
+// Fire tasks belong on the performance cores, Water and Earth tasks can sip
+// power on the efficiency cores. Each element may be given a pool of CPUs;
+// its tasks go to an idle CPU from the pool when there is one.
+
+use std::collections::HashMap;
+
+use scx_utils::Cpumask;
+
+use crate::astrology::Element;
+
+/// Parse an `ELEMENT=CPULIST` pool, e.g. `fire=0-7` or `water=8-11,14`
+pub fn parse_cpu_pool(s: &str) -> Result<(Element, Cpumask), String> {
+    let (element, cpulist) = s
+        .split_once('=')
+        .ok_or_else(|| "expected ELEMENT=CPULIST".to_string())?;
+    let element = element.trim().parse()?;
+    let mask = Cpumask::from_cpulist(cpulist.trim()).map_err(|e| format!("invalid CPU list '{cpulist}': {e}"))?;
+    Ok((element, mask))
+}
+
+/// Which CPUs belong to each element's pool
+pub struct CpuPools {
+    configured: HashMap<Element, Vec<bool>>,  // As given on the command line, indexed by CPU
+    pools: HashMap<Element, Vec<bool>>,       // The configured CPUs that are online
+}
+
+impl CpuPools {
+    pub fn new(nr_cpus: usize, pools: &[(Element, Cpumask)]) -> Self {
+        let mut members: HashMap<Element, Vec<bool>> = HashMap::new();
+        for (element, mask) in pools {
+            let pool = members.entry(*element).or_insert_with(|| vec![false; nr_cpus]);
+            for (cpu, member) in pool.iter_mut().enumerate() {
+                *member |= mask.test_cpu(cpu);
+            }
+        }
+        Self::from_members(members)
+    }
+
+    fn from_members(members: HashMap<Element, Vec<bool>>) -> Self {
+        Self { pools: members.clone(), configured: members }
+    }
+
+    /// Rebuild the pools from the configured CPUs that are `online`
+    pub fn set_online(&mut self, online: &[bool]) {
+        for (element, configured) in &self.configured {
+            let pool = configured.iter().enumerate().map(|(cpu, &member)| member && online.get(cpu).copied().unwrap_or(false));
+            self.pools.insert(*element, pool.collect());
+        }
+    }
+
+    /// The pool of an element, if one was configured
+    pub fn pool(&self, element: Element) -> Option<&[bool]> {
+        self.pools.get(&element).map(Vec::as_slice)
+    }
+}
+
+/// Steer a CPU choice into `pool`
+///
+/// Keeps `selected` when it is already in the pool, otherwise moves to the
+/// first idle CPU in the pool. When the whole pool is busy, the original
+/// choice stands if there was one; `None` means dispatch on any CPU.
+pub fn prefer_pool_cpu(pool: &[bool], idle: &[bool], selected: i32) -> Option<i32> {
+    pool_cpu(pool, idle, selected).or_else(|| (selected >= 0).then_some(selected))
+}
+
+/// A CPU from `pool` for the task: `selected` if it is a member, else the first idle member
+pub fn pool_cpu(pool: &[bool], idle: &[bool], selected: i32) -> Option<i32> {
+    let in_pool = |cpu: i32| usize::try_from(cpu).ok().and_then(|cpu| pool.get(cpu)).copied().unwrap_or(false);
+
+    if in_pool(selected) {
+        return Some(selected);
+    }
+
+    pool.iter()
+        .zip(idle)
+        .position(|(&member, &idle)| member && idle)
+        .and_then(|cpu| i32::try_from(cpu).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_pool() {
+        let (element, mask) = parse_cpu_pool("fire=0").unwrap();
+        assert_eq!(element, Element::Fire);
+        assert!(mask.test_cpu(0));
+
+        let (element, _) = parse_cpu_pool(" Water = 0-7,9 ").unwrap();
+        assert_eq!(element, Element::Water);
+
+        assert!(parse_cpu_pool("fire").is_err());
+        assert!(parse_cpu_pool("plasma=0-3").is_err());
+        assert!(parse_cpu_pool("fire=seven").is_err());
+    }
+
+    #[test]
+    fn test_pool_membership() {
+        let (element, mask) = parse_cpu_pool("earth=0").unwrap();
+        let pools = CpuPools::new(4, &[(element, mask)]);
+        assert_eq!(pools.pool(Element::Earth), Some(&[true, false, false, false][..]));
+        assert!(pools.pool(Element::Fire).is_none());
+    }
+
+    #[test]
+    fn test_pools_follow_online_cpus() {
+        let mut pools = CpuPools::from_members(HashMap::from([(Element::Fire, vec![true, true, true, false])]));
+
+        // CPU 1 goes offline, then comes back
+        pools.set_online(&[true, false, true, true]);
+        assert_eq!(pools.pool(Element::Fire), Some(&[true, false, true, false][..]));
+        assert_eq!(pool_cpu(pools.pool(Element::Fire).unwrap(), &[false, true, false, true], 3), None);
+        pools.set_online(&[true; 4]);
+        assert_eq!(pools.pool(Element::Fire), Some(&[true, true, true, false][..]));
+    }
+
+    #[test]
+    fn test_pool_preference() {
+        // Performance cores 0-3, efficiency cores 4-7
+        let performance = [true, true, true, true, false, false, false, false];
+        let all_idle = [true; 8];
+
+        // Already in the pool: stay there
+        assert_eq!(prefer_pool_cpu(&performance, &all_idle, 2), Some(2));
+
+        // Selected an efficiency core: move to the first idle performance core
+        assert_eq!(prefer_pool_cpu(&performance, &all_idle, 6), Some(0));
+        let idle = [false, false, true, true, true, true, true, true];
+        assert_eq!(prefer_pool_cpu(&performance, &idle, 6), Some(2));
+        assert_eq!(prefer_pool_cpu(&performance, &idle, -1), Some(2));
+    }
+
+    #[test]
+    fn test_busy_pool_falls_back() {
+        let performance = [true, true, false, false];
+        let idle = [false, false, true, true];
+
+        // The whole pool is busy: keep select_cpu's choice, or run anywhere
+        assert_eq!(prefer_pool_cpu(&performance, &idle, 3), Some(3));
+        assert_eq!(prefer_pool_cpu(&performance, &idle, -1), None);
+        assert_eq!(pool_cpu(&performance, &idle, 3), None);
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// SMT-aware placement for Fire tasks
+//
+// Mars-ruled compile jobs fight over shared execution units when packed onto
+// hyperthread siblings. CPU-Intensive tasks prefer a physical core whose
+// siblings are idle, and while the machine is lightly loaded they stay off
+// cores where another CPU-Intensive task is already running.
+
+use std::fs;
+
+use scx_utils::Cpumask;
+
+/// Share of busy CPUs up to which two CPU-Intensive tasks never share a core
+const LIGHT_LOAD: f64 = 0.5;
+
+/// Which CPUs share a physical core
+pub struct SmtTopology {
+    siblings: Vec<Vec<usize>>,  // The other hardware threads of each CPU's core
+}
+
+impl SmtTopology {
+    /// Build from the hardware threads of each physical core, e.g. `[[0, 4], [1, 5]]`
+    pub fn new(nr_cpus: usize, cores: &[Vec<usize>]) -> Self {
+        let mut siblings = vec![Vec::new(); nr_cpus];
+        for core in cores {
+            for &cpu in core.iter().filter(|&&cpu| cpu < nr_cpus) {
+                siblings[cpu] = core.iter().copied().filter(|&other| other != cpu && other < nr_cpus).collect();
+            }
+        }
+        Self { siblings }
+    }
+
+    /// Read the thread siblings of each CPU from sysfs; unreadable CPUs get none
+    pub fn from_sysfs(nr_cpus: usize) -> Self {
+        let cores: Vec<Vec<usize>> = (0..nr_cpus)
+            .filter_map(|cpu| {
+                let path = format!("/sys/devices/system/cpu/cpu{cpu}/topology/thread_siblings_list");
+                let mask = Cpumask::from_cpulist(fs::read_to_string(path).ok()?.trim()).ok()?;
+                Some((0..nr_cpus).filter(|&cpu| mask.test_cpu(cpu)).collect())
+            })
+            .collect();
+        Self::new(nr_cpus, &cores)
+    }
+
+    /// True when any core runs more than one hardware thread
+    pub fn has_smt(&self) -> bool {
+        self.siblings.iter().any(|siblings| !siblings.is_empty())
+    }
+
+    pub fn siblings(&self, cpu: usize) -> &[usize] {
+        self.siblings.get(cpu).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Steer a CPU-Intensive task away from busy hyperthread siblings
+///
+/// `idle` marks idle CPUs and `fire_free` marks CPUs not running a
+/// CPU-Intensive task. A CPU whose whole core is idle wins, `selected` first.
+/// Failing that, while the machine is lightly loaded (or always with
+/// `isolate`), any idle CPU whose siblings run no CPU-Intensive task will do.
+/// Otherwise the original choice stands; `None` means dispatch on any CPU.
+pub fn prefer_smt_cpu(
+    topology: &SmtTopology,
+    idle: &[bool],
+    fire_free: &[bool],
+    selected: i32,
+    isolate: bool,
+) -> Option<i32> {
+    let is = |mask: &[bool], cpu: usize| mask.get(cpu).copied().unwrap_or(false);
+    let core_idle = |cpu: usize| is(idle, cpu) && topology.siblings(cpu).iter().all(|&sibling| is(idle, sibling));
+    let no_fire_sibling =
+        |cpu: usize| is(idle, cpu) && topology.siblings(cpu).iter().all(|&sibling| is(fire_free, sibling));
+    let first = |wanted: &dyn Fn(usize) -> bool| -> Option<i32> {
+        let selected = usize::try_from(selected).ok().filter(|&cpu| wanted(cpu));
+        selected
+            .or_else(|| (0..idle.len()).find(|&cpu| wanted(cpu)))
+            .and_then(|cpu| i32::try_from(cpu).ok())
+    };
+
+    if let Some(cpu) = first(&core_idle) {
+        return Some(cpu);
+    }
+
+    let busy = idle.iter().filter(|&&idle| !idle).count();
+    #[allow(clippy::cast_precision_loss)]
+    let lightly_loaded = (busy as f64) <= LIGHT_LOAD * idle.len() as f64;
+    if isolate || lightly_loaded {
+        if let Some(cpu) = first(&no_fire_sibling) {
+            return Some(cpu);
+        }
+    }
+
+    (selected >= 0).then_some(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4 cores / 8 threads: CPU n and n + 4 share a core
+    fn four_by_two() -> SmtTopology {
+        SmtTopology::new(8, &[vec![0, 4], vec![1, 5], vec![2, 6], vec![3, 7]])
+    }
+
+    fn mask(set: &[usize]) -> Vec<bool> {
+        (0..8).map(|cpu| set.contains(&cpu)).collect()
+    }
+
+    #[test]
+    fn test_topology() {
+        let topology = four_by_two();
+        assert!(topology.has_smt());
+        assert_eq!(topology.siblings(1), &[5]);
+        assert_eq!(topology.siblings(6), &[2]);
+        assert!(topology.siblings(42).is_empty());
+        assert!(!SmtTopology::new(4, &[vec![0], vec![1], vec![2], vec![3]]).has_smt());
+    }
+
+    #[test]
+    fn test_prefers_fully_idle_core() {
+        let topology = four_by_two();
+        let all = mask(&[0, 1, 2, 3, 4, 5, 6, 7]);
+
+        // Everything idle: keep the selection
+        assert_eq!(prefer_smt_cpu(&topology, &all, &all, 5, false), Some(5));
+
+        // CPU 0 busy: its sibling 4 is a poor pick, core 1 is free
+        let idle = mask(&[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &all, 4, false), Some(1));
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &all, -1, false), Some(1));
+    }
+
+    #[test]
+    fn test_avoids_fire_siblings_under_light_load() {
+        let topology = four_by_two();
+
+        // Cores 0-2 each have one busy thread, only CPUs 1 and 2 run CPU-Intensive work
+        let idle = mask(&[3, 4, 5, 6, 7]);
+        let fire_free = mask(&[0, 3, 4, 5, 6, 7]);
+
+        // Core 3 is fully idle
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 5, false), Some(3));
+
+        // Core 3 taken too (half the machine busy): 4 shares a core with non-Fire work, 5 and 6 with Fire work
+        let idle = mask(&[4, 5, 6, 7]);
+        let fire_free = mask(&[0, 3, 4, 5, 6, 7]);
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 5, false), Some(4));
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 6, false), Some(4));
+    }
+
+    #[test]
+    fn test_heavy_load_only_isolates_on_request() {
+        let topology = four_by_two();
+
+        // Five of eight CPUs busy, all with CPU-Intensive work except CPU 0
+        let idle = mask(&[4, 5, 7]);
+        let fire_free = mask(&[0, 4, 5, 7]);
+
+        // Under heavy load the kernel's choice stands...
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 5, false), Some(5));
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, -1, false), None);
+
+        // ...unless Fire tasks must not share a core with Fire tasks
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 5, true), Some(4));
+
+        // Nowhere to isolate: fall back to the selection
+        let fire_free = mask(&[4, 5, 7]);
+        assert_eq!(prefer_smt_cpu(&topology, &idle, &fire_free, 5, true), Some(5));
+    }
+}
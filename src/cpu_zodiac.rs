@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Per-core astrological personalities
+//
+// Every CPU is born under a zodiac sign. Tasks prefer to run on a core whose
+// sign shares the element their ruling planet currently stands in: the
+// machine itself becomes a mandala.
+
+use crate::astrology::{Element, ZodiacSign};
+use crate::cpu_pools::{pool_cpu, prefer_pool_cpu, CpuPools};
+
+/// The sign of each CPU
+pub struct CpuZodiac {
+    signs: Vec<ZodiacSign>,
+}
+
+impl CpuZodiac {
+    /// Cycle through `pattern` across `nr_cpus` CPUs
+    ///
+    /// An empty pattern means the whole zodiac, Aries first.
+    pub fn new(nr_cpus: usize, pattern: &[ZodiacSign]) -> Self {
+        let pattern = if pattern.is_empty() { ZodiacSign::all() } else { pattern.to_vec() };
+        let signs = pattern.into_iter().cycle().take(nr_cpus).collect();
+        Self { signs }
+    }
+
+    pub fn signs(&self) -> &[ZodiacSign] {
+        &self.signs
+    }
+
+    /// Pick a CPU for a task favoring `element`, given the CPU chosen by `select_cpu`
+    pub fn place(&self, element: Element, selected: i32, idle: &[bool]) -> Option<i32> {
+        prefer_cpu(&self.signs, idle, element, selected)
+    }
+}
+
+/// Pick a CPU for a task, consulting the element pools first and the CPU zodiac second
+///
+/// `element` selects the pool; `ruling` is the element of the ruling planet's
+/// current sign, matched against CPU signs (falling back to `element`).
+/// Returns `None` when neither is configured, so the task keeps `selected`
+/// untouched; `Some(None)` means dispatch on any CPU.
+#[allow(clippy::option_option)]
+pub fn place_task(
+    pools: Option<&CpuPools>,
+    zodiac: Option<&CpuZodiac>,
+    element: Element,
+    ruling: Option<Element>,
+    selected: i32,
+    idle: &[bool],
+) -> Option<Option<i32>> {
+    let pool = pools.and_then(|pools| pools.pool(element));
+    if let Some(cpu) = pool.and_then(|pool| pool_cpu(pool, idle, selected)) {
+        return Some(Some(cpu));
+    }
+    if let Some(zodiac) = zodiac {
+        return Some(zodiac.place(ruling.unwrap_or(element), selected, idle));
+    }
+    pool.map(|pool| prefer_pool_cpu(pool, idle, selected))
+}
+
+/// Bias a CPU choice towards cores whose sign shares `element`
+///
+/// Keeps `selected` when it is already compatible, otherwise moves to the
+/// first idle compatible core. When no compatible core is idle, the original
+/// choice stands if there was one; `None` means dispatch on any CPU.
+pub fn prefer_cpu(signs: &[ZodiacSign], idle: &[bool], element: Element, selected: i32) -> Option<i32> {
+    let sign_of = |cpu: i32| usize::try_from(cpu).ok().and_then(|cpu| signs.get(cpu));
+
+    if sign_of(selected).is_some_and(|sign| sign.element() == element) {
+        return Some(selected);
+    }
+
+    let compatible_idle = signs
+        .iter()
+        .zip(idle)
+        .position(|(sign, &idle)| idle && sign.element() == element)
+        .and_then(|cpu| i32::try_from(cpu).ok());
+
+    compatible_idle.or_else(|| (selected >= 0).then_some(selected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CPU 0: Aries (Fire), 1: Taurus (Earth), 2: Gemini (Air), 3: Cancer (Water),
+    // 4: Leo (Fire), 5: Virgo (Earth)
+    fn six_cores() -> CpuZodiac {
+        CpuZodiac::new(6, &[])
+    }
+
+    #[test]
+    fn test_round_robin_signs() {
+        let zodiac = six_cores();
+        assert_eq!(zodiac.signs()[0], ZodiacSign::Aries);
+        assert_eq!(zodiac.signs()[5], ZodiacSign::Virgo);
+
+        let custom = CpuZodiac::new(5, &[ZodiacSign::Leo, ZodiacSign::Pisces]);
+        assert_eq!(
+            custom.signs(),
+            &[ZodiacSign::Leo, ZodiacSign::Pisces, ZodiacSign::Leo, ZodiacSign::Pisces, ZodiacSign::Leo]
+        );
+    }
+
+    #[test]
+    fn test_core_selection_preference() {
+        let zodiac = six_cores();
+        let signs = zodiac.signs();
+
+        // Already on a Fire core: stay there
+        let all_idle = [true; 6];
+        assert_eq!(prefer_cpu(signs, &all_idle, Element::Fire, 4), Some(4));
+
+        // Selected an Earth core, but a Fire core is idle
+        assert_eq!(prefer_cpu(signs, &all_idle, Element::Fire, 1), Some(0));
+
+        // Aries is busy, Leo is idle
+        let idle = [false, true, true, true, true, true];
+        assert_eq!(prefer_cpu(signs, &idle, Element::Fire, 1), Some(4));
+
+        // The only Water core is busy: keep the selection
+        let idle = [true, true, true, false, true, true];
+        assert_eq!(prefer_cpu(signs, &idle, Element::Water, 2), Some(2));
+
+        // No compatible idle core and no selection: any CPU
+        assert_eq!(prefer_cpu(signs, &idle, Element::Water, -1), None);
+        assert_eq!(prefer_cpu(signs, &idle, Element::Air, -1), Some(2));
+    }
+
+    #[test]
+    fn test_zodiac_consulted_after_pools() {
+        use scx_utils::Cpumask;
+
+        let zodiac = six_cores();
+        let pools = CpuPools::new(6, &[(Element::Water, Cpumask::from_cpulist("0").unwrap())]);
+
+        // The Water pool wins while it has room
+        let idle = [true; 6];
+        assert_eq!(place_task(Some(&pools), Some(&zodiac), Element::Water, Some(Element::Fire), 5, &idle), Some(Some(0)));
+
+        // Pool busy: the ruling planet's element picks the CPU sign
+        let idle = [false, true, true, true, true, true];
+        assert_eq!(place_task(Some(&pools), Some(&zodiac), Element::Water, Some(Element::Earth), 2, &idle), Some(Some(1)));
+        assert_eq!(place_task(Some(&pools), None, Element::Water, Some(Element::Earth), 2, &idle), Some(Some(2)));
+
+        // No pool for the element: straight to the zodiac, falling back to the task's element
+        assert_eq!(place_task(Some(&pools), Some(&zodiac), Element::Air, None, 0, &idle), Some(Some(2)));
+        assert_eq!(place_task(Some(&pools), Some(&zodiac), Element::Air, Some(Element::Fire), 1, &idle), Some(Some(4)));
+    }
+
+    #[test]
+    fn test_disabled_placement_leaves_selection_alone() {
+        let idle = [true; 6];
+        for selected in [-1, 0, 3] {
+            for element in Element::all() {
+                assert_eq!(place_task(None, None, element, Some(Element::Fire), selected, &idle), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_placement_follows_idle_cpus() {
+        use crate::cpu_idle::CpuIdleTracker;
+        use std::time::{Duration, Instant};
+
+        let zodiac = six_cores();
+        let mut tracker = CpuIdleTracker::new(6);
+        let t0 = Instant::now();
+
+        // Busy Aries pushes Fire work to Leo
+        tracker.note_dispatch(0, Duration::from_millis(5), t0);
+        assert_eq!(zodiac.place(Element::Fire, -1, &tracker.idle_mask(t0)), Some(4));
+        assert_eq!(zodiac.place(Element::Fire, -1, &tracker.idle_mask(t0 + Duration::from_millis(10))), Some(0));
+    }
+}
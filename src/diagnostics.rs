@@ -0,0 +1,442 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Diagnostic reports
+//
+// When the scheduler misbehaves, one file should hold everything a maintainer
+// needs to reproduce its state: the build, the options it ran with, the sky it
+// saw and how strongly each planet acted, what the classifier had learned, the
+// birth charts cast, the BPF counters and the last decisions made.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use scx_utils::Cpumask;
+use serde::{Serialize, Serializer};
+
+use crate::astrology::aspects::find_aspects;
+use crate::astrology::{AstroError, AstrologicalScheduler, Element, PlanetaryPosition, SchedulingDecision};
+use crate::Opts;
+
+/// Scheduling decisions kept for the report
+pub const RECENT_DECISIONS: usize = 20;
+
+/// How a diagnostic report is written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl ReportFormat {
+    /// File extension for reports in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// One dispatched task, as the report remembers it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecisionRecord {
+    pub pid: i32,
+    pub comm: String,
+    pub task_type: String,
+    pub priority: u32,
+    pub slice_us: u64,
+    pub cpu: i32,
+    pub reasoning: String,
+}
+
+/// The last few scheduling decisions, oldest first
+#[derive(Debug, Clone)]
+pub struct DecisionLog {
+    records: VecDeque<DecisionRecord>,
+    capacity: usize,
+}
+
+impl DecisionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self { records: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn record(&mut self, pid: i32, comm: &str, decision: &SchedulingDecision, slice_ns: u64, cpu: i32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(DecisionRecord {
+            pid,
+            comm: comm.to_string(),
+            task_type: decision.task_type.name().to_string(),
+            priority: decision.priority,
+            slice_us: slice_ns / 1000,
+            cpu,
+            reasoning: decision.reasoning.clone(),
+        });
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &DecisionRecord> {
+        self.records.iter()
+    }
+}
+
+/// Counters shared with the BPF side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[allow(clippy::struct_field_names)]  // Named after the BPF globals
+pub struct BpfCounters {
+    pub nr_online_cpus: u64,
+    pub nr_running: u64,
+    pub nr_queued: u64,
+    pub nr_scheduled: u64,
+    pub nr_user_dispatches: u64,
+    pub nr_kernel_dispatches: u64,
+    pub nr_cancel_dispatches: u64,
+    pub nr_bounce_dispatches: u64,
+    pub nr_failed_dispatches: u64,
+    pub nr_sched_congested: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlanetReport {
+    pub planet: String,
+    pub sign: String,
+    pub element: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub retrograde: bool,
+}
+
+impl PlanetReport {
+    pub fn new(pos: &PlanetaryPosition) -> Self {
+        Self {
+            planet: pos.planet.name().to_string(),
+            sign: pos.sign.name().to_string(),
+            element: pos.sign.element().name().to_string(),
+            longitude: pos.longitude,
+            latitude: pos.latitude,
+            retrograde: pos.retrograde,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DignityReport {
+    pub planet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AspectReport {
+    pub planet: String,
+    pub aspect: String,
+    pub other: String,
+    pub strength: f64,
+}
+
+/// Everything the scheduler knew at one moment
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticReport {
+    pub generated_at: String,
+    pub version: String,
+    pub build_date: String,
+    pub options: serde_json::Value,
+    pub planets: Vec<PlanetReport>,
+    pub dignities: Vec<DignityReport>,
+    pub aspects: Vec<AspectReport>,
+    pub elements: BTreeMap<String, usize>,
+    pub retrograde: Vec<String>,
+    pub learned: BTreeMap<i32, String>,
+    pub birth_charts: usize,
+    pub bpf: BpfCounters,
+    pub decisions: Vec<DecisionRecord>,
+}
+
+impl DiagnosticReport {
+    /// The report for the chart `astro` is scheduling with at `now`
+    pub fn new(
+        now: DateTime<Utc>,
+        opts: &Opts,
+        astro: &mut AstrologicalScheduler,
+        bpf: BpfCounters,
+        decisions: &DecisionLog,
+    ) -> Result<Self, AstroError> {
+        let positions = astro.positions(now)?;
+        let positions = positions.as_slice();
+        let planets = positions.iter().map(PlanetReport::new).collect();
+        let dignities = astro
+            .dignity_scores(now)?
+            .into_iter()
+            .map(|(planet, score)| DignityReport { planet: planet.name().to_string(), score })
+            .collect();
+
+        // Each pair once: every planet against the ones after it
+        let aspects = positions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, pos)| {
+                find_aspects(pos, &positions[i + 1..]).into_iter().map(move |aspect| AspectReport {
+                    planet: pos.planet.name().to_string(),
+                    aspect: aspect.kind.name().to_string(),
+                    other: aspect.other.name().to_string(),
+                    strength: aspect.strength,
+                })
+            })
+            .collect();
+
+        let mut elements: BTreeMap<String, usize> =
+            Element::all().into_iter().map(|element| (element.name().to_string(), 0)).collect();
+        for pos in positions {
+            *elements.entry(pos.sign.element().name().to_string()).or_default() += 1;
+        }
+
+        Ok(Self {
+            generated_at: now.to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            build_date: build_date(),
+            options: serde_json::to_value(opts).unwrap_or_default(),
+            planets,
+            dignities,
+            aspects,
+            elements,
+            retrograde: positions
+                .iter()
+                .filter(|pos| pos.retrograde)
+                .map(|pos| pos.planet.name().to_string())
+                .collect(),
+            learned: astro
+                .classifier()
+                .learned()
+                .iter()
+                .map(|(&pid, task_type)| (pid, task_type.name().to_string()))
+                .collect(),
+            birth_charts: astro.birth_chart_count(),
+            bpf,
+            decisions: decisions.records().cloned().collect(),
+        })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            ReportFormat::Text => self.render_text(),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "scx_horoscope diagnostic report");
+        let _ = writeln!(out, "Generated: {}", self.generated_at);
+
+        section(&mut out, "Version");
+        let _ = writeln!(out, "{} (built {})", self.version, self.build_date);
+
+        section(&mut out, "Options");
+        if let Some(options) = self.options.as_object() {
+            for (name, value) in options {
+                let _ = writeln!(out, "{name}: {value}");
+            }
+        }
+
+        section(&mut out, "Planetary Positions");
+        for planet in &self.planets {
+            let _ = writeln!(
+                out,
+                "{:<8} {:>7.3}° (lat {:>6.3}°) {:<12} {:<6}{}",
+                planet.planet,
+                planet.longitude,
+                planet.latitude,
+                planet.sign,
+                planet.element,
+                if planet.retrograde { " ℞" } else { "" }
+            );
+        }
+
+        section(&mut out, "Dignities");
+        for dignity in &self.dignities {
+            let _ = writeln!(out, "{:<8} {:+.2}", dignity.planet, dignity.score);
+        }
+
+        section(&mut out, "Aspects");
+        if self.aspects.is_empty() {
+            let _ = writeln!(out, "(none)");
+        }
+        for aspect in &self.aspects {
+            let _ = writeln!(
+                out,
+                "{} {} {} ({:.0}%)",
+                aspect.planet,
+                aspect.aspect,
+                aspect.other,
+                aspect.strength * 100.0
+            );
+        }
+
+        section(&mut out, "Element Distribution");
+        for (element, count) in &self.elements {
+            let _ = writeln!(out, "{element}: {count}");
+        }
+
+        section(&mut out, "Retrograde");
+        let _ = writeln!(
+            out,
+            "{}",
+            if self.retrograde.is_empty() { "(none)".to_string() } else { self.retrograde.join(", ") }
+        );
+
+        section(&mut out, "Classification Cache");
+        if self.learned.is_empty() {
+            let _ = writeln!(out, "(empty)");
+        }
+        for (pid, task_type) in &self.learned {
+            let _ = writeln!(out, "PID {pid}: {task_type}");
+        }
+
+        section(&mut out, "Birth Charts");
+        let _ = writeln!(out, "{} cast", self.birth_charts);
+
+        section(&mut out, "BPF Stats");
+        let bpf = &self.bpf;
+        for (name, value) in [
+            ("nr_online_cpus", bpf.nr_online_cpus),
+            ("nr_running", bpf.nr_running),
+            ("nr_queued", bpf.nr_queued),
+            ("nr_scheduled", bpf.nr_scheduled),
+            ("nr_user_dispatches", bpf.nr_user_dispatches),
+            ("nr_kernel_dispatches", bpf.nr_kernel_dispatches),
+            ("nr_cancel_dispatches", bpf.nr_cancel_dispatches),
+            ("nr_bounce_dispatches", bpf.nr_bounce_dispatches),
+            ("nr_failed_dispatches", bpf.nr_failed_dispatches),
+            ("nr_sched_congested", bpf.nr_sched_congested),
+        ] {
+            let _ = writeln!(out, "{name}: {value}");
+        }
+
+        section(&mut out, "Recent Decisions");
+        if self.decisions.is_empty() {
+            let _ = writeln!(out, "(none)");
+        }
+        for record in &self.decisions {
+            let _ = writeln!(
+                out,
+                "[PID {}] {} | {} | Priority: {} | Slice: {}μs | CPU: {} | {}",
+                record.pid, record.comm, record.task_type, record.priority, record.slice_us, record.cpu, record.reasoning
+            );
+        }
+
+        out
+    }
+}
+
+fn section(out: &mut String, title: &str) {
+    let _ = writeln!(out, "\n== {title} ==");
+}
+
+/// The day this binary was built, from the timestamp build.rs stamped into it
+fn build_date() -> String {
+    env!("SCX_HOROSCOPE_BUILD_EPOCH")
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map_or_else(|| "unknown".to_string(), |date| date.format("%Y-%m-%d").to_string())
+}
+
+/// `--cpu-pool` entries, with each element's CPUs listed
+pub fn serialize_cpu_pools<S: Serializer>(pools: &[(Element, Cpumask)], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        pools.iter().map(|(element, mask)| (element, (0..mask.len()).filter(|&cpu| mask.test_cpu(cpu)).collect::<Vec<_>>())),
+    )
+}
+
+/// `--compare-times` as RFC 3339 timestamps
+pub fn serialize_time_pair<S: Serializer>(
+    times: &Option<(DateTime<Utc>, DateTime<Utc>)>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    times.map(|(a, b)| (a.to_rfc3339(), b.to_rfc3339())).serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::TaskType;
+    use chrono::TimeZone;
+    use clap::Parser;
+
+    fn report() -> DiagnosticReport {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", "4000", "--cpu-pool", "fire=0"]);
+
+        let mut astro = AstrologicalScheduler::new(60);
+        astro.learn_task_type(4242, TaskType::MemoryHeavy);
+
+        let mut decisions = DecisionLog::new(RECENT_DECISIONS);
+        for pid in 0..25 {
+            let decision = astro.schedule_task("rustc", pid, 100, now).unwrap();
+            decisions.record(pid, "rustc", &decision, 2_000_000, 1);
+        }
+
+        let bpf = BpfCounters { nr_user_dispatches: 25, ..BpfCounters::default() };
+        DiagnosticReport::new(now, &opts, &mut astro, bpf, &decisions).unwrap()
+    }
+
+    #[test]
+    fn test_decision_log_keeps_the_latest() {
+        let report = report();
+        assert_eq!(report.decisions.len(), RECENT_DECISIONS);
+        assert_eq!(report.decisions[0].pid, 5);
+        assert_eq!(report.decisions[RECENT_DECISIONS - 1].pid, 24);
+        assert_eq!(report.elements.values().sum::<usize>(), report.planets.len());
+        assert_eq!(report.dignities.len(), report.planets.len());
+    }
+
+    #[test]
+    fn test_text_report_sections() {
+        let text = report().render(ReportFormat::Text);
+        for header in [
+            "== Version ==",
+            "== Options ==",
+            "== Planetary Positions ==",
+            "== Dignities ==",
+            "== Aspects ==",
+            "== Element Distribution ==",
+            "== Retrograde ==",
+            "== Classification Cache ==",
+            "== Birth Charts ==",
+            "== BPF Stats ==",
+            "== Recent Decisions ==",
+        ] {
+            assert!(text.contains(header), "missing {header}");
+        }
+        assert!(text.contains(&format!("{} (built {})", env!("CARGO_PKG_VERSION"), build_date())));
+        assert!(text.contains("slice_us: 4000"));
+        assert!(text.contains(r#"fairness: "priority""#));
+        assert!(text.contains("PID 4242: Memory-Heavy"));
+        assert!(text.contains("nr_user_dispatches: 25"));
+        assert!(text.contains("[PID 24] rustc"));
+    }
+
+    #[test]
+    fn test_json_report_sections() {
+        let json: serde_json::Value = serde_json::from_str(&report().render(ReportFormat::Json)).unwrap();
+        for key in [
+            "generated_at", "version", "build_date", "options", "planets", "dignities", "aspects", "elements",
+            "retrograde", "learned", "birth_charts", "bpf", "decisions",
+        ] {
+            assert!(json.get(key).is_some(), "missing {key}");
+        }
+        assert_eq!(json["planets"].as_array().unwrap().len(), 8);
+        assert_eq!(json["dignities"][0]["planet"], "Sun");
+        assert_eq!(json["options"]["slice_us"], 4000);
+        assert_eq!(json["options"]["cpu_pool"], serde_json::json!([["Fire", [0]]]));
+        assert!(json["options"]["compare_times"].is_null());
+        assert_eq!(json["learned"]["4242"], "Memory-Heavy");
+        assert_eq!(json["bpf"]["nr_user_dispatches"], 25);
+    }
+}
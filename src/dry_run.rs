@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Dispatch dry run
+//
+// With --dispatch-dry-run the stars are consulted for every task exactly as
+// usual -- classification, priority, slice and CPU placement -- and the
+// decision log records all of it, but the task is handed to the kernel as a
+// stand-in: on the CPU the kernel picked, with the default time slice. The
+// system keeps running as if the scheduler had no opinion, and the operator
+// reads what it would have done.
+
+use crate::astrology::SchedulingDecision;
+use crate::bpf::DispatchedTask;
+
+/// Prefix of every would-be dispatch in the log
+pub const DRY_RUN_PREFIX: &str = "🔮 DRY RUN:";
+
+/// What is actually sent in place of `planned`
+///
+/// Only the placement the kernel chose (`selected_cpu`) is kept; the slice
+/// falls back to the default and the vtime is cleared.
+pub fn stand_in(planned: &DispatchedTask, selected_cpu: i32) -> DispatchedTask {
+    DispatchedTask { cpu: selected_cpu, slice_ns: 0, vtime: 0, ..planned.clone() }
+}
+
+/// Log line for a dispatch that is not going to happen
+pub fn would_dispatch(comm: &str, decision: &SchedulingDecision, planned: &DispatchedTask) -> String {
+    format!(
+        "{DRY_RUN_PREFIX} Would dispatch [PID {}] {comm} | {} | Priority: {} | Slice: {}μs | CPU: {}",
+        planned.pid,
+        decision.task_type.name(),
+        decision.priority,
+        planned.slice_ns / 1000,
+        planned.cpu
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::AstrologicalScheduler;
+    use crate::bpf::RL_CPU_ANY;
+    use crate::diagnostics::DecisionLog;
+    use chrono::{TimeZone, Utc};
+
+    fn planned(pid: i32, slice_ns: u64, cpu: i32) -> DispatchedTask {
+        DispatchedTask { pid, cpu, flags: 8, slice_ns, vtime: 42, enq_cnt: 3 }
+    }
+
+    #[test]
+    fn test_stand_in_keeps_only_the_kernel_choice() {
+        let sent = stand_in(&planned(1234, 7_000_000, 5), RL_CPU_ANY);
+        assert_eq!(sent, DispatchedTask { pid: 1234, cpu: RL_CPU_ANY, flags: 8, slice_ns: 0, vtime: 0, enq_cnt: 3 });
+    }
+
+    #[test]
+    fn test_dry_run_decision_log_matches_real_run() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let tasks = [(1, "rustc"), (2, "firefox"), (3, "postgres"), (4, "sshd")];
+
+        // Both runs log the planned dispatch; only what reaches the kernel differs
+        let run = |dry_run: bool| {
+            let mut astro = AstrologicalScheduler::new(60);
+            let mut log = DecisionLog::new(16);
+            let mut sent = Vec::new();
+            let mut lines = Vec::new();
+            for (pid, comm) in tasks {
+                let decision = astro.schedule_task(comm, pid, 100, now).unwrap();
+                let task = planned(pid, u64::from(decision.priority) * 10_000, 2);
+                log.record(pid, comm, &decision, task.slice_ns, task.cpu);
+                if dry_run {
+                    lines.push(would_dispatch(comm, &decision, &task));
+                    sent.push(stand_in(&task, 0));
+                } else {
+                    sent.push(task);
+                }
+            }
+            (log.records().cloned().collect::<Vec<_>>(), sent, lines)
+        };
+
+        let (real_log, real_sent, real_lines) = run(false);
+        let (dry_log, dry_sent, dry_lines) = run(true);
+        assert_eq!(real_log, dry_log);
+        assert!(real_lines.is_empty());
+        assert!(dry_sent.iter().all(|task| task.slice_ns == 0 && task.cpu == 0));
+        assert!(real_sent.iter().all(|task| task.slice_ns > 0 && task.cpu == 2));
+
+        assert_eq!(dry_lines.len(), tasks.len());
+        let record = &dry_log[0];
+        assert_eq!(
+            dry_lines[0],
+            format!(
+                "🔮 DRY RUN: Would dispatch [PID 1] rustc | {} | Priority: {} | Slice: {}μs | CPU: 2",
+                record.task_type, record.priority, record.slice_us
+            )
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Energy-aware frequency scaling hints
+//
+// A CPU running tasks under a blessed ruler may as well run flat out, and one
+// running under a retrograde ruler is in no hurry. With --energy-aware each
+// CPU's cpufreq governor follows the combined planetary influence of the last
+// task dispatched to it, re-evaluated every poll interval. The governors the
+// system had before are put back on exit.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::astrology::SchedulingDecision;
+use crate::stats::StatsTicker;
+
+const SYSFS_CPU: &str = "/sys/devices/system/cpu";
+
+/// Combined influence above which a CPU runs at full speed
+pub const PERFORMANCE_ABOVE: f64 = 1.2;
+
+/// Combined influence below which a CPU saves power
+pub const POWERSAVE_BELOW: f64 = 0.8;
+
+/// A cpufreq governor the stars can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Governor {
+    Performance,
+    Schedutil,
+    Powersave,
+}
+
+impl Governor {
+    /// Governor for a combined planetary influence
+    pub fn for_influence(influence: f64) -> Self {
+        if influence > PERFORMANCE_ABOVE {
+            Governor::Performance
+        } else if influence < POWERSAVE_BELOW {
+            Governor::Powersave
+        } else {
+            Governor::Schedutil
+        }
+    }
+
+    /// Name as written to `scaling_governor`
+    pub fn name(self) -> &'static str {
+        match self {
+            Governor::Performance => "performance",
+            Governor::Schedutil => "schedutil",
+            Governor::Powersave => "powersave",
+        }
+    }
+}
+
+/// The ruling planet's influence scaled by the element boost
+///
+/// Retrograde influence is negative, so it always lands in powersave.
+pub fn combined_influence(decision: &SchedulingDecision) -> f64 {
+    decision.planetary_influence * decision.element_boost
+}
+
+/// Steers each CPU's cpufreq governor by the stars
+pub struct EnergyManager {
+    root: PathBuf,                     // Directory holding cpu<N>/cpufreq
+    poll: StatsTicker,
+    hints: BTreeMap<i32, Governor>,    // Latest hint per CPU since startup
+    applied: BTreeMap<i32, Governor>,  // What each CPU was last set to
+    original: BTreeMap<i32, String>,   // Governors to restore on exit
+}
+
+impl EnergyManager {
+    /// Manage the governors under `root` (normally `/sys/devices/system/cpu`)
+    pub fn new(root: impl Into<PathBuf>, poll_interval: Duration, start: Instant) -> Self {
+        Self {
+            root: root.into(),
+            poll: StatsTicker::new(poll_interval, start),
+            hints: BTreeMap::new(),
+            applied: BTreeMap::new(),
+            original: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_sysfs(poll_interval: Duration, start: Instant) -> Self {
+        Self::new(SYSFS_CPU, poll_interval, start)
+    }
+
+    fn governor_path(&self, cpu: i32) -> PathBuf {
+        self.root.join(format!("cpu{cpu}")).join("cpufreq").join("scaling_governor")
+    }
+
+    /// Write `governor` for `cpu`, remembering the one it replaces the first time
+    pub fn set_governor(&mut self, cpu: i32, governor: &str) -> io::Result<()> {
+        let path = self.governor_path(cpu);
+        if let Entry::Vacant(entry) = self.original.entry(cpu) {
+            entry.insert(fs::read_to_string(&path)?.trim().to_string());
+        }
+        fs::write(&path, governor)
+    }
+
+    /// Note the task just dispatched to `cpu`; tasks left on any CPU are ignored
+    pub fn note_dispatch(&mut self, cpu: i32, decision: &SchedulingDecision) {
+        if cpu >= 0 {
+            self.hints.insert(cpu, Governor::for_influence(combined_influence(decision)));
+        }
+    }
+
+    /// Once per poll interval, switch the CPUs whose hint crossed a threshold
+    ///
+    /// A CPU that refuses a governor is not asked again until its hint changes.
+    pub fn apply(&mut self, now: Instant) -> Vec<(i32, io::Error)> {
+        let mut errors = Vec::new();
+        if !self.poll.tick(now) {
+            return errors;
+        }
+
+        let changed: Vec<(i32, Governor)> = self
+            .hints
+            .iter()
+            .filter(|&(cpu, hint)| self.applied.get(cpu) != Some(hint))
+            .map(|(&cpu, &hint)| (cpu, hint))
+            .collect();
+        for (cpu, hint) in changed {
+            if let Err(e) = self.set_governor(cpu, hint.name()) {
+                errors.push((cpu, e));
+            }
+            self.applied.insert(cpu, hint);
+        }
+        errors
+    }
+
+    /// Put every governor changed so far back as it was
+    ///
+    /// Every CPU is attempted; the first failure is returned.
+    pub fn restore(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        for (cpu, governor) in std::mem::take(&mut self.original) {
+            if let Err(e) = fs::write(self.governor_path(cpu), governor) {
+                result = result.and(Err(e));
+            }
+        }
+        self.applied.clear();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::TaskType;
+
+    fn decision(planetary_influence: f64, element_boost: f64) -> SchedulingDecision {
+        SchedulingDecision {
+            task_type: TaskType::CpuIntensive,
+            critical: false,
+            ruling_element: None,
+            priority: 100,
+            astro_priority: 100,
+            weight: 100,
+            weight_factor: 1.0,
+            reasoning: String::new(),
+            planetary_influence,
+            element_boost,
+        }
+    }
+
+    #[test]
+    fn test_governor_thresholds() {
+        assert_eq!(Governor::for_influence(1.5), Governor::Performance);
+        assert_eq!(Governor::for_influence(1.2), Governor::Schedutil);
+        assert_eq!(Governor::for_influence(1.0), Governor::Schedutil);
+        assert_eq!(Governor::for_influence(0.8), Governor::Schedutil);
+        assert_eq!(Governor::for_influence(0.5), Governor::Powersave);
+        assert_eq!(Governor::for_influence(combined_influence(&decision(-0.3, 1.5))), Governor::Powersave);
+        assert!((combined_influence(&decision(0.9, 1.5)) - 1.35).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_governors_written_and_restored() {
+        let root = std::env::temp_dir().join(format!("horoscope_energy_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for cpu in 0..3 {
+            let cpufreq = root.join(format!("cpu{cpu}")).join("cpufreq");
+            fs::create_dir_all(&cpufreq).unwrap();
+            fs::write(cpufreq.join("scaling_governor"), "ondemand\n").unwrap();
+        }
+        let governor = |cpu: i32| fs::read_to_string(root.join(format!("cpu{cpu}/cpufreq/scaling_governor"))).unwrap();
+
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut energy = EnergyManager::new(&root, Duration::from_millis(100), start);
+
+        energy.note_dispatch(0, &decision(1.0, 1.5));
+        energy.note_dispatch(1, &decision(-0.5, 1.0));
+        energy.note_dispatch(-1, &decision(1.0, 2.0));
+
+        // Nothing is written before the first poll
+        assert!(energy.apply(at(50)).is_empty());
+        assert_eq!(governor(0), "ondemand\n");
+
+        assert!(energy.apply(at(100)).is_empty());
+        assert_eq!(governor(0), "performance");
+        assert_eq!(governor(1), "powersave");
+        assert_eq!(governor(2), "ondemand\n");
+
+        // Crossing back into the neutral band switches at the next poll
+        energy.note_dispatch(0, &decision(1.0, 1.0));
+        assert!(energy.apply(at(200)).is_empty());
+        assert_eq!(governor(0), "schedutil");
+
+        // A CPU without cpufreq is reported once
+        energy.note_dispatch(7, &decision(1.0, 1.0));
+        assert_eq!(energy.apply(at(300)).len(), 1);
+        assert!(energy.apply(at(400)).is_empty());
+
+        energy.restore().unwrap();
+        assert_eq!(governor(0), "ondemand");
+        assert_eq!(governor(1), "ondemand");
+        assert_eq!(governor(2), "ondemand\n");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// How long the stars take to consult
+//
+// --profile-ephemeris casts a chart the given number of times, a minute of
+// sky apart so nothing can be reused, and reports the latency of each pass:
+// the planetary positions and, with --profile-aspects, the aspects between
+// every pair of planets and to each task type's ruler. On a latency-sensitive
+// box this tells whether an update interval, or the aspects, can be afforded
+// before the scheduler is ever loaded.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::astrology::aspects::{find_aspects, AspectCalculator};
+use crate::astrology::{calculate_planetary_positions, find_position, AstroError, TaskType, DEFAULT_ASPECT_ORB};
+
+/// Latency of one pass over every iteration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyStats {
+    pub iterations: usize,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Summarize the samples, `None` without any
+    pub fn from_samples(samples: &mut [Duration]) -> Option<Self> {
+        samples.sort_unstable();
+        let iterations = samples.len();
+        let max = *samples.last()?;
+        let total: Duration = samples.iter().sum();
+        let mean = total / u32::try_from(iterations).unwrap_or(u32::MAX);
+        Some(Self { iterations, mean, median: Self::percentile(samples, 50.0), p99: Self::percentile(samples, 99.0), max })
+    }
+
+    /// Nearest-rank percentile of sorted, non-empty samples
+    fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+        let rank = (sorted.len() as f64 * percentile / 100.0).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+impl fmt::Display for LatencyStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let micros = |d: Duration| d.as_secs_f64() * 1e6;
+        write!(
+            f,
+            "mean {:.1}μs, median {:.1}μs, p99 {:.1}μs, max {:.1}μs",
+            micros(self.mean),
+            micros(self.median),
+            micros(self.p99),
+            micros(self.max)
+        )
+    }
+}
+
+/// What casting a chart costs on this machine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EphemerisProfile {
+    pub positions: LatencyStats,
+    pub aspects: Option<LatencyStats>,  // With --profile-aspects
+}
+
+impl fmt::Display for EphemerisProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🔭 Ephemeris profile: {} iterations", self.positions.iterations)?;
+        write!(f, "  Planetary positions: {}", self.positions)?;
+        if let Some(aspects) = &self.aspects {
+            write!(f, "\n  Aspect passes:       {aspects}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Cast `iterations` charts from `start`, a minute apart, timing each pass
+pub fn profile_ephemeris(iterations: usize, aspects: bool, start: DateTime<Utc>) -> Result<EphemerisProfile, AstroError> {
+    let mut position_samples = Vec::with_capacity(iterations);
+    let mut aspect_samples = Vec::with_capacity(if aspects { iterations } else { 0 });
+    let mut minute = start;
+
+    for _ in 0..iterations {
+        let began = Instant::now();
+        let positions = calculate_planetary_positions(minute)?;
+        position_samples.push(began.elapsed());
+
+        if aspects {
+            let began = Instant::now();
+            let to_rulers: usize = TaskType::all()
+                .into_iter()
+                .filter_map(|task_type| find_position(&positions, task_type.ruling_planet()).ok())
+                .map(|ruler| find_aspects(ruler, &positions).len())
+                .sum();
+            let found = AspectCalculator::new(positions).get_aspects(DEFAULT_ASPECT_ORB).len();
+            std::hint::black_box((found, to_rulers));
+            aspect_samples.push(began.elapsed());
+        }
+        minute += chrono::Duration::minutes(1);
+    }
+
+    let none = || AstroError::EphemerisFailure("no iterations to profile".to_string());
+    Ok(EphemerisProfile {
+        positions: LatencyStats::from_samples(&mut position_samples).ok_or_else(none)?,
+        aspects: LatencyStats::from_samples(&mut aspect_samples),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_micros).collect();
+        let stats = LatencyStats::from_samples(&mut samples).unwrap();
+        assert_eq!(stats.iterations, 100);
+        assert_eq!(stats.mean, Duration::from_nanos(50_500));
+        assert_eq!(stats.median, Duration::from_micros(50));
+        assert_eq!(stats.p99, Duration::from_micros(99));
+        assert_eq!(stats.max, Duration::from_micros(100));
+
+        let single = LatencyStats::from_samples(&mut [Duration::from_micros(7)]).unwrap();
+        assert_eq!((single.median, single.p99), (Duration::from_micros(7), Duration::from_micros(7)));
+        assert_eq!(LatencyStats::from_samples(&mut []), None);
+    }
+
+    #[test]
+    fn test_profile_runs_every_iteration() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let profile = profile_ephemeris(25, true, start).unwrap();
+        let aspects = profile.aspects.unwrap();
+        assert_eq!(profile.positions.iterations, 25);
+        assert_eq!(aspects.iterations, 25);
+        for stats in [profile.positions, aspects] {
+            assert!(stats.mean.as_secs_f64().is_finite());
+            assert!(stats.median <= stats.p99 && stats.p99 <= stats.max);
+        }
+        assert!(profile.to_string().contains("25 iterations"));
+
+        assert_eq!(profile_ephemeris(3, false, start).unwrap().aspects, None);
+        assert!(profile_ephemeris(0, false, start).is_err());
+    }
+}
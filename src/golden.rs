@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Golden decision table
+//
+// Drains a fixed queue of tasks in batches and hands each one to the
+// scheduler's own `dispatch_batch`, with a stand-in for the BPF side, at a
+// frozen moment under a frozen sky, and compares what was dispatched with a
+// committed table. Any change to classification, priorities, ordering or
+// slices shows up here as a diff.
+//
+// After an intended change, regenerate the table with
+//
+//     HOROSCOPE_BLESS=1 cargo test golden
+//
+// and commit it along with the change.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeZone, Utc};
+use clap::Parser;
+
+use crate::astrology::{
+    mean_north_node, FixedEphemeris, MoonPhase, Planet, PlanetaryPosition, ZodiacSign, TASK_FLAG_RT,
+};
+use crate::batch::{self, QueueEnd};
+use crate::bpf::{DispatchedTask, QueuedTask};
+use crate::comm::TASK_COMM_LEN;
+use crate::cpu_hotplug::OnlineCpus;
+use crate::diagnostics::DecisionLog;
+use crate::retry::Dispatcher;
+use crate::{build_astro_scheduler, Kernel, Opts, Scheduler};
+
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/decisions_2025-11-19.tsv");
+
+/// Julian day of 2025-11-19T22:07:46Z
+pub const GOLDEN_JD: f64 = 2_460_999.422_060_185;
+
+pub fn golden_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 11, 19, 22, 7, 46).unwrap()
+}
+
+/// The sky at `golden_time`
+///
+/// Longitudes from the MoonTracks ephemeris (see the November 2025 test in
+/// planets.rs), latitudes rounded. Frozen here so the table doesn't move with
+/// the ephemeris code.
+pub fn golden_chart() -> Vec<PlanetaryPosition> {
+    let sun = 236.9;
+    [
+        (Planet::Sun, sun, 0.0, false),
+        (Planet::Moon, 223.0, -4.0, false),
+        (Planet::Mercury, 240.2, 2.0, true),
+        (Planet::Venus, 225.1, 1.0, false),
+        (Planet::Mars, 250.5, -1.0, false),
+        (Planet::Jupiter, 115.1, 0.0, true),
+        (Planet::Saturn, 355.2, -2.0, true),
+        (Planet::Uranus, 59.5, 0.0, true),
+    ]
+    .into_iter()
+    .map(|(planet, longitude, latitude, retrograde)| PlanetaryPosition {
+        planet,
+        longitude,
+        latitude,
+        sign: ZodiacSign::from_longitude(longitude),
+        degree_in_sign: ZodiacSign::degree_in_sign(longitude),
+        retrograde,
+        moon_phase: (planet == Planet::Moon).then(|| MoonPhase::from_angle((longitude - sun).rem_euclid(360.0))),
+    })
+    .collect()
+}
+
+/// A task as the BPF side would queue it
+pub struct Queued {
+    pub pid: i32,
+    pub comm: &'static str,
+    pub weight: u64,
+    pub is_kthread: bool,
+    pub flags: u32,
+}
+
+impl Queued {
+    /// The task as the BPF side hands it over, last run on CPU 0
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn queued_task(&self) -> QueuedTask {
+        let mut comm = [0; TASK_COMM_LEN];
+        for (c, &byte) in comm.iter_mut().zip(self.comm.as_bytes()) {
+            *c = byte as libc::c_char;
+        }
+        QueuedTask {
+            pid: self.pid,
+            cpu: 0,
+            nr_cpus_allowed: 1,
+            flags: 0,
+            start_ts: 0,
+            stop_ts: 0,
+            exec_runtime: 0,
+            weight: self.weight,
+            vtime: 0,
+            enq_cnt: 0,
+            comm,
+        }
+    }
+}
+
+const fn queued(pid: i32, comm: &'static str, weight: u64) -> Queued {
+    Queued { pid, comm, weight, is_kthread: false, flags: 0 }
+}
+
+/// One of each kind of task, plus some that test the edges
+pub fn golden_queue() -> VecDeque<Queued> {
+    VecDeque::from([
+        queued(1, "systemd", 100),
+        Queued { is_kthread: true, ..queued(12, "ksoftirqd/0", 100) },
+        Queued { is_kthread: true, ..queued(40, "kworker/0:1", 100) },
+        queued(812, "sshd", 100),
+        queued(900, "postgres", 100),
+        queued(901, "redis-server", 100),
+        queued(1203, "nginx", 100),
+        queued(1500, "Xorg", 100),
+        queued(1501, "gnome-shell", 100),
+        queued(1620, "firefox", 100),
+        queued(2001, "bash", 100),
+        queued(2002, "vim", 100),
+        queued(3100, "rustc", 100),
+        queued(3101, "rustc", 10_000),
+        queued(3102, "gcc", 1),
+        queued(3300, "java", 100),
+        queued(3400, "qemu-system-x86", 100),
+        Queued { flags: TASK_FLAG_RT, ..queued(4000, "pipewire", 100) },
+        queued(5000, "mystery-app", 100),
+    ])
+}
+
+/// Stands in for the BPF side: every task keeps its CPU, and every dispatch is remembered
+#[derive(Default)]
+struct RecordingKernel {
+    sent: Vec<DispatchedTask>,
+}
+
+impl Dispatcher for RecordingKernel {
+    type Error = ();
+
+    fn dispatch(&mut self, task: &DispatchedTask) -> Result<(), Self::Error> {
+        self.sent.push(task.clone());
+        Ok(())
+    }
+}
+
+impl Kernel for RecordingKernel {
+    fn select_cpu(&mut self, _pid: i32, prev_cpu: i32, _flags: u64) -> i32 {
+        prev_cpu
+    }
+}
+
+/// A scheduler over `kernel` under the golden sky
+///
+/// It knows which of the golden queue's tasks are kernel threads and which
+/// are realtime from the queue itself, not from whatever runs under those
+/// PIDs on this machine.
+pub fn golden_scheduler<'a, K: Kernel>(kernel: K, opts: &Opts) -> Scheduler<'a, K> {
+    let ephemeris = FixedEphemeris::new(golden_chart()).with_north_node(mean_north_node(GOLDEN_JD));
+    let astro = build_astro_scheduler(opts).unwrap().with_ephemeris(Box::new(ephemeris));
+    // An empty path is never readable, so every CPU stays online
+    let online = OnlineCpus::new("", 1, Duration::from_secs(1), Instant::now());
+    let mut sched = Scheduler::with_kernel(kernel, astro, opts.clone(), online).unwrap();
+    for task in golden_queue() {
+        sched.kthreads.remember(task.pid, task.is_kthread);
+        sched.realtime.remember(task.pid, task.flags & TASK_FLAG_RT != 0);
+    }
+    sched
+}
+
+/// Run the queue through the dispatch path and render the table
+fn run_pipeline(opts: &Opts) -> String {
+    let now = golden_time();
+    let mut queue: VecDeque<QueuedTask> = golden_queue().iter().map(Queued::queued_task).collect();
+    let mut sched = golden_scheduler(RecordingKernel::default(), opts);
+    sched.decisions = Some(DecisionLog::new(queue.len()));
+
+    let batch_size = usize::try_from(opts.batch_size).unwrap();
+    loop {
+        let (tasks, end) = batch::drain(|| Ok::<_, i32>(queue.pop_front()), batch_size);
+        sched.dispatch_batch(tasks, now);
+        if end == QueueEnd::Empty {
+            break;
+        }
+    }
+
+    let records: Vec<_> = sched.decisions.as_ref().unwrap().records().collect();
+    assert_eq!(records.len(), sched.bpf.sent.len());
+    let mut table = String::from("# order\tpid\tcomm\ttask_type\tpriority\tslice_ns\n");
+    for (order, (record, sent)) in records.into_iter().zip(&sched.bpf.sent).enumerate() {
+        assert_eq!(record.pid, sent.pid);
+        let _ = writeln!(
+            table,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            order + 1,
+            record.pid,
+            record.comm,
+            record.task_type,
+            record.priority,
+            sent.slice_ns
+        );
+    }
+    table
+}
+
+#[test]
+fn test_golden_decisions() {
+    let opts = Opts::parse_from(["scx_horoscope", "--batch-size", "8"]);
+    let actual = run_pipeline(&opts);
+
+    if std::env::var_os("HOROSCOPE_BLESS").is_some() {
+        let path = std::path::Path::new(GOLDEN_PATH);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(GOLDEN_PATH)
+        .unwrap_or_else(|e| panic!("cannot read {GOLDEN_PATH} ({e}); run with HOROSCOPE_BLESS=1 to create it"));
+    assert!(
+        actual == expected,
+        "decisions drifted from {GOLDEN_PATH}\n--- expected\n{expected}--- actual\n{actual}\
+         If the change is intended, rerun with HOROSCOPE_BLESS=1 and commit the table."
+    );
+}
+
+#[test]
+fn test_pipeline_is_deterministic() {
+    let opts = Opts::parse_from(["scx_horoscope", "--batch-size", "8"]);
+    assert_eq!(run_pipeline(&opts), run_pipeline(&opts));
+}
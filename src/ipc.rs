@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Query socket protocol
+//
+// With --socket-path the scheduler answers questions on a Unix socket:
+// horoscope-query connects, writes one JSON request line and reads one JSON
+// response line back. Every answer carries a human-readable rendering and the
+// same facts as JSON, so the client never has to know how to format the sky.
+//
+// Both ends live here, in the library: the scheduler serves the socket and
+// horoscope-query is its client.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Where the scheduler listens unless told otherwise
+pub const DEFAULT_SOCKET_PATH: &str = "/run/scx_horoscope.sock";
+
+/// How long the scheduler waits on a slow client before giving up on it
+const SERVER_IO_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Connections handled per call to `serve_pending`, so queries can't starve dispatch
+const MAX_CONNECTIONS_PER_POLL: usize = 8;
+
+/// A question for the running scheduler
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Positions,
+    Weather,
+    Stats,
+    Forecast { hours: u32 },
+    Explain { comm: String },
+    AddRule { comm: String, task_type: String },
+}
+
+/// The scheduler's answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Response {
+    Ok { text: String, data: serde_json::Value },
+    Error { message: String },
+}
+
+impl Response {
+    /// A successful answer, rendered both ways
+    pub fn ok(text: impl Into<String>, data: &impl Serialize) -> Self {
+        match serde_json::to_value(data) {
+            Ok(data) => Response::Ok { text: text.into(), data },
+            Err(e) => Response::error(format!("cannot encode answer: {e}")),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Response::Error { message: message.into() }
+    }
+}
+
+fn read_line<T: for<'de> Deserialize<'de>>(reader: &mut impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"));
+    }
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_line(writer: &mut impl Write, value: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_string(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// A connection to a running scheduler
+///
+/// The scheduler answers one request per connection.
+pub struct IpcClient {
+    stream: UnixStream,
+}
+
+impl IpcClient {
+    pub fn connect(path: &Path) -> io::Result<IpcClient> {
+        Ok(IpcClient { stream: UnixStream::connect(path)? })
+    }
+
+    /// Ask one question and wait for the answer
+    pub fn request(mut self, request: &Request) -> io::Result<Response> {
+        write_line(&mut self.stream, request)?;
+        read_line(&mut BufReader::new(self.stream))
+    }
+}
+
+/// The scheduler's end of the socket
+///
+/// Non-blocking: the dispatch loop polls it between batches. The socket file
+/// is removed again when the server is dropped.
+pub struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl IpcServer {
+    /// Listen on `path`, replacing a socket left behind by an earlier run
+    pub fn bind(path: &Path) -> io::Result<IpcServer> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(IpcServer { listener, path: path.to_path_buf() })
+    }
+
+    /// Answer the clients already waiting, returning how many were served
+    ///
+    /// A client that sends garbage gets an error response; one that stalls or
+    /// hangs up is dropped.
+    pub fn serve_pending(&self, mut handle: impl FnMut(Request) -> Response) -> io::Result<usize> {
+        let mut served = 0;
+        while served < MAX_CONNECTIONS_PER_POLL {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            };
+            served += 1;
+
+            stream.set_nonblocking(false)?;
+            stream.set_read_timeout(Some(SERVER_IO_TIMEOUT))?;
+            stream.set_write_timeout(Some(SERVER_IO_TIMEOUT))?;
+            let mut reader = BufReader::new(&stream);
+            let response = match read_line::<Request>(&mut reader) {
+                Ok(request) => handle(request),
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(format!("bad request: {e}")),
+                Err(_) => continue,
+            };
+            // The client may have given up already; nothing to do about it
+            let _ = write_line(&mut &stream, &response);
+        }
+        Ok(served)
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("horoscope_ipc_{name}_{}.sock", std::process::id()))
+    }
+
+    /// Serve until `count` clients have been answered
+    fn serve(server: &IpcServer, count: usize, mut handle: impl FnMut(Request) -> Response) {
+        let mut served = 0;
+        while served < count {
+            served += server.serve_pending(&mut handle).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_request_wire_format() {
+        let request = Request::AddRule { comm: "rustc".to_string(), task_type: "cpu".to_string() };
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, r#"{"command":"add-rule","comm":"rustc","task_type":"cpu"}"#);
+        assert_eq!(serde_json::from_str::<Request>(&json).unwrap(), request);
+        assert_eq!(serde_json::from_str::<Request>(r#"{"command":"forecast","hours":6}"#).unwrap(), Request::Forecast { hours: 6 });
+
+        let response = Response::ok("Mars in Aries", &["Mars"]);
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(json, r#"{"status":"ok","text":"Mars in Aries","data":["Mars"]}"#);
+    }
+
+    #[test]
+    fn test_client_server_round_trip() {
+        let path = socket_path("round_trip");
+        let server = IpcServer::bind(&path).unwrap();
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let answers: Vec<Response> = [Request::Stats, Request::Explain { comm: "rustc".to_string() }]
+                .iter()
+                .map(|request| IpcClient::connect(&client_path).unwrap().request(request).unwrap())
+                .collect();
+
+            // Garbage gets an error, not a hang
+            let mut raw = UnixStream::connect(&client_path).unwrap();
+            raw.write_all(b"what is my sign?\n").unwrap();
+            let garbage: Response = read_line(&mut BufReader::new(raw)).unwrap();
+            (answers, garbage)
+        });
+
+        let mut seen = Vec::new();
+        serve(&server, 3, |request| {
+            seen.push(request.clone());
+            match request {
+                Request::Explain { comm } => Response::ok(format!("{comm} is ruled by Mars"), &comm),
+                _ => Response::error("not today"),
+            }
+        });
+
+        let (answers, garbage) = client.join().unwrap();
+        assert_eq!(seen, [Request::Stats, Request::Explain { comm: "rustc".to_string() }]);
+        assert_eq!(answers[0], Response::error("not today"));
+        assert_eq!(answers[1], Response::ok("rustc is ruled by Mars", &"rustc"));
+        assert!(matches!(garbage, Response::Error { message } if message.starts_with("bad request")));
+
+        // A stale socket is replaced, and the file goes away with the server
+        drop(server);
+        assert!(!path.exists());
+        fs::write(&path, "stale").unwrap();
+        let server = IpcServer::bind(&path).unwrap();
+        assert_eq!(server.serve_pending(|_| Response::error("unused")).unwrap(), 0);
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Per-PID dispatch bookkeeping
+//
+// Counts how often each PID is dispatched within a window, so that anything
+// expensive (like reading /proc) can be spent on the tasks that matter most.
+// It also keeps each PID's virtual runtime: the CPU time it was given, scaled
+// down by its astrological priority.
+
+use std::collections::HashMap;
+
+/// PIDs whose virtual runtime is tracked before the ledger starts over
+const MAX_VTIMES: usize = 16384;
+
+/// Priority at which a nanosecond of slice costs a nanosecond of vruntime
+const VTIME_UNIT_PRIORITY: u64 = 100;
+
+#[derive(Debug, Default)]
+pub struct TaskLedger {
+    dispatches: HashMap<i32, u64>,
+    vtimes: HashMap<i32, u64>,  // Virtual runtime in weighted nanoseconds
+}
+
+impl TaskLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one dispatch of `pid`
+    pub fn record(&mut self, pid: i32) {
+        *self.dispatches.entry(pid).or_insert(0) += 1;
+    }
+
+    /// Return the `n` most-dispatched PIDs (busiest first) and start a new window
+    pub fn take_hottest(&mut self, n: usize) -> Vec<i32> {
+        let mut counts: Vec<_> = self.dispatches.drain().collect();
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts.into_iter().take(n).map(|(pid, _)| pid).collect()
+    }
+
+    /// Lowest virtual runtime of any tracked PID
+    pub fn min_vtime(&self) -> u64 {
+        self.vtimes.values().copied().min().unwrap_or(0)
+    }
+
+    /// Start tracking `pid` at the lowest vruntime, so newcomers neither
+    /// starve nor monopolize; known PIDs keep theirs
+    pub fn admit(&mut self, pid: i32) {
+        if self.vtimes.contains_key(&pid) {
+            return;
+        }
+        if self.vtimes.len() >= MAX_VTIMES {
+            self.vtimes.clear();
+        }
+        let vtime = self.min_vtime();
+        self.vtimes.insert(pid, vtime);
+    }
+
+    pub fn vtime(&self, pid: i32) -> u64 {
+        self.vtimes.get(&pid).copied().unwrap_or_else(|| self.min_vtime())
+    }
+
+    /// Charge `pid` for a slice, weighted by its priority
+    pub fn charge(&mut self, pid: i32, slice_ns: u64, priority: u32) {
+        self.admit(pid);
+        let cost = slice_ns.saturating_mul(VTIME_UNIT_PRIORITY) / u64::from(priority.max(1));
+        if let Some(vtime) = self.vtimes.get_mut(&pid) {
+            *vtime = vtime.saturating_add(cost);
+        }
+    }
+
+    /// PIDs whose virtual runtime is tracked
+    pub fn tracked(&self) -> usize {
+        self.vtimes.len()
+    }
+
+    /// Virtual runtime between the most and the least served PID
+    pub fn vtime_spread(&self) -> u64 {
+        self.vtimes.values().max().map_or(0, |max| max - self.min_vtime())
+    }
+
+    /// Stop tracking a PID that has exited
+    pub fn forget(&mut self, pid: i32) {
+        self.dispatches.remove(&pid);
+        self.vtimes.remove(&pid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_hottest() {
+        let mut ledger = TaskLedger::new();
+        for _ in 0..5 {
+            ledger.record(300);
+        }
+        for _ in 0..3 {
+            ledger.record(100);
+        }
+        ledger.record(200);
+
+        assert_eq!(ledger.take_hottest(2), vec![300, 100]);
+        // The window starts over
+        assert!(ledger.take_hottest(2).is_empty());
+    }
+
+    #[test]
+    fn test_vtime_bookkeeping() {
+        let mut ledger = TaskLedger::new();
+        ledger.charge(1, 1_000_000, 100);
+        assert_eq!(ledger.vtime(1), 1_000_000);
+
+        // A newcomer starts level with the least-served task, then pays by priority
+        ledger.charge(2, 1_000_000, 400);
+        assert_eq!(ledger.vtime(2), 1_250_000);
+        ledger.admit(3);
+        assert_eq!(ledger.vtime(3), 1_000_000);
+        ledger.charge(3, 1_000_000, 0);
+        assert_eq!(ledger.vtime(3), 101_000_000);
+
+        assert_eq!(ledger.tracked(), 3);
+        assert_eq!(ledger.vtime_spread(), 100_000_000);
+
+        ledger.forget(1);
+        assert_eq!(ledger.min_vtime(), 1_250_000);
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Levenshtein edit distance
+//
+// The fewest single-character insertions, deletions and substitutions that
+// turn one string into another. The classifier uses it to recognize command
+// names the kernel cut short at 15 bytes, or that were mistyped.
+
+/// Edit distance between `a` and `b`, counted in chars
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Distances from the prefix of `a` seen so far to every prefix of `b`
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("rustc", "rustc"), 0);
+        assert_eq!(distance("", "gcc"), 3);
+        assert_eq!(distance("gcc", ""), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        // Truncation is deletion; a transposition costs two substitutions
+        assert_eq!(distance("cloud-hyperviso", "cloud-hypervisor"), 1);
+        assert_eq!(distance("rsutc", "rustc"), 2);
+        assert_eq!(distance("gcc", "gcd"), distance("gcd", "gcc"));
+        // Chars, not bytes
+        assert_eq!(distance("café", "cafe"), 1);
+    }
+}
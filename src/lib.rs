@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// scx_horoscope's astrology engine and query protocol
+//
+// Everything the scheduler knows about the sky, with no BPF attached: the
+// scheduler builds on it, and so does horoscope-report, which needs nothing
+// else. The query socket's protocol is here too, shared by the scheduler and
+// horoscope-query.
+
+pub mod astrology;
+pub mod ipc;
+mod levenshtein;
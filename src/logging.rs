@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Rotating log files
+//
+// A scheduler left running for weeks writes a lot of prophecy. The log file
+// is rotated by size, every few hours or at midnight UTC, keeping a fixed
+// number of old files (optionally gzipped) next to the live one. Gzipping
+// happens on a thread of its own: the write that triggers a rotation only
+// waits for a rename.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+/// What makes the log file rotate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RotationTrigger {
+    Size,
+    Time,
+    Daily,
+}
+
+/// When to rotate and what to keep
+#[derive(Debug, Clone)]
+pub struct RotationPolicy {
+    pub trigger: RotationTrigger,
+    pub max_size: u64,       // Bytes, for size-based rotation
+    pub interval: Duration,  // For time-based rotation
+    pub keep: usize,         // Rotated files kept as .1 (newest) .. .N (oldest)
+    pub compress: bool,      // Gzip rotated files into .N.gz
+}
+
+/// A log file that rotates itself as it is written
+///
+/// Rotation only happens at the start of a line: a record written in several
+/// pieces never ends up split across two files.
+pub struct RotatingFileLogger {
+    path: PathBuf,
+    file: File,
+    policy: RotationPolicy,
+    written: u64,
+    opened_at: DateTime<Utc>,
+    at_line_start: bool,
+    compressing: Option<JoinHandle<io::Result<()>>>,  // Gzipping the newest rotated file
+}
+
+impl RotatingFileLogger {
+    /// Open (or continue) the log file at `path`
+    pub fn new(path: impl Into<PathBuf>, policy: RotationPolicy) -> io::Result<Self> {
+        Self::open_at(path.into(), policy, Utc::now())
+    }
+
+    fn open_at(path: PathBuf, policy: RotationPolicy, now: DateTime<Utc>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        let mut logger = Self { path, file, policy, written, opened_at: now, at_line_start: true, compressing: None };
+        // Pick up a compression that an exit cut short
+        if logger.policy.compress && logger.numbered_path(1, "").exists() {
+            logger.compress_newest();
+        }
+        Ok(logger)
+    }
+
+    fn rotation_due(&self, incoming: usize, now: DateTime<Utc>) -> bool {
+        match self.policy.trigger {
+            RotationTrigger::Size => self.written > 0 && self.written + incoming as u64 > self.policy.max_size,
+            RotationTrigger::Time => (now - self.opened_at).to_std().is_ok_and(|age| age >= self.policy.interval),
+            RotationTrigger::Daily => now.date_naive() != self.opened_at.date_naive(),
+        }
+    }
+
+    /// Name of the `index`th rotated file
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        self.numbered_path(index, if self.policy.compress { ".gz" } else { "" })
+    }
+
+    fn numbered_path(&self, index: usize, suffix: &str) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{index}{suffix}"));
+        PathBuf::from(name)
+    }
+
+    /// Gzip the plain .1 file into .1.gz in the background
+    fn compress_newest(&mut self) {
+        let (plain, compressed) = (self.numbered_path(1, ""), self.rotated_path(1));
+        self.compressing = Some(thread::spawn(move || {
+            gzip(&plain, &compressed)?;
+            fs::remove_file(&plain)
+        }));
+    }
+
+    /// Wait for the last compression, if one is running, and report how it went
+    fn wait_for_compression(&mut self) -> io::Result<()> {
+        match self.compressing.take() {
+            Some(handle) => handle.join().unwrap_or_else(|_| Err(io::Error::other("log compression panicked"))),
+            None => Ok(()),
+        }
+    }
+
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.file.flush()?;
+        // The last rotation's file has to be compressed before it moves along
+        self.wait_for_compression()?;
+
+        // Shift .1 -> .2 and so on, dropping whatever falls off the end
+        if self.policy.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            remove_if_exists(&self.rotated_path(self.policy.keep))?;
+            for index in (1..self.policy.keep).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            if self.policy.compress {
+                fs::rename(&self.path, self.numbered_path(1, ""))?;
+                self.compress_newest();
+            } else {
+                fs::rename(&self.path, self.rotated_path(1))?;
+            }
+        }
+
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.written = 0;
+        self.opened_at = now;
+        Ok(())
+    }
+
+    fn write_at(&mut self, buf: &[u8], now: DateTime<Utc>) -> io::Result<usize> {
+        if self.at_line_start && !buf.is_empty() && self.rotation_due(buf.len(), now) {
+            self.rotate(now)?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        if written > 0 {
+            self.at_line_start = buf[written - 1] == b'\n';
+        }
+        Ok(written)
+    }
+}
+
+impl Drop for RotatingFileLogger {
+    fn drop(&mut self) {
+        let _ = self.wait_for_compression();
+    }
+}
+
+impl Write for RotatingFileLogger {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, Utc::now())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn gzip(from: &Path, to: &Path) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(from)?);
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(to)?), Compression::default());
+    io::copy(&mut reader, &mut encoder)?;
+    encoder.finish()?.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("horoscope_logging_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn policy(trigger: RotationTrigger, keep: usize, compress: bool) -> RotationPolicy {
+        RotationPolicy { trigger, max_size: 100, interval: Duration::from_secs(3600), keep, compress }
+    }
+
+    fn files_in(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_count() {
+        let dir = scratch_dir("size");
+        let path = dir.join("horoscope.log");
+        let mut logger = RotatingFileLogger::new(&path, policy(RotationTrigger::Size, 3, false)).unwrap();
+
+        // 40 lines of 25 bytes, 4 lines per 100-byte file
+        for i in 0..40 {
+            writeln!(logger, "Mars enters Aries #{i:04}").unwrap();
+        }
+        logger.flush().unwrap();
+
+        assert_eq!(files_in(&dir), ["horoscope.log", "horoscope.log.1", "horoscope.log.2", "horoscope.log.3"]);
+        assert!(fs::read_to_string(&path).unwrap().ends_with("#0039\n"));
+        let newest_rotated = fs::read_to_string(dir.join("horoscope.log.1")).unwrap();
+        assert!(newest_rotated.starts_with("Mars enters Aries #0032"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_lines_are_never_split() {
+        let dir = scratch_dir("lines");
+        let path = dir.join("horoscope.log");
+        let mut logger = RotatingFileLogger::new(&path, policy(RotationTrigger::Size, 2, false)).unwrap();
+
+        // One record in many small writes, like a formatting logger
+        for _ in 0..20 {
+            for piece in ["[INFO] ", "Venus ", "trine ", "Jupiter", "\n"] {
+                logger.write_all(piece.as_bytes()).unwrap();
+            }
+        }
+
+        for name in files_in(&dir) {
+            let text = fs::read_to_string(dir.join(name)).unwrap();
+            assert!(text.lines().all(|line| line == "[INFO] Venus trine Jupiter"));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_daily_and_time_rotation() {
+        let daily_dir = scratch_dir("daily");
+        let path = daily_dir.join("horoscope.log");
+        let evening = Utc.with_ymd_and_hms(2025, 3, 20, 23, 0, 0).unwrap();
+        let mut logger = RotatingFileLogger::open_at(path.clone(), policy(RotationTrigger::Daily, 5, false), evening).unwrap();
+
+        logger.write_at(b"before midnight\n", evening + chrono::Duration::minutes(59)).unwrap();
+        assert_eq!(files_in(&daily_dir).len(), 1);
+        logger.write_at(b"after midnight\n", evening + chrono::Duration::minutes(61)).unwrap();
+        assert_eq!(files_in(&daily_dir), ["horoscope.log", "horoscope.log.1"]);
+
+        let dir = scratch_dir("time");
+        let path = dir.join("horoscope.log");
+        let mut logger = RotatingFileLogger::open_at(path, policy(RotationTrigger::Time, 5, false), evening).unwrap();
+        logger.write_at(b"first\n", evening + chrono::Duration::minutes(30)).unwrap();
+        logger.write_at(b"second\n", evening + chrono::Duration::minutes(90)).unwrap();
+        logger.write_at(b"third\n", evening + chrono::Duration::minutes(100)).unwrap();
+        assert_eq!(files_in(&dir), ["horoscope.log", "horoscope.log.1"]);
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&daily_dir).unwrap();
+    }
+
+    #[test]
+    fn test_compressed_rotation() {
+        let dir = scratch_dir("gzip");
+        let path = dir.join("horoscope.log");
+        let mut logger = RotatingFileLogger::new(&path, policy(RotationTrigger::Size, 2, true)).unwrap();
+
+        for i in 0..20 {
+            writeln!(logger, "Saturn returns, cycle #{i:04}").unwrap();
+        }
+        drop(logger);
+
+        assert_eq!(files_in(&dir), ["horoscope.log", "horoscope.log.1.gz", "horoscope.log.2.gz"]);
+        let mut text = String::new();
+        GzDecoder::new(File::open(dir.join("horoscope.log.1.gz")).unwrap()).read_to_string(&mut text).unwrap();
+        assert!(text.starts_with("Saturn returns"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_compression_is_resumed() {
+        let dir = scratch_dir("resume");
+        let path = dir.join("horoscope.log");
+
+        // Rotated, but the scheduler exited before the .1 file was gzipped
+        fs::write(dir.join("horoscope.log.1"), "Pluto stations direct\n").unwrap();
+        drop(RotatingFileLogger::new(&path, policy(RotationTrigger::Size, 2, true)).unwrap());
+
+        assert_eq!(files_in(&dir), ["horoscope.log", "horoscope.log.1.gz"]);
+        let mut text = String::new();
+        GzDecoder::new(File::open(dir.join("horoscope.log.1.gz")).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text, "Pluto stations direct\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
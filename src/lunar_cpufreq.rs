@@ -0,0 +1,216 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Energy-performance preferences by the phase of the Moon
+//
+// The Full Moon is no time to hold back and the New Moon no time to push.
+// With --lunar-cpufreq each CPU's energy_performance_preference asks for
+// performance while the Moon is full and balance_power while it is new; the
+// rest of the month the preferences the system had are put back, as they are
+// on exit. The Moon is consulted from the housekeeping loop, never while
+// dispatching. CPUs without the file (no intel_pstate or amd_pstate in
+// active mode) are left alone, and a CPU that refuses a write is not asked
+// again.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::astrology::MoonPhase;
+use crate::stats::StatsTicker;
+
+const SYSFS_CPU: &str = "/sys/devices/system/cpu";
+
+/// How often the Moon is looked at again; a phase lasts days
+pub const LUNAR_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An energy-performance preference the Moon can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EppHint {
+    Performance,
+    BalancePower,
+}
+
+impl EppHint {
+    /// Preference for a Moon phase; `None` leaves the system's own
+    pub fn for_phase(phase: MoonPhase) -> Option<Self> {
+        match phase {
+            MoonPhase::FullMoon => Some(EppHint::Performance),
+            MoonPhase::NewMoon => Some(EppHint::BalancePower),
+            _ => None,
+        }
+    }
+
+    /// Name as written to `energy_performance_preference`
+    pub fn name(self) -> &'static str {
+        match self {
+            EppHint::Performance => "performance",
+            EppHint::BalancePower => "balance_power",
+        }
+    }
+}
+
+/// Steers each CPU's energy-performance preference by the Moon
+pub struct LunarCpufreq {
+    root: PathBuf,                       // Directory holding cpu<N>/cpufreq
+    poll: StatsTicker,
+    checked: bool,                       // The Moon has been looked at once
+    cpus: Vec<usize>,                    // CPUs still taking hints
+    applied: Option<EppHint>,
+    original: BTreeMap<usize, String>,   // Preferences to restore
+}
+
+impl LunarCpufreq {
+    /// Manage the preferences of CPUs under `root` (normally `/sys/devices/system/cpu`) that have one
+    pub fn new(root: impl Into<PathBuf>, nr_cpus: usize, poll_interval: Duration, start: Instant) -> Self {
+        let root = root.into();
+        let cpus = (0..nr_cpus).filter(|&cpu| Self::path_in(&root, cpu).exists()).collect();
+        Self {
+            root,
+            poll: StatsTicker::new(poll_interval, start),
+            checked: false,
+            cpus,
+            applied: None,
+            original: BTreeMap::new(),
+        }
+    }
+
+    pub fn from_sysfs(nr_cpus: usize, start: Instant) -> Self {
+        Self::new(SYSFS_CPU, nr_cpus, LUNAR_POLL_INTERVAL, start)
+    }
+
+    fn path_in(root: &Path, cpu: usize) -> PathBuf {
+        root.join(format!("cpu{cpu}")).join("cpufreq").join("energy_performance_preference")
+    }
+
+    /// CPUs whose preference can be steered
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+
+    /// The preference in force, `None` while the system's own apply
+    pub fn applied(&self) -> Option<EppHint> {
+        self.applied
+    }
+
+    /// True the first time, then once per poll interval
+    pub fn due(&mut self, now: Instant) -> bool {
+        let first = !std::mem::replace(&mut self.checked, true);
+        self.poll.tick(now) || first
+    }
+
+    /// Write the preference for `phase` if it differs from the one in force
+    ///
+    /// A CPU whose preference cannot be read or written is reported and
+    /// dropped.
+    pub fn apply(&mut self, phase: MoonPhase) -> Vec<(usize, io::Error)> {
+        let hint = EppHint::for_phase(phase);
+        if hint == self.applied {
+            return Vec::new();
+        }
+        self.applied = hint;
+        let Some(hint) = hint else {
+            return self.put_back();
+        };
+
+        let mut errors = Vec::new();
+        let mut kept = Vec::with_capacity(self.cpus.len());
+        for cpu in std::mem::take(&mut self.cpus) {
+            match self.write(cpu, hint) {
+                Ok(()) => kept.push(cpu),
+                Err(e) => errors.push((cpu, e)),
+            }
+        }
+        self.cpus = kept;
+        errors
+    }
+
+    /// Write `hint` for `cpu`, remembering the preference it replaces the first time
+    fn write(&mut self, cpu: usize, hint: EppHint) -> io::Result<()> {
+        let path = Self::path_in(&self.root, cpu);
+        if let Entry::Vacant(entry) = self.original.entry(cpu) {
+            entry.insert(fs::read_to_string(&path)?.trim().to_string());
+        }
+        fs::write(&path, hint.name()).inspect_err(|_| {
+            self.original.remove(&cpu);
+        })
+    }
+
+    /// Write back every preference changed so far
+    fn put_back(&mut self) -> Vec<(usize, io::Error)> {
+        std::mem::take(&mut self.original)
+            .into_iter()
+            .filter_map(|(cpu, preference)| fs::write(Self::path_in(&self.root, cpu), preference).err().map(|e| (cpu, e)))
+            .collect()
+    }
+
+    /// Put every preference changed so far back as it was
+    ///
+    /// Every CPU is attempted; the first failure is returned.
+    pub fn restore(&mut self) -> io::Result<()> {
+        self.applied = None;
+        match self.put_back().into_iter().next() {
+            Some((_, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_for_phase() {
+        assert_eq!(EppHint::for_phase(MoonPhase::FullMoon), Some(EppHint::Performance));
+        assert_eq!(EppHint::for_phase(MoonPhase::NewMoon), Some(EppHint::BalancePower));
+        let defaults = MoonPhase::all().into_iter().filter(|&phase| EppHint::for_phase(phase).is_none()).count();
+        assert_eq!(defaults, 6);
+        assert_eq!(EppHint::BalancePower.name(), "balance_power");
+    }
+
+    #[test]
+    fn test_preferences_written_and_restored() {
+        let root = std::env::temp_dir().join(format!("horoscope_lunar_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for cpu in 0..4 {
+            fs::create_dir_all(root.join(format!("cpu{cpu}/cpufreq"))).unwrap();
+        }
+        fs::write(LunarCpufreq::path_in(&root, 0), "balance_performance\n").unwrap();
+        fs::write(LunarCpufreq::path_in(&root, 1), "default\n").unwrap();
+        // CPU 2 has no preference at all; CPU 3's cannot be read
+        fs::create_dir_all(LunarCpufreq::path_in(&root, 3)).unwrap();
+        let preference = |cpu: usize| fs::read_to_string(LunarCpufreq::path_in(&root, cpu)).unwrap();
+
+        let start = Instant::now();
+        let mut lunar = LunarCpufreq::new(&root, 4, Duration::from_secs(60), start);
+        assert_eq!(lunar.cpus(), &[0, 1, 3]);
+        assert!(lunar.due(start));
+        assert!(!lunar.due(start + Duration::from_secs(30)));
+        assert!(lunar.due(start + Duration::from_secs(60)));
+
+        // The Full Moon: CPU 3 is reported once and dropped
+        let errors = lunar.apply(MoonPhase::FullMoon);
+        assert_eq!(errors.iter().map(|(cpu, _)| *cpu).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(lunar.cpus(), &[0, 1]);
+        assert_eq!(preference(0), "performance");
+        assert_eq!(preference(1), "performance");
+        assert!(lunar.apply(MoonPhase::FullMoon).is_empty());
+
+        // Waning: back to what the system had
+        assert!(lunar.apply(MoonPhase::WaningGibbous).is_empty());
+        assert_eq!(lunar.applied(), None);
+        assert_eq!(preference(0), "balance_performance");
+        assert_eq!(preference(1), "default");
+
+        // The New Moon, then shutdown
+        assert!(lunar.apply(MoonPhase::NewMoon).is_empty());
+        assert_eq!(preference(0), "balance_power");
+        lunar.restore().unwrap();
+        assert_eq!(preference(0), "balance_performance");
+        assert_eq!(preference(1), "default");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -5,7 +5,50 @@
 // An experimental sched_ext scheduler that makes scheduling decisions based on
 // real-time planetary positions, zodiac signs, and astrological principles.
 
-mod astrology;
+#[cfg(test)]
+mod alloc_count;
+mod backpressure;
+mod batch;
+mod bpf_hooks;
+mod bpf_hooks_skel;
+mod caution;
+mod comm;
+mod compat;
+mod cpu_affinity;
+mod cpu_fallback;
+mod cpu_hotplug;
+mod cpu_idle;
+mod cpu_numa;
+mod cpu_pools;
+mod cpu_smt;
+mod cpu_zodiac;
+mod diagnostics;
+mod dry_run;
+mod energy;
+mod ephemeris_profile;
+#[cfg(test)]
+mod golden;
+mod ledger;
+mod logging;
+mod lunar_cpufreq;
+mod map_dump;
+mod memo;
+mod metrics;
+mod nice_map;
+mod probe;
+mod query;
+mod restart;
+mod retrograde_grace;
+mod retry;
+mod schedule_optimizer;
+mod self_pin;
+mod shutdown;
+mod slice;
+mod starvation;
+mod stats;
+mod void_moon;
+mod wakeup;
+mod watchdog;
 
 mod bpf_skel;
 pub use bpf_skel::*;
@@ -13,228 +56,2275 @@ pub mod bpf_intf;
 
 #[rustfmt::skip]
 mod bpf;
-use bpf::{BpfScheduler, DispatchedTask, RL_CPU_ANY};
+use bpf::{BpfScheduler, DispatchedTask, QueuedTask, RL_CPU_ANY};
 
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use libbpf_rs::OpenObject;
-use log::{info, debug, error};
+use log::{info, debug, error, warn};
+use scx_horoscope::astrology;
+use scx_horoscope::ipc;
+use scx_stats::StatsServer;
 use scx_utils::libbpf_clap_opts::LibbpfOpts;
-use scx_utils::UserExitInfo;
-use simplelog::{Config, LevelFilter, TermLogger, TerminalMode, ColorChoice};
+use scx_utils::{Cpumask, UserExitInfo};
+use serde::Serialize;
+use simplelog::{CombinedLogger, Config, LevelFilter, TermLogger, TerminalMode, ColorChoice, WriteLogger};
 use std::mem::MaybeUninit;
-use std::time::SystemTime;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
 
-use astrology::AstrologicalScheduler;
+use astrology::{find_position, AstroError, AstrologicalScheduler, Chart, CosmicRng, CriticalPolicy, Element, Planet, RulershipMap, SchedulingDecision, TaskClassifier, TaskType, ZodiacSign, TASK_FLAG_RT};
+use backpressure::{Backpressure, Mode};
+use batch::{BatchStats, Fairness, IterationBudget, QueueEnd};
+use bpf_hooks::SyscallBoosts;
+use caution::Caution;
+use comm::Comm;
+use cpu_fallback::{CpuFallback, FallbackStats};
+use cpu_hotplug::OnlineCpus;
+use cpu_idle::CpuIdleTracker;
+use cpu_numa::{NumaPlacement, NumaTopology};
+use cpu_pools::{parse_cpu_pool, CpuPools};
+use cpu_smt::{prefer_smt_cpu, SmtTopology};
+use cpu_zodiac::{place_task, CpuZodiac};
+use diagnostics::{BpfCounters, DecisionLog, DiagnosticReport, ReportFormat, RECENT_DECISIONS};
+use energy::EnergyManager;
+use ipc::{IpcServer, DEFAULT_SOCKET_PATH};
+use logging::{RotatingFileLogger, RotationPolicy, RotationTrigger};
+use lunar_cpufreq::LunarCpufreq;
+use ledger::TaskLedger;
+use map_dump::{DumpFormat, MapDumper};
+use memo::{DecisionMemo, MemoKey, DECISION_MEMO_SIZE};
+use metrics::{Metrics, METRICS_VERSION};
+use nice_map::{parse_nice_entry, NiceMap};
+use query::StatsSnapshot;
+use restart::{RestartDecision, RestartPolicy};
+use retry::{DispatchOutcome, Dispatcher, RetryQueue, RetryStats, RETRY_QUEUE_CAPACITY};
+use probe::{
+    FdSockets, KthreadCache, ProbeEvent, ProbeHandle, RealtimeCache, Sampler, StartTimeCache, StatmRss, ThresholdProbe,
+};
+use schedule_optimizer::optimize_opts;
+use shutdown::{ExitSummary, RunTally, SHUTDOWN};
+use slice::SlicePersonality;
+use starvation::StarvationGuard;
+use stats::{PeriodicSnapshot, PeriodicStats, SchedulerStats, StatsTicker};
+use void_moon::VoidMoonPenalty;
+use retrograde_grace::RetrogradeGrace;
+use wakeup::{WakeupChain, WakeupTable};
+use watchdog::{Heartbeat, SharedCounter, Watchdog};
+
+// Maximum number of wakees tracked for the pair boost
+const WAKEUP_TABLE_SIZE: usize = 1024;
 
 /// An astrological `sched_ext` scheduler
-#[derive(Debug, Clone, Parser)]
+#[derive(Debug, Clone, Parser, Serialize)]
 #[allow(clippy::struct_excessive_bools)]
-struct Opts {
+pub(crate) struct Opts {
+    /// Name to register the scheduler with the kernel as: up to 16 letters, digits and underscores
+    #[clap(long, value_name = "NAME", default_value = "horoscope")]
+    scheduler_name: String,
+
     /// Time slice duration for tasks in microseconds
     #[clap(short = 's', long, default_value = "5000")]
     slice_us: u64,
 
-    /// Minimum time slice in microseconds
-    #[clap(long, default_value = "500")]
-    slice_us_min: u64,
+    /// Minimum time slice in microseconds
+    #[clap(long, default_value = "500")]
+    slice_us_min: u64,
+
+    /// Maximum time slice in microseconds, after every boost, critical tasks included (default: --slice-us)
+    #[clap(long, value_name = "US", visible_alias = "max-slice-us")]
+    slice_us_max: Option<u64>,
+
+    /// Fixed time slice for critical tasks in microseconds, so they run first but yield early
+    #[clap(long, value_name = "US")]
+    critical_slice_us: Option<u64>,
+
+    /// Tasks drained from the queue and ordered by priority before dispatching
+    #[clap(long, value_name = "N", default_value = "64", value_parser = clap::value_parser!(u64).range(1..))]
+    batch_size: u64,
+
+    /// Most tasks drained per dispatch iteration before yielding back to the BPF side
+    #[clap(long, value_name = "N", default_value = "256", value_parser = clap::value_parser!(u64).range(1..))]
+    dispatch_budget: u64,
+
+    /// Also yield once a dispatch iteration has run this long, in microseconds
+    #[clap(long, value_name = "US", value_parser = clap::value_parser!(u64).range(1..))]
+    dispatch_budget_us: Option<u64>,
+
+    /// Past this many queued tasks, decide from a per-type table until the queue falls to half of it
+    #[clap(long, value_name = "TASKS", value_parser = clap::value_parser!(u64).range(1..))]
+    backpressure_threshold: Option<u64>,
+
+    /// Once the chart's tension score (0-100) reaches this, cut slices to 75% and stop sorting batches, until it falls 10 below
+    #[clap(long, value_name = "SCORE", value_parser = clap::value_parser!(u32).range(1..=100))]
+    caution_threshold: Option<u32>,
+
+    /// Boost a task's priority for one dispatch after it makes one of these syscalls, e.g. sendmsg,recvmsg
+    #[clap(long, value_name = "SYSCALL_LIST", value_delimiter = ',')]
+    prio_boost_system_calls: Vec<String>,
+
+    /// Priority multiplier for tasks caught by --prio-boost-system-calls
+    #[clap(long, value_name = "FACTOR", default_value = "1.1", value_parser = parse_syscall_boost_factor)]
+    syscall_boost_factor: f64,
+
+    /// Order of each batch after Critical tasks: by priority, or by priority-weighted virtual runtime
+    #[clap(long, value_enum, default_value = "priority")]
+    fairness: Fairness,
+
+    /// Shuffle tasks of equal priority by --seed and the Julian day, instead of leaving them in queue order
+    #[clap(long)]
+    cosmic_tiebreak: bool,
+
+    /// Seed every random choice (out-of-bounds jitter, the --cosmic-tiebreak shuffle, --update-jitter), so a run can be repeated
+    #[clap(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// Log every decision and slice, but dispatch tasks with the default slice on the kernel's CPU
+    #[clap(long, conflicts_with_all = ["energy_aware", "lunar_cpufreq", "cpu_nice_map"])]
+    dispatch_dry_run: bool,
+
+    /// Consecutive failed dispatches tolerated before the scheduler gives up and exits
+    #[clap(long, value_name = "N", default_value = "100", value_parser = clap::value_parser!(u32).range(1..))]
+    max_dispatch_failures: u32,
+
+    /// Dispatch any task waiting longer than this first, with at least the minimum slice, in milliseconds
+    #[clap(long, value_name = "MS", default_value = "100", value_parser = clap::value_parser!(u64).range(1..))]
+    starvation_threshold_ms: u64,
+
+    /// Enable verbose logging
+    #[clap(short = 'v', long)]
+    verbose: bool,
+
+    /// How often verbose statistics are printed, in milliseconds
+    #[clap(long, value_name = "MS", default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+    stats_interval_ms: u64,
+
+    /// Print a one-line summary every SECS seconds, with or without --verbose (0 = off)
+    #[clap(long = "stats", value_name = "SECS", default_value = "0")]
+    stats_secs: u64,
+
+    /// Show cosmic weather report on startup
+    #[clap(short = 'w', long)]
+    cosmic_weather: bool,
+
+    /// Log the Sabian symbol of the Sun's degree at startup, and again each time the Sun moves on a degree
+    #[clap(long)]
+    daily_message: bool,
+
+    /// Update planetary positions every N seconds
+    #[clap(short = 'u', long, default_value = "60")]
+    update_interval: u64,
+
+    /// Refresh each chart up to PERCENT of the update interval early or late, at random
+    #[clap(long, value_name = "PERCENT", default_value = "0", value_parser = clap::value_parser!(u8).range(0..=50))]
+    update_jitter: u8,
+
+    /// Print scheduling decisions for debugging
+    #[clap(short = 'd', long)]
+    debug_decisions: bool,
+
+    /// Disable retrograde effects (boring mode)
+    #[clap(long)]
+    no_retrograde: bool,
+
+    /// Influence of a direct planet in its weakest element; element bonuses add on top
+    #[clap(long, default_value = "1.0", value_parser = parse_influence_baseline)]
+    influence_baseline: f64,
+
+    /// Influence of a retrograde planet, from -1.0 (full penalty) to 1.0 (no penalty)
+    #[clap(long, default_value = "-1.0", allow_negative_numbers = true, value_parser = parse_retrograde_influence)]
+    retrograde_influence: f64,
+
+    /// Influence of one planet when retrograde, overriding --retrograde-influence, e.g. mercury=-1.0 (repeatable)
+    #[clap(long, value_name = "PLANET=F", allow_hyphen_values = true, value_parser = parse_planet_retrograde_influence)]
+    planet_retrograde_influence: Vec<(Planet, f64)>,
+
+    /// Slice multiplier for tasks under a retrograde ruler, 0.1-1.0 (tuned live by --auto-tune)
+    #[clap(long = "retrograde-slice-factor", value_name = "FACTOR", default_value = "0.5", value_parser = parse_slice_factor)]
+    retrograde_factor: f64,
+
+    /// Ease tasks already running when their ruler turns retrograde into the slice penalty over SECS seconds (0 = off)
+    #[clap(long, value_name = "SECS", default_value = "0")]
+    retrograde_grace_secs: u64,
+
+    /// Slice multiplier, 0.1-1.0, for tasks first dispatched while the Moon is void of course
+    #[clap(long, value_name = "FACTOR", value_parser = parse_slice_factor)]
+    void_moon_penalty: Option<f64>,
+
+    /// Task types spared the retrograde penalty, e.g. system,network (their ruler counts as direct)
+    #[clap(long, value_name = "TYPE,...", value_delimiter = ',')]
+    retrograde_exempt: Vec<TaskType>,
+
+    /// Disable the priority jitter for tasks whose ruling planet is out of bounds
+    #[clap(long)]
+    no_out_of_bounds_jitter: bool,
+
+    /// Ignore planetary aspects (trines, squares...) to the ruling planet
+    #[clap(long)]
+    no_aspects: bool,
+
+    /// Ignore the Behenian fixed stars (Regulus, Algol...) the ruling planet stands on
+    #[clap(long)]
+    no_fixed_stars: bool,
+
+    /// Share of the priority driven by the task's nice weight (0.0 = stars only, 1.0 = full weight)
+    #[clap(long, default_value = "0.5", value_parser = parse_unit_interval)]
+    weight_blend: f64,
+
+    /// Priority multiplier for a task queued right behind a task in a compatible element (1.0 = off)
+    #[clap(long, value_name = "FACTOR", default_value = "1.0", value_parser = parse_pair_boost)]
+    pair_boost: f64,
+
+    /// How long a wakeup pair boost lasts, in milliseconds
+    #[clap(long, default_value = "50")]
+    pair_boost_ttl_ms: u64,
+
+    /// Priority multiplier for a task dispatched right after an Interactive one, halving with each further hop (1.0 = off)
+    #[clap(long, value_name = "FACTOR", default_value = "1.0")]
+    chain_boost: f64,
+
+    /// How soon after the task before it a task must be dispatched to count as woken by it, in milliseconds
+    #[clap(long, value_name = "MS", default_value = "10")]
+    chain_boost_window_ms: u64,
+
+    /// How many hops from an Interactive task a wakeup chain boost reaches
+    #[clap(long, value_name = "N", default_value = "2", value_parser = clap::value_parser!(u32).range(1..=8))]
+    chain_boost_hops: u32,
+
+    /// Give each CPU a zodiac sign and prefer cores in the task's favored element
+    #[clap(long)]
+    cpu_personalities: bool,
+
+    /// Signs to cycle across CPUs, e.g. aries,cancer (implies --cpu-personalities)
+    #[clap(long, value_name = "SIGN,...", value_delimiter = ',')]
+    cpu_zodiac: Vec<ZodiacSign>,
+
+    /// Send an element's tasks to idle CPUs from this pool, e.g. fire=0-7 (repeatable)
+    #[clap(long, value_name = "ELEMENT=CPULIST", value_parser = parse_cpu_pool)]
+    #[serde(serialize_with = "diagnostics::serialize_cpu_pools")]
+    cpu_pool: Vec<(Element, Cpumask)>,
+
+    /// Where a task goes when the kernel finds no CPU for it: its previous CPU, any CPU, or an idle one first
+    #[clap(long, value_enum, default_value = "any")]
+    cpu_fallback: CpuFallback,
+
+    /// Never let CPU-Intensive (Fire) tasks share a physical core with each other, even under heavy load
+    #[clap(long)]
+    no_smt_for_fire: bool,
+
+    /// How long a Memory-Heavy task waits for its NUMA node to free a CPU before crossing to another
+    #[clap(long, value_name = "MS", default_value = "10")]
+    numa_patience_ms: u64,
+
+    /// Steer each CPU's cpufreq governor by the planetary influence of its tasks
+    #[clap(long)]
+    energy_aware: bool,
+
+    /// Put the original cpufreq governors back when the scheduler exits
+    #[clap(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    energy_governor_restore_on_exit: bool,
+
+    /// Milliseconds between re-evaluations of the governor hints
+    #[clap(long, value_name = "MS", default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+    energy_poll_interval: u64,
+
+    /// Ask for performance from each CPU's energy-performance preference at the Full Moon, and balance_power at the New Moon
+    #[clap(long)]
+    lunar_cpufreq: bool,
+
+    /// Shape slices by the element of the ruling planet, e.g. fire=throughput,air=latency (priority is untouched)
+    #[clap(long, value_name = "ELEMENT=PERSONALITY", value_delimiter = ',', value_parser = slice::parse_element_slice_entry)]
+    element_slice: Vec<(Element, SlicePersonality)>,
+
+    /// Renice each task by the element of its ruling planet, e.g. fire=-5,earth=0,air=5,water=10
+    #[clap(long, value_name = "ELEMENT=NICE", value_delimiter = ',', allow_hyphen_values = true, value_parser = parse_nice_entry)]
+    cpu_nice_map: Vec<(Element, i8)>,
+
+    /// Check for `CAP_SYS_NICE` before renicing, and skip --cpu-nice-map without it
+    #[clap(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    nice_map_require_cap_sys_nice: bool,
+
+    /// Pin the dispatch thread to this CPU, and keep Fire tasks looking for idle CPUs off it
+    #[clap(long, value_name = "N")]
+    self_cpu: Option<usize>,
+
+    /// Run the dispatch thread under `SCHED_FIFO` so busy tasks can't starve it
+    #[clap(long)]
+    self_rt: bool,
+
+    /// Restarts in a row, each waiting longer, before giving up; a run of over a minute starts the count afresh
+    #[clap(long, default_value = "10", value_name = "N")]
+    max_restarts: u32,
+
+    /// Sample the RSS of the busiest tasks and treat big ones as Memory-Heavy
+    #[clap(long)]
+    probe_memory: bool,
+
+    /// RSS above which a probed task becomes Memory-Heavy, in MiB
+    #[clap(long, default_value = "2048")]
+    probe_memory_threshold_mb: u64,
+
+    /// Count the open sockets of the busiest tasks and treat well-connected ones as Network
+    #[clap(long)]
+    probe_network: bool,
+
+    /// Open sockets at which a probed task becomes Network
+    #[clap(long, default_value = "32")]
+    probe_network_threshold: u64,
+
+    /// Probe rounds below the socket threshold before a task stops being Network
+    #[clap(long, default_value = "5")]
+    probe_network_decay: u32,
+
+    /// How often the busiest tasks are probed, in milliseconds
+    #[clap(long, default_value = "1000")]
+    probe_interval_ms: u64,
+
+    /// Number of busiest tasks probed per interval
+    #[clap(long, default_value = "16")]
+    probe_top: usize,
+
+    /// Dump the contents of the BPF maps every N seconds (diagnostics)
+    #[clap(long, value_name = "INTERVAL")]
+    bpf_map_dump: Option<u64>,
+
+    /// File the BPF map dumps are appended to
+    #[clap(long, value_name = "PATH", default_value = "horoscope_bpf_maps.log")]
+    bpf_dump_file: PathBuf,
+
+    /// Format of the BPF map dumps
+    #[clap(long, value_enum, default_value = "json")]
+    bpf_dump_format: DumpFormat,
+
+    /// Answer horoscope-query on this Unix socket (default path if none is given)
+    #[clap(long, value_name = "PATH", num_args = 0..=1, default_missing_value = DEFAULT_SOCKET_PATH)]
+    socket_path: Option<PathBuf>,
+
+    /// Write a diagnostic report to `horoscope_diagnostic_<timestamp>.<ext>` after the first dispatch cycle
+    #[clap(long)]
+    diagnostic_report: bool,
+
+    /// Format of the diagnostic report
+    #[clap(long, value_enum, default_value = "text")]
+    diagnostic_report_format: ReportFormat,
+
+    /// On exit, write a JSON summary of the run (dispatches per task type, time under each retrograde...) to PATH
+    #[clap(long, value_name = "PATH")]
+    summary_file: Option<PathBuf>,
+
+    /// Print the metrics of the running scheduler every SECS seconds (default 1), instead of scheduling
+    #[clap(long, value_name = "SECS", num_args = 0..=1, default_missing_value = "1.0")]
+    monitor: Option<f64>,
+
+    /// scx_stats socket to serve metrics on, and for --monitor to read them from (default: /var/run/scx/root/stats)
+    #[clap(long, value_name = "PATH")]
+    stats_socket: Option<PathBuf>,
+
+    /// Also write the log to this file
+    #[clap(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// What rotates the log file
+    #[clap(long, value_enum, default_value = "size", requires = "log_file")]
+    log_rotation_trigger: RotationTrigger,
+
+    /// Rotate the log file once it would grow beyond this size (size trigger)
+    #[clap(long, value_name = "MB", default_value = "64", requires = "log_file")]
+    log_max_size_mb: u64,
+
+    /// Rotate the log file after this many hours (time trigger)
+    #[clap(long, value_name = "HOURS", default_value = "24", requires = "log_file")]
+    log_rotation_hours: u64,
+
+    /// Number of rotated log files to keep
+    #[clap(long, value_name = "N", default_value = "5", requires = "log_file")]
+    log_rotation_count: usize,
+
+    /// Gzip rotated log files
+    #[clap(long, requires = "log_file")]
+    log_compress_rotated: bool,
+
+    /// Warn when the dispatch loop makes no progress for this many seconds
+    #[clap(long, value_name = "SECS")]
+    watchdog_timeout: Option<u64>,
+
+    /// Restart the scheduler when the watchdog detects a stall
+    #[clap(long, requires = "watchdog_timeout")]
+    watchdog_restart: bool,
+
+    /// Exit when a stall lasts this many seconds, so `sched_ext` falls back to the default scheduler
+    #[clap(long, value_name = "SECS", requires = "watchdog_timeout")]
+    watchdog_deadline: Option<u64>,
+
+    /// Periodically retune the slice, retrograde penalty and base priorities from observed stats
+    #[clap(long)]
+    auto_tune: bool,
+
+    /// Seconds of observation between auto-tuning rounds
+    #[clap(long, value_name = "SECS", default_value = "300")]
+    auto_tune_interval: u64,
+
+    /// Base priority overrides per task type (tuned live by --auto-tune)
+    #[clap(skip)]
+    base_priority: Vec<(TaskType, u32)>,
+
+    /// Always give tasks with this command name full priority (repeatable)
+    #[clap(long, value_name = "COMM")]
+    critical_comm: Vec<String>,
+
+    /// Favor tasks whose ruling planet stands in the element of their sun sign (the Sun's sign at launch)
+    #[clap(long)]
+    sun_sign_affinity: bool,
+
+    /// Send a task next to the CPU of the one dispatched before it when their composite chart is harmonious
+    #[clap(long)]
+    enable_process_affinity: bool,
+
+    /// Prefer an idle CPU on which the task's ruling planet was angular in its process's natal chart
+    #[clap(long)]
+    enable_astrocartography: bool,
+
+    /// Watch for world events: a Jupiter-Saturn great conjunction rebalances every CPU, their square halves `MemoryHeavy` tasks for a day
+    #[clap(long)]
+    mundane_astrology: bool,
+
+    /// Progress each process's natal chart by solar arc, one day of solar motion per SECONDS of runtime, and log its lifecycle events
+    #[clap(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    progressed_day_unit: Option<u64>,
+
+    /// Give the Moon phase boost only to tasks a rule classified as Interactive, not to unknown tasks
+    #[clap(long)]
+    unknown_no_moon: bool,
+
+    /// Let the stars judge core kernel threads (ksoftirqd, migration, rcu_*...) too
+    #[clap(long)]
+    no_critical_kthreads: bool,
+
+    /// Let the stars judge tasks that switched to a real-time policy too
+    #[clap(long)]
+    no_critical_rt: bool,
+
+    /// Reassign a task type to a different ruling planet, e.g. system=sun (repeatable)
+    #[clap(long, value_name = "TYPE=PLANET", value_parser = parse_rulership)]
+    rulership: Vec<(TaskType, Planet)>,
+
+    /// Classify tasks with a JSON ruleset (as written by --dump-classifier) instead of the builtin one
+    #[clap(long, value_name = "PATH")]
+    classifier_file: Option<PathBuf>,
+
+    /// Classify a task no rule matches by the closest pattern within this many edits (0 disables)
+    #[clap(long, value_name = "N", default_value = "0")]
+    fuzzy_match_distance: usize,
+
+    /// Classify a task no rule matches by astrological keywords in its command line, e.g. python3 run_network_monitor.py
+    #[clap(long)]
+    keyword_classify: bool,
+
+    /// Schedule the first N tasks at their base priority, before the first chart is cast (0 disables)
+    #[clap(long, value_name = "N", default_value = "0")]
+    warmup_dispatches: u64,
+
+    /// Write the effective classifier ruleset as JSON (to PATH, or stdout) and exit
+    #[clap(long, value_name = "PATH", num_args = 0..=1)]
+    #[allow(clippy::option_option)]
+    dump_classifier: Option<Option<PathBuf>>,
+
+    /// List the zodiac signs, their elements and the moon phases, then exit
+    #[clap(long)]
+    list_signs: bool,
+
+    /// List the planets, their domains and the task types they rule, then exit
+    #[clap(long)]
+    list_planets: bool,
+
+    /// Compare the scheduling conditions at two RFC 3339 timestamps (A,B) and exit
+    #[clap(long, value_name = "RFC3339,RFC3339", value_parser = parse_time_pair)]
+    #[serde(serialize_with = "diagnostics::serialize_time_pair")]
+    compare_times: Option<(DateTime<Utc>, DateTime<Utc>)>,
+
+    /// Print today's planetary hours for a task type, marking the lucky and unlucky ones, then exit
+    #[clap(long, value_name = "TASK_TYPE", requires_all = ["latitude", "longitude"])]
+    lucky_hours: Option<TaskType>,
+
+    /// Print the outlook for one element's tasks, hour by hour, then exit
+    #[clap(long, value_name = "ELEMENT")]
+    element_forecast: Option<Element>,
+
+    /// How many hours --element-forecast looks ahead
+    #[clap(long, value_name = "N", default_value = "24", requires = "element_forecast", value_parser = clap::value_parser!(u8).range(1..))]
+    hours: u8,
+
+    /// Print when the Sun and Moon next come back to where they stood as PID started, then exit
+    #[clap(long, value_name = "PID")]
+    return_report: Option<i32>,
+
+    /// Cast N charts and report how long each took (mean, median, p99), then exit
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    profile_ephemeris: Option<u32>,
+
+    /// With --profile-ephemeris, time the aspect passes over each chart as well
+    #[clap(long, requires = "profile_ephemeris")]
+    profile_aspects: bool,
+
+    /// Check that the kernel can run this scheduler, then exit (0 if it can, 2 if not)
+    #[clap(long)]
+    check_only: bool,
+
+    /// Observer latitude in degrees (north positive), for sunrise-based planetary hours
+    #[clap(long, value_name = "DEG", allow_negative_numbers = true)]
+    latitude: Option<f64>,
+
+    /// Observer longitude in degrees (east positive), for sunrise-based planetary hours
+    #[clap(long, value_name = "DEG", allow_negative_numbers = true)]
+    longitude: Option<f64>,
+}
+
+impl Opts {
+    /// Check what clap cannot: options that depend on each other, and the scheduler's name
+    fn validate(&self) -> Result<(), String> {
+        compat::validate_scheduler_name(&self.scheduler_name)?;
+        if self.monitor.is_some_and(|secs| !(secs.is_finite() && secs > 0.0)) {
+            return Err("--monitor interval must be a positive number of seconds".to_string());
+        }
+        slice::validate_bounds(self)?;
+        watchdog::validate_deadline(self.watchdog_timeout, self.watchdog_deadline)
+    }
+}
+
+fn parse_rulership(s: &str) -> Result<(TaskType, Planet), String> {
+    let (task_type, planet) = s
+        .split_once('=')
+        .ok_or_else(|| "expected TYPE=PLANET".to_string())?;
+    Ok((task_type.trim().parse()?, planet.trim().parse()?))
+}
+
+fn parse_time_pair(s: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let (a, b) = s
+        .split_once(',')
+        .ok_or_else(|| "expected two timestamps separated by a comma".to_string())?;
+    let parse = |t: &str| {
+        DateTime::parse_from_rfc3339(t.trim())
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| format!("invalid timestamp '{t}': {e}"))
+    };
+    Ok((parse(a)?, parse(b)?))
+}
+
+fn parse_unit_interval(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{value} is not in 0.0..=1.0"))
+    }
+}
+
+fn parse_influence_baseline(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{value} must be a positive number"))
+    }
+}
+
+fn parse_slice_factor(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if (0.1..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{value} is not in 0.1..=1.0"))
+    }
+}
+
+fn parse_syscall_boost_factor(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if value.is_finite() && value >= 1.0 {
+        Ok(value)
+    } else {
+        Err(format!("{value} must be at least 1.0"))
+    }
+}
+
+fn parse_pair_boost(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{value} must be above 0.0"))
+    }
+}
+
+fn parse_retrograde_influence(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if (-1.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{value} is not in -1.0..=1.0"))
+    }
+}
+
+fn parse_planet_retrograde_influence(s: &str) -> Result<(Planet, f64), String> {
+    let (planet, influence) = s
+        .split_once('=')
+        .ok_or_else(|| "expected PLANET=INFLUENCE".to_string())?;
+    Ok((planet.trim().parse()?, parse_retrograde_influence(influence.trim())?))
+}
+
+/// The BPF side as the dispatch path sees it, so tests can stand in for it
+trait Kernel: Dispatcher {
+    /// A CPU for the task, or a negative value if the kernel finds none
+    fn select_cpu(&mut self, pid: i32, prev_cpu: i32, flags: u64) -> i32;
+}
+
+impl Kernel for BpfScheduler<'_> {
+    fn select_cpu(&mut self, pid: i32, prev_cpu: i32, flags: u64) -> i32 {
+        BpfScheduler::select_cpu(self, pid, prev_cpu, flags)
+    }
+}
+
+struct Scheduler<'a, K = BpfScheduler<'a>> {
+    bpf: K,
+    astro: AstrologicalScheduler,
+    opts: Opts,
+    pending_chart: Option<JoinHandle<Result<Chart, AstroError>>>,  // Being computed in the background
+    wakeups: Option<WakeupTable>,
+    wakeup_chain: Option<WakeupChain>,
+    cpu_zodiac: Option<CpuZodiac>,
+    cpu_pools: Option<CpuPools>,
+    cpu_idle: Option<CpuIdleTracker>,
+    smt: Option<SmtTopology>,
+    numa: Option<NumaPlacement>,  // With more than one node
+    fire_cpus: Option<CpuIdleTracker>,  // "Idle" here means not running CPU-Intensive work
+    affinity_partner: Option<(i32, i32)>,  // PID and CPU of the last dispatch, under --enable-process-affinity
+    affinity_placements: u64,
+    angular_placements: u64,    // Tasks sent to a CPU where their ruler was angular
+    online: OnlineCpus,
+    hotplug_reroutes: u64,  // Dispatches placed on a CPU that had gone offline, sent to any CPU instead
+    fallbacks: FallbackStats,  // Tasks select_cpu found no CPU for, by where they went
+    lifecycle_events: u64,  // Logged under --progressed-day-unit
+    memo: DecisionMemo,
+    probe: Option<ProbeHandle>,
+    map_dumper: Option<MapDumper>,
+    kthreads: KthreadCache,
+    realtime: RealtimeCache,
+    start_times: Option<StartTimeCache>,
+    stats: Option<SchedulerStats>,
+    last_tune: Instant,
+    started: Instant,
+    tally: RunTally,            // Totals for the exit summary
+    metrics: Metrics,           // Counted on the dispatch path, served over scx_stats
+    stats_server: Option<StatsServer<(), Metrics>>,  // Serving metrics over scx_stats
+    heartbeat: Heartbeat,
+    watchdog: Option<Watchdog>,
+    decisions: Option<DecisionLog>,
+    batches: BatchStats,
+    vtimes: TaskLedger,
+    starvation: StarvationGuard,
+    dry_run_dispatches: u64,  // Would-be dispatches under --dispatch-dry-run
+    retries: RetryQueue,
+    backpressure: Option<Backpressure>,
+    caution: Option<Caution>,
+    void_moon: Option<VoidMoonPenalty>,
+    retrograde_grace: Option<RetrogradeGrace>,
+    moon_void: bool,  // As of the start of this dispatch round
+    daily_message: Option<String>,  // Last logged under --daily-message
+    syscall_boosts: Option<SyscallBoosts<'a>>,
+    energy: Option<EnergyManager>,
+    lunar_cpufreq: Option<LunarCpufreq>,
+    nice_map: Option<NiceMap>,
+    ipc: Option<IpcServer>,
+}
+
+impl<K> Drop for Scheduler<'_, K> {
+    fn drop(&mut self) {
+        // The watchdog reads the skeleton's .bss, so it has to stop first
+        self.watchdog = None;
+    }
+}
+
+impl<'a> Scheduler<'a> {
+    #[allow(clippy::too_many_lines)]
+    fn init(
+        open_object: &'a mut MaybeUninit<OpenObject>,
+        hooks_object: &'a mut MaybeUninit<OpenObject>,
+        opts: Opts,
+    ) -> Result<Self> {
+        let open_opts = LibbpfOpts::default();
+        let slice_ns = opts.slice_us.saturating_mul(1000); // Convert to nanoseconds
+
+        // Hook the syscalls before the scheduler takes over, refusing any this kernel doesn't have
+        let syscall_boosts = if opts.prio_boost_system_calls.is_empty() {
+            None
+        } else {
+            let kallsyms = std::fs::read_to_string("/proc/kallsyms")?;
+            let symbols = bpf_hooks::syscall_symbols(&opts.prio_boost_system_calls, &kallsyms)
+                .map_err(anyhow::Error::msg)?;
+            Some(SyscallBoosts::attach(hooks_object, &symbols)?)
+        };
+
+        let bpf = BpfScheduler::init(
+            open_object,
+            open_opts.clone().into_bpf_open_opts(),
+            0,            // exit_dump_len
+            false,        // partial
+            opts.verbose, // debugt
+            true,         // builtin_idle
+            slice_ns,     // default time slice
+            &opts.scheduler_name,  // scx ops name
+        )?;
+
+        let mut astro = build_astro_scheduler(&opts)?;
+        // Cast the first chart now so the first dispatch doesn't have to; a warm-up leaves it to the
+        // background instead
+        if opts.warmup_dispatches == 0 {
+            if let Err(e) = astro.refresh(Utc::now()) {
+                error!("Failed to cast the first chart: {e}");
+            }
+        }
+        let stats_server = metrics::launch_server(opts.stats_socket.as_deref())
+            .inspect_err(|e| warn!("Cannot serve metrics over scx_stats: {e}"))
+            .ok();
+
+        let nr_cpus = libbpf_rs::num_possible_cpus()?;
+        if let Some(cpu) = opts.self_cpu {
+            self_pin::validate_self_cpu(cpu, nr_cpus).map_err(anyhow::Error::msg)?;
+        }
+        let cpu_zodiac = if opts.cpu_personalities || !opts.cpu_zodiac.is_empty() {
+            Some(CpuZodiac::new(nr_cpus, &opts.cpu_zodiac))
+        } else {
+            None
+        };
+        let cpu_pools = (!opts.cpu_pool.is_empty()).then(|| CpuPools::new(nr_cpus, &opts.cpu_pool));
+        let smt = Some(SmtTopology::from_sysfs(nr_cpus)).filter(SmtTopology::has_smt);
+        let fire_cpus = smt.is_some().then(|| CpuIdleTracker::new(nr_cpus));
+        let numa = Some(NumaTopology::from_sysfs(nr_cpus))
+            .filter(NumaTopology::is_numa)
+            .map(|topology| NumaPlacement::new(topology, Duration::from_millis(opts.numa_patience_ms)));
+        let cpu_idle = (cpu_zodiac.is_some()
+            || cpu_pools.is_some()
+            || smt.is_some()
+            || numa.is_some()
+            || opts.enable_process_affinity
+            || opts.enable_astrocartography
+            || opts.cpu_fallback == CpuFallback::IdleFirst)
+            .then(|| CpuIdleTracker::new(nr_cpus));
+        let online = OnlineCpus::from_sysfs(nr_cpus, Instant::now());
+
+        let mut probes: Vec<Box<dyn Sampler + Send>> = Vec::new();
+        if opts.probe_memory {
+            probes.push(Box::new(ThresholdProbe::new(
+                StatmRss::new(),
+                opts.probe_memory_threshold_mb * 1024 * 1024,
+                TaskType::MemoryHeavy,
+                opts.probe_top,
+            )));
+        }
+        if opts.probe_network {
+            probes.push(Box::new(
+                ThresholdProbe::new(FdSockets, opts.probe_network_threshold, TaskType::Network, opts.probe_top)
+                    .with_decay(opts.probe_network_decay),
+            ));
+        }
+        let probe = if probes.is_empty() {
+            None
+        } else {
+            Some(ProbeHandle::spawn(probes, Duration::from_millis(opts.probe_interval_ms), opts.probe_top)?)
+        };
+
+        let map_dumper = opts.bpf_map_dump.map(|secs| {
+            MapDumper::new(opts.bpf_dump_file.clone(), opts.bpf_dump_format, Duration::from_secs(secs))
+        });
+
+        let stats = if opts.auto_tune {
+            Some(SchedulerStats::new(libbpf_rs::num_possible_cpus()?))
+        } else {
+            None
+        };
+
+        let energy = opts
+            .energy_aware
+            .then(|| EnergyManager::from_sysfs(Duration::from_millis(opts.energy_poll_interval), Instant::now()));
+        let lunar_cpufreq = opts
+            .lunar_cpufreq
+            .then(|| LunarCpufreq::from_sysfs(nr_cpus, Instant::now()))
+            .filter(|lunar| {
+                if lunar.cpus().is_empty() {
+                    warn!("🌑 No CPU has an energy_performance_preference to steer: --lunar-cpufreq does nothing");
+                }
+                !lunar.cpus().is_empty()
+            });
+
+        let nice_map = (!opts.cpu_nice_map.is_empty())
+            .then(|| NiceMap::new(&opts.cpu_nice_map))
+            .filter(|_| {
+                let capable = !opts.nice_map_require_cap_sys_nice || nice_map::has_cap_sys_nice();
+                if !capable {
+                    warn!("🙅 No CAP_SYS_NICE to renice tasks with: --cpu-nice-map does nothing");
+                }
+                capable
+            });
+
+        let start_times = (opts.sun_sign_affinity
+            || opts.enable_process_affinity
+            || opts.enable_astrocartography
+            || opts.progressed_day_unit.is_some())
+        .then(StartTimeCache::new);
+
+        let ipc = match &opts.socket_path {
+            Some(path) => Some(
+                IpcServer::bind(path).map_err(|e| anyhow::anyhow!("cannot listen on {}: {e}", path.display()))?,
+            ),
+            None => None,
+        };
+
+        let mut sched = Self::with_kernel(bpf, astro, opts, online)?;
+        sched.cpu_zodiac = cpu_zodiac;
+        sched.cpu_pools = cpu_pools;
+        sched.cpu_idle = cpu_idle;
+        sched.smt = smt;
+        sched.numa = numa;
+        sched.fire_cpus = fire_cpus;
+        sched.probe = probe;
+        sched.map_dumper = map_dumper;
+        sched.start_times = start_times;
+        sched.stats = stats;
+        sched.stats_server = stats_server;
+        sched.syscall_boosts = syscall_boosts;
+        sched.energy = energy;
+        sched.lunar_cpufreq = lunar_cpufreq;
+        sched.nice_map = nice_map;
+        sched.ipc = ipc;
+
+        if let Some(secs) = sched.opts.watchdog_timeout {
+            // SAFETY: the counter is in the skeleton's memory-mapped .bss, which stays put however the
+            // scheduler moves, and Drop stops the watchdog before the skeleton goes
+            let nr_queued = unsafe { SharedCounter::new(std::ptr::from_mut(sched.bpf.nr_queued_mut())) };
+            sched.watchdog = Some(Watchdog::spawn(
+                sched.heartbeat.clone(),
+                Box::new(move || nr_queued.read()),
+                Duration::from_secs(secs),
+                sched.opts.watchdog_deadline.map(Duration::from_secs),
+                sched.opts.watchdog_restart,
+            )?);
+        }
+        Ok(sched)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    /// Install the chart computed in the background, if it has finished
+    fn collect_chart(&mut self) {
+        if !self.pending_chart.as_ref().is_some_and(JoinHandle::is_finished) {
+            return;
+        }
+        let Some(handle) = self.pending_chart.take() else {
+            return;
+        };
+        match handle.join() {
+            Ok(Ok(chart)) => {
+                self.tally.note_chart(chart.time, &chart.positions);
+                if let Some(grace) = self.retrograde_grace.as_mut() {
+                    grace.note_chart(&chart.positions, Instant::now());
+                }
+                self.astro.install_chart(chart);
+                self.realtime.clear();
+                self.update_caution();
+                self.log_progressions();
+                self.log_daily_message();
+            }
+            Ok(Err(e)) => error!("Failed to update planetary positions: {e}"),
+            Err(_) => error!("Planetary position update panicked"),
+        }
+    }
+
+    /// Tell of every process whose progressed chart has turned a corner
+    fn log_progressions(&mut self) {
+        let now = Utc::now();
+        for pid in self.astro.progressing_pids() {
+            let events = self.astro.check_progressions(pid, now);
+            if events.is_empty() {
+                continue;
+            }
+            let comm = probe::read_comm(pid).unwrap_or_else(|| format!("PID {pid}"));
+            let (task_type, _) = self.astro.classifier().classify_pid(pid, &comm);
+            for event in events {
+                info!("{}", event.describe(&comm, task_type));
+                self.lifecycle_events += 1;
+            }
+        }
+    }
+
+    /// Weigh the tension of a freshly cast chart, and turn cautious or lift the caution
+    fn update_caution(&mut self) {
+        let Some(caution) = self.caution.as_mut() else {
+            return;
+        };
+        let score = match self.astro.tension_score(Utc::now()) {
+            Ok(score) => score,
+            Err(e) => {
+                error!("The cosmos is clouded, no tension score: {e}");
+                return;
+            }
+        };
+        match caution.update(score) {
+            Some(true) => warn!("⚠️ Tension at {score}: the stars advise caution, slices cut to 75% and batches left unsorted"),
+            Some(false) => info!("🕊️ Tension down to {score}: the stars no longer advise caution"),
+            None => debug!("Chart tension: {score}"),
+        }
+    }
+
+    /// Log the daily cosmic message when the Sun has moved on to a new degree
+    fn log_daily_message(&mut self) {
+        if !self.opts.daily_message {
+            return;
+        }
+        match self.astro.daily_cosmic_message_line(Utc::now()) {
+            Ok(line) if self.daily_message.as_ref() != Some(&line) => {
+                info!("📜 {line}");
+                self.daily_message = Some(line);
+            }
+            Ok(_) => {}
+            Err(e) => error!("The cosmos is clouded, no daily message: {e}"),
+        }
+    }
+
+    fn print_cosmic_weather(&mut self) {
+        let now = Utc::now();
+        match self.astro.get_cosmic_weather(now) {
+            Ok(weather) => println!("\n{weather}\n"),
+            Err(e) => error!("The cosmos is clouded, no weather report: {e}"),
+        }
+        if self.opts.verbose {
+            if let Ok(line) = self.astro.daily_cosmic_message_line(now) {
+                println!("{line}\n");
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn dispatch_tasks(&mut self) -> Result<()> {
+        self.heartbeat.beat();
+        let now_chrono = Utc::now();
+
+        // Recast the chart on the update interval, in the background, and swap it in once ready
+        self.collect_chart();
+        if self.pending_chart.is_none() && self.astro.needs_refresh(now_chrono) {
+            debug!("Updating planetary positions...");
+            let source = self.astro.chart_source();
+            self.pending_chart = Some(std::thread::spawn(move || source.compute(now_chrono)));
+        }
+
+        // Apply whatever the probes have learned since the last round
+        if let Some(probe) = self.probe.as_mut() {
+            probe.tick(Instant::now());
+            for event in probe.drain() {
+                match event {
+                    ProbeEvent::Promote(pid, task_type) => {
+                        debug!("[PID {pid}] promoted to {} by a task probe", task_type.name());
+                        self.astro.learn_task_type(pid, task_type);
+                    }
+                    ProbeEvent::Demote(pid, task_type) => {
+                        debug!("[PID {pid}] no longer looks {}", task_type.name());
+                        self.astro.unlearn_task_type(pid, task_type);
+                    }
+                    ProbeEvent::Vanished(pid) => {
+                        self.astro.forget_task(pid);
+                        self.memo.forget(pid);
+                        self.vtimes.forget(pid);
+                        if let Some(numa) = self.numa.as_mut() {
+                            numa.forget(pid);
+                        }
+                        self.starvation.forget(pid);
+                        if let Some(void_moon) = self.void_moon.as_mut() {
+                            void_moon.forget(pid);
+                        }
+                        if let Some(grace) = self.retrograde_grace.as_mut() {
+                            grace.forget(pid);
+                        }
+                        if let Some(nice_map) = self.nice_map.as_mut() {
+                            nice_map.forget(pid);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(stats) = self.stats.as_mut() {
+            stats.record_queue_depth(*self.bpf.nr_queued_mut());
+        }
+
+        // A runaway queue gets table lookups instead of full readings until it drains
+        if let Some(backpressure) = self.backpressure.as_mut() {
+            let nr_queued = *self.bpf.nr_queued_mut();
+            match backpressure.update(nr_queued, Instant::now()) {
+                Some(Mode::Degraded) => warn!("⏩ {nr_queued} tasks queued: switching to the fast path"),
+                Some(Mode::Normal) => info!("🔭 Queue down to {nr_queued}: back to full readings"),
+                None => {}
+            }
+        }
+
+        // Nothing started under a void Moon comes to much
+        if self.void_moon.is_some() {
+            let moon_void = self.astro.moon_void_of_course(now_chrono).unwrap_or(false);
+            if moon_void != self.moon_void {
+                if moon_void {
+                    info!("🌑 The Moon is void of course: new tasks start with shorter slices");
+                } else {
+                    info!("🌒 The Moon has entered a new sign: new tasks start normally again");
+                }
+            }
+            self.moon_void = moon_void;
+        }
+
+        // Tasks that couldn't be dispatched last round go before anything new
+        if self.retries.len() > 0 {
+            let sent = self.retries.drain(&mut self.bpf);
+            debug!("Retried {sent} failed dispatches, {} still waiting", self.retries.len());
+        }
+
+        // Drain the queue a batch at a time, dispatching each batch highest priority first,
+        // and yield once the budget is spent so a flood can't hold up everything else
+        let batch_size = usize::try_from(self.opts.batch_size).unwrap_or(usize::MAX);
+        let max_time = self.opts.dispatch_budget_us.map(Duration::from_micros);
+        let max_tasks = usize::try_from(self.opts.dispatch_budget).unwrap_or(usize::MAX);
+        let mut budget = IterationBudget::new(max_tasks, max_time, Instant::now());
+        let end = batch::drain_within_budget(
+            self,
+            |sched| sched.bpf.dequeue_task(),
+            |sched, tasks| sched.dispatch_batch(tasks, now_chrono),
+            batch_size,
+            &mut budget,
+        );
+
+        // Tell the BPF side what is still waiting so it keeps waking us up
+        let mut nr_pending = self.retries.len() as u64;
+        match end {
+            QueueEnd::More => {
+                debug!("Dispatch budget spent after {} tasks, yielding", budget.spent());
+                nr_pending += (*self.bpf.nr_queued_mut()).max(1);
+            }
+            QueueEnd::Empty => {}
+            QueueEnd::Failed(code) => log::error!("Failed to dequeue task from ring buffer: error code {code}"),
+        }
+
+        // Notify completion and sleep until more tasks arrive
+        self.bpf.notify_complete(nr_pending);
+        self.retries.health().map_err(anyhow::Error::msg)
+    }
+}
+
+impl<'a, K: Kernel> Scheduler<'a, K> {
+    /// A scheduler dispatching through `bpf`, with none of the hardware- or
+    /// procfs-backed extras: `init` adds those for a real run
+    fn with_kernel(bpf: K, mut astro: AstrologicalScheduler, opts: Opts, online: OnlineCpus) -> Result<Self> {
+        let mut tally = RunTally::default();
+        if let Some(epoch) = astro.chart_epoch() {
+            tally.note_chart(epoch, &astro.positions(epoch)?);
+        }
+        #[allow(clippy::float_cmp)]
+        let wakeups = (opts.pair_boost != 1.0).then(|| {
+            WakeupTable::new(opts.pair_boost, Duration::from_millis(opts.pair_boost_ttl_ms), WAKEUP_TABLE_SIZE)
+        });
+        #[allow(clippy::float_cmp)]
+        let wakeup_chain = (opts.chain_boost != 1.0).then(|| {
+            WakeupChain::new(opts.chain_boost, Duration::from_millis(opts.chain_boost_window_ms), opts.chain_boost_hops)
+        });
+        let decisions = opts.diagnostic_report.then(|| DecisionLog::new(RECENT_DECISIONS));
+        let starvation = StarvationGuard::new(Duration::from_millis(opts.starvation_threshold_ms));
+        let retries = RetryQueue::new(RETRY_QUEUE_CAPACITY, opts.max_dispatch_failures);
+        let backpressure = opts.backpressure_threshold.map(Backpressure::new);
+        let caution = opts.caution_threshold.map(Caution::new);
+        let void_moon = opts.void_moon_penalty.map(VoidMoonPenalty::new);
+        let mut retrograde_grace = (opts.retrograde_grace_secs > 0)
+            .then(|| RetrogradeGrace::new(Duration::from_secs(opts.retrograde_grace_secs)));
+        if let (Some(grace), Some(epoch)) = (retrograde_grace.as_mut(), astro.chart_epoch()) {
+            grace.note_chart(&astro.positions(epoch)?, Instant::now());
+        }
+
+        Ok(Self {
+            bpf,
+            astro,
+            opts,
+            pending_chart: None,
+            wakeups,
+            wakeup_chain,
+            cpu_zodiac: None,
+            cpu_pools: None,
+            cpu_idle: None,
+            smt: None,
+            numa: None,
+            fire_cpus: None,
+            affinity_partner: None,
+            affinity_placements: 0,
+            angular_placements: 0,
+            online,
+            hotplug_reroutes: 0,
+            fallbacks: FallbackStats::default(),
+            lifecycle_events: 0,
+            memo: DecisionMemo::new(DECISION_MEMO_SIZE),
+            probe: None,
+            map_dumper: None,
+            kthreads: KthreadCache::new(),
+            realtime: RealtimeCache::new(),
+            start_times: None,
+            stats: None,
+            last_tune: Instant::now(),
+            started: Instant::now(),
+            tally,
+            metrics: Metrics::default(),
+            stats_server: None,
+            heartbeat: Heartbeat::new(),
+            watchdog: None,
+            decisions,
+            batches: BatchStats::default(),
+            vtimes: TaskLedger::new(),
+            starvation,
+            dry_run_dispatches: 0,
+            retries,
+            backpressure,
+            caution,
+            void_moon,
+            retrograde_grace,
+            moon_void: false,
+            daily_message: None,
+            syscall_boosts: None,
+            energy: None,
+            lunar_cpufreq: None,
+            nice_map: None,
+            ipc: None,
+        })
+    }
+
+    /// Consult the stars for a drained batch and dispatch it in order
+    fn dispatch_batch(&mut self, tasks: Vec<QueuedTask>, now: DateTime<Utc>) {
+        self.batches.record(tasks.len());
+
+        let mut decided: Vec<_> = tasks.into_iter().filter_map(|task| self.decide(task, now)).collect();
+        // When the stars advise caution, the batch goes out in the order it was queued
+        let cautious = self.caution.as_ref().is_some_and(Caution::is_active);
+        match self.opts.fairness {
+            _ if cautious => {}
+            Fairness::Priority => {
+                batch::sort_by_priority(&mut decided, |(_, _, decision)| decision);
+                if self.opts.cosmic_tiebreak {
+                    batch::shuffle_ties(&mut decided, batch::cosmic_seed(self.astro.rng(), now), |(task, _, decision)| {
+                        (task.pid, decision)
+                    });
+                }
+            }
+            Fairness::Vruntime => {
+                batch::sort_by_vtime(&mut decided, &mut self.vtimes, |(task, _, decision)| (task.pid, decision));
+            }
+        }
+        // However the stars ordered the batch, nobody waits forever
+        let forced = self.starvation.promote(&mut decided, |(task, _, _)| task.pid, Instant::now());
+        for (i, (task, comm, decision)) in decided.into_iter().enumerate() {
+            self.dispatch_decided(&task, comm.as_str(), &decision, i < forced);
+        }
+    }
+
+    /// Hand a task to the kernel, retrying it if the ring buffer refuses
+    fn send(&mut self, task: DispatchedTask) {
+        let pid = task.pid;
+        if self.retries.dispatch(&mut self.bpf, task) == DispatchOutcome::Dropped {
+            error!("Failed to dispatch task {pid}: retry queue full");
+        }
+    }
+
+    /// Consult the stars for one queued task
+    ///
+    /// A task the stars cannot place is dispatched straight away with the
+    /// default time slice and yields `None`.
+    fn decide(&mut self, task: QueuedTask, now: DateTime<Utc>) -> Option<(QueuedTask, Comm, SchedulingDecision)> {
+        let comm = Comm::from_raw(&task.comm);
+
+        // Make astrological scheduling decision; under backpressure, only the critical
+        // policy, the classifier and the per-type table
+        let degraded = self.backpressure.as_ref().is_some_and(Backpressure::is_degraded);
+        let is_kthread = self.kthreads.is_kthread(task.pid);
+        let flags = if !self.opts.no_critical_rt && self.realtime.is_realtime(task.pid) { TASK_FLAG_RT } else { 0 };
+        if let Some(start_times) = self.start_times.as_mut().filter(|_| !degraded) {
+            if !self.astro.knows_sun_sign(task.pid) {
+                if let Some(start) = start_times.start_time(task.pid) {
+                    self.astro.note_task_start(task.pid, start);
+                    self.memo.forget(task.pid);
+                }
+            }
+        }
+        let decided_at = Instant::now();
+        let key = |astro: &AstrologicalScheduler| MemoKey {
+            epoch: astro.chart_epoch(),
+            revision: astro.revision(),
+            comm,
+            weight: task.weight,
+            is_kthread,
+            flags,
+        };
+        let decision = if let Some(decision) = self.memo.get(task.pid, &key(&self.astro)) {
+            Ok(decision.clone())
+        } else if degraded {
+            // Table decisions are only for while the queue is backed up: not worth remembering
+            self.astro.schedule_task_fast(comm.as_str(), task.pid, task.weight, is_kthread, flags, now)
+        } else {
+            let decision =
+                self.astro.schedule_task_with_flags(comm.as_str(), task.pid, task.weight, is_kthread, flags, now);
+            if let Ok(decision) = &decision {
+                self.memo.insert(task.pid, key(&self.astro), decision.clone());
+            }
+            decision
+        };
+        let mut decision = match decision {
+            Ok(decision) => decision,
+            Err(e) => {
+                // Don't strand the task: dispatch it with the default time slice
+                let pid = task.pid;
+                error!("[PID {pid}] {comm}: astrological decision failed: {e}");
+                let mut dispatched_task = DispatchedTask::new(&task);
+                let cpu = self.bpf.select_cpu(task.pid, task.cpu, task.flags);
+                dispatched_task.cpu = if cpu >= 0 { cpu } else { self.fallback_cpu(&task, None, None) };
+                self.send(dispatched_task);
+                self.starvation.note_dispatch(pid);
+                return None;
+            }
+        };
+
+        if let Some(stats) = self.stats.as_mut() {
+            let retrograde = decision.planetary_influence < 0.0;
+            stats.record_decision(decision.task_type, decision.priority, retrograde, decided_at.elapsed());
+        }
+
+        // Compatible wakeup pairs keep the pipe flowing. Pairs are taken in the
+        // order the batch was dequeued, before it is sorted: a wakee is queued
+        // behind its waker, and the boost then counts towards the sort.
+        if let (Some(wakeups), Some(element)) = (self.wakeups.as_mut().filter(|_| !degraded), decision.ruling_element) {
+            let boost = wakeups.on_dispatch(task.pid, element, Instant::now());
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let boosted = (f64::from(decision.priority) * boost) as u32;
+            decision.priority = boosted.max(1);
+        }
+
+        // Work an Interactive task woke, and the work that woke, shares a little of its urgency
+        if let Some(chain) = self.wakeup_chain.as_mut().filter(|_| !degraded) {
+            let boost = chain.on_dispatch(task.pid, decision.task_type == TaskType::Interactive, Instant::now());
+            if !decision.critical {
+                decision.priority = wakeup::chain_boosted_priority(decision.priority, boost);
+            }
+        }
+
+        // A task just made one of the hooked syscalls: boost it for this dispatch only
+        if self.syscall_boosts.as_mut().is_some_and(|boosts| boosts.take(task.pid)) {
+            decision.priority = bpf_hooks::boosted_priority(decision.priority, self.opts.syscall_boost_factor);
+        }
+
+        Some((task, comm, decision))
+    }
+
+    /// An idle CPU next to the last dispatch's, if that task's process and this one get along
+    fn cpu_near_partner(&mut self, pid: i32) -> Option<i32> {
+        let (partner, partner_cpu) = self.affinity_partner.filter(|&(partner, _)| partner != pid)?;
+        let affinity = self.astro.compute_process_affinity(pid, partner, Utc::now())?;
+        if affinity < cpu_affinity::AFFINITY_THRESHOLD {
+            return None;
+        }
+        let idle = self.cpu_idle.as_ref()?.idle_mask(Instant::now());
+        cpu_affinity::neighbor_cpu(partner_cpu, &idle)
+    }
+
+    /// An idle CPU on which the ruler of `task_type` was angular as the task's process started
+    ///
+    /// The CPU the task last ran on is kept if it is one of them.
+    fn angular_cpu(&mut self, pid: i32, task_type: TaskType, prev_cpu: i32) -> Option<i32> {
+        if !self.opts.enable_astrocartography {
+            return None;
+        }
+        let idle = self.cpu_idle.as_ref()?.idle_mask(Instant::now());
+        let cpus = self.astro.angular_cpus(pid, task_type, Utc::now(), u32::try_from(idle.len()).ok()?)?;
+        let idle_cpus: Vec<i32> = cpus
+            .into_iter()
+            .filter(|&cpu| idle.get(cpu as usize).copied().unwrap_or(false))
+            .filter_map(|cpu| i32::try_from(cpu).ok())
+            .collect();
+        idle_cpus.iter().copied().find(|&cpu| cpu == prev_cpu).or_else(|| idle_cpus.first().copied())
+    }
+
+    /// A CPU for a task `select_cpu` found none for, by `--cpu-fallback`
+    ///
+    /// `element` picks the task's CPU pool and `ruling` its CPU zodiac element,
+    /// as for placement; without either, every CPU is a candidate.
+    fn fallback_cpu(&mut self, task: &QueuedTask, element: Option<Element>, ruling: Option<Element>) -> i32 {
+        let pool = element.and_then(|element| self.cpu_pools.as_ref()?.pool(element));
+        let signs = element.and_then(|element| Some((self.cpu_zodiac.as_ref()?.signs(), ruling.unwrap_or(element))));
+        let online = self.online.mask();
+        let allowed = |cpu: usize| {
+            online.get(cpu).copied().unwrap_or(false)
+                && match (pool, signs) {
+                    (Some(pool), _) => pool.get(cpu).copied().unwrap_or(false),
+                    (None, Some((signs, element))) => signs.get(cpu).is_some_and(|sign| sign.element() == element),
+                    (None, None) => true,
+                }
+        };
+        let idle = match (&self.cpu_idle, self.opts.cpu_fallback) {
+            (Some(tracker), CpuFallback::IdleFirst) => tracker.idle_mask(Instant::now()),
+            _ => Vec::new(),
+        };
+        let (cpu, outcome) = self.opts.cpu_fallback.pick(task.cpu, allowed, &idle);
+        self.fallbacks.record(outcome);
+        cpu.unwrap_or(RL_CPU_ANY)
+    }
+
+    /// Place and dispatch a task the stars have ruled on
+    ///
+    /// A task the starvation guard `forced` ahead gets at least the minimum slice.
+    #[allow(clippy::too_many_lines)]
+    fn dispatch_decided(&mut self, task: &QueuedTask, comm: &str, decision: &SchedulingDecision, forced: bool) {
+        let mut dispatched_task = DispatchedTask::new(task);
+
+        // Select CPU, and if the kernel finds none, fall back by --cpu-fallback among the CPUs the
+        // element pools and CPU zodiac allow
+        let selected = self.bpf.select_cpu(task.pid, task.cpu, task.flags);
+        let cpu = if selected >= 0 {
+            selected
+        } else {
+            self.fallback_cpu(task, decision.task_type.favored_element(), decision.ruling_element)
+        };
+        dispatched_task.cpu = cpu;
+
+        // Keep a harmonious pair close, or a task where its ruler is angular; failing that, prefer the
+        // element's CPU pool, then a core whose sign shares the ruling planet's element, and otherwise
+        // keep CPU-Intensive work off busy hyperthread siblings and Memory-Heavy work on its home NUMA node
+        if let Some(near) = self.cpu_near_partner(task.pid) {
+            dispatched_task.cpu = near;
+            self.affinity_placements += 1;
+        } else if let Some(angular) = self.angular_cpu(task.pid, decision.task_type, cpu) {
+            dispatched_task.cpu = angular;
+            self.angular_placements += 1;
+        } else if let (Some(tracker), Some(element)) = (&self.cpu_idle, decision.task_type.favored_element()) {
+            let mut idle = tracker.idle_mask(Instant::now());
+            if element == Element::Fire {
+                self_pin::exclude_self_cpu(&mut idle, self.opts.self_cpu);
+            }
+            let placed = place_task(
+                self.cpu_pools.as_ref(),
+                self.cpu_zodiac.as_ref(),
+                element,
+                decision.ruling_element,
+                cpu,
+                &idle,
+            );
+            if let Some(placed) = placed {
+                dispatched_task.cpu = placed.unwrap_or(RL_CPU_ANY);
+            } else if let (Some(smt), Some(fire_cpus), TaskType::CpuIntensive) =
+                (&self.smt, &self.fire_cpus, decision.task_type)
+            {
+                let fire_free = fire_cpus.idle_mask(Instant::now());
+                let placed = prefer_smt_cpu(smt, &idle, &fire_free, cpu, self.opts.no_smt_for_fire);
+                dispatched_task.cpu = placed.unwrap_or(RL_CPU_ANY);
+            } else if let (Some(numa), TaskType::MemoryHeavy) = (self.numa.as_mut(), decision.task_type) {
+                let placed = numa.place(task.pid, cpu, &idle, Instant::now());
+                dispatched_task.cpu = placed.unwrap_or(RL_CPU_ANY);
+            }
+        }
+        // Let CFS hear the stars too; critical tasks keep whatever nice value they have
+        let element = decision.ruling_element.filter(|_| !decision.critical);
+        if let (Some(nice_map), Some(element)) = (self.nice_map.as_mut(), element) {
+            match nice_map.apply(task.pid, element) {
+                Ok(Some(nice)) => debug!("[PID {}] {comm} reniced to {nice} ({})", task.pid, element.name()),
+                Ok(None) => {}
+                Err(e) => debug!("[PID {}] {comm} could not be reniced: {e}", task.pid),
+            }
+        }
+
+        // Whatever was remembered about a CPU that has since gone offline, it runs nothing now
+        if !self.online.is_online(dispatched_task.cpu) {
+            debug!("[PID {}] {comm} placed on offline CPU {}, dispatching to any CPU", task.pid, dispatched_task.cpu);
+            dispatched_task.cpu = RL_CPU_ANY;
+            self.hotplug_reroutes += 1;
+        }
+        if let Some(numa) = self.numa.as_mut() {
+            numa.note_dispatch(task.pid, dispatched_task.cpu, decision.task_type == TaskType::MemoryHeavy);
+        }
+
+        if self.opts.enable_process_affinity {
+            self.affinity_partner = Some((task.pid, dispatched_task.cpu));
+        }
+
+        // Tasks already running when their ruler turned retrograde are eased into the penalty
+        let retrograde_factor = match self.retrograde_grace.as_mut() {
+            Some(grace) => {
+                let ruler = self.astro.ruler(decision.task_type);
+                grace.factor(task.pid, ruler, self.opts.retrograde_factor, Instant::now())
+            }
+            None => self.opts.retrograde_factor,
+        };
+        let (slice_ns, trace) = slice::eased_slice_ns(&self.opts, decision, retrograde_factor);
+        if self.opts.debug_decisions && !trace.is_empty() {
+            debug!("[PID {}] {comm} slice: {trace}", task.pid);
+        }
+        dispatched_task.slice_ns = slice_ns;
+        if let Some(void_moon) = self.void_moon.as_mut() {
+            let min_ns = self.opts.slice_us_min.saturating_mul(1000);
+            let moon_void = self.moon_void && !decision.critical;
+            dispatched_task.slice_ns = void_moon.apply(task.pid, dispatched_task.slice_ns, min_ns, moon_void);
+        }
+        if let Some(caution) = &self.caution {
+            dispatched_task.slice_ns = caution.apply(dispatched_task.slice_ns, self.opts.slice_us_min.saturating_mul(1000));
+        }
+        if forced {
+            dispatched_task.slice_ns = dispatched_task.slice_ns.max(self.opts.slice_us_min.saturating_mul(1000));
+            debug!("[PID {}] {comm} waited too long; dispatched ahead of the stars", task.pid);
+        }
+        self.starvation.note_dispatch(task.pid);
+        self.tally.note_dispatch(decision.task_type, dispatched_task.slice_ns);
+        let penalized = slice::retrograde_penalized(&self.opts, decision);
+        self.metrics.record_dispatch(decision, dispatched_task.slice_ns, penalized);
+
+        if self.opts.fairness == Fairness::Vruntime {
+            self.vtimes.charge(task.pid, dispatched_task.slice_ns, decision.priority);
+        }
+
+        if let Some(decisions) = self.decisions.as_mut() {
+            decisions.record(task.pid, comm, decision, dispatched_task.slice_ns, dispatched_task.cpu);
+        }
+
+        if let Some(tracker) = self.cpu_idle.as_mut() {
+            tracker.note_dispatch(dispatched_task.cpu, Duration::from_nanos(dispatched_task.slice_ns), Instant::now());
+        }
+        if let (Some(fire_cpus), TaskType::CpuIntensive) = (self.fire_cpus.as_mut(), decision.task_type) {
+            fire_cpus.note_dispatch(dispatched_task.cpu, Duration::from_nanos(dispatched_task.slice_ns), Instant::now());
+        }
+
+        if self.opts.debug_decisions {
+            let slice_microseconds = dispatched_task.slice_ns / 1000;
+            debug!(
+                "[PID {}] {} | Priority: {} (astro {} × weight {:.2}) | Slice: {slice_microseconds}μs | {}",
+                task.pid,
+                comm,
+                decision.priority,
+                decision.astro_priority,
+                decision.weight_factor,
+                decision.reasoning
+            );
+        }
+
+        if let Some(probe) = self.probe.as_mut() {
+            probe.note_dispatch(task.pid);
+        }
+        if let Some(energy) = self.energy.as_mut() {
+            energy.note_dispatch(dispatched_task.cpu, decision);
+        }
+
+        // A dry run keeps the decision to itself and lets the kernel carry on
+        if self.opts.dispatch_dry_run {
+            info!("{}", dry_run::would_dispatch(comm, decision, &dispatched_task));
+            dispatched_task = dry_run::stand_in(&dispatched_task, if selected >= 0 { selected } else { RL_CPU_ANY });
+            self.dry_run_dispatches += 1;
+        }
+
+        // Dispatch the task
+        self.send(dispatched_task);
+    }
+}
+
+impl<'a> Scheduler<'a> {
+    /// What this run amounted to, for the way out
+    fn exit_summary(&mut self) -> ExitSummary {
+        ExitSummary {
+            uptime: self.started.elapsed(),
+            signal: SHUTDOWN.signal_name(),
+            user_dispatches: *self.bpf.nr_user_dispatches_mut(),
+            kernel_dispatches: *self.bpf.nr_kernel_dispatches_mut(),
+            batches: self.batches.batches,
+            ledger_tasks: self.vtimes.tracked(),
+            vtime_spread_ns: self.vtimes.vtime_spread(),
+            watchdog_trips: self.watchdog.as_ref().map_or(0, Watchdog::trips),
+            tally: self.tally.clone(),
+        }
+    }
+
+    fn restart_requested(&self) -> bool {
+        self.watchdog.as_ref().is_some_and(Watchdog::restart_requested)
+    }
+
+    /// Where things stand, for the --stats line
+    fn periodic_snapshot(&mut self) -> PeriodicSnapshot {
+        PeriodicSnapshot {
+            user_dispatches: *self.bpf.nr_user_dispatches_mut(),
+            kernel_dispatches: *self.bpf.nr_kernel_dispatches_mut(),
+            queued: *self.bpf.nr_queued_mut(),
+            scheduled: *self.bpf.nr_scheduled_mut(),
+            chart_epoch: self.astro.chart_epoch(),
+            degraded: self.backpressure.as_ref().is_some_and(Backpressure::is_degraded),
+        }
+    }
+
+    fn print_stats(&mut self) {
+        let nr_user_dispatches = *self.bpf.nr_user_dispatches_mut();
+        let nr_kernel_dispatches = *self.bpf.nr_kernel_dispatches_mut();
+        let nr_queued = *self.bpf.nr_queued_mut();
+        let nr_scheduled = *self.bpf.nr_scheduled_mut();
+
+        if self.opts.dispatch_dry_run {
+            info!(
+                "⭐ Dispatches: user={nr_user_dispatches} ({} {} would-be) kernel={nr_kernel_dispatches} | Tasks: queued={nr_queued} scheduled={nr_scheduled}",
+                dry_run::DRY_RUN_PREFIX,
+                self.dry_run_dispatches
+            );
+        } else {
+            info!(
+                "⭐ Dispatches: user={nr_user_dispatches} kernel={nr_kernel_dispatches} | Tasks: queued={nr_queued} scheduled={nr_scheduled}"
+            );
+        }
 
-    /// Enable verbose logging
-    #[clap(short = 'v', long)]
-    verbose: bool,
+        info!(
+            "📦 Batches: {} | Size: avg={:.1} max={} | Starvation overrides: {}",
+            self.batches.batches,
+            self.batches.average(),
+            self.batches.largest,
+            self.starvation.forced()
+        );
+        let anonymous = self.astro.anonymous_tasks();
+        if anonymous > 0 {
+            info!("👤 Unnamed tasks: {anonymous} decisions");
+        }
 
-    /// Show cosmic weather report on startup
-    #[clap(short = 'w', long)]
-    cosmic_weather: bool,
+        if let Some(backpressure) = &self.backpressure {
+            info!(
+                "⏩ Fast path: {:.1}s over {} spells{}",
+                backpressure.time_degraded(Instant::now()).as_secs_f64(),
+                backpressure.spells(),
+                if backpressure.is_degraded() { " (active)" } else { "" }
+            );
+        }
 
-    /// Update planetary positions every N seconds
-    #[clap(short = 'u', long, default_value = "60")]
-    update_interval: u64,
+        if let Some(caution) = &self.caution {
+            info!("⚠️ Caution advised: {} spells{}", caution.spells(), if caution.is_active() { " (active)" } else { "" });
+        }
 
-    /// Print scheduling decisions for debugging
-    #[clap(short = 'd', long)]
-    debug_decisions: bool,
+        let retries = self.retries.stats();
+        if retries != RetryStats::default() {
+            info!(
+                "🔁 Dispatch retries: rerouted={} queued={} dropped={} waiting={}",
+                retries.rerouted,
+                retries.queued,
+                retries.dropped,
+                self.retries.len()
+            );
+        }
 
-    /// Disable retrograde effects (boring mode)
-    #[clap(long)]
-    no_retrograde: bool,
-}
+        if self.opts.enable_process_affinity {
+            info!("💞 Process affinity placements: {}", self.affinity_placements);
+        }
+        if self.opts.enable_astrocartography {
+            info!("🗺️ Astrocartography placements: {}", self.angular_placements);
+        }
 
-struct Scheduler<'a> {
-    bpf: BpfScheduler<'a>,
-    astro: AstrologicalScheduler,
-    opts: Opts,
-    last_update: u64,
-}
+        if self.fallbacks.total() > 0 {
+            info!(
+                "🪂 CPU fallbacks ({}): prev={} idle={} any={}",
+                self.opts.cpu_fallback.name(),
+                self.fallbacks.prev,
+                self.fallbacks.idle,
+                self.fallbacks.any
+            );
+        }
 
-impl<'a> Scheduler<'a> {
-    fn init(open_object: &'a mut MaybeUninit<OpenObject>, opts: Opts) -> Result<Self> {
-        let open_opts = LibbpfOpts::default();
-        let slice_ns = opts.slice_us * 1000; // Convert to nanoseconds
+        if let Some(nice_map) = &self.nice_map {
+            info!("🙏 Nice values set: {} | failed: {}", nice_map.reniced(), nice_map.failures());
+        }
 
-        let bpf = BpfScheduler::init(
-            open_object,
-            open_opts.clone().into_bpf_open_opts(),
-            0,            // exit_dump_len
-            false,        // partial
-            opts.verbose, // debugt
-            true,         // builtin_idle
-            slice_ns,     // default time slice
-            "horoscope",  // scx ops name
-        )?;
+        if self.hotplug_reroutes > 0 {
+            info!("🔌 Dispatches rerouted from offline CPUs: {}", self.hotplug_reroutes);
+        }
 
-        #[allow(clippy::cast_possible_wrap)]
-        let astro = AstrologicalScheduler::new(opts.update_interval as i64);
-        let last_update = Self::now();
+        if let Some(numa) = &self.numa {
+            let nodes: Vec<String> =
+                numa.dispatches().iter().enumerate().map(|(node, count)| format!("node{node}={count}")).collect();
+            info!("🧭 NUMA dispatches: {} | spills={}", nodes.join(" "), numa.spills());
+        }
+
+        if self.opts.progressed_day_unit.is_some() {
+            info!("🌅 Lifecycle events: {}", self.lifecycle_events);
+        }
+
+        info!(
+            "🗂️ Decision memo: {:.1}% hits ({} of {}), {} reused PIDs",
+            self.memo.hit_rate() * 100.0,
+            self.memo.hits(),
+            self.memo.hits() + self.memo.misses(),
+            self.memo.reused()
+        );
+
+        if let Some(void_moon) = &self.void_moon {
+            info!(
+                "🌑 Void Moon starts: {} penalized{}",
+                void_moon.penalized(),
+                if self.moon_void { " (void now)" } else { "" }
+            );
+        }
 
-        Ok(Self { bpf, astro, opts, last_update })
+        if let Some(grace) = &self.retrograde_grace {
+            info!("🕊️ Retrograde grace: {} slices eased", grace.graced());
+        }
+
+        if let Some(boosts) = &self.syscall_boosts {
+            info!("📞 Syscall boosts: {}", boosts.applied());
+        }
+
+        self.print_wakeup_stats();
+        self.print_task_type_metrics();
     }
 
-    fn now() -> u64 {
-        SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
+    /// How the wakeup pair and chain boosts went
+    fn print_wakeup_stats(&self) {
+        if let Some(wakeups) = &self.wakeups {
+            let stats = wakeups.stats();
+            info!(
+                "🤝 Wakeup pairs: seen={} compatible={} boosted={} evicted={}",
+                stats.pairs_seen, stats.compatible_pairs, stats.boosts_applied, stats.evictions
+            );
+        }
+
+        if let Some(chain) = &self.wakeup_chain {
+            let stats = chain.stats();
+            info!("⛓️ Wakeup chains: boosted={} past the hop limit={}", stats.boosted, stats.hop_capped);
+        }
     }
 
-    fn print_cosmic_weather(&mut self) {
-        let now = Utc::now();
-        let weather = self.astro.get_cosmic_weather(now);
-        println!("\n{weather}\n");
+    fn bpf_counters(&mut self) -> BpfCounters {
+        BpfCounters {
+            nr_online_cpus: *self.bpf.nr_online_cpus_mut(),
+            nr_running: *self.bpf.nr_running_mut(),
+            nr_queued: *self.bpf.nr_queued_mut(),
+            nr_scheduled: *self.bpf.nr_scheduled_mut(),
+            nr_user_dispatches: *self.bpf.nr_user_dispatches_mut(),
+            nr_kernel_dispatches: *self.bpf.nr_kernel_dispatches_mut(),
+            nr_cancel_dispatches: *self.bpf.nr_cancel_dispatches_mut(),
+            nr_bounce_dispatches: *self.bpf.nr_bounce_dispatches_mut(),
+            nr_failed_dispatches: *self.bpf.nr_failed_dispatches_mut(),
+            nr_sched_congested: *self.bpf.nr_sched_congested_mut(),
+        }
     }
 
-    fn dispatch_tasks(&mut self) {
-        let now_chrono = Utc::now();
+    /// Answer whatever horoscope-query clients are waiting
+    fn serve_queries(&mut self) {
+        let Some(server) = self.ipc.take() else {
+            return;
+        };
+        let stats = StatsSnapshot {
+            bpf: self.bpf_counters(),
+            batches: self.batches,
+            forced_dispatches: self.starvation.forced(),
+            degraded_ms: self.backpressure.as_ref().map_or(0, |backpressure| {
+                u64::try_from(backpressure.time_degraded(Instant::now()).as_millis()).unwrap_or(u64::MAX)
+            }),
+        };
+        let result = server.serve_pending(|request| query::answer(request, &mut self.astro, &self.opts, &stats, Utc::now()));
+        if let Err(e) = result {
+            error!("Failed to accept a query connection: {e}");
+        }
+        self.ipc = Some(server);
+    }
 
-        // Update planetary positions periodically
-        let current_time = Self::now();
-        if current_time - self.last_update >= self.opts.update_interval {
-            debug!("Updating planetary positions...");
-            self.last_update = current_time;
-        }
-
-        // Process each waiting task
-        loop {
-            match self.bpf.dequeue_task() {
-                Ok(Some(task)) => {
-                    // Get task name from comm field - convert i8 to u8
-                    #[allow(clippy::cast_sign_loss)]
-                    let comm_bytes: Vec<u8> = task.comm.iter().map(|&c| c as u8).collect();
-                    let comm = String::from_utf8_lossy(&comm_bytes)
-                        .trim_end_matches('\0')
-                        .to_string();
-
-                    // Make astrological scheduling decision
-                    let decision = self.astro.schedule_task(&comm, task.pid, now_chrono);
-
-                    // Create dispatched task
-                    let mut dispatched_task = DispatchedTask::new(&task);
-
-                    // Select CPU
-                    let cpu = self.bpf.select_cpu(task.pid, task.cpu, task.flags);
-                    dispatched_task.cpu = if cpu >= 0 { cpu } else { RL_CPU_ANY };
-
-                    // Calculate time slice based on priority
-                    // Higher astrological priority = longer time slice
-                    let priority_factor = (f64::from(decision.priority) / 1000.0).clamp(0.1, 1.0);
-                    #[allow(clippy::cast_precision_loss)]
-                    let base_slice = (self.opts.slice_us * 1000) as f64; // to nanoseconds
-                    #[allow(clippy::cast_precision_loss)]
-                    let min_slice = (self.opts.slice_us_min * 1000) as f64;
-
-                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-                    let slice_ns = (min_slice + (base_slice - min_slice) * priority_factor) as u64;
-                    dispatched_task.slice_ns = slice_ns;
-
-                    // Apply retrograde penalty if enabled
-                    if !self.opts.no_retrograde && decision.planetary_influence < 0.0 {
-                        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-                        let penalized = (dispatched_task.slice_ns as f64 * 0.5) as u64;
-                        dispatched_task.slice_ns = penalized;
-                    }
+    /// Answer the `scx_stats` requests waiting since the last round
+    fn serve_metrics(&mut self) {
+        let Some((responses, requests)) = self.stats_server.as_ref().map(StatsServer::channels) else {
+            return;
+        };
+        while requests.try_recv().is_ok() {
+            let metrics = self.metrics_snapshot();
+            if responses.send(metrics).is_err() {
+                break;
+            }
+        }
+    }
 
-                    if self.opts.debug_decisions {
-                        let slice_microseconds = dispatched_task.slice_ns / 1000;
-                        debug!(
-                            "[PID {}] {} | Priority: {} | Slice: {slice_microseconds}μs | {}",
-                            task.pid,
-                            comm,
-                            decision.priority,
-                            decision.reasoning
-                        );
-                    }
+    /// Where things stand, for `scx_stats` clients
+    fn metrics_snapshot(&mut self) -> Metrics {
+        let now = Utc::now();
+        let tension = self.astro.positions(now).map_or(0, |positions| astrology::tension_score(&positions));
+        Metrics {
+            version: METRICS_VERSION,
+            user_dispatches: *self.bpf.nr_user_dispatches_mut(),
+            kernel_dispatches: *self.bpf.nr_kernel_dispatches_mut(),
+            queued: *self.bpf.nr_queued_mut(),
+            scheduled: *self.bpf.nr_scheduled_mut(),
+            memo_hit_rate: self.memo.hit_rate(),
+            tension_score: tension,
+            ..self.metrics.clone()
+        }
+    }
 
-                    // Dispatch the task
-                    if let Err(e) = self.bpf.dispatch_task(&dispatched_task) {
-                        let pid = task.pid;
-                        error!("Failed to dispatch task {pid}: {e:?}");
-                    }
-                }
-                Ok(None) => {
-                    // Queue empty, exit loop normally
-                    break;
-                }
-                Err(code) => {
-                    log::error!("Failed to dequeue task from ring buffer: error code {code}");
-                    break;
-                }
-            }
+    /// The per-task-type block of the stats output
+    fn print_task_type_metrics(&self) {
+        let lines = self.metrics.task_type_lines();
+        if lines.is_empty() {
+            return;
         }
+        info!("🔮 By task type:");
+        for line in lines {
+            info!("{line}");
+        }
+    }
 
-        // Notify completion and sleep until more tasks arrive
-        self.bpf.notify_complete(0);
+    /// Capture everything the scheduler knows right now
+    fn diagnostic_report(&mut self, decisions: &DecisionLog) -> Result<String> {
+        let now = Utc::now();
+        let bpf = self.bpf_counters();
+        let report = DiagnosticReport::new(now, &self.opts, &mut self.astro, bpf, decisions)?;
+        Ok(report.render(self.opts.diagnostic_report_format))
     }
 
-    fn print_stats(&mut self) {
-        let nr_user_dispatches = *self.bpf.nr_user_dispatches_mut();
-        let nr_kernel_dispatches = *self.bpf.nr_kernel_dispatches_mut();
-        let nr_queued = *self.bpf.nr_queued_mut();
-        let nr_scheduled = *self.bpf.nr_scheduled_mut();
+    fn write_diagnostic_report(&mut self) {
+        let Some(decisions) = self.decisions.take() else {
+            return;
+        };
+
+        let path = PathBuf::from(format!(
+            "horoscope_diagnostic_{}.{}",
+            Self::now(),
+            self.opts.diagnostic_report_format.extension()
+        ));
+        match self.diagnostic_report(&decisions) {
+            Ok(report) => match std::fs::write(&path, report) {
+                Ok(()) => info!("🩺 Diagnostic report written to {}", path.display()),
+                Err(e) => error!("Failed to write diagnostic report to {}: {e}", path.display()),
+            },
+            Err(e) => error!("Failed to build diagnostic report: {e}"),
+        }
+    }
+
+    fn auto_tune(&mut self) {
+        let Some(stats) = self.stats.as_mut() else {
+            return;
+        };
+        if self.last_tune.elapsed() < Duration::from_secs(self.opts.auto_tune_interval) {
+            return;
+        }
 
         info!(
-            "⭐ Dispatches: user={nr_user_dispatches} kernel={nr_kernel_dispatches} | Tasks: queued={nr_queued} scheduled={nr_scheduled}"
+            "🔧 Auto-tune: {} decisions, average queue depth {:.1}, P99 decision latency under {}μs",
+            stats.decisions(),
+            stats.average_queue_depth(),
+            stats.decision_latency(99.0).unwrap_or_default().as_micros()
         );
+        let changes = optimize_opts(stats, &self.opts);
+        if changes.is_empty() {
+            info!("🔧 Auto-tune: the cosmos is content, no changes");
+        }
+        for rationale in &changes.rationale {
+            info!("🔧 Auto-tune: {rationale}");
+        }
+        changes.apply(&mut self.opts);
+        for (&task_type, &priority) in &changes.priority_floor_changes {
+            self.astro.set_base_priority(task_type, priority);
+        }
+
+        stats.reset();
+        self.last_tune = Instant::now();
+    }
+
+    fn apply_energy_hints(&mut self) {
+        let Some(energy) = self.energy.as_mut() else {
+            return;
+        };
+        for (cpu, e) in energy.apply(Instant::now()) {
+            error!("Failed to set the cpufreq governor of CPU {cpu}: {e}");
+        }
+    }
+
+    /// Notice CPUs going offline or coming back, and stop placing tasks on the ones that are gone
+    fn check_hotplug(&mut self) {
+        let change = match self.online.poll(Instant::now()) {
+            Ok(Some(change)) => change,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("Cannot read the online CPUs: {e}");
+                return;
+            }
+        };
+        if !change.offline.is_empty() {
+            warn!("🔌 CPUs went offline: {:?}", change.offline);
+        }
+        if !change.online.is_empty() {
+            info!("🔌 CPUs came online: {:?}", change.online);
+        }
+        self.apply_online_mask();
+        if let Some(numa) = self.numa.as_mut() {
+            numa.forget_cpus(&change.offline);
+        }
+        if self
+            .affinity_partner
+            .is_some_and(|(_, cpu)| usize::try_from(cpu).is_ok_and(|cpu| change.offline.contains(&cpu)))
+        {
+            self.affinity_partner = None;
+        }
+    }
+
+    /// Pin the dispatch thread and raise it to `SCHED_FIFO` as asked, warning rather than failing without permission
+    fn pin_self(&self) {
+        if let Some(cpu) = self.opts.self_cpu {
+            if let Err(e) = self_pin::pin_to_cpu(cpu) {
+                warn!("📌 Cannot pin the scheduler to CPU {cpu}: {}", self_pin::describe_refusal("sched_setaffinity", &e));
+            }
+        }
+        if self.opts.self_rt {
+            if let Err(e) = self_pin::set_fifo(self_pin::SELF_RT_PRIORITY) {
+                warn!("📌 Staying under SCHED_OTHER: {}", self_pin::describe_refusal("SCHED_FIFO", &e));
+            }
+        }
+    }
+
+    /// Rebuild the pools and idle masks from the CPUs that are online
+    fn apply_online_mask(&mut self) {
+        let online = self.online.mask();
+        if let Some(pools) = self.cpu_pools.as_mut() {
+            pools.set_online(online);
+        }
+        for tracker in [self.cpu_idle.as_mut(), self.fire_cpus.as_mut()].into_iter().flatten() {
+            tracker.set_online(online);
+        }
+    }
+
+    /// Look at the Moon, and change the energy-performance preferences if its phase calls for it
+    fn apply_lunar_hints(&mut self) {
+        let Some(lunar) = self.lunar_cpufreq.as_mut() else {
+            return;
+        };
+        if !lunar.due(Instant::now()) {
+            return;
+        }
+        let phase = match self.astro.positions(Utc::now()) {
+            Ok(positions) => find_position(&positions, Planet::Moon).ok().and_then(|moon| moon.moon_phase),
+            Err(e) => {
+                error!("The Moon is hidden, energy preferences unchanged: {e}");
+                return;
+            }
+        };
+        let Some(phase) = phase else {
+            return;
+        };
+        let before = lunar.applied();
+        for (cpu, e) in lunar.apply(phase) {
+            error!("Failed to set the energy-performance preference of CPU {cpu}, leaving it be: {e}");
+        }
+        if lunar.applied() != before {
+            match lunar.applied() {
+                Some(hint) => info!("🌕 {}: energy-performance preference {}", phase.name(), hint.name()),
+                None => info!("🌓 {}: energy-performance preferences back to the system's", phase.name()),
+            }
+        }
+    }
+
+    fn restore_energy_preferences(&mut self) {
+        let Some(lunar) = self.lunar_cpufreq.as_mut() else {
+            return;
+        };
+        match lunar.restore() {
+            Ok(()) => info!("🌙 Restored the original energy-performance preferences"),
+            Err(e) => error!("Failed to restore energy-performance preferences: {e}"),
+        }
+    }
+
+    fn restore_governors(&mut self) {
+        let Some(energy) = self.energy.as_mut() else {
+            return;
+        };
+        if !self.opts.energy_governor_restore_on_exit {
+            return;
+        }
+        match energy.restore() {
+            Ok(()) => info!("⚡ Restored the original cpufreq governors"),
+            Err(e) => error!("Failed to restore cpufreq governors: {e}"),
+        }
+    }
+
+    fn restore_nice_values(&mut self) {
+        let Some(nice_map) = self.nice_map.as_mut() else {
+            return;
+        };
+        match nice_map.restore() {
+            Ok(()) => info!("🙏 Restored the original nice values"),
+            Err(e) => error!("Failed to restore nice values: {e}"),
+        }
+    }
+
+    fn dump_bpf_maps(&mut self) {
+        let Some(dumper) = self.map_dumper.as_mut() else {
+            return;
+        };
+
+        let now = Instant::now();
+        if dumper.due(now) {
+            let dumps = map_dump::collect_maps(&self.bpf.skel, Self::now());
+            if let Err(e) = dumper.write(&dumps, now) {
+                error!("Failed to write BPF map dump to {}: {e}", self.opts.bpf_dump_file.display());
+            }
+        }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn run(&mut self) -> Result<UserExitInfo> {
-        let mut prev_ts = Self::now();
+        let mut stats_ticker = StatsTicker::new(Duration::from_millis(self.opts.stats_interval_ms), Instant::now());
+        let mut periodic_stats =
+            (self.opts.stats_secs > 0).then(|| PeriodicStats::new(Duration::from_secs(self.opts.stats_secs), Instant::now()));
 
-        info!("🌟 Horoscope Scheduler Starting 🌟");
+        info!("🌟 Scheduler {} Starting 🌟", self.opts.scheduler_name);
         info!("The cosmos shall guide your CPU scheduling decisions!");
 
         if self.opts.cosmic_weather {
             self.print_cosmic_weather();
         }
+        self.log_daily_message();
+        self.apply_online_mask();
+        // After init, so the probe and watchdog threads it spawned stay where they were
+        self.pin_self();
+        // After init too, replacing the Ctrl-C handler the BPF scheduler sets up
+        if let Err(e) = shutdown::install() {
+            warn!("Cannot catch SIGINT and SIGTERM, they will cut the scheduler off mid-dispatch: {e}");
+        }
+        // Under a warm-up the first chart arrives in the background, and is weighed then
+        if self.opts.warmup_dispatches == 0 {
+            self.update_caution();
+        }
+
+        if self.opts.dispatch_dry_run {
+            info!("{} decisions are logged, not applied: every task runs with the default slice", dry_run::DRY_RUN_PREFIX);
+        }
 
         info!("Scheduler configuration:");
         info!("  Default time slice: {}μs", self.opts.slice_us);
         info!("  Min time slice: {}μs", self.opts.slice_us_min);
-        info!("  Planetary update interval: {}s", self.opts.update_interval);
-        info!("  Retrograde effects: {}", if self.opts.no_retrograde { "DISABLED" } else { "ENABLED" });
+        info!("  Max time slice: {}μs", slice::slice_us_max(&self.opts));
+        if let Some(critical_slice_us) = self.opts.critical_slice_us {
+            info!("  Critical time slice: {critical_slice_us}μs");
+        }
+        for (element, personality) in &self.opts.element_slice {
+            info!("  {} slices favor {} (×{:.1})", element.name(), personality.name(), personality.factor());
+        }
+        info!("  Dispatch batch size: {} (ordered by {:?})", self.opts.batch_size, self.opts.fairness);
+        if self.opts.cosmic_tiebreak {
+            info!("  Cosmic tie-break: equal priorities shuffled by seed {} and the Julian day", self.astro.rng().seed());
+        }
+        if let Some(cpu) = self.opts.self_cpu {
+            info!("  Scheduler thread pinned to CPU {cpu}, kept free of Fire tasks");
+        }
+        if self.opts.self_rt {
+            info!("  Scheduler thread under SCHED_FIFO, priority {}", self_pin::SELF_RT_PRIORITY);
+        }
+        match self.opts.dispatch_budget_us {
+            Some(us) => info!("  Dispatch budget: {} tasks or {us}μs per iteration", self.opts.dispatch_budget),
+            None => info!("  Dispatch budget: {} tasks per iteration", self.opts.dispatch_budget),
+        }
+        if let Some(backpressure) = &self.backpressure {
+            info!(
+                "  Backpressure: fast path above {} queued tasks, full readings again at {}",
+                self.opts.backpressure_threshold.unwrap_or_default(),
+                backpressure.low()
+            );
+        }
+        if let Some(caution) = &self.caution {
+            info!(
+                "  Caution: slices cut to 75% and batches unsorted at tension {}, lifted at {}",
+                self.opts.caution_threshold.unwrap_or_default(),
+                caution.exit()
+            );
+        }
+        if !self.opts.prio_boost_system_calls.is_empty() {
+            info!(
+                "  Syscall boosts: ×{:.2} after {}",
+                self.opts.syscall_boost_factor,
+                self.opts.prio_boost_system_calls.join(", ")
+            );
+        }
+        if self.opts.update_jitter > 0 {
+            info!("  Planetary update interval: {}s ± {}%", self.opts.update_interval, self.opts.update_jitter);
+        } else {
+            info!("  Planetary update interval: {}s", self.opts.update_interval);
+        }
+        match self.opts.seed {
+            Some(seed) => info!("  Random seed: {seed}"),
+            None => info!("  Random seed: {} (from entropy; pass it to --seed to repeat this run)", self.astro.rng().seed()),
+        }
+        if self.opts.no_retrograde {
+            info!("  Retrograde effects: DISABLED");
+        } else {
+            info!("  Retrograde effects: ENABLED (slice ×{:.2})", self.opts.retrograde_factor);
+            if !self.opts.retrograde_exempt.is_empty() {
+                let exempt: Vec<_> = self.opts.retrograde_exempt.iter().map(|task_type| task_type.name()).collect();
+                info!("  Retrograde exemptions: {}", exempt.join(", "));
+            }
+        }
+        if self.opts.retrograde_grace_secs > 0 {
+            info!("  Retrograde grace: running tasks eased into the penalty over {}s", self.opts.retrograde_grace_secs);
+        }
+        if let Some(penalty) = self.opts.void_moon_penalty {
+            info!("  Void-of-course Moon: first slice ×{penalty:.2} for new tasks");
+        }
+        info!("  Out-of-bounds jitter: {}", if self.opts.no_out_of_bounds_jitter { "DISABLED" } else { "ENABLED" });
+        info!("  Aspects: {}", if self.opts.no_aspects { "DISABLED" } else { "ENABLED" });
+        info!("  Fixed stars: {}", if self.opts.no_fixed_stars { "DISABLED" } else { "ENABLED" });
+        info!(
+            "  Planetary influence: baseline {:.2}, retrograde {:.2}",
+            self.opts.influence_baseline, self.opts.retrograde_influence
+        );
+        for &(planet, influence) in &self.opts.planet_retrograde_influence {
+            info!("  Retrograde {}: influence {influence:.2}", planet.name());
+        }
+        info!("  Nice weight blend: {:.2}", self.opts.weight_blend);
+        info!("  Starvation guard: after {}ms", self.opts.starvation_threshold_ms);
+        if self.opts.verbose {
+            info!("  Statistics: every {}ms", self.opts.stats_interval_ms);
+        }
+        if self.opts.stats_secs > 0 {
+            info!("  Stats line: every {}s", self.opts.stats_secs);
+        }
+        if self.opts.sun_sign_affinity {
+            info!("  Sun sign affinity: ENABLED");
+        }
+        if self.opts.enable_process_affinity {
+            info!("  Process affinity: pairs scoring {:.1}+ on neighboring CPUs", cpu_affinity::AFFINITY_THRESHOLD);
+        }
+        if self.opts.enable_astrocartography {
+            info!("  Astrocartography: rulers within {:.0}° of a CPU's angles", astrology::ANGULAR_ORB);
+        }
+        if self.opts.mundane_astrology {
+            info!("  Mundane astrology: Jupiter and Saturn watched within {:.0}°", astrology::MUNDANE_ORB);
+        }
+        if let Some(unit) = self.opts.progressed_day_unit {
+            info!("  Solar arc progressions: a day of solar motion every {unit}s of runtime");
+        }
+        if self.opts.unknown_no_moon {
+            info!("  Moon phase for unclassified tasks: DISABLED");
+        }
+        if self.opts.fuzzy_match_distance > 0 {
+            info!("  Fuzzy classification: up to {} edits from a pattern", self.opts.fuzzy_match_distance);
+        }
+        if self.opts.keyword_classify {
+            let words: usize = astrology::KEYWORD_TABLE.iter().map(|(_, words)| words.len()).sum();
+            info!("  Keyword classification: {words} words from the command line");
+        }
+        if self.opts.warmup_dispatches > 0 {
+            info!("  Warm-up: first {} tasks at base priority", self.opts.warmup_dispatches);
+        }
+        if let Some(zodiac) = &self.cpu_zodiac {
+            let signs: Vec<_> = zodiac
+                .signs()
+                .iter()
+                .enumerate()
+                .map(|(cpu, sign)| format!("{cpu}={} ({})", sign.name(), sign.element().name()))
+                .collect();
+            info!("  CPU zodiac: {}", signs.join(", "));
+        }
+        if self.smt.is_some() {
+            info!(
+                "  SMT: CPU-Intensive tasks avoid busy siblings{}",
+                if self.opts.no_smt_for_fire { ", never sharing a core with each other" } else { "" }
+            );
+        }
+        if let Some(numa) = &self.numa {
+            info!(
+                "  NUMA: Memory-Heavy tasks stay on their home node of {}, waiting up to {}ms for it",
+                numa.topology().nr_nodes(),
+                self.opts.numa_patience_ms
+            );
+        }
+        if let Some(nice_map) = &self.nice_map {
+            info!("  Nice values by element: {}", nice_map.describe());
+        }
+        if let Some(lunar) = &self.lunar_cpufreq {
+            info!("  Lunar energy preferences: {} CPUs, restored on exit", lunar.cpus().len());
+        }
+        if self.opts.energy_aware {
+            info!(
+                "  Energy-aware governors: every {}ms{}",
+                self.opts.energy_poll_interval,
+                if self.opts.energy_governor_restore_on_exit { ", restored on exit" } else { "" }
+            );
+        }
+        for (element, mask) in &self.opts.cpu_pool {
+            info!("  CPU pool: {} tasks on {} CPUs", element.name(), mask.weight());
+        }
+        info!("  CPU fallback: {}", self.opts.cpu_fallback.name());
+        if self.opts.auto_tune {
+            info!("  Auto-tune: every {}s", self.opts.auto_tune_interval);
+        }
+        if let Some(timeout) = self.opts.watchdog_timeout {
+            info!(
+                "  Watchdog: {timeout}s timeout{}{}",
+                if self.opts.watchdog_restart { ", restarts on stall" } else { "" },
+                self.opts.watchdog_deadline.map(|deadline| format!(", exits after {deadline}s")).unwrap_or_default()
+            );
+        }
+        if let Some(path) = &self.opts.log_file {
+            let trigger = match self.opts.log_rotation_trigger {
+                RotationTrigger::Size => format!("at {} MB", self.opts.log_max_size_mb),
+                RotationTrigger::Time => format!("every {}h", self.opts.log_rotation_hours),
+                RotationTrigger::Daily => "daily at midnight UTC".to_string(),
+            };
+            info!(
+                "  Log file: {} (rotated {trigger}, keeping {}{})",
+                path.display(),
+                self.opts.log_rotation_count,
+                if self.opts.log_compress_rotated { " gzipped" } else { "" }
+            );
+        }
+        if self.opts.diagnostic_report {
+            info!("  Diagnostic report: after the first dispatch cycle ({:?})", self.opts.diagnostic_report_format);
+        }
+        if let Some(path) = &self.opts.summary_file {
+            info!("  Summary file: {}", path.display());
+        }
+        if let Some(path) = &self.opts.socket_path {
+            info!("  Query socket: {}", path.display());
+        }
+        if let Some(interval) = self.opts.bpf_map_dump {
+            info!("  BPF map dump: every {interval}s to {}", self.opts.bpf_dump_file.display());
+        }
+        if self.opts.probe_memory {
+            info!(
+                "  Memory probe: top {} tasks every {}ms, Memory-Heavy above {} MiB",
+                self.opts.probe_top, self.opts.probe_interval_ms, self.opts.probe_memory_threshold_mb
+            );
+        }
+        if self.opts.probe_network {
+            info!(
+                "  Network probe: top {} tasks every {}ms, Network at {} sockets (decays after {} rounds)",
+                self.opts.probe_top, self.opts.probe_interval_ms, self.opts.probe_network_threshold, self.opts.probe_network_decay
+            );
+        }
+        let mut critical = vec!["init"];
+        if !self.opts.no_critical_kthreads {
+            critical.push("core kernel threads");
+        }
+        if !self.opts.no_critical_rt {
+            critical.push("real-time tasks");
+        }
+        critical.extend(self.opts.critical_comm.iter().map(String::as_str));
+        info!("  Critical tasks: {}", critical.join(", "));
+        for (task_type, planet) in &self.opts.rulership {
+            info!("  Rulership: {} tasks ruled by {}", task_type.name(), planet.name());
+        }
 
-        while !self.bpf.exited() {
-            self.dispatch_tasks();
+        self.heartbeat.beat();
+        let mut failure = None;
+        while !self.bpf.exited() && !self.restart_requested() && !SHUTDOWN.is_requested() {
+            if let Err(e) = self.dispatch_tasks() {
+                failure = Some(e);
+                break;
+            }
+            self.write_diagnostic_report();
+            self.dump_bpf_maps();
+            self.auto_tune();
+            self.apply_energy_hints();
+            self.apply_lunar_hints();
+            self.check_hotplug();
+            self.serve_queries();
+            self.serve_metrics();
 
-            let curr_ts = Self::now();
-            if curr_ts > prev_ts {
-                if self.opts.verbose {
-                    self.print_stats();
+            if stats_ticker.tick(Instant::now()) && self.opts.verbose {
+                self.print_stats();
+            }
+            if let Some(periodic) = periodic_stats.as_mut() {
+                if let Some(line) = periodic.poll(Instant::now(), || self.periodic_snapshot()) {
+                    info!("{line}");
+                    self.print_task_type_metrics();
                 }
-                prev_ts = curr_ts;
             }
         }
 
-        info!("🌙 Scheduler shutting down gracefully...");
+        if let Some(e) = failure {
+            error!("💥 {e}");
+            self.restore_governors();
+            self.restore_energy_preferences();
+            self.restore_nice_values();
+            return Err(e);
+        }
+
+        info!("🌙 Scheduler {} shutting down gracefully...", self.opts.scheduler_name);
+        if SHUTDOWN.is_requested() {
+            self.print_stats();
+            for line in self.exit_summary().lines(Utc::now()) {
+                info!("{line}");
+            }
+        }
+        if let Some(path) = self.opts.summary_file.clone() {
+            match self.exit_summary().write_report(&path, Utc::now()) {
+                Ok(()) => info!("🧾 Summary written to {}", path.display()),
+                Err(e) => error!("Failed to write summary to {}: {e}", path.display()),
+            }
+        }
+        self.restore_governors();
+        self.restore_energy_preferences();
+        self.restore_nice_values();
         self.bpf.shutdown_and_report()
     }
 }
 
-fn print_warning() {
-    let warning = r"
+fn build_astro_scheduler(opts: &Opts) -> Result<AstrologicalScheduler> {
+    let mut rulership = RulershipMap::default();
+    for &(task_type, planet) in &opts.rulership {
+        rulership.reassign(task_type, planet);
+    }
+
+    let critical = opts.critical_comm.iter().fold(
+        CriticalPolicy::new()
+            .with_kthreads(!opts.no_critical_kthreads)
+            .with_rt_class(!opts.no_critical_rt),
+        |policy, comm| policy.with_comm(comm),
+    );
+
+    #[allow(clippy::cast_possible_wrap)]
+    let mut astro = AstrologicalScheduler::new(opts.update_interval as i64)
+        .with_weight_blend(opts.weight_blend)
+        .with_influence_baseline(opts.influence_baseline)
+        .with_retrograde_influence(opts.retrograde_influence)
+        .with_retrograde_exemptions(opts.retrograde_exempt.iter().copied())
+        .with_out_of_bounds_jitter(!opts.no_out_of_bounds_jitter)
+        .with_aspects(!opts.no_aspects)
+        .with_fixed_stars(!opts.no_fixed_stars)
+        .with_reasoning(opts.debug_decisions || opts.diagnostic_report)
+        .with_sun_sign_affinity(opts.sun_sign_affinity)
+        .with_mundane_astrology(opts.mundane_astrology)
+        .with_unknown_moon(!opts.unknown_no_moon)
+        .with_rulership(rulership)
+        .with_critical_policy(critical)
+        .with_warmup_dispatches(opts.warmup_dispatches)
+        .with_rng(CosmicRng::new(opts.seed))
+        .with_update_jitter(f64::from(opts.update_jitter) / 100.0);
+    for &(task_type, priority) in &opts.base_priority {
+        astro.set_base_priority(task_type, priority);
+    }
+    for &(planet, influence) in &opts.planet_retrograde_influence {
+        astro = astro.with_planet_retrograde_influence(planet, influence);
+    }
+    if let (Some(latitude), Some(longitude)) = (opts.latitude, opts.longitude) {
+        astro = astro.with_location(latitude, longitude);
+    }
+    if let Some(unit) = opts.progressed_day_unit {
+        astro = astro.with_progressions(unit);
+    }
+    if let Some(path) = &opts.classifier_file {
+        let json = std::fs::read_to_string(path)?;
+        astro = astro.with_classifier(TaskClassifier::from_json(&json)?);
+    }
+    astro.ingress_notifier_mut().register_callback(Box::new(|event| info!("🪐 {event}")));
+    Ok(astro.with_keyword_classify(opts.keyword_classify).with_fuzzy_match_distance(opts.fuzzy_match_distance))
+}
+
+fn print_warning(name: &str) {
+    let warning = format!(
+        r"
 **************************************************************************
 
-🌌 ASTROLOGICAL SCHEDULER - COSMIC WARNING 🌌
+🌌 ASTROLOGICAL SCHEDULER '{name}' - COSMIC WARNING 🌌
 
 This scheduler makes task scheduling decisions based on planetary positions,
 zodiac signs, and astrological principles. While the astronomical calculations
@@ -249,13 +2339,46 @@ CPU tasks is:
 
 If Mercury goes retrograde during your compile, don't say we didn't warn you!
 
-**************************************************************************";
+**************************************************************************"
+    );
 
     println!("{warning}");
 }
 
+/// Run the scheduler, bringing it back up with a growing wait whenever it asks to restart
+fn run_with_restarts(opts: &Opts) -> Result<()> {
+    let mut open_object = MaybeUninit::uninit();
+    let mut hooks_object = MaybeUninit::uninit();
+    let mut restarts = RestartPolicy::new(opts.max_restarts);
+    loop {
+        let mut sched = Scheduler::init(&mut open_object, &mut hooks_object, opts.clone())?;
+        let uei = sched.run()?;
+        let watchdog_stall = sched.restart_requested();
+        if !watchdog_stall && !uei.should_restart() {
+            break;
+        }
+        let reason = restart::restart_reason(&uei, watchdog_stall);
+        match restarts.next(sched.started.elapsed()) {
+            RestartDecision::Restart { attempt, after } => {
+                info!(
+                    "🔄 Restarting the scheduler after {reason} (restart {attempt}/{} in a row, in {after:?})",
+                    restarts.max_restarts()
+                );
+                drop(sched);
+                std::thread::sleep(after);
+            }
+            RestartDecision::GiveUp { restarts } => {
+                anyhow::bail!("Giving up after {restarts} restarts in a row without a healthy run; the last was for {reason}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let opts = Opts::parse();
+    opts.validate().map_err(anyhow::Error::msg)?;
 
     // Set up logging
     let log_level = if opts.verbose || opts.debug_decisions {
@@ -264,23 +2387,101 @@ fn main() -> Result<()> {
         LevelFilter::Info
     };
 
-    TermLogger::init(
-        log_level,
-        Config::default(),
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )?;
+    match &opts.log_file {
+        Some(path) => {
+            let policy = RotationPolicy {
+                trigger: opts.log_rotation_trigger,
+                max_size: opts.log_max_size_mb.saturating_mul(1024 * 1024),
+                interval: Duration::from_secs(opts.log_rotation_hours.saturating_mul(3600)),
+                keep: opts.log_rotation_count,
+                compress: opts.log_compress_rotated,
+            };
+            let file = RotatingFileLogger::new(path, policy)?;
+            CombinedLogger::init(vec![
+                TermLogger::new(log_level, Config::default(), TerminalMode::Mixed, ColorChoice::Auto),
+                WriteLogger::new(log_level, Config::default(), file),
+            ])?;
+        }
+        None => TermLogger::init(log_level, Config::default(), TerminalMode::Mixed, ColorChoice::Auto)?,
+    }
 
-    print_warning();
+    // Informational commands that don't need the BPF scheduler
+    if let Some(path) = &opts.dump_classifier {
+        let json = build_astro_scheduler(&opts)?.classifier().export_json();
+        match path {
+            Some(path) => std::fs::write(path, json + "\n")?,
+            None => println!("{json}"),
+        }
+        return Ok(());
+    }
+    if opts.list_signs {
+        println!("{}", AstrologicalScheduler::sign_reference());
+    }
+    if opts.list_planets {
+        println!("{}", build_astro_scheduler(&opts)?.planet_reference());
+    }
+    if opts.list_signs || opts.list_planets {
+        return Ok(());
+    }
 
-    // Initialize and run the scheduler
-    let mut open_object = MaybeUninit::uninit();
-    loop {
-        let mut sched = Scheduler::init(&mut open_object, opts.clone())?;
-        if !sched.run()?.should_restart() {
-            break;
+    if let Some((a, b)) = opts.compare_times {
+        let comparison = build_astro_scheduler(&opts)?.compare_conditions(a, b)?;
+        println!("{comparison}");
+        return Ok(());
+    }
+
+    if let Some(task_type) = opts.lucky_hours {
+        print!("{}", build_astro_scheduler(&opts)?.lucky_hours_reference(task_type, Utc::now())?);
+        return Ok(());
+    }
+
+    if let Some(element) = opts.element_forecast {
+        print!("{}", build_astro_scheduler(&opts)?.element_forecast_reference(element, Utc::now(), opts.hours)?);
+        return Ok(());
+    }
+
+    if let Some(pid) = opts.return_report {
+        let Some(birth) = StartTimeCache::new().start_time(pid) else {
+            anyhow::bail!("No start time for PID {pid}: is it running?");
+        };
+        let mut astro = build_astro_scheduler(&opts)?;
+        astro.note_task_start(pid, birth);
+        match astro.return_reference(pid, Utc::now()) {
+            Some(reference) => print!("{reference}"),
+            None => anyhow::bail!("Could not find the returns of PID {pid}"),
         }
+        return Ok(());
     }
 
-    Ok(())
+    if let Some(interval) = opts.monitor {
+        shutdown::install()?;
+        return metrics::monitor(opts.stats_socket.as_deref(), Duration::from_secs_f64(interval), || SHUTDOWN.is_requested());
+    }
+
+    if let Some(iterations) = opts.profile_ephemeris {
+        let iterations = usize::try_from(iterations)?;
+        println!("{}", ephemeris_profile::profile_ephemeris(iterations, opts.profile_aspects, Utc::now())?);
+        return Ok(());
+    }
+
+    // Fail with a readable message rather than deep inside libbpf
+    let kernel = compat::check_kernel_version().and_then(|version| {
+        compat::check_bpf_sched_ext_support()?;
+        Ok(version)
+    });
+    match kernel {
+        Ok((major, minor, patch)) if opts.check_only => {
+            info!("✅ Linux {major}.{minor}.{patch} with sched_ext: ready for the stars");
+            return Ok(());
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("❌ {e}");
+            std::process::exit(2);
+        }
+    }
+
+    print_warning(&opts.scheduler_name);
+
+    run_with_restarts(&opts)
 }
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Periodic BPF map dumps
+//
+// A diagnostic aid for debugging the BPF side: every so often, walk the maps
+// of the loaded skeleton and append their key/value pairs to a file.
+
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use libbpf_rs::skel::Skel;
+use libbpf_rs::{MapCore, MapFlags, MapType};
+use serde::{Deserialize, Serialize};
+
+use crate::bpf_skel::BpfSkel;
+
+/// How map dumps are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DumpFormat {
+    Json,
+    Csv,
+    Text,
+}
+
+/// A map key or value: an integer when it fits in 8 bytes, hex otherwise
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MapValue {
+    Int(u64),
+    Hex(String),
+}
+
+impl MapValue {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        if bytes.len() <= 8 {
+            let mut buf = [0u8; 8];
+            buf[..bytes.len()].copy_from_slice(bytes);
+            MapValue::Int(u64::from_le_bytes(buf))
+        } else {
+            MapValue::Hex(bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            }))
+        }
+    }
+}
+
+impl std::fmt::Display for MapValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapValue::Int(n) => write!(f, "{n}"),
+            MapValue::Hex(hex) => write!(f, "0x{hex}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapEntry {
+    pub key: MapValue,
+    pub value: MapValue,
+}
+
+/// The contents of one map at one moment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapDump {
+    pub timestamp: u64,
+    pub map: String,
+    pub entries: Vec<MapEntry>,
+}
+
+impl MapDump {
+    /// Render this dump in `format`, one record per line
+    pub fn render(&self, format: DumpFormat) -> String {
+        match format {
+            DumpFormat::Json => {
+                let mut line = serde_json::to_string(self).unwrap_or_default();
+                line.push('\n');
+                line
+            }
+            DumpFormat::Csv => self.entries.iter().fold(String::new(), |mut out, entry| {
+                let _ = writeln!(out, "{},{},{},{}", self.timestamp, self.map, entry.key, entry.value);
+                out
+            }),
+            DumpFormat::Text => {
+                let mut out = format!("[{}] {} ({} entries)\n", self.timestamp, self.map, self.entries.len());
+                for entry in &self.entries {
+                    let _ = writeln!(out, "    {} => {}", entry.key, entry.value);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Read every iterable map of the skeleton
+///
+/// Ring buffers have no keys to walk and are skipped.
+pub fn collect_maps(skel: &BpfSkel, timestamp: u64) -> Vec<MapDump> {
+    skel.object()
+        .maps()
+        .filter(|map| !matches!(map.map_type(), MapType::RingBuf | MapType::UserRingBuf))
+        .map(|map| {
+            let entries = map
+                .keys()
+                .filter_map(|key| {
+                    let value = map.lookup(&key, MapFlags::ANY).ok().flatten()?;
+                    Some(MapEntry { key: MapValue::from_bytes(&key), value: MapValue::from_bytes(&value) })
+                })
+                .collect();
+            MapDump { timestamp, map: map.name().to_string_lossy().into_owned(), entries }
+        })
+        .collect()
+}
+
+/// Appends map dumps to a file every `interval`
+pub struct MapDumper {
+    path: PathBuf,
+    format: DumpFormat,
+    interval: Duration,
+    last_dump: Option<Instant>,
+}
+
+impl MapDumper {
+    pub fn new(path: PathBuf, format: DumpFormat, interval: Duration) -> Self {
+        Self { path, format, interval, last_dump: None }
+    }
+
+    /// True when the next dump is due
+    pub fn due(&self, now: Instant) -> bool {
+        self.last_dump.is_none_or(|last| now.duration_since(last) >= self.interval)
+    }
+
+    pub fn write(&mut self, dumps: &[MapDump], now: Instant) -> io::Result<()> {
+        self.last_dump = Some(now);
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        for dump in dumps {
+            file.write_all(dump.render(self.format).as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dump() -> MapDump {
+        MapDump {
+            timestamp: 1_732_000_000,
+            map: "bss".to_string(),
+            entries: vec![
+                MapEntry { key: MapValue::from_bytes(&0u32.to_le_bytes()), value: MapValue::from_bytes(&42u64.to_le_bytes()) },
+                MapEntry { key: MapValue::from_bytes(&1u32.to_le_bytes()), value: MapValue::from_bytes(&[0xde, 0xad, 0xbe, 0xef, 0, 0, 0, 0, 1]) },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_value_encoding() {
+        assert_eq!(MapValue::from_bytes(&7u32.to_le_bytes()), MapValue::Int(7));
+        assert_eq!(MapValue::from_bytes(&[]), MapValue::Int(0));
+        assert_eq!(MapValue::from_bytes(&[0xab; 9]), MapValue::Hex("ababababababababab".to_string()));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dump = sample_dump();
+        let line = dump.render(DumpFormat::Json);
+        assert!(line.ends_with('\n'));
+
+        let parsed: MapDump = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(parsed, dump);
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let json: serde_json::Value = serde_json::from_str(&sample_dump().render(DumpFormat::Json)).unwrap();
+
+        assert_eq!(json["timestamp"], 1_732_000_000);
+        assert_eq!(json["map"], "bss");
+        assert_eq!(json["entries"][0]["key"], 0);
+        assert_eq!(json["entries"][0]["value"], 42);
+        assert_eq!(json["entries"][1]["value"], "deadbeef0000000001");
+    }
+
+    #[test]
+    fn test_csv_and_text() {
+        let dump = sample_dump();
+        assert_eq!(
+            dump.render(DumpFormat::Csv),
+            "1732000000,bss,0,42\n1732000000,bss,1,0xdeadbeef0000000001\n"
+        );
+        assert!(dump.render(DumpFormat::Text).starts_with("[1732000000] bss (2 entries)\n"));
+    }
+
+    #[test]
+    fn test_dumper_interval() {
+        let path = std::env::temp_dir().join(format!("horoscope_map_dump_{}.jsonl", std::process::id()));
+        let mut dumper = MapDumper::new(path.clone(), DumpFormat::Json, Duration::from_secs(5));
+        let t0 = Instant::now();
+
+        assert!(dumper.due(t0));
+        dumper.write(&[sample_dump(), sample_dump()], t0).unwrap();
+        assert!(!dumper.due(t0 + Duration::from_secs(1)));
+        assert!(dumper.due(t0 + Duration::from_secs(5)));
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        for line in contents.lines() {
+            assert!(serde_json::from_str::<MapDump>(line).is_ok());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Per-PID decision memo
+//
+// Within one chart, a task's decision depends only on its name, weight and
+// flags, what the classifier has learned and the base priorities. The memo
+// keeps the last decision for each PID along with all of those, so a task
+// dispatched again and again under the same sky costs one hash lookup. A new
+// chart or a new scheduler revision (a learned class, a classifier rule, a
+// base priority) makes every entry stale; a PID that turns up under another
+// name has been reused, and its entry is dropped. Slices are still worked out
+// per dispatch, since auto-tune can move their bounds.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::astrology::SchedulingDecision;
+use crate::comm::Comm;
+
+/// PIDs remembered before the memo starts over
+pub const DECISION_MEMO_SIZE: usize = 4096;
+
+/// Everything a memoized decision was made from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoKey {
+    pub epoch: Option<DateTime<Utc>>,
+    pub revision: u64,
+    pub comm: Comm,
+    pub weight: u64,
+    pub is_kthread: bool,
+    pub flags: u32,
+}
+
+/// The last decision for each PID, for as long as it holds
+#[derive(Debug, Clone)]
+pub struct DecisionMemo {
+    entries: HashMap<i32, (MemoKey, SchedulingDecision)>,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+    reused: u64,
+}
+
+impl DecisionMemo {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: HashMap::new(), capacity, hits: 0, misses: 0, reused: 0 }
+    }
+
+    /// The decision remembered for `pid`, if it was made from `key`
+    pub fn get(&mut self, pid: i32, key: &MemoKey) -> Option<&SchedulingDecision> {
+        let comm_changed = self.entries.get(&pid).is_some_and(|(made, _)| made.comm != key.comm);
+        if comm_changed {
+            self.entries.remove(&pid);
+            self.reused += 1;
+        }
+        match self.entries.get(&pid) {
+            Some((made, decision)) if made == key => {
+                self.hits += 1;
+                Some(decision)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Remember the decision just made for `pid`
+    pub fn insert(&mut self, pid: i32, key: MemoKey, decision: SchedulingDecision) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&pid) {
+            self.entries.clear();
+        }
+        self.entries.insert(pid, (key, decision));
+    }
+
+    /// Drop a PID's entry, once it has exited or something about it changed
+    pub fn forget(&mut self, pid: i32) {
+        self.entries.remove(&pid);
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Entries dropped because their PID came back under another name
+    pub fn reused(&self) -> u64 {
+        self.reused
+    }
+
+    /// Share of lookups answered from the memo, 0.0 before any
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.hits as f64 / lookups as f64;
+        rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::{AstrologicalScheduler, TaskType, DEFAULT_TASK_WEIGHT};
+    use chrono::TimeZone;
+
+    fn comm(name: &str) -> Comm {
+        #[allow(clippy::cast_possible_wrap)]
+        let raw: Vec<libc::c_char> = name.bytes().map(|byte| byte as libc::c_char).collect();
+        Comm::from_raw(&raw)
+    }
+
+    fn key(epoch: DateTime<Utc>, revision: u64, name: &str) -> MemoKey {
+        MemoKey { epoch: Some(epoch), revision, comm: comm(name), weight: DEFAULT_TASK_WEIGHT, is_kthread: false, flags: 0 }
+    }
+
+    #[test]
+    fn test_memo_hits_within_an_epoch() {
+        let epoch = Utc.with_ymd_and_hms(2025, 11, 19, 22, 0, 0).unwrap();
+        let mut astro = AstrologicalScheduler::new(300);
+        let decision = astro.schedule_task("rustc", 4242, DEFAULT_TASK_WEIGHT, epoch).unwrap();
+        let mut memo = DecisionMemo::new(DECISION_MEMO_SIZE);
+
+        assert!(memo.get(4242, &key(epoch, 0, "rustc")).is_none());
+        memo.insert(4242, key(epoch, 0, "rustc"), decision.clone());
+        assert_eq!(memo.get(4242, &key(epoch, 0, "rustc")).map(|d| d.priority), Some(decision.priority));
+        assert_eq!(memo.get(4242, &key(epoch, 0, "rustc")).map(|d| d.task_type), Some(TaskType::CpuIntensive));
+
+        // Another weight is another decision
+        let heavier = MemoKey { weight: 10_000, ..key(epoch, 0, "rustc") };
+        assert!(memo.get(4242, &heavier).is_none());
+        assert_eq!((memo.hits(), memo.misses()), (2, 2));
+        assert!((memo.hit_rate() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_memo_epoch_and_revision_invalidation() {
+        let epoch = Utc.with_ymd_and_hms(2025, 11, 19, 22, 0, 0).unwrap();
+        let next = epoch + chrono::Duration::minutes(5);
+        let mut astro = AstrologicalScheduler::new(300);
+        let decision = astro.schedule_task("rustc", 4242, DEFAULT_TASK_WEIGHT, epoch).unwrap();
+        let mut memo = DecisionMemo::new(DECISION_MEMO_SIZE);
+        memo.insert(4242, key(epoch, astro.revision(), "rustc"), decision.clone());
+
+        // A new chart
+        assert!(memo.get(4242, &key(next, astro.revision(), "rustc")).is_none());
+
+        // A learned class moves the revision on
+        astro.learn_task_type(4242, TaskType::Network);
+        assert!(memo.get(4242, &key(epoch, astro.revision(), "rustc")).is_none());
+        let revision = astro.revision();
+        astro.add_classifier_rule("rustc", TaskType::System).unwrap();
+        assert_ne!(astro.revision(), revision);
+        let revision = astro.revision();
+        astro.set_base_priority(TaskType::System, 300);
+        assert_ne!(astro.revision(), revision);
+    }
+
+    #[test]
+    fn test_memo_pid_reuse() {
+        let epoch = Utc.with_ymd_and_hms(2025, 11, 19, 22, 0, 0).unwrap();
+        let mut astro = AstrologicalScheduler::new(300);
+        let decision = astro.schedule_task("rustc", 4242, DEFAULT_TASK_WEIGHT, epoch).unwrap();
+        let mut memo = DecisionMemo::new(DECISION_MEMO_SIZE);
+        memo.insert(4242, key(epoch, 0, "rustc"), decision);
+
+        // The PID comes back as something else: its entry goes, even if the old name shows up again
+        assert!(memo.get(4242, &key(epoch, 0, "bash")).is_none());
+        assert_eq!(memo.reused(), 1);
+        assert!(memo.get(4242, &key(epoch, 0, "rustc")).is_none());
+        assert_eq!(memo.reused(), 1);
+    }
+
+    #[test]
+    fn test_memo_is_bounded() {
+        let epoch = Utc.with_ymd_and_hms(2025, 11, 19, 22, 0, 0).unwrap();
+        let mut astro = AstrologicalScheduler::new(300);
+        let decision = astro.schedule_task("rustc", 4242, DEFAULT_TASK_WEIGHT, epoch).unwrap();
+        let mut memo = DecisionMemo::new(2);
+        for pid in [1, 2, 3] {
+            memo.insert(pid, key(epoch, 0, "rustc"), decision.clone());
+        }
+        assert!(memo.get(1, &key(epoch, 0, "rustc")).is_none());
+        assert!(memo.get(3, &key(epoch, 0, "rustc")).is_some());
+        memo.forget(3);
+        assert!(memo.get(3, &key(epoch, 0, "rustc")).is_none());
+    }
+}
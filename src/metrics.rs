@@ -0,0 +1,310 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Metrics over scx_stats
+//
+// The scheduler serves its counters on the scx_stats socket like the other
+// sched_ext schedulers, so generic tools (scxstats_to_openmetrics and the
+// like) can read them, on the scx_stats default path or on --stats-socket.
+// The dispatch loop answers each request between rounds. `--monitor` is the
+// client side: it connects to a running instance at the same path and prints
+// a line per interval, without loading any BPF.
+//
+// The dispatch path keeps its counters in a `Metrics` of its own, and every
+// snapshot served is a copy of it with the kernel's counters filled in.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use scx_stats::prelude::*;
+use scx_stats_derive::Stats;
+use serde::{Deserialize, Serialize};
+
+use crate::astrology::SchedulingDecision;
+
+/// Version of `Metrics`, bumped whenever a field is removed or changes meaning
+///
+/// The `_version` the derive serves has to be a literal; a test keeps the two in step.
+pub const METRICS_VERSION: u32 = 2;
+
+/// What the scheduler has handed out to one task type
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Stats)]
+#[stat(desc = "Per-task-type metrics", _om_prefix = "t_", _om_label = "task_type")]
+pub struct TypeMetrics {
+    #[stat(desc = "Tasks of this type dispatched")]
+    pub dispatches: u64,
+    #[stat(desc = "Time slice granted in all, in nanoseconds")]
+    pub slice_ns: u64,
+    #[stat(desc = "Average priority dispatched with")]
+    pub average_priority: f64,
+    #[stat(desc = "Time slices cut short for a retrograde ruling planet")]
+    pub retrograde_penalties: u64,
+}
+
+/// Counters served to `scx_stats` clients
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, Stats)]
+#[stat(top, desc = "scx_horoscope metrics", _version = "2")]
+pub struct Metrics {
+    #[stat(desc = "Version of these metrics")]
+    pub version: u32,
+    #[stat(desc = "Tasks dispatched by the scheduler")]
+    pub user_dispatches: u64,
+    #[stat(desc = "Tasks dispatched by the kernel")]
+    pub kernel_dispatches: u64,
+    #[stat(desc = "Tasks waiting to be scheduled")]
+    pub queued: u64,
+    #[stat(desc = "Tasks scheduled but not yet dispatched")]
+    pub scheduled: u64,
+    #[stat(desc = "Dispatches, slices, priorities and retrograde cuts, by task type")]
+    pub task_types: BTreeMap<String, TypeMetrics>,
+    #[stat(desc = "Share of decisions answered from the decision memo, 0.0 to 1.0")]
+    pub memo_hit_rate: f64,
+    #[stat(desc = "Time slices cut short for a retrograde ruling planet")]
+    pub retrograde_penalties: u64,
+    #[stat(desc = "Hard aspects' share of the sky's aspects, 0 to 100")]
+    pub tension_score: u32,
+}
+
+impl Metrics {
+    /// Count a dispatch of `slice_ns` for `decision`, `penalized` if its ruler's retrograde cut the slice
+    pub fn record_dispatch(&mut self, decision: &SchedulingDecision, slice_ns: u64, penalized: bool) {
+        // Only a task type's first dispatch pays for its key; the dispatch path mustn't allocate
+        let name = decision.task_type.name();
+        if !self.task_types.contains_key(name) {
+            self.task_types.insert(name.to_string(), TypeMetrics::default());
+        }
+        let Some(task_type) = self.task_types.get_mut(name) else {
+            return;
+        };
+        task_type.dispatches += 1;
+        task_type.slice_ns = task_type.slice_ns.saturating_add(slice_ns);
+        #[allow(clippy::cast_precision_loss)]
+        let weight = task_type.dispatches as f64;
+        task_type.average_priority += (f64::from(decision.priority) - task_type.average_priority) / weight;
+        if penalized {
+            task_type.retrograde_penalties += 1;
+            self.retrograde_penalties += 1;
+        }
+    }
+
+    /// A line per task type dispatched, for the stats output
+    pub fn task_type_lines(&self) -> Vec<String> {
+        self.task_types
+            .iter()
+            .filter(|(_, metrics)| metrics.dispatches > 0)
+            .map(|(task_type, metrics)| {
+                #[allow(clippy::cast_precision_loss)]
+                let granted_ms = metrics.slice_ns as f64 / 1e6;
+                format!(
+                    "   {task_type:<14} {:>8} dispatched | {granted_ms:.1}ms granted | avg priority {:.0} | retrograde cuts {}",
+                    metrics.dispatches, metrics.average_priority, metrics.retrograde_penalties
+                )
+            })
+            .collect()
+    }
+
+    /// One line for the monitor, with the dispatch rate since `previous` `interval` ago
+    pub fn line(&self, previous: Option<&Metrics>, interval: Duration) -> String {
+        let dispatches = self.user_dispatches + self.kernel_dispatches;
+        let mut line = format!("⭐ dispatches={dispatches}");
+        if let Some(previous) = previous.filter(|_| !interval.is_zero()) {
+            let delta = dispatches.saturating_sub(previous.user_dispatches + previous.kernel_dispatches);
+            #[allow(clippy::cast_precision_loss)]
+            let rate = delta as f64 / interval.as_secs_f64();
+            let _ = write!(line, " ({rate:.0}/s)");
+        }
+        let _ = write!(
+            line,
+            " user={} kernel={} | queued={} scheduled={} | memo {:.1}% | retrograde cuts={} | tension {}",
+            self.user_dispatches,
+            self.kernel_dispatches,
+            self.queued,
+            self.scheduled,
+            self.memo_hit_rate * 100.0,
+            self.retrograde_penalties,
+            self.tension_score
+        );
+        for (task_type, metrics) in self.task_types.iter().filter(|(_, metrics)| metrics.dispatches > 0) {
+            let _ = write!(line, " | {task_type}={}", metrics.dispatches);
+        }
+        line
+    }
+}
+
+/// What the stats server serves: each request asks the dispatch loop for fresh metrics
+pub fn server_data() -> StatsServerData<(), Metrics> {
+    StatsServerData::new().add_meta(Metrics::meta()).add_meta(TypeMetrics::meta()).add_stats(
+        "top",
+        Box::new(|_args, (req_ch, res_ch)| {
+            req_ch.send(())?;
+            res_ch.recv()?.to_json()
+        }),
+    )
+}
+
+/// Start serving metrics, on `path` or the `scx_stats` default
+pub fn launch_server(path: Option<&Path>) -> Result<StatsServer<(), Metrics>> {
+    let server = StatsServer::new(server_data());
+    match path {
+        Some(path) => server.set_path(path),
+        None => server,
+    }
+    .launch()
+}
+
+fn connect(path: Option<&Path>) -> Result<StatsClient> {
+    let client = StatsClient::new();
+    match path {
+        Some(path) => client.set_path(path),
+        None => client,
+    }
+    .connect()
+}
+
+/// Ask a running scheduler for its metrics
+fn fetch(client: &mut StatsClient) -> Result<Metrics> {
+    client.request("stats", vec![])
+}
+
+/// Print the metrics of a running scheduler every `interval` until `shutdown`
+///
+/// Waits for the scheduler to come up, and for it to come back if it goes away.
+pub fn monitor(path: Option<&Path>, interval: Duration, shutdown: impl Fn() -> bool) -> Result<()> {
+    let mut client = None;
+    let mut previous: Option<Metrics> = None;
+    while !shutdown() {
+        if client.is_none() {
+            client = connect(path).ok();
+        }
+        if let Some(Ok(metrics)) = client.as_mut().map(fetch) {
+            if metrics.version != METRICS_VERSION {
+                bail!("the scheduler serves metrics version {}, this monitor reads {METRICS_VERSION}", metrics.version);
+            }
+            println!("{}", metrics.line(previous.as_ref(), interval));
+            previous = Some(metrics);
+        } else {
+            // Not up yet, or gone: try again next time round
+            client = None;
+            previous = None;
+        }
+        std::thread::sleep(interval);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::TaskType;
+
+    fn sample() -> Metrics {
+        Metrics {
+            version: METRICS_VERSION,
+            user_dispatches: 1200,
+            kernel_dispatches: 34,
+            queued: 3,
+            scheduled: 1,
+            task_types: BTreeMap::from([
+                ("CPU-Intensive".to_string(), TypeMetrics { dispatches: 900, ..TypeMetrics::default() }),
+                ("Network".to_string(), TypeMetrics::default()),
+            ]),
+            memo_hit_rate: 0.925,
+            retrograde_penalties: 56,
+            tension_score: 42,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_over_a_socket() {
+        let dir = std::env::temp_dir().join(format!("horoscope_metrics_{}", std::process::id()));
+        let path = dir.join("stats");
+        let server = launch_server(Some(&path)).unwrap();
+
+        // Stands in for the dispatch loop, answering every request
+        let (res_ch, req_ch) = server.channels();
+        let answering = std::thread::spawn(move || {
+            let mut served = 0;
+            while req_ch.recv_timeout(Duration::from_secs(5)).is_ok() {
+                served += 1;
+                if res_ch.send(sample()).is_err() {
+                    break;
+                }
+            }
+            served
+        });
+
+        let mut client = connect(Some(&path)).unwrap();
+        assert_eq!(fetch(&mut client).unwrap(), sample());
+        assert_eq!(fetch(&mut client).unwrap(), sample());
+        // Generic clients can discover the fields, and the version
+        let meta: serde_json::Value = client.request("stats_meta", vec![]).unwrap();
+        let fields = &meta["Metrics"]["fields"];
+        assert!(fields["retrograde_penalties"].is_object(), "{meta}");
+        assert_eq!(meta["Metrics"]["user"]["_version"], METRICS_VERSION.to_string(), "{meta}");
+        assert!(meta["TypeMetrics"]["fields"]["average_priority"].is_object(), "{meta}");
+
+        drop(client);
+        drop(server);
+        assert_eq!(answering.join().unwrap(), 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_served_version_matches() {
+        let meta = serde_json::to_value(Metrics::meta()).unwrap();
+        assert_eq!(meta["user"]["_version"], METRICS_VERSION.to_string(), "{meta}");
+    }
+
+    fn decision(task_type: TaskType, priority: u32) -> SchedulingDecision {
+        SchedulingDecision {
+            task_type,
+            critical: false,
+            ruling_element: None,
+            priority,
+            astro_priority: priority,
+            weight: 100,
+            weight_factor: 1.0,
+            reasoning: String::new(),
+            planetary_influence: 0.0,
+            element_boost: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_dispatches_aggregate_by_task_type() {
+        let mut metrics = Metrics::default();
+        metrics.record_dispatch(&decision(TaskType::CpuIntensive, 400), 4_000_000, false);
+        metrics.record_dispatch(&decision(TaskType::CpuIntensive, 200), 1_000_000, true);
+        metrics.record_dispatch(&decision(TaskType::CpuIntensive, 300), 2_000_000, true);
+        metrics.record_dispatch(&decision(TaskType::Network, 150), 500_000, false);
+
+        let cpu = &metrics.task_types["CPU-Intensive"];
+        assert_eq!((cpu.dispatches, cpu.slice_ns, cpu.retrograde_penalties), (3, 7_000_000, 2));
+        assert!((cpu.average_priority - 300.0).abs() < 1e-9);
+        let network = &metrics.task_types["Network"];
+        assert_eq!((network.dispatches, network.slice_ns, network.retrograde_penalties), (1, 500_000, 0));
+        assert!((network.average_priority - 150.0).abs() < 1e-9);
+        assert_eq!(metrics.retrograde_penalties, 2);
+        assert!(!metrics.task_types.contains_key("Memory-Heavy"));
+
+        assert_eq!(
+            metrics.task_type_lines(),
+            [
+                "   CPU-Intensive         3 dispatched | 7.0ms granted | avg priority 300 | retrograde cuts 2",
+                "   Network               1 dispatched | 0.5ms granted | avg priority 150 | retrograde cuts 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monitor_line() {
+        let previous = Metrics { user_dispatches: 1000, kernel_dispatches: 34, ..sample() };
+        assert_eq!(
+            sample().line(Some(&previous), Duration::from_secs(2)),
+            "⭐ dispatches=1234 (100/s) user=1200 kernel=34 | queued=3 scheduled=1 | memo 92.5% | retrograde cuts=56 | tension 42 | CPU-Intensive=900"
+        );
+        assert!(sample().line(None, Duration::from_secs(2)).starts_with("⭐ dispatches=1234 user=1200"));
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Nice values by element
+//
+// The stars decide slice lengths under sched_ext, but tasks the horoscope
+// scheduler doesn't manage still see everyone's nice value. With
+// --cpu-nice-map each task is reniced by the element its ruling planet stands
+// in, e.g. fire=-5,water=10, so the cosmos is heard by CFS too. A task is only
+// reniced when its element changes, not at every dispatch. Lowering a nice
+// value takes CAP_SYS_NICE; without it the map is skipped rather than failing
+// at every task. Every task keeps a note of the nice value it had before we
+// first touched it, and of when it started, and gets the value back when the
+// scheduler exits unless its PID has been reused meanwhile. Critical tasks are
+// never reniced.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use nix::errno::Errno;
+
+use crate::astrology::Element;
+use crate::probe;
+
+/// Lowest nice value, the highest priority
+pub const NICE_MIN: i8 = -20;
+
+/// Highest nice value, the lowest priority
+pub const NICE_MAX: i8 = 19;
+
+/// Tasks remembered before the record of applied nice values starts over
+pub const NICE_APPLIED_SIZE: usize = 4096;
+
+/// Tasks whose original nice value is kept; past this, new tasks are left alone
+pub const NICE_ORIGINAL_SIZE: usize = 4096;
+
+/// Bit of `CAP_SYS_NICE` in the capability sets of /proc/<pid>/status
+const CAP_SYS_NICE: u32 = 23;
+
+/// Parse an `ELEMENT=NICE` entry, e.g. `fire=-5`
+pub fn parse_nice_entry(s: &str) -> Result<(Element, i8), String> {
+    let (element, nice) = s.split_once('=').ok_or_else(|| "expected ELEMENT=NICE".to_string())?;
+    let element = element.trim().parse()?;
+    let nice: i8 = nice.trim().parse().map_err(|_| format!("invalid nice value '{}'", nice.trim()))?;
+    if !(NICE_MIN..=NICE_MAX).contains(&nice) {
+        return Err(format!("nice value {nice} is not in {NICE_MIN}..={NICE_MAX}"));
+    }
+    Ok((element, nice))
+}
+
+/// Whether a `/proc/<pid>/status` grants `CAP_SYS_NICE` in its effective set
+pub fn cap_sys_nice_in(status: &str) -> bool {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+        .is_some_and(|caps| caps & (1 << CAP_SYS_NICE) != 0)
+}
+
+/// Whether this process may lower nice values
+pub fn has_cap_sys_nice() -> bool {
+    fs::read_to_string("/proc/self/status").is_ok_and(|status| cap_sys_nice_in(&status))
+}
+
+/// The nice value of a task (or, for a TID, of that thread)
+pub fn get_nice(pid: i32) -> io::Result<i8> {
+    let id = libc::id_t::try_from(pid).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    // getpriority can return -1 legitimately; errno tells an error apart.
+    // nix has no wrapper for it, only for errno.
+    Errno::clear();
+    // SAFETY: getpriority takes no pointers
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, id) };
+    match Errno::last() {
+        errno if errno != Errno::UnknownErrno && nice == -1 => Err(errno.into()),
+        _ => i8::try_from(nice).map_err(|_| io::Error::from(io::ErrorKind::InvalidData)),
+    }
+}
+
+/// Set the nice value of a task (or, for a TID, of that thread)
+pub fn set_nice(pid: i32, nice: i8) -> io::Result<()> {
+    let id = libc::id_t::try_from(pid).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    // SAFETY: setpriority takes no pointers
+    Errno::result(unsafe { libc::setpriority(libc::PRIO_PROCESS, id, libc::c_int::from(nice)) })?;
+    Ok(())
+}
+
+/// Renices tasks by the element of their ruling planet
+#[derive(Debug, Clone)]
+pub struct NiceMap {
+    by_element: HashMap<Element, i8>,
+    applied: HashMap<i32, i8>,  // Nice value last set on each PID
+    original: HashMap<i32, (i8, u64)>,  // Nice value before the first renice, and start time
+    reniced: u64,
+    failures: u64,
+}
+
+impl NiceMap {
+    /// Later entries for the same element win
+    pub fn new(entries: &[(Element, i8)]) -> Self {
+        Self {
+            by_element: entries.iter().copied().collect(),
+            applied: HashMap::new(),
+            original: HashMap::new(),
+            reniced: 0,
+            failures: 0,
+        }
+    }
+
+    /// The nice value for tasks ruled from `element`, if the map has one
+    pub fn nice_for(&self, element: Element) -> Option<i8> {
+        self.by_element.get(&element).copied()
+    }
+
+    /// Renice `pid` for `element` unless it already has that nice value from us
+    ///
+    /// Returns the nice value set, `None` when nothing needed doing. A task
+    /// that couldn't be reniced is not asked again until its element changes.
+    /// Once `NICE_ORIGINAL_SIZE` tasks are waiting to get their nice values
+    /// back, new ones are left alone.
+    pub fn apply(&mut self, pid: i32, element: Element) -> io::Result<Option<i8>> {
+        let Some(nice) = self.nice_for(element) else {
+            return Ok(None);
+        };
+        if self.applied.get(&pid) == Some(&nice) {
+            return Ok(None);
+        }
+        if self.original.len() >= NICE_ORIGINAL_SIZE && !self.original.contains_key(&pid) {
+            return Ok(None);
+        }
+        if self.applied.len() >= NICE_APPLIED_SIZE && !self.applied.contains_key(&pid) {
+            self.applied.clear();
+        }
+        self.applied.insert(pid, nice);
+        let renice = match self.original.entry(pid) {
+            Entry::Occupied(_) => set_nice(pid, nice),
+            Entry::Vacant(vacant) => get_nice(pid).and_then(|original| {
+                let started = probe::start_ticks(pid)?;
+                set_nice(pid, nice)?;
+                vacant.insert((original, started));
+                Ok(())
+            }),
+        };
+        match renice {
+            Ok(()) => {
+                self.reniced += 1;
+                Ok(Some(nice))
+            }
+            Err(e) => {
+                self.failures += 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// Drop a task that has exited
+    pub fn forget(&mut self, pid: i32) {
+        self.applied.remove(&pid);
+        self.original.remove(&pid);
+    }
+
+    /// Give every task we reniced its original nice value back
+    ///
+    /// Tasks that have exited since are skipped, and so are PIDs now held by
+    /// another task.
+    pub fn restore(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        for (pid, (nice, started)) in std::mem::take(&mut self.original) {
+            if probe::start_ticks(pid).ok() != Some(started) {
+                continue;
+            }
+            match set_nice(pid, nice) {
+                Err(e) if e.raw_os_error() != Some(libc::ESRCH) => result = result.and(Err(e)),
+                _ => {}
+            }
+        }
+        self.applied.clear();
+        result
+    }
+
+    /// Nice values set since startup
+    pub fn reniced(&self) -> u64 {
+        self.reniced
+    }
+
+    /// Nice values that could not be set
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// The map, in element order, e.g. fire=-5 water=10
+    pub fn describe(&self) -> String {
+        Element::all()
+            .into_iter()
+            .filter_map(|element| Some(format!("{}={}", element.name().to_lowercase(), self.nice_for(element)?)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_parse_nice_entry() {
+        assert_eq!(parse_nice_entry("fire=-5"), Ok((Element::Fire, -5)));
+        assert_eq!(parse_nice_entry(" Water = 10 "), Ok((Element::Water, 10)));
+        assert!(parse_nice_entry("fire").is_err());
+        assert!(parse_nice_entry("plasma=0").is_err());
+        assert!(parse_nice_entry("air=20").is_err());
+        assert!(parse_nice_entry("air=-21").is_err());
+        assert!(parse_nice_entry("air=loud").is_err());
+    }
+
+    #[test]
+    fn test_cap_sys_nice_in_status() {
+        assert!(cap_sys_nice_in("Name:\tx\nCapEff:\t000001ffffffffff\n"));
+        assert!(cap_sys_nice_in("CapEff:\t0000000000800000\n"));
+        assert!(!cap_sys_nice_in("CapEff:\t0000000000000000\n"));
+        assert!(!cap_sys_nice_in("Name:\tx\n"));
+    }
+
+    #[test]
+    fn test_nice_value_is_set() {
+        // Renice a thread of our own: raising a nice value needs no capability
+        let (sender, receiver) = mpsc::channel();
+        let (done, finish) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            // SAFETY: gettid takes no arguments
+            let tid = unsafe { libc::gettid() };
+            sender.send(tid).unwrap();
+            let _ = finish.recv();
+        });
+        let tid = receiver.recv().unwrap();
+
+        let before = get_nice(tid).unwrap();
+        let water = (before + 2).min(NICE_MAX);
+        let mut map = NiceMap::new(&[(Element::Fire, before), (Element::Water, water)]);
+        assert_eq!(map.describe(), format!("fire={before} water={water}"));
+
+        assert_eq!(map.apply(tid, Element::Water).unwrap(), Some(water));
+        assert_eq!(get_nice(tid).unwrap(), water);
+        // Already there: nothing to do; no entry for Air: nothing either
+        assert_eq!(map.apply(tid, Element::Water).unwrap(), None);
+        assert_eq!(map.apply(tid, Element::Air).unwrap(), None);
+        assert_eq!(map.reniced(), 1);
+
+        // Tasks that are gone fail once, and are not asked again
+        assert!(map.apply(i32::MAX, Element::Water).is_err());
+        assert_eq!(map.apply(i32::MAX, Element::Water).unwrap(), None);
+        assert_eq!(map.failures(), 1);
+
+        done.send(()).unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_apply_then_restore() {
+        // Putting a raised nice value back down takes CAP_SYS_NICE
+        if !has_cap_sys_nice() {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let (done, finish) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            // SAFETY: gettid takes no arguments
+            let tid = unsafe { libc::gettid() };
+            sender.send(tid).unwrap();
+            let _ = finish.recv();
+        });
+        let tid = receiver.recv().unwrap();
+
+        // Raised by one, then by two
+        let before = get_nice(tid).unwrap();
+        let earth = (before + 1).min(NICE_MAX);
+        let water = (before + 2).min(NICE_MAX);
+        let mut map = NiceMap::new(&[(Element::Earth, earth), (Element::Water, water)]);
+        map.apply(tid, Element::Earth).unwrap();
+        map.apply(tid, Element::Water).unwrap();
+        assert_eq!(get_nice(tid).unwrap(), water);
+
+        // Back to where it started, not to the first value set; tasks gone meanwhile don't matter
+        map.original.insert(i32::MAX, (0, 0));
+        map.restore().unwrap();
+        assert_eq!(get_nice(tid).unwrap(), before);
+        // Nothing left to restore, and the next apply renices again
+        map.restore().unwrap();
+        assert_eq!(map.apply(tid, Element::Water).unwrap(), Some(water));
+
+        done.send(()).unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_reused_pid_is_left_alone() {
+        let (sender, receiver) = mpsc::channel();
+        let (done, finish) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            // SAFETY: gettid takes no arguments
+            let tid = unsafe { libc::gettid() };
+            sender.send(tid).unwrap();
+            let _ = finish.recv();
+        });
+        let tid = receiver.recv().unwrap();
+
+        let before = get_nice(tid).unwrap();
+        let water = (before + 1).min(NICE_MAX);
+        let mut map = NiceMap::new(&[(Element::Water, water)]);
+        map.apply(tid, Element::Water).unwrap();
+
+        // As if the thread had exited and its TID gone to a newer one
+        map.original.get_mut(&tid).unwrap().1 += 1;
+        map.restore().unwrap();
+        assert_eq!(get_nice(tid).unwrap(), water);
+
+        done.send(()).unwrap();
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_new_tasks_left_alone_when_full() {
+        let mut map = NiceMap::new(&[(Element::Water, 5)]);
+        for pid in 0..NICE_ORIGINAL_SIZE {
+            map.original.insert(-1 - i32::try_from(pid).unwrap(), (0, 0));
+        }
+        // Full: a new task isn't touched, so there's nothing to give back for it
+        assert_eq!(map.apply(i32::MAX, Element::Water).unwrap(), None);
+        assert_eq!(map.failures(), 0);
+    }
+}
@@ -0,0 +1,541 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Behavioral task probes
+//
+// Names lie. A process called "worker" holding 8 GB is Jupiter's child no
+// matter what it calls itself, and an "app" holding dozens of sockets is
+// Mercury's. Probes sample /proc for the hottest PIDs on a background thread,
+// far from the dispatch path, and promote tasks whose readings cross a
+// threshold to a learned task type.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::astrology::TaskType;
+use crate::ledger::TaskLedger;
+
+/// A per-PID measurement; `NotFound` means the PID has vanished
+pub trait PidReading {
+    fn read(&mut self, pid: i32) -> io::Result<u64>;
+}
+
+/// Resident set size in bytes, from `/proc/<pid>/statm`
+pub struct StatmRss {
+    page_size: u64,
+}
+
+impl StatmRss {
+    pub fn new() -> Self {
+        // SAFETY: sysconf has no preconditions
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        Self { page_size: u64::try_from(page_size).unwrap_or(4096) }
+    }
+}
+
+impl PidReading for StatmRss {
+    fn read(&mut self, pid: i32) -> io::Result<u64> {
+        let statm = fs::read_to_string(format!("/proc/{pid}/statm"))?;
+        statm
+            .split_whitespace()
+            .nth(1)
+            .and_then(|pages| pages.parse::<u64>().ok())
+            .map(|resident_pages| resident_pages * self.page_size)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed statm"))
+    }
+}
+
+/// Most file descriptors looked at per PID, to keep fd-hoarders cheap to probe
+const MAX_FDS_SCANNED: usize = 1024;
+
+/// Unreadable PIDs remembered before starting over (PIDs get recycled)
+const MAX_UNREADABLE: usize = 4096;
+
+/// Number of open sockets, from the links in `/proc/<pid>/fd`
+pub struct FdSockets;
+
+impl PidReading for FdSockets {
+    fn read(&mut self, pid: i32) -> io::Result<u64> {
+        let mut sockets = 0;
+        for entry in fs::read_dir(format!("/proc/{pid}/fd"))?.take(MAX_FDS_SCANNED) {
+            // fds come and go while we look; a failed readlink is just skipped
+            let Ok(target) = entry.and_then(|entry| fs::read_link(entry.path())) else {
+                continue;
+            };
+            if target.to_string_lossy().starts_with("socket:") {
+                sockets += 1;
+            }
+        }
+        Ok(sockets)
+    }
+}
+
+/// `PF_KTHREAD` from the kernel's task flags
+const PF_KTHREAD: u64 = 0x0020_0000;
+
+/// Task flags (`task_struct->flags`) from the text of `/proc/<pid>/stat`
+pub fn parse_stat_flags(stat: &str) -> Option<u64> {
+    // comm may contain spaces and parens; the fields resume after the last ')'
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(6)?.parse().ok()
+}
+
+/// Remembers which PIDs are kernel threads, so the dispatch path reads /proc once per PID
+pub struct KthreadCache {
+    known: HashMap<i32, bool>,
+}
+
+impl KthreadCache {
+    pub fn new() -> Self {
+        Self { known: HashMap::new() }
+    }
+
+    pub fn is_kthread(&mut self, pid: i32) -> bool {
+        if let Some(&known) = self.known.get(&pid) {
+            return known;
+        }
+        // PIDs get recycled; starting over now and then keeps stale answers rare
+        if self.known.len() >= MAX_UNREADABLE {
+            self.known.clear();
+        }
+        let is_kthread = fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .and_then(|stat| parse_stat_flags(&stat))
+            .is_some_and(|flags| flags & PF_KTHREAD != 0);
+        self.known.insert(pid, is_kthread);
+        is_kthread
+    }
+
+    /// Take `is_kthread` as known for `pid`, without asking procfs
+    #[cfg(test)]
+    pub fn remember(&mut self, pid: i32, is_kthread: bool) {
+        self.known.insert(pid, is_kthread);
+    }
+}
+
+/// Start time in clock ticks after boot, from the text of `/proc/<pid>/stat`
+pub fn parse_stat_starttime(stat: &str) -> Option<u64> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// When a task started, in clock ticks since boot, to tell a reused PID apart
+pub fn start_ticks(pid: i32) -> io::Result<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat"))?;
+    parse_stat_starttime(&stat).ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))
+}
+
+/// Boot time in seconds since the epoch, from the text of `/proc/stat`
+pub fn parse_btime(proc_stat: &str) -> Option<i64> {
+    proc_stat
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|btime| btime.trim().parse().ok())
+}
+
+/// Wall-clock start of a task that started `starttime` ticks after boot
+pub fn start_time(btime: i64, ticks_per_sec: u64, starttime: u64) -> Option<DateTime<Utc>> {
+    let secs = i64::try_from(starttime / ticks_per_sec.max(1)).ok()?;
+    DateTime::from_timestamp(btime.checked_add(secs)?, 0)
+}
+
+/// Remembers when each PID started, so the dispatch path reads /proc once per PID
+pub struct StartTimeCache {
+    btime: Option<i64>,
+    ticks_per_sec: u64,
+    known: HashMap<i32, Option<DateTime<Utc>>>,
+}
+
+impl StartTimeCache {
+    pub fn new() -> Self {
+        // SAFETY: sysconf has no preconditions
+        let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        Self {
+            btime: fs::read_to_string("/proc/stat").ok().and_then(|stat| parse_btime(&stat)),
+            ticks_per_sec: u64::try_from(ticks_per_sec).unwrap_or(100),
+            known: HashMap::new(),
+        }
+    }
+
+    pub fn start_time(&mut self, pid: i32) -> Option<DateTime<Utc>> {
+        let btime = self.btime?;
+        if let Some(&known) = self.known.get(&pid) {
+            return known;
+        }
+        // PIDs get recycled; starting over now and then keeps stale answers rare
+        if self.known.len() >= MAX_UNREADABLE {
+            self.known.clear();
+        }
+        let start = fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .and_then(|stat| parse_stat_starttime(&stat))
+            .and_then(|starttime| start_time(btime, self.ticks_per_sec, starttime));
+        self.known.insert(pid, start);
+        start
+    }
+}
+
+/// A PID's command name from `/proc/<pid>/comm`, if it is still around
+pub fn read_comm(pid: i32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{pid}/comm")).ok().map(|comm| comm.trim_end().to_string())
+}
+
+/// True if the task has switched to `SCHED_FIFO` or `SCHED_RR`
+pub fn is_realtime(pid: i32) -> bool {
+    // SAFETY: sched_getscheduler has no memory-safety preconditions
+    let policy = unsafe { libc::sched_getscheduler(pid) };
+    policy == libc::SCHED_FIFO || policy == libc::SCHED_RR
+}
+
+/// Remembers which PIDs run under a realtime policy, so the dispatch path asks the kernel once per PID
+///
+/// A task can change its policy whenever it likes; clearing the cache with
+/// every new chart notices the switch within one update interval.
+pub struct RealtimeCache {
+    known: HashMap<i32, bool>,
+}
+
+impl RealtimeCache {
+    pub fn new() -> Self {
+        Self { known: HashMap::new() }
+    }
+
+    pub fn is_realtime(&mut self, pid: i32) -> bool {
+        if let Some(&known) = self.known.get(&pid) {
+            return known;
+        }
+        // PIDs get recycled; starting over now and then keeps stale answers rare
+        if self.known.len() >= MAX_UNREADABLE {
+            self.known.clear();
+        }
+        let realtime = is_realtime(pid);
+        self.known.insert(pid, realtime);
+        realtime
+    }
+
+    /// Take `realtime` as known for `pid`, without asking the kernel
+    #[cfg(test)]
+    pub fn remember(&mut self, pid: i32, realtime: bool) {
+        self.known.insert(pid, realtime);
+    }
+
+    pub fn clear(&mut self) {
+        self.known.clear();
+    }
+}
+
+/// What a probe concluded about a PID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeEvent {
+    Promote(i32, TaskType),
+    Demote(i32, TaskType),
+    Vanished(i32),
+}
+
+/// Anything that can look at a batch of hot PIDs
+pub trait Sampler {
+    fn sample(&mut self, pids: &[i32]) -> Vec<ProbeEvent>;
+}
+
+/// Promotes PIDs whose reading reaches `threshold` to `task_type`
+pub struct ThresholdProbe<R> {
+    reader: R,
+    threshold: u64,
+    task_type: TaskType,
+    max_reads: usize,
+    decay_rounds: Option<u32>,
+    promoted: HashMap<i32, u32>,  // PID -> consecutive samples below threshold
+    unreadable: HashSet<i32>,     // e.g. other users' fds; not worth asking again
+}
+
+impl<R: PidReading> ThresholdProbe<R> {
+    pub fn new(reader: R, threshold: u64, task_type: TaskType, max_reads: usize) -> Self {
+        Self {
+            reader,
+            threshold,
+            task_type,
+            max_reads,
+            decay_rounds: None,
+            promoted: HashMap::new(),
+            unreadable: HashSet::new(),
+        }
+    }
+
+    /// Demote a PID after `rounds` consecutive samples below the threshold
+    ///
+    /// Without decay, promotions are sticky until the PID vanishes.
+    #[must_use]
+    pub fn with_decay(mut self, rounds: u32) -> Self {
+        self.decay_rounds = Some(rounds.max(1));
+        self
+    }
+}
+
+impl<R: PidReading> Sampler for ThresholdProbe<R> {
+    /// Read up to `max_reads` of `pids` and report what changed
+    ///
+    /// A vanished PID always loses its learned type, so a recycled PID starts
+    /// over. PIDs we may not read are remembered and skipped without counting
+    /// against the read budget.
+    fn sample(&mut self, pids: &[i32]) -> Vec<ProbeEvent> {
+        let mut events = Vec::new();
+        let readable: Vec<i32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| !self.unreadable.contains(pid))
+            .take(self.max_reads)
+            .collect();
+
+        for pid in readable {
+            match self.reader.read(pid) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    self.unreadable.remove(&pid);
+                    if self.promoted.remove(&pid).is_some() {
+                        events.push(ProbeEvent::Vanished(pid));
+                    }
+                }
+                Err(_) => {
+                    if self.unreadable.len() >= MAX_UNREADABLE {
+                        self.unreadable.clear();
+                    }
+                    self.unreadable.insert(pid);
+                }
+                Ok(value) if value >= self.threshold => {
+                    if self.promoted.insert(pid, 0).is_none() {
+                        events.push(ProbeEvent::Promote(pid, self.task_type));
+                    }
+                }
+                Ok(_) => {
+                    let Some(decay_rounds) = self.decay_rounds else {
+                        continue;
+                    };
+                    if let Some(quiet) = self.promoted.get_mut(&pid) {
+                        *quiet += 1;
+                        if *quiet >= decay_rounds {
+                            self.promoted.remove(&pid);
+                            events.push(ProbeEvent::Demote(pid, self.task_type));
+                        }
+                    }
+                }
+            }
+        }
+
+        events
+    }
+}
+
+/// Dispatcher-side end of the probes running on their own thread
+pub struct ProbeHandle {
+    ledger: TaskLedger,
+    interval: Duration,
+    top_n: usize,
+    last_submit: Instant,
+    candidates: SyncSender<Vec<i32>>,
+    events: Receiver<ProbeEvent>,
+}
+
+impl ProbeHandle {
+    /// Start the sampler thread; it exits once the handle is dropped
+    pub fn spawn(
+        mut probes: Vec<Box<dyn Sampler + Send>>,
+        interval: Duration,
+        top_n: usize,
+    ) -> io::Result<Self> {
+        let (candidates, candidates_rx) = mpsc::sync_channel::<Vec<i32>>(1);
+        let (events_tx, events) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("horoscope-probe".to_string())
+            .spawn(move || {
+                for pids in candidates_rx {
+                    for event in probes.iter_mut().flat_map(|probe| probe.sample(&pids)) {
+                        if events_tx.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            ledger: TaskLedger::new(),
+            interval,
+            top_n,
+            last_submit: Instant::now(),
+            candidates,
+            events,
+        })
+    }
+
+    /// Count a dispatch towards the current sampling window
+    pub fn note_dispatch(&mut self, pid: i32) {
+        self.ledger.record(pid);
+    }
+
+    /// Hand the hottest PIDs to the sampler once per interval, never blocking
+    pub fn tick(&mut self, now: Instant) {
+        if now.duration_since(self.last_submit) < self.interval {
+            return;
+        }
+        self.last_submit = now;
+
+        let hottest = self.ledger.take_hottest(self.top_n);
+        // If the sampler is still busy with the last batch, skip this one
+        if let Err(TrySendError::Disconnected(_)) = self.candidates.try_send(hottest) {
+            log::warn!("Task probe thread has exited");
+        }
+    }
+
+    /// Collect whatever the sampler has concluded so far
+    pub fn drain(&self) -> Vec<ProbeEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    /// Injected readings; PIDs not in the map have vanished
+    struct FakeReadings {
+        values: HashMap<i32, u64>,
+        forbidden: HashSet<i32>,
+        reads: usize,
+    }
+
+    impl PidReading for FakeReadings {
+        fn read(&mut self, pid: i32) -> io::Result<u64> {
+            self.reads += 1;
+            if self.forbidden.contains(&pid) {
+                return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+            }
+            self.values.get(&pid).copied().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    fn readings(values: &[(i32, u64)]) -> FakeReadings {
+        FakeReadings { values: values.iter().copied().collect(), forbidden: HashSet::new(), reads: 0 }
+    }
+
+    fn probe(rss: &[(i32, u64)], max_reads: usize) -> ThresholdProbe<FakeReadings> {
+        ThresholdProbe::new(readings(rss), 2 * GIB, TaskType::MemoryHeavy, max_reads)
+    }
+
+    fn socket_probe(sockets: &[(i32, u64)]) -> ThresholdProbe<FakeReadings> {
+        ThresholdProbe::new(readings(sockets), 32, TaskType::Network, 16).with_decay(2)
+    }
+
+    #[test]
+    fn test_promotes_above_threshold_once() {
+        let mut probe = probe(&[(100, 8 * GIB), (200, GIB)], 16);
+
+        assert_eq!(probe.sample(&[100, 200]), vec![ProbeEvent::Promote(100, TaskType::MemoryHeavy)]);
+        // Already promoted: no duplicate events
+        assert!(probe.sample(&[100, 200]).is_empty());
+    }
+
+    #[test]
+    fn test_vanished_pids_are_forgotten() {
+        let mut probe = probe(&[(100, 8 * GIB)], 16);
+        probe.sample(&[100]);
+
+        probe.reader.values.remove(&100);
+        assert_eq!(probe.sample(&[100, 300]), vec![ProbeEvent::Vanished(100)]);
+
+        // The PID comes back as a different, equally hungry process
+        probe.reader.values.insert(100, 3 * GIB);
+        assert_eq!(probe.sample(&[100]), vec![ProbeEvent::Promote(100, TaskType::MemoryHeavy)]);
+    }
+
+    #[test]
+    fn test_reads_are_rate_limited() {
+        let mut probe = probe(&[(1, 8 * GIB), (2, 8 * GIB), (3, 8 * GIB)], 2);
+
+        let events = probe.sample(&[1, 2, 3]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(probe.reader.reads, 2);
+    }
+
+    #[test]
+    fn test_socket_promotion_decays() {
+        let mut probe = socket_probe(&[(100, 48), (200, 3)]);
+        assert_eq!(probe.sample(&[100, 200]), vec![ProbeEvent::Promote(100, TaskType::Network)]);
+
+        // Connections drop: one quiet round is forgiven, the second demotes
+        probe.reader.values.insert(100, 4);
+        assert!(probe.sample(&[100]).is_empty());
+        assert_eq!(probe.sample(&[100]), vec![ProbeEvent::Demote(100, TaskType::Network)]);
+
+        // A busy round resets the countdown
+        probe.reader.values.insert(100, 64);
+        probe.sample(&[100]);
+        probe.reader.values.insert(100, 4);
+        probe.sample(&[100]);
+        probe.reader.values.insert(100, 64);
+        probe.sample(&[100]);
+        probe.reader.values.insert(100, 4);
+        assert!(probe.sample(&[100]).is_empty());
+    }
+
+    #[test]
+    fn test_realtime_policy_is_cached() {
+        let pid = i32::try_from(std::process::id()).unwrap();
+        let mut cache = RealtimeCache::new();
+        assert_eq!(cache.is_realtime(pid), is_realtime(pid));
+        assert_eq!(cache.known.len(), 1);
+        cache.is_realtime(pid);
+        assert_eq!(cache.known.len(), 1);
+        cache.clear();
+        assert!(cache.known.is_empty());
+    }
+
+    #[test]
+    fn test_parse_stat_flags() {
+        let kthread = "15 (ksoftirqd/0) S 2 0 0 0 -1 69238880 0 0 0 0 0 11 0 0 20 0 1 0 9 0 0";
+        assert_eq!(parse_stat_flags(kthread), Some(69_238_880));
+        assert!(parse_stat_flags(kthread).unwrap() & PF_KTHREAD != 0);
+
+        // A comm with spaces and parens doesn't shift the fields
+        let user = "4242 (my (odd) app) R 1 4242 4242 0 -1 4194560 120 0 0 0 5 1 0 0 20 0 1 0";
+        assert_eq!(parse_stat_flags(user), Some(4_194_560));
+        assert!(parse_stat_flags(user).unwrap() & PF_KTHREAD == 0);
+
+        assert_eq!(parse_stat_flags("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_start_time() {
+        let kthread = "15 (ksoftirqd/0) S 2 0 0 0 -1 69238880 0 0 0 0 0 11 0 0 20 0 1 0 9 0 0";
+        assert_eq!(parse_stat_starttime(kthread), Some(9));
+        let user = "4242 (my (odd) app) R 1 4242 4242 0 -1 4194560 120 0 0 0 5 1 0 0 20 0 1 0 360000 1 2";
+        assert_eq!(parse_stat_starttime(user), Some(360_000));
+        assert_eq!(parse_stat_starttime("1 (init) S 0"), None);
+
+        let proc_stat = "cpu  1 2 3 4\nintr 12345\nbtime 1700000000\nprocesses 42\n";
+        assert_eq!(parse_btime(proc_stat), Some(1_700_000_000));
+        assert_eq!(parse_btime("cpu 1 2 3"), None);
+
+        // 3600 seconds after boot at 100 ticks per second
+        let start = start_time(1_700_000_000, 100, 360_000).unwrap();
+        assert_eq!(start.timestamp(), 1_700_003_600);
+    }
+
+    #[test]
+    fn test_permission_errors_are_skipped() {
+        let mut probe = socket_probe(&[(100, 48), (200, 48)]);
+        probe.reader.forbidden.insert(100);
+
+        assert_eq!(probe.sample(&[100, 200]), vec![ProbeEvent::Promote(200, TaskType::Network)]);
+        assert_eq!(probe.reader.reads, 2);
+
+        // Unreadable PIDs are not asked again
+        probe.sample(&[100, 200]);
+        assert_eq!(probe.reader.reads, 3);
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Answers for horoscope-query
+//
+// The scheduler's side of the query socket: every request is answered from
+// the live scheduler state, as text for people and as JSON for scripts.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::astrology::{AstrologicalScheduler, TaskType, DEFAULT_TASK_WEIGHT};
+use crate::batch::BatchStats;
+use crate::diagnostics::{BpfCounters, PlanetReport};
+use crate::ipc::{Request, Response};
+use crate::slice;
+use crate::Opts;
+
+/// Longest forecast computed for one request
+pub const MAX_FORECAST_HOURS: u32 = 168;
+
+/// PID a task is explained as when all we have is its name
+const EXPLAIN_PID: i32 = 0;
+
+/// Counters reported by `stats`
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StatsSnapshot {
+    pub bpf: BpfCounters,
+    pub batches: BatchStats,
+    pub forced_dispatches: u64,  // Pulled ahead by the starvation guard
+    pub degraded_ms: u64,        // Time spent on the backpressure fast path
+}
+
+#[derive(Debug, Serialize)]
+struct ForecastReport {
+    time: String,
+    priorities: BTreeMap<String, u32>,
+    retrograde: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainReport {
+    comm: String,
+    task_type: String,
+    critical: bool,
+    priority: u32,
+    slice_us: u64,
+    reasoning: String,
+}
+
+/// Answer one query against the running scheduler
+pub fn answer(
+    request: Request,
+    astro: &mut AstrologicalScheduler,
+    opts: &Opts,
+    stats: &StatsSnapshot,
+    now: DateTime<Utc>,
+) -> Response {
+    match request {
+        Request::Positions => positions(astro, now),
+        Request::Weather => match astro.get_cosmic_weather(now) {
+            Ok(report) => Response::ok(report.clone(), &serde_json::json!({ "report": report })),
+            Err(e) => Response::error(e.to_string()),
+        },
+        Request::Stats => Response::ok(stats_text(stats), stats),
+        Request::Forecast { hours } => forecast(astro, now, hours),
+        Request::Explain { comm } => explain(astro, opts, &comm, now),
+        Request::AddRule { comm, task_type } => add_rule(astro, &comm, &task_type),
+    }
+}
+
+fn positions(astro: &mut AstrologicalScheduler, now: DateTime<Utc>) -> Response {
+    let positions = match astro.positions(now) {
+        Ok(positions) => positions,
+        Err(e) => return Response::error(e.to_string()),
+    };
+    let mut text = String::new();
+    for pos in &positions {
+        let _ = writeln!(
+            text,
+            "{:<8} {:<12} {:>5.1}° {:<6}{}",
+            pos.planet.name(),
+            pos.sign.name(),
+            pos.degree_in_sign,
+            pos.sign.element().name(),
+            if pos.retrograde { " ℞" } else { "" }
+        );
+    }
+    let reports: Vec<_> = positions.iter().map(PlanetReport::new).collect();
+    Response::ok(text.trim_end(), &reports)
+}
+
+fn stats_text(stats: &StatsSnapshot) -> String {
+    let bpf = &stats.bpf;
+    format!(
+        "⭐ Dispatches: user={} kernel={} failed={} | Tasks: queued={} scheduled={}\n📦 Batches: {} | Size: avg={:.1} max={} | Starvation overrides: {} | Fast path: {:.1}s",
+        bpf.nr_user_dispatches,
+        bpf.nr_kernel_dispatches,
+        bpf.nr_failed_dispatches,
+        bpf.nr_queued,
+        bpf.nr_scheduled,
+        stats.batches.batches,
+        stats.batches.average(),
+        stats.batches.largest,
+        stats.forced_dispatches,
+        Duration::from_millis(stats.degraded_ms).as_secs_f64()
+    )
+}
+
+fn forecast(astro: &AstrologicalScheduler, now: DateTime<Utc>, hours: u32) -> Response {
+    let hours = hours.clamp(1, MAX_FORECAST_HOURS);
+    let forecast = match astro.forecast(now, hours) {
+        Ok(forecast) => forecast,
+        Err(e) => return Response::error(e.to_string()),
+    };
+
+    let mut text = format!("🔮 FORECAST (next {hours}h)\n{:<17}", "Hour (UTC)");
+    if let Some(first) = forecast.first() {
+        for (task_type, _) in &first.priorities {
+            let _ = write!(text, " {:>14}", task_type.name());
+        }
+    }
+    text.push_str("  Retrograde");
+    for hour in &forecast {
+        let _ = write!(text, "\n{:<17}", hour.time.format("%Y-%m-%d %H:%M"));
+        for (_, priority) in &hour.priorities {
+            let _ = write!(text, " {priority:>14}");
+        }
+        let retrograde: Vec<_> = hour.retrograde.iter().map(|planet| planet.name()).collect();
+        let _ = write!(text, "  {}", if retrograde.is_empty() { "-".to_string() } else { retrograde.join(", ") });
+    }
+
+    let reports: Vec<_> = forecast
+        .iter()
+        .map(|hour| ForecastReport {
+            time: hour.time.to_rfc3339(),
+            priorities: hour.priorities.iter().map(|(task_type, priority)| (task_type.name().to_string(), *priority)).collect(),
+            retrograde: hour.retrograde.iter().map(|planet| planet.name().to_string()).collect(),
+        })
+        .collect();
+    Response::ok(text, &reports)
+}
+
+fn explain(astro: &mut AstrologicalScheduler, opts: &Opts, comm: &str, now: DateTime<Utc>) -> Response {
+    let decision = match astro.explain_task(comm, EXPLAIN_PID, DEFAULT_TASK_WEIGHT, now) {
+        Ok(decision) => decision,
+        Err(e) => return Response::error(e.to_string()),
+    };
+    let report = ExplainReport {
+        comm: comm.to_string(),
+        task_type: decision.task_type.name().to_string(),
+        critical: decision.critical,
+        priority: decision.priority,
+        slice_us: slice::slice_ns(opts, &decision) / 1000,
+        reasoning: decision.reasoning,
+    };
+    let text = format!(
+        "{}: {} | Priority: {} | Slice: {}μs\n  {}",
+        report.comm, report.task_type, report.priority, report.slice_us, report.reasoning
+    );
+    Response::ok(text, &report)
+}
+
+fn add_rule(astro: &mut AstrologicalScheduler, comm: &str, task_type: &str) -> Response {
+    let task_type: TaskType = match task_type.parse() {
+        Ok(TaskType::Critical) => return Response::error("critical tasks are set with --critical-comm"),
+        Ok(task_type) => task_type,
+        Err(e) => return Response::error(e),
+    };
+    if let Err(e) = astro.add_classifier_rule(comm, task_type) {
+        return Response::error(e.to_string());
+    }
+    Response::ok(
+        format!("🔬 Tasks named like '{comm}' are now {}", task_type.name()),
+        &serde_json::json!({ "comm": comm, "task_type": task_type.name() }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use clap::Parser;
+
+    fn ask(astro: &mut AstrologicalScheduler, request: Request) -> (String, serde_json::Value) {
+        let opts = Opts::parse_from(["scx_horoscope"]);
+        let stats = StatsSnapshot { bpf: BpfCounters { nr_user_dispatches: 42, ..BpfCounters::default() }, batches: BatchStats::default(), forced_dispatches: 3, degraded_ms: 1500 };
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        match answer(request, astro, &opts, &stats, now) {
+            Response::Ok { text, data } => (text, data),
+            Response::Error { message } => (message, serde_json::Value::Null),
+        }
+    }
+
+    #[test]
+    fn test_positions_and_stats() {
+        let mut astro = AstrologicalScheduler::new(300);
+        let (text, data) = ask(&mut astro, Request::Positions);
+        assert_eq!(data.as_array().unwrap().len(), text.lines().count());
+        assert!(text.lines().any(|line| line.starts_with("Mars")));
+        assert_eq!(data[0]["planet"], "Sun");
+
+        let (text, data) = ask(&mut astro, Request::Stats);
+        assert!(text.contains("user=42"));
+        assert_eq!(data["bpf"]["nr_user_dispatches"], 42);
+        assert!(text.contains("Starvation overrides: 3"));
+        assert_eq!(data["forced_dispatches"], 3);
+        assert!(text.contains("Fast path: 1.5s"));
+        assert_eq!(data["degraded_ms"], 1500);
+    }
+
+    #[test]
+    fn test_forecast_is_bounded() {
+        let mut astro = AstrologicalScheduler::new(300);
+        let (text, data) = ask(&mut astro, Request::Forecast { hours: 3 });
+        assert_eq!(data.as_array().unwrap().len(), 3);
+        assert_eq!(text.lines().count(), 2 + 3);
+        assert!(data[0]["priorities"]["CPU-Intensive"].is_u64());
+
+        let (_, data) = ask(&mut astro, Request::Forecast { hours: 100_000 });
+        assert_eq!(data.as_array().unwrap().len(), MAX_FORECAST_HOURS as usize);
+    }
+
+    #[test]
+    fn test_explain_follows_new_rules() {
+        let mut astro = AstrologicalScheduler::new(300);
+        let (text, data) = ask(&mut astro, Request::Explain { comm: "rustc".to_string() });
+        assert!(text.starts_with("rustc: CPU-Intensive"));
+        assert_eq!(data["task_type"], "CPU-Intensive");
+        assert!(data["slice_us"].as_u64().unwrap() >= 500);
+
+        let (text, _) = ask(&mut astro, Request::AddRule { comm: "rustc".to_string(), task_type: "memory".to_string() });
+        assert!(text.contains("Memory-Heavy"));
+        let (_, data) = ask(&mut astro, Request::Explain { comm: "rustc".to_string() });
+        assert_eq!(data["task_type"], "Memory-Heavy");
+
+        for task_type in ["critical", "astral"] {
+            let (_, data) = ask(&mut astro, Request::AddRule { comm: "rustc".to_string(), task_type: task_type.to_string() });
+            assert!(data.is_null(), "{task_type}");
+        }
+    }
+}
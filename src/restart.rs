@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Restarting without hot-looping
+//
+// When the kernel or our own watchdog asks for a restart, main() brings the
+// scheduler back up. Something persistently broken (another scheduler taking
+// sched_ext straight back, say) would turn that into a tight loop, so each
+// restart waits twice as long as the last, up to MAX_BACKOFF, and after
+// --max-restarts of them in a row the scheduler gives up with an error. A run
+// that lasted longer than HEALTHY_RUN proves things work again, and wipes the
+// slate clean.
+
+use std::time::Duration;
+
+use scx_utils::UserExitInfo;
+
+/// Wait before the first restart in a row
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest wait between restarts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A run this long resets the backoff and the restart count
+pub const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+/// What to do once a run has ended asking for a restart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartDecision {
+    Restart { attempt: u32, after: Duration },  // The attempt'th restart in a row, after a wait
+    GiveUp { restarts: u32 },                   // Restarts in a row already made
+}
+
+/// Restarts in a row, and how long the next one waits
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    max_restarts: u32,
+    restarts: u32,
+    backoff: Duration,
+}
+
+impl RestartPolicy {
+    pub fn new(max_restarts: u32) -> Self {
+        Self { max_restarts, restarts: 0, backoff: INITIAL_BACKOFF }
+    }
+
+    /// Decide about a restart, after a run that lasted `ran`
+    pub fn next(&mut self, ran: Duration) -> RestartDecision {
+        if ran > HEALTHY_RUN {
+            self.restarts = 0;
+            self.backoff = INITIAL_BACKOFF;
+        }
+        if self.restarts >= self.max_restarts {
+            return RestartDecision::GiveUp { restarts: self.restarts };
+        }
+        self.restarts += 1;
+        let after = self.backoff;
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        RestartDecision::Restart { attempt: self.restarts, after }
+    }
+
+    pub fn max_restarts(&self) -> u32 {
+        self.max_restarts
+    }
+}
+
+/// Why a run ended asking for a restart, for the log
+pub fn restart_reason(uei: &UserExitInfo, watchdog_stall: bool) -> String {
+    if watchdog_stall {
+        return "a watchdog stall".to_string();
+    }
+    match uei.exit_code() {
+        Some(code) => format!("the kernel asked for it (exit code {code:#x})"),
+        None => "the kernel asked for it".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restart(attempt: u32, secs: u64) -> RestartDecision {
+        RestartDecision::Restart { attempt, after: Duration::from_secs(secs) }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let mut policy = RestartPolicy::new(10);
+        let quick = Duration::from_secs(2);
+        let waits: Vec<_> = (0..7).map(|_| policy.next(quick)).collect();
+        assert_eq!(waits, [restart(1, 1), restart(2, 2), restart(3, 4), restart(4, 8), restart(5, 16), restart(6, 30), restart(7, 30)]);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_restarts() {
+        let mut policy = RestartPolicy::new(2);
+        let quick = Duration::ZERO;
+        assert_eq!(policy.next(quick), restart(1, 1));
+        assert_eq!(policy.next(quick), restart(2, 2));
+        assert_eq!(policy.next(quick), RestartDecision::GiveUp { restarts: 2 });
+        assert_eq!(policy.next(quick), RestartDecision::GiveUp { restarts: 2 });
+
+        // With no restarts allowed, the first exit is the last
+        assert_eq!(RestartPolicy::new(0).next(quick), RestartDecision::GiveUp { restarts: 0 });
+    }
+
+    #[test]
+    fn test_healthy_run_resets() {
+        let mut policy = RestartPolicy::new(2);
+        policy.next(Duration::ZERO);
+        policy.next(Duration::ZERO);
+        // A minute is not quite enough; a little more is
+        assert_eq!(policy.next(HEALTHY_RUN), RestartDecision::GiveUp { restarts: 2 });
+        assert_eq!(policy.next(HEALTHY_RUN + Duration::from_secs(1)), restart(1, 1));
+        assert_eq!(policy.next(Duration::ZERO), restart(2, 2));
+    }
+
+    #[test]
+    fn test_restart_reason() {
+        let uei = UserExitInfo::default();
+        assert_eq!(restart_reason(&uei, true), "a watchdog stall");
+        assert_eq!(restart_reason(&uei, false), "the kernel asked for it");
+    }
+}
@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Retrograde grace
+//
+// When a planet stations retrograde mid-run, every task it rules would have
+// its slice cut on its very next dispatch. With --retrograde-grace-secs, tasks
+// that were already running when the retrograde began are eased into the
+// penalty instead: it starts at nothing and deepens steadily until the grace
+// window is over. Tasks first seen during the retrograde take it in full.
+// Each task is remembered with its start time, so a PID handed to a new task
+// doesn't inherit the old one's grace.
+
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::astrology::{Planet, PlanetaryPosition};
+use crate::probe;
+
+/// Tasks remembered before the grace starts over
+pub const GRACE_TABLE_SIZE: usize = 4096;
+
+/// Eases tasks that predate a retrograde into its slice penalty
+#[derive(Debug, Clone)]
+pub struct RetrogradeGrace {
+    grace: Duration,
+    first_seen: HashMap<i32, (Option<u64>, Instant)>,  // Start time in clock ticks, and first dispatch
+    retrograde_since: HashMap<Planet, Instant>,  // When each retrograde planet was first seen retrograde
+    graced: u64,
+    start_ticks: fn(i32) -> io::Result<u64>,
+}
+
+impl RetrogradeGrace {
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            first_seen: HashMap::new(),
+            retrograde_since: HashMap::new(),
+            graced: 0,
+            start_ticks: probe::start_ticks,
+        }
+    }
+
+    /// Note which planets are retrograde in the chart cast at `now`
+    pub fn note_chart(&mut self, positions: &[PlanetaryPosition], now: Instant) {
+        for pos in positions {
+            if pos.retrograde {
+                self.retrograde_since.entry(pos.planet).or_insert(now);
+            } else {
+                self.retrograde_since.remove(&pos.planet);
+            }
+        }
+    }
+
+    /// Note a dispatch of `pid` under `ruler`, returning the slice factor to apply in place of `full`
+    ///
+    /// A task seen before its ruler turned retrograde gets nothing of the
+    /// penalty at the start of the grace window, all of it at the end, and
+    /// its share in between.
+    pub fn factor(&mut self, pid: i32, ruler: Planet, full: f64, now: Instant) -> f64 {
+        let (started, first_seen) = self.first_seen(pid, now);
+        let Some(&since) = self.retrograde_since.get(&ruler) else {
+            return full;
+        };
+        let elapsed = now.saturating_duration_since(since);
+        if first_seen >= since || elapsed >= self.grace {
+            return full;
+        }
+        // Only a task about to be eased is looked up again: under a reused PID, it is new
+        let current = (self.start_ticks)(pid).ok();
+        if started.is_none() || current != started {
+            self.first_seen.insert(pid, (current, now));
+            return full;
+        }
+        self.graced += 1;
+        let progress = elapsed.as_secs_f64() / self.grace.as_secs_f64();
+        1.0 - (1.0 - full) * progress
+    }
+
+    /// Start time and first dispatch of `pid`, noting both if it is new
+    fn first_seen(&mut self, pid: i32, now: Instant) -> (Option<u64>, Instant) {
+        if let Some(&seen) = self.first_seen.get(&pid) {
+            return seen;
+        }
+        if self.first_seen.len() >= GRACE_TABLE_SIZE {
+            self.first_seen.clear();
+        }
+        let seen = ((self.start_ticks)(pid).ok(), now);
+        self.first_seen.insert(pid, seen);
+        seen
+    }
+
+    /// Drop a task that has exited
+    pub fn forget(&mut self, pid: i32) {
+        self.first_seen.remove(&pid);
+    }
+
+    /// Dispatches eased into a retrograde penalty since startup
+    pub fn graced(&self) -> u64 {
+        self.graced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use crate::astrology::ZodiacSign;
+
+    thread_local! {
+        static GENERATION: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Start times that change only when a test reuses every PID at once
+    #[allow(clippy::unnecessary_wraps)]
+    fn fake_start_ticks(pid: i32) -> io::Result<u64> {
+        Ok(u64::from(pid.unsigned_abs()) * 1000 + GENERATION.with(Cell::get))
+    }
+
+    fn fake_grace(secs: u64) -> RetrogradeGrace {
+        RetrogradeGrace { start_ticks: fake_start_ticks, ..RetrogradeGrace::new(Duration::from_secs(secs)) }
+    }
+
+    fn mercury(retrograde: bool) -> PlanetaryPosition {
+        PlanetaryPosition {
+            planet: Planet::Mercury,
+            longitude: 100.0,
+            latitude: 0.0,
+            sign: ZodiacSign::from_longitude(100.0),
+            degree_in_sign: ZodiacSign::degree_in_sign(100.0),
+            retrograde,
+            moon_phase: None,
+        }
+    }
+
+    #[test]
+    fn test_pre_existing_task_is_eased_in() {
+        let mut grace = fake_grace(60);
+        let start = Instant::now();
+        let secs = |s| start + Duration::from_secs(s);
+
+        // Running while Mercury is direct
+        grace.note_chart(&[mercury(false)], start);
+        assert!((grace.factor(1, Planet::Mercury, 0.5, start) - 0.5).abs() < 1e-9);
+
+        // Mercury stations retrograde ten seconds in; a newcomer arrives with it
+        grace.note_chart(&[mercury(true)], secs(10));
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(10)) - 1.0).abs() < 1e-9);
+        assert!((grace.factor(2, Planet::Mercury, 0.5, secs(10)) - 0.5).abs() < 1e-9);
+
+        // Halfway through the window, half the penalty; a later chart doesn't restart the clock
+        grace.note_chart(&[mercury(true)], secs(30));
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(40)) - 0.75).abs() < 1e-9);
+        assert!((grace.factor(2, Planet::Mercury, 0.5, secs(40)) - 0.5).abs() < 1e-9);
+
+        // Once the window is over, the full penalty
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(70)) - 0.5).abs() < 1e-9);
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(100)) - 0.5).abs() < 1e-9);
+        assert_eq!(grace.graced(), 2);
+
+        // The next retrograde brings a new grace window
+        grace.note_chart(&[mercury(false)], secs(200));
+        grace.note_chart(&[mercury(true)], secs(300));
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(315)) - 0.875).abs() < 1e-9);
+
+        // A PID that exited and came back is new
+        grace.forget(1);
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(316)) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reused_pid_is_not_eased_in() {
+        let mut grace = fake_grace(60);
+        let start = Instant::now();
+        let secs = |s| start + Duration::from_secs(s);
+
+        grace.note_chart(&[mercury(false)], start);
+        grace.factor(1, Planet::Mercury, 0.5, start);
+        grace.note_chart(&[mercury(true)], secs(10));
+
+        // PID 1 now belongs to a task started during the retrograde
+        GENERATION.with(|generation| generation.set(1));
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(40)) - 0.5).abs() < 1e-9);
+        assert!((grace.factor(1, Planet::Mercury, 0.5, secs(41)) - 0.5).abs() < 1e-9);
+        assert_eq!(grace.graced(), 0);
+    }
+
+    #[test]
+    fn test_table_is_bounded() {
+        let mut grace = fake_grace(60);
+        let now = Instant::now();
+        for pid in 0..=GRACE_TABLE_SIZE as i32 {
+            grace.factor(pid, Planet::Mercury, 0.5, now);
+        }
+        assert!(grace.first_seen.len() <= GRACE_TABLE_SIZE);
+    }
+}
@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Dispatch retries
+//
+// A failed `dispatch_task` used to be logged and forgotten, leaving a runnable
+// task stranded until the kernel noticed. Now a failure is retried once on
+// any CPU, in case the chosen one went offline, and a task that still can't
+// be sent waits in a small queue drained at the start of the next dispatch
+// round. A long enough run of failures means something is structurally
+// wrong, and the scheduler gives up rather than strand everything.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use log::warn;
+
+use crate::bpf::{BpfScheduler, DispatchedTask, RL_CPU_ANY};
+
+/// Tasks held back for the next round; beyond this they are left to the kernel
+pub const RETRY_QUEUE_CAPACITY: usize = 256;
+
+/// Where dispatched tasks go
+pub trait Dispatcher {
+    type Error: fmt::Debug;
+
+    fn dispatch(&mut self, task: &DispatchedTask) -> Result<(), Self::Error>;
+}
+
+impl Dispatcher for BpfScheduler<'_> {
+    type Error = libbpf_rs::Error;
+
+    fn dispatch(&mut self, task: &DispatchedTask) -> Result<(), Self::Error> {
+        self.dispatch_task(task)
+    }
+}
+
+/// What became of one task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    Dispatched,
+    Rerouted,  // Sent on the retry to any CPU
+    Queued,    // Waiting for the next round
+    Dropped,   // The retry queue was full
+}
+
+/// Retry counters since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryStats {
+    pub rerouted: u64,
+    pub queued: u64,
+    pub dropped: u64,
+}
+
+/// Failed dispatches waiting for another try
+pub struct RetryQueue {
+    pending: VecDeque<DispatchedTask>,
+    capacity: usize,
+    consecutive_failures: u32,
+    max_failures: u32,  // Consecutive failures tolerated before giving up
+    stats: RetryStats,
+}
+
+impl RetryQueue {
+    pub fn new(capacity: usize, max_failures: u32) -> Self {
+        Self { pending: VecDeque::new(), capacity, consecutive_failures: 0, max_failures, stats: RetryStats::default() }
+    }
+
+    fn attempt<D: Dispatcher>(&mut self, dispatcher: &mut D, task: &DispatchedTask) -> bool {
+        match dispatcher.dispatch(task) {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                true
+            }
+            Err(e) => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                warn!("Failed to dispatch task {} to CPU {}: {e:?}", task.pid, task.cpu);
+                false
+            }
+        }
+    }
+
+    /// Dispatch `task`, retrying on any CPU and then queueing it if that fails too
+    pub fn dispatch<D: Dispatcher>(&mut self, dispatcher: &mut D, mut task: DispatchedTask) -> DispatchOutcome {
+        if self.attempt(dispatcher, &task) {
+            return DispatchOutcome::Dispatched;
+        }
+        if task.cpu != RL_CPU_ANY {
+            task.cpu = RL_CPU_ANY;
+            if self.attempt(dispatcher, &task) {
+                self.stats.rerouted += 1;
+                return DispatchOutcome::Rerouted;
+            }
+        }
+        if self.pending.len() >= self.capacity {
+            self.stats.dropped += 1;
+            return DispatchOutcome::Dropped;
+        }
+        self.stats.queued += 1;
+        self.pending.push_back(task);
+        DispatchOutcome::Queued
+    }
+
+    /// Send the queued tasks in order, stopping at the first one that still fails
+    ///
+    /// Returns how many were dispatched.
+    pub fn drain<D: Dispatcher>(&mut self, dispatcher: &mut D) -> usize {
+        let mut sent = 0;
+        while let Some(task) = self.pending.pop_front() {
+            if !self.attempt(dispatcher, &task) {
+                self.pending.push_front(task);
+                break;
+            }
+            sent += 1;
+        }
+        sent
+    }
+
+    /// An error once consecutive failures exceed the limit
+    pub fn health(&self) -> Result<(), String> {
+        if self.consecutive_failures > self.max_failures {
+            return Err(format!(
+                "{} dispatches failed in a row (limit {}); giving up, {} tasks still waiting",
+                self.consecutive_failures,
+                self.max_failures,
+                self.pending.len()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn stats(&self) -> RetryStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails the next `failures` dispatches, then records the rest
+    #[derive(Default)]
+    struct FlakyDispatcher {
+        failures: usize,
+        offline_cpu: Option<i32>,  // Always refuses this CPU
+        sent: Vec<(i32, i32)>,
+    }
+
+    impl Dispatcher for FlakyDispatcher {
+        type Error = &'static str;
+
+        fn dispatch(&mut self, task: &DispatchedTask) -> Result<(), Self::Error> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err("ring buffer full");
+            }
+            if self.offline_cpu == Some(task.cpu) {
+                return Err("cpu offline");
+            }
+            self.sent.push((task.pid, task.cpu));
+            Ok(())
+        }
+    }
+
+    fn task(pid: i32, cpu: i32) -> DispatchedTask {
+        DispatchedTask { pid, cpu, flags: 0, slice_ns: 1_000_000, vtime: 0, enq_cnt: 1 }
+    }
+
+    #[test]
+    fn test_retry_on_any_cpu() {
+        let mut retries = RetryQueue::new(4, 10);
+        let mut dispatcher = FlakyDispatcher { offline_cpu: Some(3), ..FlakyDispatcher::default() };
+
+        assert_eq!(retries.dispatch(&mut dispatcher, task(1, 2)), DispatchOutcome::Dispatched);
+        assert_eq!(retries.dispatch(&mut dispatcher, task(2, 3)), DispatchOutcome::Rerouted);
+        assert_eq!(dispatcher.sent, [(1, 2), (2, RL_CPU_ANY)]);
+        assert_eq!(retries.stats(), RetryStats { rerouted: 1, queued: 0, dropped: 0 });
+        assert_eq!(retries.len(), 0);
+        assert!(retries.health().is_ok());
+    }
+
+    #[test]
+    fn test_queued_until_next_round() {
+        let mut retries = RetryQueue::new(2, 10);
+        let mut dispatcher = FlakyDispatcher { failures: 2 * 3, ..FlakyDispatcher::default() };
+
+        // Both attempts fail for three tasks: two are queued, the third doesn't fit
+        assert_eq!(retries.dispatch(&mut dispatcher, task(1, 0)), DispatchOutcome::Queued);
+        assert_eq!(retries.dispatch(&mut dispatcher, task(2, 1)), DispatchOutcome::Queued);
+        assert_eq!(retries.dispatch(&mut dispatcher, task(3, 2)), DispatchOutcome::Dropped);
+        assert_eq!(retries.len(), 2);
+        assert!(dispatcher.sent.is_empty());
+
+        // The next round sends them in order, on any CPU
+        dispatcher.failures = 1;
+        assert_eq!(retries.drain(&mut dispatcher), 0);
+        assert_eq!(retries.len(), 2);
+        assert_eq!(retries.drain(&mut dispatcher), 2);
+        assert_eq!(dispatcher.sent, [(1, RL_CPU_ANY), (2, RL_CPU_ANY)]);
+        assert_eq!(retries.stats(), RetryStats { rerouted: 0, queued: 2, dropped: 1 });
+        assert!(retries.health().is_ok());
+    }
+
+    #[test]
+    fn test_gives_up_after_too_many_failures() {
+        let mut retries = RetryQueue::new(RETRY_QUEUE_CAPACITY, 5);
+        let mut dispatcher = FlakyDispatcher { failures: 5, ..FlakyDispatcher::default() };
+
+        // Five failures in a row are tolerated, and a success resets the count
+        retries.dispatch(&mut dispatcher, task(1, 0));
+        retries.dispatch(&mut dispatcher, task(2, 0));
+        assert_eq!(retries.drain(&mut dispatcher), 0);
+        assert!(retries.health().is_ok());
+        assert_eq!(retries.drain(&mut dispatcher), 2);
+        assert!(retries.health().is_ok());
+
+        dispatcher.failures = 6;
+        for pid in 3..6 {
+            retries.dispatch(&mut dispatcher, task(pid, 0));
+        }
+        let err = retries.health().unwrap_err();
+        assert!(err.starts_with("6 dispatches failed in a row (limit 5)"), "{err}");
+    }
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Schedule optimizer
+//
+// Looks back over a window of scheduler statistics and suggests option
+// changes that would have made it go smoother: shorter slices for a
+// chronically backed-up queue, gentler retrograde cuts when the sky is
+// mostly retrograde, and a higher base priority for task types the stars
+// keep pinned to the floor.
+
+use std::collections::HashMap;
+
+use crate::astrology::{AstrologicalScheduler, TaskType};
+use crate::stats::SchedulerStats;
+use crate::Opts;
+
+/// Share of queue samples that must be busy before the queue counts as chronically high
+const CHRONIC_QUEUE_FRACTION: f64 = 0.75;
+
+/// Slice reduction applied to a chronically high queue
+const SLICE_REDUCTION: f64 = 0.75;
+
+/// Share of decisions under a retrograde ruler that counts as dominating
+const RETROGRADE_DOMINANCE: f64 = 0.5;
+
+/// Retrograde slice factor suggested when retrograde tasks dominate
+const GENTLE_RETROGRADE_FACTOR: f64 = 0.8;
+
+/// Priority at or below which the time slice bottoms out (priority factor 0.1)
+pub const PRIORITY_FLOOR: u32 = 100;
+
+/// Raise applied to the base priority of a floored task type
+const FLOOR_RAISE: f64 = 1.5;
+
+/// Observations needed before any heuristic trusts the numbers
+const MIN_SAMPLES: u64 = 100;
+
+/// Suggested option changes; `None` / absent entries stay as they are
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptsChangeset {
+    pub slice_us: Option<u64>,
+    pub retrograde_factor: Option<f64>,
+    pub priority_floor_changes: HashMap<TaskType, u32>,  // New base priority per task type
+    pub rationale: Vec<String>,                           // One line per suggested change
+}
+
+impl OptsChangeset {
+    pub fn is_empty(&self) -> bool {
+        self.slice_us.is_none() && self.retrograde_factor.is_none() && self.priority_floor_changes.is_empty()
+    }
+
+    /// Write the suggested changes into `opts`
+    pub fn apply(&self, opts: &mut Opts) {
+        if let Some(slice_us) = self.slice_us {
+            opts.slice_us = slice_us;
+        }
+        if let Some(factor) = self.retrograde_factor {
+            opts.retrograde_factor = factor;
+        }
+        for (&task_type, &priority) in &self.priority_floor_changes {
+            if let Some(entry) = opts.base_priority.iter_mut().find(|(t, _)| *t == task_type) {
+                entry.1 = priority;
+            } else {
+                opts.base_priority.push((task_type, priority));
+            }
+        }
+    }
+}
+
+/// Base priority of a task type under the given options
+pub fn base_priority(opts: &Opts, task_type: TaskType) -> u32 {
+    opts.base_priority
+        .iter()
+        .rev()
+        .find(|(t, _)| *t == task_type)
+        .map_or_else(|| AstrologicalScheduler::default_base_priority(task_type), |&(_, priority)| priority)
+}
+
+/// Suggest option changes based on what the scheduler has seen
+pub fn optimize_opts(current_stats: &SchedulerStats, current_opts: &Opts) -> OptsChangeset {
+    let mut changes = OptsChangeset::default();
+
+    // A queue that never drains wants shorter turns
+    if current_stats.queue_samples() >= MIN_SAMPLES
+        && current_stats.busy_queue_fraction() >= CHRONIC_QUEUE_FRACTION
+    {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+        let shorter = ((current_opts.slice_us as f64 * SLICE_REDUCTION) as u64).max(current_opts.slice_us_min);
+        if shorter < current_opts.slice_us {
+            changes.slice_us = Some(shorter);
+            changes.rationale.push(format!(
+                "slice_us {} -> {shorter}: the queue held {}+ tasks {:.0}% of the time (average {:.1})",
+                current_opts.slice_us,
+                current_stats.high_queue_depth(),
+                current_stats.busy_queue_fraction() * 100.0,
+                current_stats.average_queue_depth()
+            ));
+        }
+    }
+
+    // When most of the work is under a retrograde ruler, punishing it punishes everything
+    if current_stats.decisions() >= MIN_SAMPLES
+        && current_stats.retrograde_fraction() > RETROGRADE_DOMINANCE
+        && current_opts.retrograde_factor < GENTLE_RETROGRADE_FACTOR
+    {
+        changes.retrograde_factor = Some(GENTLE_RETROGRADE_FACTOR);
+        changes.rationale.push(format!(
+            "retrograde_factor {:.2} -> {GENTLE_RETROGRADE_FACTOR:.2}: {:.0}% of decisions were under a retrograde ruler",
+            current_opts.retrograde_factor,
+            current_stats.retrograde_fraction() * 100.0
+        ));
+    }
+
+    // A type whose P99 sits on the floor never gets more than the minimum slice
+    for task_type in TaskType::all() {
+        let Some(histogram) = current_stats.priorities(task_type) else {
+            continue;
+        };
+        let Some(p99) = histogram.percentile(99.0) else {
+            continue;
+        };
+        if histogram.count() < MIN_SAMPLES || p99 > PRIORITY_FLOOR {
+            continue;
+        }
+
+        let base = base_priority(current_opts, task_type);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let raised = ((f64::from(base) * FLOOR_RAISE) as u32).min(1000);
+        if raised > base {
+            changes.priority_floor_changes.insert(task_type, raised);
+            changes.rationale.push(format!(
+                "{} base priority {base} -> {raised}: P99 priority {p99} is at the floor ({PRIORITY_FLOOR})",
+                task_type.name()
+            ));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::time::Duration;
+
+    fn opts() -> Opts {
+        Opts::parse_from(["scx_horoscope"])
+    }
+
+    fn record(stats: &mut SchedulerStats, n: u64, task_type: TaskType, priority: u32, retrograde: bool) {
+        for _ in 0..n {
+            stats.record_decision(task_type, priority, retrograde, Duration::from_micros(2));
+        }
+    }
+
+    #[test]
+    fn test_quiet_system_needs_nothing() {
+        let mut stats = SchedulerStats::new(4);
+        for _ in 0..500 {
+            stats.record_queue_depth(1);
+        }
+        record(&mut stats, 500, TaskType::CpuIntensive, 400, false);
+
+        let changes = optimize_opts(&stats, &opts());
+        assert!(changes.is_empty());
+        assert!(changes.rationale.is_empty());
+    }
+
+    #[test]
+    fn test_chronic_queue_shortens_slice() {
+        let mut stats = SchedulerStats::new(4);
+        for depth in 0..200 {
+            stats.record_queue_depth(if depth % 10 == 0 { 0 } else { 50 });
+        }
+
+        let changes = optimize_opts(&stats, &opts());
+        assert_eq!(changes.slice_us, Some(3750));
+        assert_eq!(changes.rationale.len(), 1);
+
+        // Never below the minimum slice
+        let mut tight = opts();
+        tight.slice_us = 600;
+        assert_eq!(optimize_opts(&stats, &tight).slice_us, Some(500));
+        tight.slice_us = 500;
+        assert_eq!(optimize_opts(&stats, &tight).slice_us, None);
+    }
+
+    #[test]
+    fn test_dominant_retrograde_softens_penalty() {
+        let mut stats = SchedulerStats::new(4);
+        record(&mut stats, 80, TaskType::Network, 300, true);
+        record(&mut stats, 40, TaskType::Desktop, 300, false);
+
+        let mut current = opts();
+        let changes = optimize_opts(&stats, &current);
+        assert_eq!(changes.retrograde_factor, Some(0.8));
+
+        // Already gentle enough
+        changes.apply(&mut current);
+        assert!(optimize_opts(&stats, &current).retrograde_factor.is_none());
+    }
+
+    #[test]
+    fn test_floored_type_gets_raised() {
+        let mut stats = SchedulerStats::new(4);
+        record(&mut stats, 200, TaskType::MemoryHeavy, 40, false);
+        record(&mut stats, 200, TaskType::System, 40, false);
+        record(&mut stats, 1, TaskType::System, 900, false);
+        record(&mut stats, 50, TaskType::Network, 20, false);  // Too few to judge
+
+        let mut current = opts();
+        let changes = optimize_opts(&stats, &current);
+        assert_eq!(changes.priority_floor_changes.get(&TaskType::MemoryHeavy), Some(&120));
+        assert_eq!(changes.priority_floor_changes.get(&TaskType::System), Some(&300));
+        assert!(!changes.priority_floor_changes.contains_key(&TaskType::Network));
+
+        changes.apply(&mut current);
+        assert_eq!(base_priority(&current, TaskType::MemoryHeavy), 120);
+        let changes = optimize_opts(&stats, &current);
+        assert_eq!(changes.priority_floor_changes.get(&TaskType::MemoryHeavy), Some(&180));
+    }
+
+    #[test]
+    fn test_floor_raise_caps_at_max_priority() {
+        let mut stats = SchedulerStats::new(4);
+        record(&mut stats, 200, TaskType::Critical, 0, false);
+
+        let mut current = opts();
+        current.base_priority.push((TaskType::Critical, 1000));
+        assert!(optimize_opts(&stats, &current).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Keeping the scheduler itself on its feet
+//
+// Every task waits on the dispatch loop, so a starved horoscope process
+// stalls the whole machine. With --self-cpu the dispatch thread is pinned to
+// one CPU, and with --self-rt it runs SCHED_FIFO at a modest priority. Both
+// are best effort: without the permission for it the scheduler says so and
+// carries on unpinned, or under SCHED_OTHER. Fire tasks look for idle CPUs
+// to burn, so the pinned CPU is left out of their candidates rather than
+// have them compete with the thread that places them.
+
+use std::io;
+use std::mem;
+
+/// `SCHED_FIFO` priority asked for by --self-rt: above the default, well below the kernel's own threads
+pub const SELF_RT_PRIORITY: i32 = 10;
+
+/// Check that `cpu` exists on a machine with `nr_cpus` CPUs
+pub fn validate_self_cpu(cpu: usize, nr_cpus: usize) -> Result<(), String> {
+    if cpu < nr_cpus {
+        Ok(())
+    } else {
+        Err(format!("--self-cpu {cpu} is not a CPU of this machine (0-{})", nr_cpus.saturating_sub(1)))
+    }
+}
+
+/// Take the scheduler's own CPU out of an idle mask
+pub fn exclude_self_cpu(idle: &mut [bool], self_cpu: Option<usize>) {
+    if let Some(idle) = self_cpu.and_then(|cpu| idle.get_mut(cpu)) {
+        *idle = false;
+    }
+}
+
+/// Pin the calling thread to `cpu`
+pub fn pin_to_cpu(cpu: usize) -> io::Result<()> {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+    // SAFETY: the set is a plain bitmask, zeroed before use and only touched below CPU_SETSIZE
+    let result = unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &raw const set)
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Run the calling thread under `SCHED_FIFO` at `priority`
+pub fn set_fifo(priority: i32) -> io::Result<()> {
+    let param = libc::sched_param { sched_priority: priority };
+    // SAFETY: param outlives the call
+    if unsafe { libc::sched_setscheduler(0, libc::SCHED_FIFO, &raw const param) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Why a request about our own thread was refused, in words an operator can act on
+pub fn describe_refusal(what: &str, e: &io::Error) -> String {
+    match e.raw_os_error() {
+        Some(libc::EPERM) => format!("{what}: not permitted (needs CAP_SYS_NICE or root)"),
+        Some(libc::EINVAL) => format!("{what}: rejected by the kernel (CPU offline or not allowed?)"),
+        _ => format!("{what}: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_self_cpu() {
+        assert!(validate_self_cpu(0, 1).is_ok());
+        assert!(validate_self_cpu(7, 8).is_ok());
+        assert_eq!(validate_self_cpu(8, 8), Err("--self-cpu 8 is not a CPU of this machine (0-7)".to_string()));
+    }
+
+    #[test]
+    fn test_self_cpu_is_not_idle_for_fire() {
+        let mut idle = vec![true, true, false, true];
+        exclude_self_cpu(&mut idle, Some(1));
+        assert_eq!(idle, vec![true, false, false, true]);
+
+        // No --self-cpu, or one past the mask: nothing changes
+        exclude_self_cpu(&mut idle, None);
+        exclude_self_cpu(&mut idle, Some(42));
+        assert_eq!(idle, vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn test_refusals_are_explained() {
+        let eperm = io::Error::from_raw_os_error(libc::EPERM);
+        assert_eq!(describe_refusal("SCHED_FIFO", &eperm), "SCHED_FIFO: not permitted (needs CAP_SYS_NICE or root)");
+        assert!(pin_to_cpu(usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Leaving on a signal
+//
+// SIGINT or SIGTERM only raises a flag; the dispatch loop checks it next to
+// bpf.exited() and winds down the usual way: final stats, a summary of the
+// run, a parting horoscope, then shutdown_and_report() so the kernel gets its
+// scheduler back cleanly. A second signal means the operator has run out of
+// patience, and the process exits on the spot.
+//
+// With --summary-file the same summary is also written out as JSON, for CI
+// and benchmark harnesses to read back.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+
+use crate::astrology::hours::day_ruler;
+use crate::astrology::{Element, Planet, PlanetaryPosition, TaskType};
+
+/// Exit status when a second signal cuts the shutdown short, as for a shell's Ctrl-C
+const FORCED_EXIT_STATUS: i32 = 130;
+
+/// Signals received, and the first of them
+pub struct ShutdownFlag {
+    signals: AtomicU32,
+    first_signal: AtomicI32,
+}
+
+impl ShutdownFlag {
+    pub const fn new() -> Self {
+        Self { signals: AtomicU32::new(0), first_signal: AtomicI32::new(0) }
+    }
+
+    /// Ask for a shutdown because of `signal` (0 for none); true the first time
+    pub fn request(&self, signal: i32) -> bool {
+        let first = self.signals.fetch_add(1, Ordering::Relaxed) == 0;
+        if first {
+            self.first_signal.store(signal, Ordering::Relaxed);
+        }
+        first
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.signals.load(Ordering::Relaxed) > 0
+    }
+
+    /// Name of the signal that asked for the shutdown, if one did
+    pub fn signal_name(&self) -> Option<&'static str> {
+        match self.first_signal.load(Ordering::Relaxed) {
+            0 => None,
+            libc::SIGINT => Some("SIGINT"),
+            libc::SIGTERM => Some("SIGTERM"),
+            _ => Some("a signal"),
+        }
+    }
+}
+
+/// Raised by SIGINT and SIGTERM once `install` has run
+pub static SHUTDOWN: ShutdownFlag = ShutdownFlag::new();
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only atomics and _exit: both are async-signal-safe
+    if !SHUTDOWN.request(signal) {
+        // SAFETY: _exit skips every destructor, which is the point
+        unsafe { libc::_exit(FORCED_EXIT_STATUS) };
+    }
+}
+
+/// Route SIGINT and SIGTERM to `SHUTDOWN`
+///
+/// Call it after the BPF scheduler is set up, as that installs a Ctrl-C
+/// handler of its own that this one replaces.
+pub fn install() -> io::Result<()> {
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        // SAFETY: the action is zeroed, then filled in; on_signal is async-signal-safe
+        let result = unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&raw mut action.sa_mask);
+            libc::sigaction(signal, &raw const action, std::ptr::null_mut())
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// A farewell from the planet ruling the day
+pub fn parting_horoscope(now: DateTime<Utc>) -> &'static str {
+    match day_ruler(now.weekday()) {
+        Planet::Sun => "The Sun salutes a run that shone brightly",
+        Planet::Moon => "The Moon will keep watch over your tasks tonight",
+        Planet::Mercury => "Mercury carries your last messages safely home",
+        Planet::Venus => "Venus leaves every window in harmony",
+        Planet::Mars => "Mars sheathes his sword: the battle for the CPUs is over",
+        Planet::Jupiter => "Jupiter blesses the memory you leave behind",
+        Planet::Saturn | Planet::Uranus => "Saturn thanks you for your discipline",
+    }
+}
+
+/// Element with the most planets in a chart, the first in `Element::all` order on a tie
+fn dominant_element(positions: &[PlanetaryPosition]) -> Option<Element> {
+    let count = |element: Element| positions.iter().filter(|pos| pos.sign.element() == element).count();
+    Element::all().into_iter().filter(|&element| count(element) > 0).rev().max_by_key(|&element| count(element))
+}
+
+/// Totals kept over the run for the exit summary
+#[derive(Debug, Clone, Default)]
+pub struct RunTally {
+    by_type: HashMap<TaskType, u64>,  // Dispatches of each task type
+    slice_ns: u64,                    // Sum of the slices dispatched
+    retrograde: HashMap<Planet, Duration>,  // Time each planet spent retrograde, up to the current chart
+    dominant: HashMap<Element, u64>,  // Charts cast with each element dominant
+    chart: Option<(DateTime<Utc>, Vec<Planet>)>,  // When the current chart was cast, and who is retrograde in it
+}
+
+impl RunTally {
+    pub fn note_dispatch(&mut self, task_type: TaskType, slice_ns: u64) {
+        *self.by_type.entry(task_type).or_default() += 1;
+        self.slice_ns = self.slice_ns.saturating_add(slice_ns);
+    }
+
+    /// Take in a chart cast at `time`, crediting the time since the last one to the planets retrograde in it
+    pub fn note_chart(&mut self, time: DateTime<Utc>, positions: &[PlanetaryPosition]) {
+        self.credit_retrograde(time);
+        if let Some(element) = dominant_element(positions) {
+            *self.dominant.entry(element).or_default() += 1;
+        }
+        let retrograde = positions.iter().filter(|pos| pos.retrograde).map(|pos| pos.planet).collect();
+        self.chart = Some((time, retrograde));
+    }
+
+    fn credit_retrograde(&mut self, until: DateTime<Utc>) {
+        let Some((since, planets)) = &self.chart else {
+            return;
+        };
+        let elapsed = (until - *since).to_std().unwrap_or_default();
+        for &planet in planets {
+            *self.retrograde.entry(planet).or_default() += elapsed;
+        }
+    }
+
+    pub fn dispatches(&self) -> u64 {
+        self.by_type.values().sum()
+    }
+
+    /// Dispatches of every task type, by name
+    pub fn dispatches_by_type(&self) -> BTreeMap<String, u64> {
+        TaskType::all()
+            .into_iter()
+            .map(|task_type| (task_type.name().to_string(), self.by_type.get(&task_type).copied().unwrap_or(0)))
+            .collect()
+    }
+
+    /// Mean slice dispatched, in microseconds, 0.0 before any
+    pub fn average_slice_us(&self) -> f64 {
+        match self.dispatches() {
+            0 => 0.0,
+            #[allow(clippy::cast_precision_loss)]
+            dispatches => self.slice_ns as f64 / dispatches as f64 / 1000.0,
+        }
+    }
+
+    /// Time each planet has spent retrograde, the current chart counted up to `now`
+    pub fn retrograde_time(&self, now: DateTime<Utc>) -> HashMap<Planet, Duration> {
+        let mut tally = self.clone();
+        tally.credit_retrograde(now);
+        tally.retrograde
+    }
+}
+
+/// The exit summary as written to `--summary-file`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryReport {
+    pub ended_at: String,
+    pub run_duration_secs: f64,
+    pub signal: Option<String>,
+    pub user_dispatches: u64,
+    pub kernel_dispatches: u64,
+    pub batches: u64,
+    pub watchdog_trips: u64,
+    pub dispatches_by_type: BTreeMap<String, u64>,
+    pub retrograde_secs: BTreeMap<String, f64>,  // Only the planets that were retrograde at all
+    pub dominant_elements: BTreeMap<String, u64>,  // Charts cast with each element dominant
+    pub average_slice_us: f64,
+}
+
+/// What a run amounted to, for the log on the way out
+#[derive(Debug, Clone, Default)]
+pub struct ExitSummary {
+    pub uptime: Duration,
+    pub signal: Option<&'static str>,
+    pub user_dispatches: u64,
+    pub kernel_dispatches: u64,
+    pub batches: u64,
+    pub ledger_tasks: usize,
+    pub vtime_spread_ns: u64,  // Between the most and least served task on the ledger
+    pub watchdog_trips: u64,   // Dispatch stalls the watchdog caught
+    pub tally: RunTally,
+}
+
+impl ExitSummary {
+    /// The summary, one log line each, ending with a parting horoscope for `now`
+    pub fn lines(&self, now: DateTime<Utc>) -> Vec<String> {
+        let secs = self.uptime.as_secs();
+        let mut lines = vec![format!(
+            "🧾 Ran for {}h{:02}m{:02}s{}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.signal.map(|signal| format!(", until {signal}")).unwrap_or_default()
+        )];
+        lines.push(format!(
+            "🧾 Dispatches: user={} kernel={} in {} batches",
+            self.user_dispatches, self.kernel_dispatches, self.batches
+        ));
+        if self.ledger_tasks > 0 {
+            lines.push(format!(
+                "🧾 Ledger: {} tasks, {:.1}ms of vruntime between the most and least served",
+                self.ledger_tasks,
+                Duration::from_nanos(self.vtime_spread_ns).as_secs_f64() * 1000.0
+            ));
+        }
+        if self.watchdog_trips > 0 {
+            lines.push(format!("🚨 Watchdog: {} dispatch stalls", self.watchdog_trips));
+        }
+        lines.push(format!("🔮 {}", parting_horoscope(now)));
+        lines
+    }
+
+    /// The summary for `--summary-file`, as the run ends at `now`
+    pub fn report(&self, now: DateTime<Utc>) -> SummaryReport {
+        let tally = &self.tally;
+        SummaryReport {
+            ended_at: now.to_rfc3339(),
+            run_duration_secs: self.uptime.as_secs_f64(),
+            signal: self.signal.map(str::to_string),
+            user_dispatches: self.user_dispatches,
+            kernel_dispatches: self.kernel_dispatches,
+            batches: self.batches,
+            watchdog_trips: self.watchdog_trips,
+            dispatches_by_type: tally.dispatches_by_type(),
+            retrograde_secs: tally
+                .retrograde_time(now)
+                .into_iter()
+                .map(|(planet, time)| (planet.name().to_string(), time.as_secs_f64()))
+                .collect(),
+            dominant_elements: Element::all()
+                .into_iter()
+                .map(|element| (element.name().to_string(), tally.dominant.get(&element).copied().unwrap_or(0)))
+                .collect(),
+            average_slice_us: tally.average_slice_us(),
+        }
+    }
+
+    /// Write the summary to `path` as JSON
+    pub fn write_report(&self, path: &Path, now: DateTime<Utc>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.report(now))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_flag_stops_the_loop() {
+        // Stands in for the dispatch loop, with the flag raised by hand instead of a signal
+        let flag = Arc::new(ShutdownFlag::new());
+        let worker = {
+            let flag = Arc::clone(&flag);
+            std::thread::spawn(move || {
+                while !flag.is_requested() {
+                    std::thread::yield_now();
+                }
+            })
+        };
+        assert!(flag.request(libc::SIGTERM));
+        worker.join().unwrap();
+        assert_eq!(flag.signal_name(), Some("SIGTERM"));
+
+        // The second request is the one that forces the exit
+        assert!(!flag.request(libc::SIGINT));
+        assert_eq!(flag.signal_name(), Some("SIGTERM"));
+        assert_eq!(ShutdownFlag::new().signal_name(), None);
+    }
+
+    #[test]
+    fn test_exit_summary() {
+        let saturday = Utc.with_ymd_and_hms(2025, 3, 22, 12, 0, 0).unwrap();
+        let summary = ExitSummary {
+            uptime: Duration::from_secs(3725),
+            signal: Some("SIGINT"),
+            user_dispatches: 1200,
+            kernel_dispatches: 34,
+            batches: 80,
+            ledger_tasks: 12,
+            vtime_spread_ns: 2_500_000,
+            watchdog_trips: 2,
+            tally: RunTally::default(),
+        };
+        assert_eq!(
+            summary.lines(saturday),
+            [
+                "🧾 Ran for 1h02m05s, until SIGINT",
+                "🧾 Dispatches: user=1200 kernel=34 in 80 batches",
+                "🧾 Ledger: 12 tasks, 2.5ms of vruntime between the most and least served",
+                "🚨 Watchdog: 2 dispatch stalls",
+                "🔮 Saturn thanks you for your discipline",
+            ]
+        );
+
+        // Nothing on the ledger, no signal, no stalls: those lines are left out
+        let quiet = ExitSummary { uptime: Duration::from_secs(59), ..ExitSummary::default() };
+        let lines = quiet.lines(saturday + chrono::Duration::days(1));
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "🧾 Ran for 0h00m59s");
+        assert_eq!(lines[2], "🔮 The Sun salutes a run that shone brightly");
+    }
+
+    #[test]
+    fn test_summary_file_for_a_scripted_session() {
+        use crate::astrology::calculate_planetary_positions;
+
+        // Three charts an hour apart; Saturn was retrograde all through the summer of 2025
+        let start = Utc.with_ymd_and_hms(2025, 8, 1, 0, 0, 0).unwrap();
+        let positions = calculate_planetary_positions(start).unwrap();
+        assert!(positions.iter().any(|pos| pos.planet == Planet::Saturn && pos.retrograde));
+        let dominant = dominant_element(&positions).unwrap();
+
+        let mut tally = RunTally::default();
+        for hour in 0..3 {
+            tally.note_chart(start + chrono::Duration::hours(hour), &positions);
+        }
+        for _ in 0..3 {
+            tally.note_dispatch(TaskType::CpuIntensive, 4_000_000);
+        }
+        tally.note_dispatch(TaskType::Network, 2_000_000);
+        let summary = ExitSummary {
+            uptime: Duration::from_secs(3 * 3600),
+            signal: Some("SIGTERM"),
+            user_dispatches: 4,
+            kernel_dispatches: 1,
+            batches: 2,
+            tally,
+            ..ExitSummary::default()
+        };
+
+        let path = std::env::temp_dir().join(format!("horoscope_summary_{}.json", std::process::id()));
+        summary.write_report(&path, start + chrono::Duration::hours(3)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(json["run_duration_secs"], 10800.0);
+        assert_eq!(json["signal"], "SIGTERM");
+        assert_eq!(json["user_dispatches"], 4);
+        assert_eq!(json["dispatches_by_type"]["CPU-Intensive"], 3);
+        assert_eq!(json["dispatches_by_type"]["Network"], 1);
+        assert_eq!(json["dispatches_by_type"]["Memory-Heavy"], 0);
+        let by_type: u64 = json["dispatches_by_type"].as_object().unwrap().values().map(|n| n.as_u64().unwrap()).sum();
+        assert_eq!(by_type, 4);
+        // The last chart counts up to the end of the run
+        assert_eq!(json["retrograde_secs"]["Saturn"], 10800.0);
+        assert_eq!(json["dominant_elements"][dominant.name()], 3);
+        assert_eq!(json["average_slice_us"], 3500.0);
+    }
+}
@@ -0,0 +1,501 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Time slice policy
+//
+// Turns a scheduling decision into a time slice: higher astrological
+// priority earns a longer turn, a retrograde ruler cuts it short, and
+// critical tasks can be pinned to a fixed slice so being scheduled first
+// doesn't also mean running longest. Apart from how important a task is,
+// --element-slice can say how bursty its element is: a throughput element
+// gets longer turns, a latency element shorter and so more frequent ones, at
+// the same priority. Whatever the stars say, the result stays
+// between --slice-us-min and --slice-us-max, and not even a critical pin gets
+// past the latter. With --debug-decisions, `traced_slice_ns` says which of
+// those clamps and penalties bent the result.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::astrology::{Element, SchedulingDecision};
+use crate::Opts;
+
+/// Priority that earns the full default slice
+const MAX_PRIORITY: f64 = 1000.0;
+
+/// Smallest share of the slice range any priority gets
+const MIN_PRIORITY_FACTOR: f64 = 0.1;
+
+/// Slice multiplier for an element favoring throughput
+pub const THROUGHPUT_FACTOR: f64 = 1.5;
+
+/// Slice multiplier for an element favoring latency
+pub const LATENCY_FACTOR: f64 = 0.5;
+
+/// What an element's tasks want from their turns on the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlicePersonality {
+    Throughput,  // Long turns, fewer switches
+    Latency,     // Short turns, back on the CPU sooner
+}
+
+impl SlicePersonality {
+    pub fn name(self) -> &'static str {
+        match self {
+            SlicePersonality::Throughput => "throughput",
+            SlicePersonality::Latency => "latency",
+        }
+    }
+
+    pub fn factor(self) -> f64 {
+        match self {
+            SlicePersonality::Throughput => THROUGHPUT_FACTOR,
+            SlicePersonality::Latency => LATENCY_FACTOR,
+        }
+    }
+}
+
+impl FromStr for SlicePersonality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [SlicePersonality::Throughput, SlicePersonality::Latency]
+            .into_iter()
+            .find(|personality| personality.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown slice personality '{s}' (throughput or latency)"))
+    }
+}
+
+/// Parse an `ELEMENT=PERSONALITY` entry, e.g. `air=latency`
+pub fn parse_element_slice_entry(s: &str) -> Result<(Element, SlicePersonality), String> {
+    let (element, personality) = s.split_once('=').ok_or_else(|| "expected ELEMENT=PERSONALITY".to_string())?;
+    Ok((element.trim().parse()?, personality.trim().parse()?))
+}
+
+/// The slice personality of each element, from --element-slice
+///
+/// An element left out keeps the slice its priority earns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementSlicePolicy {
+    fire: Option<SlicePersonality>,
+    earth: Option<SlicePersonality>,
+    air: Option<SlicePersonality>,
+    water: Option<SlicePersonality>,
+}
+
+impl ElementSlicePolicy {
+    /// The policy for `--element-slice` entries; a later entry for an element wins
+    pub fn new(entries: &[(Element, SlicePersonality)]) -> Self {
+        let mut policy = Self::default();
+        for &(element, personality) in entries {
+            *policy.slot(element) = Some(personality);
+        }
+        policy
+    }
+
+    fn slot(&mut self, element: Element) -> &mut Option<SlicePersonality> {
+        match element {
+            Element::Fire => &mut self.fire,
+            Element::Earth => &mut self.earth,
+            Element::Air => &mut self.air,
+            Element::Water => &mut self.water,
+        }
+    }
+
+    pub fn personality(self, element: Element) -> Option<SlicePersonality> {
+        match element {
+            Element::Fire => self.fire,
+            Element::Earth => self.earth,
+            Element::Air => self.air,
+            Element::Water => self.water,
+        }
+    }
+}
+
+/// Longest slice in microseconds: `--slice-us-max`, or the default slice
+pub fn slice_us_max(opts: &Opts) -> u64 {
+    opts.slice_us_max.unwrap_or(opts.slice_us)
+}
+
+/// Check that min <= default <= max
+pub fn validate_bounds(opts: &Opts) -> Result<(), String> {
+    let max = slice_us_max(opts);
+    if opts.slice_us_min > opts.slice_us || opts.slice_us > max {
+        return Err(format!(
+            "inconsistent time slices: need --slice-us-min ({}) <= --slice-us ({}) <= --slice-us-max ({max})",
+            opts.slice_us_min, opts.slice_us
+        ));
+    }
+    Ok(())
+}
+
+/// Time slice in nanoseconds for a task the stars have judged
+///
+/// A `--critical-slice-us` pin is taken as given up to the maximum; everything
+/// else is clamped to the configured bounds after every boost and penalty.
+pub fn slice_ns(opts: &Opts, decision: &SchedulingDecision) -> u64 {
+    traced_slice_ns(opts, decision).0
+}
+
+/// Like `slice_ns`, noting every clamp and penalty that changed the slice
+pub fn traced_slice_ns(opts: &Opts, decision: &SchedulingDecision) -> (u64, SliceTrace) {
+    eased_slice_ns(opts, decision, opts.retrograde_factor)
+}
+
+/// Like `traced_slice_ns`, cutting a retrograde slice by `retrograde_factor` instead of the configured one
+pub fn eased_slice_ns(opts: &Opts, decision: &SchedulingDecision, retrograde_factor: f64) -> (u64, SliceTrace) {
+    let mut trace = SliceTrace::default();
+    let slice = bounded_slice_ns(opts, decision, retrograde_factor, &mut trace);
+    (slice, trace)
+}
+
+fn bounded_slice_ns(
+    opts: &Opts,
+    decision: &SchedulingDecision,
+    retrograde_factor: f64,
+    trace: &mut SliceTrace,
+) -> u64 {
+    let min_ns = opts.slice_us_min.saturating_mul(1000);
+    let max_ns = slice_us_max(opts).saturating_mul(1000).max(min_ns);
+    if decision.critical {
+        if let Some(critical_slice_us) = opts.critical_slice_us {
+            let pinned = critical_slice_us.saturating_mul(1000);
+            if pinned > max_ns {
+                trace.bounds = Some((pinned, max_ns));
+            }
+            return pinned.min(max_ns);
+        }
+    }
+
+    let unclamped = unclamped_slice_ns(opts, decision, retrograde_factor, trace);
+    let clamped = unclamped.clamp(min_ns, max_ns);
+    if clamped != unclamped {
+        trace.bounds = Some((unclamped, clamped));
+    }
+    clamped
+}
+
+fn unclamped_slice_ns(
+    opts: &Opts,
+    decision: &SchedulingDecision,
+    retrograde_factor: f64,
+    trace: &mut SliceTrace,
+) -> u64 {
+    // Higher astrological priority = longer time slice
+    let raw_factor = f64::from(decision.priority) / MAX_PRIORITY;
+    let priority_factor = raw_factor.clamp(MIN_PRIORITY_FACTOR, 1.0);
+    #[allow(clippy::float_cmp)]
+    if priority_factor != raw_factor {
+        trace.priority_factor = Some((raw_factor, priority_factor));
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let base_slice = opts.slice_us.saturating_mul(1000) as f64; // to nanoseconds
+    #[allow(clippy::cast_precision_loss)]
+    let min_slice = opts.slice_us_min.saturating_mul(1000) as f64;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let mut slice_ns = (min_slice + (base_slice - min_slice) * priority_factor) as u64;
+
+    // How bursty the ruler's element is, as opposed to how important
+    let personality = decision
+        .ruling_element
+        .and_then(|element| Some((element, ElementSlicePolicy::new(&opts.element_slice).personality(element)?)));
+    if let Some((element, personality)) = personality {
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let shaped = (slice_ns as f64 * personality.factor()) as u64;
+        trace.personality = Some((element, personality, slice_ns, shaped));
+        slice_ns = shaped;
+    }
+
+    if retrograde_penalized(opts, decision) {
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let penalized = (slice_ns as f64 * retrograde_factor) as u64;
+        trace.retrograde = Some((slice_ns, penalized));
+        return penalized;
+    }
+    slice_ns
+}
+
+/// Whether a decision's slice takes the retrograde penalty
+///
+/// Only with a retrograde ruler, and not with `--no-retrograde`; critical and
+/// exempt tasks are spared.
+pub fn retrograde_penalized(opts: &Opts, decision: &SchedulingDecision) -> bool {
+    let exempt = decision.critical || opts.retrograde_exempt.contains(&decision.task_type);
+    !opts.no_retrograde && !exempt && decision.planetary_influence < 0.0
+}
+
+/// The clamps and penalties that changed a slice, each as (before, after)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SliceTrace {
+    pub priority_factor: Option<(f64, f64)>,
+    pub personality: Option<(Element, SlicePersonality, u64, u64)>,  // Nanoseconds, shaped by --element-slice
+    pub retrograde: Option<(u64, u64)>,  // Nanoseconds
+    pub bounds: Option<(u64, u64)>,      // Nanoseconds, clamped to --slice-us-min/--slice-us-max
+}
+
+impl SliceTrace {
+    /// True when the slice came out of the formula untouched
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for SliceTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        if let Some((raw, clamped)) = self.priority_factor {
+            write!(f, "priority_factor clamped to {clamped:.1} (was {raw:.3})")?;
+            sep = "; ";
+        }
+        if let Some((element, personality, before, after)) = self.personality {
+            write!(f, "{sep}{} favors {} {}μs -> {}μs", element.name(), personality.name(), before / 1000, after / 1000)?;
+            sep = "; ";
+        }
+        if let Some((before, after)) = self.retrograde {
+            write!(f, "{sep}retrograde penalty {}μs -> {}μs", before / 1000, after / 1000)?;
+            sep = "; ";
+        }
+        if let Some((before, after)) = self.bounds {
+            let bound = if after > before { "floor" } else { "ceiling" };
+            write!(f, "{sep}slice {bound} {}μs -> {}μs", before / 1000, after / 1000)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astrology::TaskType;
+    use clap::Parser;
+
+    fn decision(priority: u32, critical: bool, planetary_influence: f64) -> SchedulingDecision {
+        SchedulingDecision {
+            task_type: if critical { TaskType::Critical } else { TaskType::CpuIntensive },
+            critical,
+            ruling_element: None,
+            priority,
+            astro_priority: priority,
+            weight: 100,
+            weight_factor: 1.0,
+            reasoning: String::new(),
+            planetary_influence,
+            element_boost: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_slice_scales_with_priority() {
+        let opts = Opts::parse_from(["scx_horoscope"]);
+        assert_eq!(slice_ns(&opts, &decision(1000, false, 1.0)), 5_000_000);
+        assert_eq!(slice_ns(&opts, &decision(0, false, 1.0)), 950_000);
+        assert_eq!(slice_ns(&opts, &decision(1000, false, -1.0)), 2_500_000);
+        assert_eq!(slice_ns(&opts, &decision(1000, true, -1.0)), 5_000_000);
+    }
+
+    #[test]
+    fn test_slice_trace() {
+        let opts = Opts::parse_from(["scx_horoscope"]);
+
+        // Above 1000 the factor tops out
+        let (slice, trace) = traced_slice_ns(&opts, &decision(1500, false, 1.0));
+        assert_eq!(slice, 5_000_000);
+        assert_eq!(trace.priority_factor, Some((1.5, 1.0)));
+        assert!(trace.to_string().starts_with("priority_factor clamped to 1.0"), "{trace}");
+        assert_eq!((trace.retrograde, trace.bounds), (None, None));
+
+        // Right at 1000 nothing was clamped
+        assert!(traced_slice_ns(&opts, &decision(1000, false, 1.0)).1.is_empty());
+
+        // Too low, and retrograde besides: the floor lifts the penalized slice back up
+        let (slice, trace) = traced_slice_ns(&opts, &decision(50, false, -1.0));
+        assert_eq!(trace.priority_factor, Some((0.05, 0.1)));
+        assert_eq!(trace.retrograde, Some((950_000, 475_000)));
+        assert_eq!(trace.bounds, Some((475_000, slice)));
+        assert_eq!(
+            trace.to_string(),
+            "priority_factor clamped to 0.1 (was 0.050); retrograde penalty 950μs -> 475μs; slice floor 475μs -> 500μs"
+        );
+
+        // A critical pin past the maximum
+        let opts = Opts::parse_from(["scx_horoscope", "--critical-slice-us", "9000", "--slice-us-max", "6000"]);
+        let (slice, trace) = traced_slice_ns(&opts, &decision(1000, true, 1.0));
+        assert_eq!(slice, 6_000_000);
+        assert_eq!(trace.to_string(), "slice ceiling 9000μs -> 6000μs");
+    }
+
+    #[test]
+    fn test_huge_slice_saturates() {
+        let max = u64::MAX.to_string();
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", &max]);
+        assert_eq!(slice_ns(&opts, &decision(1000, false, 1.0)), u64::MAX);
+    }
+
+    #[test]
+    fn test_retrograde_slice_factor() {
+        for (factor, expected) in [("0.1", 500_000), ("0.5", 2_500_000), ("1.0", 5_000_000)] {
+            let opts = Opts::parse_from(["scx_horoscope", "--retrograde-slice-factor", factor]);
+            assert_eq!(slice_ns(&opts, &decision(1000, false, -1.0)), expected, "factor {factor}");
+            assert_eq!(slice_ns(&opts, &decision(1000, false, 1.0)), 5_000_000, "factor {factor}");
+
+            // --no-retrograde wins over any factor
+            let opts = Opts::parse_from(["scx_horoscope", "--retrograde-slice-factor", factor, "--no-retrograde"]);
+            assert_eq!(slice_ns(&opts, &decision(1000, false, -1.0)), 5_000_000, "factor {factor}");
+        }
+
+        assert!((Opts::parse_from(["scx_horoscope"]).retrograde_factor - 0.5).abs() < f64::EPSILON);
+        for factor in ["0.05", "1.5", "0", "-0.5", "NaN", "half"] {
+            assert!(Opts::try_parse_from(["scx_horoscope", "--retrograde-slice-factor", factor]).is_err(), "{factor}");
+        }
+    }
+
+    #[test]
+    fn test_retrograde_exemption() {
+        use crate::astrology::{calculate_planetary_positions, AstrologicalScheduler, FixedEphemeris, Planet, DEFAULT_TASK_WEIGHT};
+        use chrono::{TimeZone, Utc};
+
+        // Saturn (System) and Mars (CPU-Intensive) both retrograde
+        let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let mut positions = calculate_planetary_positions(now).unwrap();
+        for pos in &mut positions {
+            pos.retrograde = matches!(pos.planet, Planet::Saturn | Planet::Mars);
+        }
+        let decide = |opts: &Opts, comm: &str| {
+            let mut astro = AstrologicalScheduler::new(300)
+                .with_aspects(false)
+                .with_retrograde_exemptions(opts.retrograde_exempt.iter().copied())
+                .with_ephemeris(Box::new(FixedEphemeris::new(positions.clone())));
+            astro.schedule_task(comm, 100, DEFAULT_TASK_WEIGHT, now).unwrap()
+        };
+
+        let opts = Opts::parse_from(["scx_horoscope"]);
+        let system = decide(&opts, "systemd");
+        assert!(system.planetary_influence < 0.0);
+
+        let exempt_opts = Opts::parse_from(["scx_horoscope", "--retrograde-exempt", "system"]);
+        let exempt = decide(&exempt_opts, "systemd");
+        assert_eq!(exempt.task_type, TaskType::System);
+        assert!(exempt.planetary_influence > 0.0);
+        assert!(exempt.priority > system.priority);
+        assert!(exempt.reasoning.contains("Saturn retrograde, System exempt"), "{}", exempt.reasoning);
+
+        // System keeps its full slice even from a penalized decision...
+        let retrograde_system = SchedulingDecision { task_type: TaskType::System, ..decision(1000, false, -1.0) };
+        assert_eq!(slice_ns(&exempt_opts, &retrograde_system), 5_000_000);
+        assert_eq!(slice_ns(&opts, &retrograde_system), 2_500_000);
+        assert_eq!(slice_ns(&exempt_opts, &exempt), slice_ns(&opts, &SchedulingDecision { planetary_influence: 1.0, ..exempt.clone() }));
+
+        // ...while CPU-Intensive work under retrograde Mars still pays
+        let cpu = decide(&exempt_opts, "rustc");
+        assert_eq!(cpu.task_type, TaskType::CpuIntensive);
+        assert!(cpu.planetary_influence < 0.0);
+        assert_eq!(slice_ns(&exempt_opts, &cpu), slice_ns(&opts, &decide(&opts, "rustc")));
+        assert!(slice_ns(&exempt_opts, &cpu) < slice_ns(&exempt_opts, &decision(cpu.priority, false, 1.0)));
+
+        assert_eq!(
+            Opts::parse_from(["scx_horoscope", "--retrograde-exempt", "system,network"]).retrograde_exempt,
+            [TaskType::System, TaskType::Network]
+        );
+        assert!(Opts::try_parse_from(["scx_horoscope", "--retrograde-exempt", "astral"]).is_err());
+    }
+
+    #[test]
+    fn test_slice_bounds() {
+        // The retrograde cut never goes below the minimum...
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us-min", "3000"]);
+        assert_eq!(slice_ns(&opts, &decision(1000, false, -1.0)), 3_000_000);
+        assert_eq!(slice_ns(&opts, &decision(0, false, -1.0)), 3_000_000);
+
+        // ...and no priority, however extreme, goes past the maximum
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", "5000", "--slice-us-max", "4000"]);
+        assert!(validate_bounds(&opts).is_err());
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", "5000", "--slice-us-max", "8000"]);
+        assert!(validate_bounds(&opts).is_ok());
+        for priority in [0, 1, 999, 1000, 5000, u32::MAX] {
+            for influence in [-1.0, -0.3, 0.0, 1.0] {
+                let slice = slice_ns(&opts, &decision(priority, false, influence));
+                assert!((500_000..=8_000_000).contains(&slice), "priority {priority}, influence {influence}: {slice}");
+            }
+        }
+        assert_eq!(slice_ns(&opts, &decision(u32::MAX, false, 1.0)), 5_000_000);
+
+        // The default maximum is the default slice
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", "2000"]);
+        assert_eq!(slice_us_max(&opts), 2000);
+        assert_eq!(slice_ns(&opts, &decision(u32::MAX, false, 1.0)), 2_000_000);
+
+        for args in [
+            &["scx_horoscope", "--slice-us-min", "6000"][..],
+            &["scx_horoscope", "--slice-us", "100"][..],
+            &["scx_horoscope", "--slice-us-min", "100", "--slice-us", "200", "--slice-us-max", "150"][..],
+        ] {
+            let err = validate_bounds(&Opts::parse_from(args)).unwrap_err();
+            assert!(err.contains("inconsistent time slices"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_maximum_caps_critical_pins() {
+        // --max-slice-us is the same option, and a critical pin can't get past it either
+        let opts = Opts::parse_from(["scx_horoscope", "--slice-us", "5000", "--critical-slice-us", "50000", "--max-slice-us", "8000"]);
+        assert_eq!(opts.slice_us_max, Some(8000));
+        assert!(validate_bounds(&opts).is_ok());
+        assert_eq!(slice_ns(&opts, &decision(1000, true, 1.0)), 8_000_000);
+        assert_eq!(slice_ns(&opts, &decision(u32::MAX, false, 1.0)), 5_000_000);
+
+        // Both spellings at once is one option given twice
+        assert!(Opts::try_parse_from(["scx_horoscope", "--slice-us-max", "8000", "--max-slice-us", "9000"]).is_err());
+
+        // Without a maximum the default slice caps the pin
+        let opts = Opts::parse_from(["scx_horoscope", "--critical-slice-us", "50000"]);
+        assert_eq!(slice_ns(&opts, &decision(1000, true, 1.0)), 5_000_000);
+
+        // ...and like --slice-us-max it can't sit below the default slice
+        let err = validate_bounds(&Opts::parse_from(["scx_horoscope", "--max-slice-us", "2000"])).unwrap_err();
+        assert!(err.contains("inconsistent time slices"), "{err}");
+    }
+
+    #[test]
+    fn test_critical_slice_override() {
+        for args in [
+            &["scx_horoscope", "--critical-slice-us", "200"][..],
+            &["scx_horoscope", "--critical-slice-us", "200", "--slice-us", "20000", "--slice-us-min", "1000"][..],
+            &["scx_horoscope", "--critical-slice-us", "200", "--no-retrograde"][..],
+        ] {
+            let opts = Opts::parse_from(args);
+            assert_eq!(slice_ns(&opts, &decision(1000, true, 1.0)), 200_000);
+            assert_eq!(slice_ns(&opts, &decision(1000, true, -1.0)), 200_000);
+        }
+
+        // Everyone else still follows the priority formula
+        let opts = Opts::parse_from(["scx_horoscope", "--critical-slice-us", "200"]);
+        assert_eq!(slice_ns(&opts, &decision(1000, false, 1.0)), 5_000_000);
+    }
+
+    #[test]
+    fn test_element_slice_personality() {
+        let ruled_by = |element: Element| SchedulingDecision { ruling_element: Some(element), ..decision(500, false, 1.0) };
+
+        // Off by default: priority alone decides
+        let opts = Opts::parse_from(["scx_horoscope"]);
+        assert_eq!(slice_ns(&opts, &ruled_by(Element::Air)), slice_ns(&opts, &ruled_by(Element::Fire)));
+
+        let opts = Opts::parse_from(["scx_horoscope", "--element-slice", "fire=throughput,air=latency", "--slice-us-max", "10000"]);
+        let fire = slice_ns(&opts, &ruled_by(Element::Fire));
+        let air = slice_ns(&opts, &ruled_by(Element::Air));
+        assert!(air < fire, "air {air}ns, fire {fire}ns");
+        assert_eq!((fire, air), (4_125_000, 1_375_000));
+        assert_eq!(slice_ns(&opts, &ruled_by(Element::Water)), 2_750_000);
+
+        let (_, trace) = traced_slice_ns(&opts, &ruled_by(Element::Air));
+        assert_eq!(trace.to_string(), "Air favors latency 2750μs -> 1375μs");
+
+        assert_eq!(parse_element_slice_entry("Water=Throughput"), Ok((Element::Water, SlicePersonality::Throughput)));
+        for entry in ["fire", "fire=bursty", "plasma=latency"] {
+            assert!(parse_element_slice_entry(entry).is_err(), "{entry}");
+        }
+    }
+}
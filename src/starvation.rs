@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Starvation guard
+//
+// A long Mercury retrograde under a Fire-dominant sky can leave Network tasks
+// with tiny slices at the back of every batch. The guard remembers when each
+// PID was first seen queued since its last dispatch and pulls any task that
+// has waited longer than the threshold to the front of its batch, with at
+// least the minimum slice, whatever the stars decided. Time asleep doesn't
+// count: a dispatch drops the PID, and its wait starts over the next time it
+// is queued. Every such overruling is counted.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Queued PIDs remembered before the guard starts over
+pub const STARVATION_TABLE_SIZE: usize = 4096;
+
+/// Tracks how long each queued task has gone without a dispatch
+#[derive(Debug, Clone)]
+pub struct StarvationGuard {
+    threshold: Duration,
+    queued_since: HashMap<i32, Instant>,
+    forced: u64,
+}
+
+impl StarvationGuard {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold, queued_since: HashMap::new(), forced: 0 }
+    }
+
+    /// Whether `pid` has waited longer than the threshold at `now`
+    ///
+    /// A task's wait starts the first time the guard sees it queued after its
+    /// last dispatch.
+    fn is_starving(&mut self, pid: i32, now: Instant) -> bool {
+        if self.queued_since.len() >= STARVATION_TABLE_SIZE && !self.queued_since.contains_key(&pid) {
+            self.queued_since.clear();
+        }
+        let since = *self.queued_since.entry(pid).or_insert(now);
+        now.saturating_duration_since(since) > self.threshold
+    }
+
+    /// Move starving tasks to the front of `batch`, returning how many were moved
+    ///
+    /// Both groups keep their order: the starving tasks as they were queued,
+    /// the rest as the stars arranged them.
+    pub fn promote<T>(&mut self, batch: &mut Vec<T>, pid: impl Fn(&T) -> i32, now: Instant) -> usize {
+        let (mut starving, rest): (Vec<T>, Vec<T>) = batch.drain(..).partition(|item| self.is_starving(pid(item), now));
+        let forced = starving.len();
+        self.forced += forced as u64;
+        starving.extend(rest);
+        *batch = starving;
+        forced
+    }
+
+    /// End the wait of a task just dispatched
+    pub fn note_dispatch(&mut self, pid: i32) {
+        self.queued_since.remove(&pid);
+    }
+
+    /// Drop a task that has exited
+    pub fn forget(&mut self, pid: i32) {
+        self.queued_since.remove(&pid);
+    }
+
+    /// Dispatches forced to the front since startup
+    pub fn forced(&self) -> u64 {
+        self.forced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starving_task_goes_first() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut guard = StarvationGuard::new(Duration::from_millis(100));
+
+        // PID 3 is queued at 10ms, then keeps losing to 1 and 2 while they
+        // are dispatched every 10ms and it never is
+        for ms in (10..=110).step_by(10) {
+            let mut batch = vec![1, 2, 3];
+            assert_eq!(guard.promote(&mut batch, |&pid| pid, at(ms)), 0);
+            assert_eq!(batch, [1, 2, 3]);
+            guard.note_dispatch(1);
+            guard.note_dispatch(2);
+        }
+
+        // Past the threshold it is pulled ahead, once
+        let mut batch = vec![1, 2, 3];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(120)), 1);
+        assert_eq!(batch, [3, 1, 2]);
+        assert_eq!(guard.forced(), 1);
+        for pid in batch {
+            guard.note_dispatch(pid);
+        }
+        let mut batch = vec![1, 2, 3];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(130)), 0);
+        assert_eq!(batch, [1, 2, 3]);
+        assert_eq!(guard.forced(), 1);
+    }
+
+    #[test]
+    fn test_wait_starts_when_first_seen() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut guard = StarvationGuard::new(Duration::from_millis(100));
+
+        // A new PID isn't starving yet; several starving ones keep their order
+        let mut batch = vec![5, 4, 6];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(0)), 0);
+        guard.note_dispatch(6);
+        let mut batch = vec![6, 5, 7, 4];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(150)), 2);
+        assert_eq!(batch, [5, 4, 6, 7]);
+
+        // A forgotten PID starts over
+        guard.forget(5);
+        let mut batch = vec![4, 5];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(200)), 1);
+        assert_eq!(batch, [4, 5]);
+        assert_eq!(guard.forced(), 3);
+    }
+
+    #[test]
+    fn test_sleeping_task_is_not_starving() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut guard = StarvationGuard::new(Duration::from_millis(100));
+
+        // Dispatched, then asleep for far longer than the threshold
+        let mut batch = vec![8];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(0)), 0);
+        guard.note_dispatch(8);
+
+        // Queued again: its wait starts now, not at its last dispatch
+        let mut batch = vec![8];
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(5_000)), 0);
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(5_050)), 0);
+        assert_eq!(guard.promote(&mut batch, |&pid| pid, at(5_101)), 1);
+        assert_eq!(guard.forced(), 1);
+    }
+
+    #[test]
+    fn test_table_is_bounded() {
+        let now = Instant::now();
+        let mut guard = StarvationGuard::new(Duration::from_millis(100));
+        let mut batch: Vec<i32> = (0..=STARVATION_TABLE_SIZE as i32).collect();
+        guard.promote(&mut batch, |&pid| pid, now);
+        assert!(guard.queued_since.len() <= STARVATION_TABLE_SIZE);
+    }
+}
@@ -0,0 +1,376 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Scheduler statistics
+//
+// Running aggregates gathered on the dispatch path: how deep the queue runs,
+// how long the stars take to make up their mind, and where each task type's
+// priorities land. They feed the auto-tuner. With --stats, a one-line
+// summary is also printed on its own clock, whatever the log level.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::astrology::TaskType;
+
+/// Queued tasks per CPU that count as a busy queue
+pub const HIGH_QUEUE_PER_CPU: u64 = 2;
+
+/// Priorities per histogram bucket
+const PRIORITY_BUCKET_WIDTH: u32 = 10;
+
+/// Priority buckets; everything from 1000 up shares the last one
+const PRIORITY_BUCKETS: usize = 101;
+
+/// Power-of-two microsecond buckets for decision latency
+const LATENCY_BUCKETS: usize = 32;
+
+/// Distribution of priorities handed to one task type
+#[derive(Debug, Clone)]
+pub struct PriorityHistogram {
+    buckets: [u64; PRIORITY_BUCKETS],
+    count: u64,
+}
+
+impl PriorityHistogram {
+    pub fn new() -> Self {
+        Self { buckets: [0; PRIORITY_BUCKETS], count: 0 }
+    }
+
+    pub fn record(&mut self, priority: u32) {
+        let bucket = ((priority / PRIORITY_BUCKET_WIDTH) as usize).min(PRIORITY_BUCKETS - 1);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Lower edge of the bucket holding the given percentile (0-100)
+    pub fn percentile(&self, percentile: f64) -> Option<u32> {
+        let bucket = percentile_bucket(&self.buckets, self.count, percentile)?;
+        #[allow(clippy::cast_possible_truncation)]
+        Some(bucket as u32 * PRIORITY_BUCKET_WIDTH)
+    }
+}
+
+impl Default for PriorityHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn percentile_bucket(buckets: &[u64], count: u64, percentile: f64) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+    let rank = ((count as f64 * percentile.clamp(0.0, 100.0) / 100.0).ceil() as u64).max(1);
+    let mut seen = 0;
+    buckets.iter().position(|&n| {
+        seen += n;
+        seen >= rank
+    })
+}
+
+/// Everything the dispatch loop has seen since the last reset
+#[derive(Debug, Clone)]
+pub struct SchedulerStats {
+    high_queue_depth: u64,
+    queue_samples: u64,
+    queue_depth_sum: u64,
+    busy_queue_samples: u64,
+    latency_buckets: [u64; LATENCY_BUCKETS],
+    decisions: u64,
+    retrograde_decisions: u64,
+    priorities: HashMap<TaskType, PriorityHistogram>,
+}
+
+impl SchedulerStats {
+    pub fn new(nr_cpus: usize) -> Self {
+        Self {
+            high_queue_depth: (nr_cpus.max(1) as u64) * HIGH_QUEUE_PER_CPU,
+            queue_samples: 0,
+            queue_depth_sum: 0,
+            busy_queue_samples: 0,
+            latency_buckets: [0; LATENCY_BUCKETS],
+            decisions: 0,
+            retrograde_decisions: 0,
+            priorities: HashMap::new(),
+        }
+    }
+
+    /// Sample the number of tasks waiting in the queue
+    pub fn record_queue_depth(&mut self, nr_queued: u64) {
+        self.queue_samples += 1;
+        self.queue_depth_sum += nr_queued;
+        if nr_queued >= self.high_queue_depth {
+            self.busy_queue_samples += 1;
+        }
+    }
+
+    /// Record one scheduling decision and how long it took
+    pub fn record_decision(&mut self, task_type: TaskType, priority: u32, retrograde: bool, latency: Duration) {
+        self.decisions += 1;
+        if retrograde {
+            self.retrograde_decisions += 1;
+        }
+        self.priorities.entry(task_type).or_default().record(priority);
+
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.latency_buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+
+    pub fn queue_samples(&self) -> u64 {
+        self.queue_samples
+    }
+
+    pub fn average_queue_depth(&self) -> f64 {
+        if self.queue_samples == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let average = self.queue_depth_sum as f64 / self.queue_samples as f64;
+        average
+    }
+
+    /// Fraction of queue samples at or above the busy threshold
+    pub fn busy_queue_fraction(&self) -> f64 {
+        ratio(self.busy_queue_samples, self.queue_samples)
+    }
+
+    pub fn high_queue_depth(&self) -> u64 {
+        self.high_queue_depth
+    }
+
+    pub fn decisions(&self) -> u64 {
+        self.decisions
+    }
+
+    /// Fraction of decisions made under a retrograde ruler
+    pub fn retrograde_fraction(&self) -> f64 {
+        ratio(self.retrograde_decisions, self.decisions)
+    }
+
+    /// Upper bound of the decision latency at the given percentile (0-100)
+    pub fn decision_latency(&self, percentile: f64) -> Option<Duration> {
+        let bucket = percentile_bucket(&self.latency_buckets, self.decisions, percentile)?;
+        Some(Duration::from_micros(1 << bucket))
+    }
+
+    pub fn priorities(&self, task_type: TaskType) -> Option<&PriorityHistogram> {
+        self.priorities.get(&task_type)
+    }
+
+    /// Start a fresh observation window
+    pub fn reset(&mut self) {
+        *self = Self { high_queue_depth: self.high_queue_depth, ..Self::new(1) };
+    }
+}
+
+/// Paces the stats output on a millisecond clock
+pub struct StatsTicker {
+    interval: Duration,
+    next: Instant,
+}
+
+impl StatsTicker {
+    pub fn new(interval: Duration, start: Instant) -> Self {
+        Self { interval, next: start + interval }
+    }
+
+    /// True once per interval; a loop that fell behind skips the missed ticks
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next += self.interval;
+        if self.next <= now {
+            self.next = now + self.interval;
+        }
+        true
+    }
+}
+
+/// What the --stats line reports, as it stood at one moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeriodicSnapshot {
+    pub user_dispatches: u64,
+    pub kernel_dispatches: u64,
+    pub queued: u64,
+    pub scheduled: u64,
+    pub chart_epoch: Option<DateTime<Utc>>,  // When the chart in use was cast
+    pub degraded: bool,                      // On the fast path while the queue is backed up
+}
+
+impl PeriodicSnapshot {
+    pub fn dispatches(&self) -> u64 {
+        self.user_dispatches + self.kernel_dispatches
+    }
+
+    /// The stats line, with `rate` dispatches per second since the last one
+    pub fn line(&self, rate: f64) -> String {
+        let chart = self
+            .chart_epoch
+            .map_or_else(|| "not cast yet".to_string(), |epoch| epoch.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+        format!(
+            "📊 {rate:.1} dispatches/s | user={} kernel={} | queued={} scheduled={} | chart {chart} | degraded: {}",
+            self.user_dispatches,
+            self.kernel_dispatches,
+            self.queued,
+            self.scheduled,
+            if self.degraded { "yes" } else { "no" }
+        )
+    }
+}
+
+/// Paces the --stats line and works out the dispatch rate between lines
+pub struct PeriodicStats {
+    ticker: StatsTicker,
+    last: (Instant, u64),  // When the last line was due (or the clock started), and the dispatches then
+}
+
+impl PeriodicStats {
+    pub fn new(interval: Duration, start: Instant) -> Self {
+        Self { ticker: StatsTicker::new(interval, start), last: (start, 0) }
+    }
+
+    /// The stats line, if one is due at `now`; `snapshot` is only taken when it is
+    pub fn poll(&mut self, now: Instant, snapshot: impl FnOnce() -> PeriodicSnapshot) -> Option<String> {
+        if !self.ticker.tick(now) {
+            return None;
+        }
+        let snapshot = snapshot();
+        let (since, dispatched) = self.last;
+        let elapsed = now.saturating_duration_since(since).as_secs_f64();
+        #[allow(clippy::cast_precision_loss)]
+        let rate = if elapsed > 0.0 { snapshot.dispatches().saturating_sub(dispatched) as f64 / elapsed } else { 0.0 };
+        self.last = (now, snapshot.dispatches());
+        Some(snapshot.line(rate))
+    }
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let ratio = part as f64 / whole as f64;
+    ratio
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_percentiles() {
+        let mut histogram = PriorityHistogram::new();
+        assert_eq!(histogram.percentile(99.0), None);
+
+        for _ in 0..98 {
+            histogram.record(55);
+        }
+        histogram.record(500);
+        histogram.record(4000);
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile(50.0), Some(50));
+        assert_eq!(histogram.percentile(99.0), Some(500));
+        assert_eq!(histogram.percentile(100.0), Some(1000));
+    }
+
+    #[test]
+    fn test_queue_and_decision_aggregates() {
+        let mut stats = SchedulerStats::new(4);
+        assert_eq!(stats.high_queue_depth(), 8);
+
+        for depth in [0, 4, 8, 20] {
+            stats.record_queue_depth(depth);
+        }
+        assert!((stats.average_queue_depth() - 8.0).abs() < f64::EPSILON);
+        assert!((stats.busy_queue_fraction() - 0.5).abs() < f64::EPSILON);
+
+        stats.record_decision(TaskType::CpuIntensive, 300, true, Duration::from_micros(3));
+        stats.record_decision(TaskType::CpuIntensive, 300, false, Duration::from_micros(3));
+        stats.record_decision(TaskType::Network, 120, false, Duration::from_micros(100));
+        assert!((stats.retrograde_fraction() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.decision_latency(50.0), Some(Duration::from_micros(4)));
+        assert_eq!(stats.decision_latency(100.0), Some(Duration::from_micros(128)));
+        assert_eq!(stats.priorities(TaskType::CpuIntensive).map(PriorityHistogram::count), Some(2));
+
+        stats.reset();
+        assert_eq!(stats.decisions(), 0);
+        assert_eq!(stats.queue_samples(), 0);
+        assert_eq!(stats.high_queue_depth(), 8);
+    }
+
+    #[test]
+    fn test_stats_ticker_cadence() {
+        let start = Instant::now();
+        let mut ticker = StatsTicker::new(Duration::from_millis(250), start);
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // A dispatch loop spinning every 10ms for one second ticks 4 times
+        let ticks: Vec<u64> = (1..=100).map(|i| i * 10).filter(|&ms| ticker.tick(at(ms))).collect();
+        assert_eq!(ticks, [250, 500, 750, 1000]);
+
+        // Late iterations keep the cadence instead of firing in a burst
+        assert!(ticker.tick(at(1900)));
+        assert!(!ticker.tick(at(1950)));
+        assert!(ticker.tick(at(2150)));
+    }
+
+    fn snapshot(user_dispatches: u64, kernel_dispatches: u64) -> PeriodicSnapshot {
+        PeriodicSnapshot {
+            user_dispatches,
+            kernel_dispatches,
+            queued: 3,
+            scheduled: 7,
+            chart_epoch: None,
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn test_periodic_stats_interval_and_rate() {
+        let start = Instant::now();
+        let mut periodic = PeriodicStats::new(Duration::from_secs(2), start);
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // Not due yet: no line, and no snapshot taken
+        assert_eq!(periodic.poll(at(1500), || unreachable!("snapshot taken before the line was due")), None);
+
+        // 1000 dispatches in the first two seconds, 3000 more in the next two (a little late)
+        let first = periodic.poll(at(2000), || snapshot(800, 200)).unwrap();
+        assert!(first.starts_with("📊 500.0 dispatches/s"), "{first}");
+        assert_eq!(periodic.poll(at(3000), || snapshot(0, 0)), None);
+        let second = periodic.poll(at(4000), || snapshot(3000, 1000)).unwrap();
+        assert!(second.starts_with("📊 1500.0 dispatches/s"), "{second}");
+
+        // Counters that went backwards count as no dispatches, not a wrap
+        let third = periodic.poll(at(6000), || snapshot(10, 0)).unwrap();
+        assert!(third.starts_with("📊 0.0 dispatches/s"), "{third}");
+    }
+
+    #[test]
+    fn test_stats_line() {
+        use chrono::TimeZone;
+        assert_eq!(
+            snapshot(12, 34).line(2.26),
+            "📊 2.3 dispatches/s | user=12 kernel=34 | queued=3 scheduled=7 | chart not cast yet | degraded: no"
+        );
+        let cast = PeriodicSnapshot {
+            chart_epoch: Some(Utc.with_ymd_and_hms(2025, 11, 19, 22, 7, 46).unwrap()),
+            degraded: true,
+            ..snapshot(1, 2)
+        };
+        assert_eq!(
+            cast.line(1234.56),
+            "📊 1234.6 dispatches/s | user=1 kernel=2 | queued=3 scheduled=7 | chart 2025-11-19 22:07:46 UTC | degraded: yes"
+        );
+    }
+}
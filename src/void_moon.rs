@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Void-of-course Moon
+//
+// Between its last aspect in a sign and its ingress into the next, the Moon
+// is void of course, and tradition says nothing begun then comes to much.
+// With --void-moon-penalty, a task dispatched for the first time while the
+// Moon is void has its slice cut by that factor. Tasks that were already
+// running when the Moon went void carry on as before: the warning is about
+// new undertakings.
+
+use std::collections::HashSet;
+
+/// Shortens the first slice of tasks started under a void Moon
+#[derive(Debug, Clone)]
+pub struct VoidMoonPenalty {
+    factor: f64,
+    seen: HashSet<i32>,  // Every PID dispatched so far
+    penalized: u64,
+}
+
+impl VoidMoonPenalty {
+    pub fn new(factor: f64) -> Self {
+        Self { factor, seen: HashSet::new(), penalized: 0 }
+    }
+
+    /// Note a dispatch of `pid`, returning its slice after any penalty
+    ///
+    /// The slice never drops below `min_ns`.
+    pub fn apply(&mut self, pid: i32, slice_ns: u64, min_ns: u64, moon_void: bool) -> u64 {
+        let first_seen = self.seen.insert(pid);
+        if !(first_seen && moon_void) {
+            return slice_ns;
+        }
+        self.penalized += 1;
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let penalized = (slice_ns as f64 * self.factor) as u64;
+        penalized.max(min_ns).min(slice_ns)
+    }
+
+    /// Drop a task that has exited
+    pub fn forget(&mut self, pid: i32) {
+        self.seen.remove(&pid);
+    }
+
+    /// Tasks whose first slice was cut since startup
+    pub fn penalized(&self) -> u64 {
+        self.penalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_new_tasks_are_penalized() {
+        let mut penalty = VoidMoonPenalty::new(0.5);
+
+        // Seen before the Moon went void, then again while it is
+        assert_eq!(penalty.apply(1, 4_000_000, 0, false), 4_000_000);
+        assert_eq!(penalty.apply(1, 4_000_000, 0, true), 4_000_000);
+
+        // New under the void Moon: cut once, then left alone
+        assert_eq!(penalty.apply(2, 4_000_000, 0, true), 2_000_000);
+        assert_eq!(penalty.apply(2, 4_000_000, 0, true), 4_000_000);
+        assert_eq!(penalty.penalized(), 1);
+
+        // Never below the minimum slice
+        assert_eq!(penalty.apply(3, 4_000_000, 3_000_000, true), 3_000_000);
+
+        // A PID that exited and came back is new again
+        penalty.forget(2);
+        assert_eq!(penalty.apply(2, 4_000_000, 0, true), 2_000_000);
+    }
+}
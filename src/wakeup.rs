@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Wakeup pair compatibility boost, and wakeup chains
+//
+// The BPF side doesn't tell us who woke whom, so a task dequeued shortly
+// after another one is treated as its wakee. Tasks are fed in in dequeue
+// order, before their batch is sorted. When the two tasks' ruling
+// planets sit in compatible elements, the wakee gets a short-lived priority
+// boost so producer/consumer pipelines keep flowing.
+//
+// Interactive work rarely finishes in the task that was classified as such:
+// a keystroke wakes the terminal, which wakes the shell, which wakes the
+// pipeline it runs. Followed the same way, such a chain lends its first hops
+// a boost that halves with each hop, up to a hop limit, and never lifts a
+// task past the priority of Critical ones.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::astrology::Element;
+
+/// Counters describing how often the pipes were allowed to flow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WakeupStats {
+    pub pairs_seen: u64,
+    pub compatible_pairs: u64,
+    pub boosts_applied: u64,
+    pub evictions: u64,
+}
+
+/// Highest priority a wakeup chain can lift a task to, just short of Critical
+pub const CHAIN_PRIORITY_CAP: u32 = 999;
+
+/// Recent wakeups, bounded in size and expiring after a TTL
+pub struct WakeupTable {
+    boost: f64,
+    ttl: Duration,
+    capacity: usize,
+    last_dispatch: Option<(i32, Element, Instant)>,
+    boosted: HashMap<i32, Instant>, // wakee PID -> boost expiry
+    stats: WakeupStats,
+}
+
+impl WakeupTable {
+    pub fn new(boost: f64, ttl: Duration, capacity: usize) -> Self {
+        Self {
+            boost,
+            ttl,
+            capacity: capacity.max(1),
+            last_dispatch: None,
+            boosted: HashMap::new(),
+            stats: WakeupStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> WakeupStats {
+        self.stats
+    }
+
+    /// Record a dispatch and return the priority multiplier for this task
+    pub fn on_dispatch(&mut self, pid: i32, element: Element, now: Instant) -> f64 {
+        if let Some((waker_pid, waker_element, at)) = self.last_dispatch {
+            if waker_pid != pid && now.duration_since(at) <= self.ttl {
+                self.stats.pairs_seen += 1;
+                if waker_element.is_compatible(element) {
+                    self.stats.compatible_pairs += 1;
+                    self.grant(pid, now);
+                }
+            }
+        }
+        self.last_dispatch = Some((pid, element, now));
+
+        match self.boosted.get(&pid) {
+            Some(&expiry) if now < expiry => {
+                self.stats.boosts_applied += 1;
+                self.boost
+            }
+            Some(_) => {
+                self.boosted.remove(&pid);
+                1.0
+            }
+            None => 1.0,
+        }
+    }
+
+    fn grant(&mut self, pid: i32, now: Instant) {
+        if self.boosted.len() >= self.capacity && !self.boosted.contains_key(&pid) {
+            // Drop whatever has expired, then the entry closest to expiry if still full
+            self.boosted.retain(|_, &mut e| e > now);
+            if self.boosted.len() >= self.capacity {
+                if let Some(victim) = self.boosted.iter().min_by_key(|(_, &e)| e).map(|(&p, _)| p) {
+                    self.boosted.remove(&victim);
+                }
+            }
+            self.stats.evictions += 1;
+        }
+        self.boosted.insert(pid, now + self.ttl);
+    }
+}
+
+/// Counters describing how far interactive work was followed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChainStats {
+    pub boosted: u64,     // Dispatches boosted for following interactive work
+    pub hop_capped: u64,  // Dispatches that followed a chain already at its hop limit
+}
+
+/// Tasks dispatched in a run after an Interactive one, each taken as woken by the one before
+pub struct WakeupChain {
+    boost: f64,
+    window: Duration,
+    max_hops: u32,
+    last_dispatch: Option<(i32, u32, Instant)>,  // PID, hops from the Interactive task, when
+    stats: ChainStats,
+}
+
+impl WakeupChain {
+    pub fn new(boost: f64, window: Duration, max_hops: u32) -> Self {
+        Self { boost, window, max_hops, last_dispatch: None, stats: ChainStats::default() }
+    }
+
+    pub fn stats(&self) -> ChainStats {
+        self.stats
+    }
+
+    /// Priority multiplier `hop` hops down a chain: the full boost first, then half as much extra each hop
+    pub fn boost_at(&self, hop: u32) -> f64 {
+        let decay = 0.5_f64.powi(i32::try_from(hop.saturating_sub(1)).unwrap_or(i32::MAX));
+        1.0 + (self.boost - 1.0) * decay
+    }
+
+    /// Record a dispatch and return the priority multiplier for this task
+    ///
+    /// The same task dispatched again straight away keeps its place in the chain.
+    pub fn on_dispatch(&mut self, pid: i32, interactive: bool, now: Instant) -> f64 {
+        if interactive {
+            self.last_dispatch = Some((pid, 0, now));
+            return 1.0;
+        }
+        let hop = match self.last_dispatch {
+            Some((waker, hops, at)) if now.saturating_duration_since(at) <= self.window => {
+                if waker == pid { hops } else { hops + 1 }
+            }
+            _ => 0,
+        };
+        if hop == 0 {
+            self.last_dispatch = None;
+            return 1.0;
+        }
+        if hop > self.max_hops {
+            self.stats.hop_capped += 1;
+            self.last_dispatch = None;
+            return 1.0;
+        }
+        self.last_dispatch = Some((pid, hop, now));
+        self.stats.boosted += 1;
+        self.boost_at(hop)
+    }
+}
+
+/// Priority after a wakeup chain boost of `factor`: never past `CHAIN_PRIORITY_CAP`, never lowered
+pub fn chain_boosted_priority(priority: u32, factor: f64) -> u32 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let boosted = (f64::from(priority) * factor) as u32;
+    boosted.min(CHAIN_PRIORITY_CAP).max(priority).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn test_compatible_pair_boosted_within_ttl() {
+        let mut table = WakeupTable::new(1.2, TTL, 16);
+        let t0 = Instant::now();
+
+        // Fire waker, Air wakee: the pipe flows
+        assert!((table.on_dispatch(100, Element::Fire, t0) - 1.0).abs() < f64::EPSILON);
+        let boosted = table.on_dispatch(200, Element::Air, t0 + Duration::from_millis(5));
+        assert!((boosted - 1.2).abs() < f64::EPSILON);
+
+        // The wakee keeps its boost when it comes back inside the TTL
+        let again = table.on_dispatch(200, Element::Air, t0 + Duration::from_millis(30));
+        assert!((again - 1.2).abs() < f64::EPSILON);
+
+        // ...but not after it expires
+        let expired = table.on_dispatch(200, Element::Air, t0 + Duration::from_millis(200));
+        assert!((expired - 1.0).abs() < f64::EPSILON);
+
+        let stats = table.stats();
+        assert_eq!(stats.compatible_pairs, 1);
+        assert_eq!(stats.boosts_applied, 2);
+    }
+
+    #[test]
+    fn test_incompatible_or_late_pairs_not_boosted() {
+        let mut table = WakeupTable::new(1.2, TTL, 16);
+        let t0 = Instant::now();
+
+        // Fire opposes Water
+        table.on_dispatch(100, Element::Fire, t0);
+        let clash = table.on_dispatch(200, Element::Water, t0 + Duration::from_millis(5));
+        assert!((clash - 1.0).abs() < f64::EPSILON);
+
+        // Compatible, but too long after the waker
+        let late = table.on_dispatch(300, Element::Earth, t0 + Duration::from_millis(100));
+        assert!((late - 1.0).abs() < f64::EPSILON);
+
+        let stats = table.stats();
+        assert_eq!(stats.pairs_seen, 1);
+        assert_eq!(stats.compatible_pairs, 0);
+        assert_eq!(stats.boosts_applied, 0);
+    }
+
+    #[test]
+    fn test_table_is_bounded() {
+        let mut table = WakeupTable::new(1.2, TTL, 4);
+        let t0 = Instant::now();
+
+        for pid in 0..20 {
+            table.on_dispatch(pid, Element::Fire, t0);
+        }
+
+        assert!(table.boosted.len() <= 4);
+        assert!(table.stats().evictions > 0);
+    }
+
+    #[test]
+    fn test_chain_decays_and_stops_at_the_hop_limit() {
+        let mut chain = WakeupChain::new(1.5, Duration::from_millis(10), 2);
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        // Terminal, shell, child, grandchild: the first two hops are boosted, less each time
+        assert!((chain.on_dispatch(100, true, t0) - 1.0).abs() < f64::EPSILON);
+        assert!((chain.on_dispatch(200, false, t0 + ms(1)) - 1.5).abs() < f64::EPSILON);
+        assert!((chain.on_dispatch(300, false, t0 + ms(2)) - 1.25).abs() < f64::EPSILON);
+        assert!((chain.on_dispatch(400, false, t0 + ms(3)) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(chain.stats(), ChainStats { boosted: 2, hop_capped: 1 });
+
+        // Past the hop limit the chain is over, even for whatever comes right after
+        assert!((chain.on_dispatch(500, false, t0 + ms(4)) - 1.0).abs() < f64::EPSILON);
+
+        // A new chain; the shell is dispatched twice and keeps its hop
+        chain.on_dispatch(100, true, t0 + ms(20));
+        assert!((chain.on_dispatch(200, false, t0 + ms(21)) - 1.5).abs() < f64::EPSILON);
+        assert!((chain.on_dispatch(200, false, t0 + ms(22)) - 1.5).abs() < f64::EPSILON);
+        // Outside the window, the child wasn't woken by the shell
+        assert!((chain.on_dispatch(300, false, t0 + ms(40)) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(chain.stats().boosted, 4);
+
+        // With a longer limit the boost keeps halving
+        let long = WakeupChain::new(1.8, Duration::from_millis(10), 4);
+        assert!((long.boost_at(3) - 1.2).abs() < 1e-9);
+        assert!((long.boost_at(4) - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chain_boost_never_passes_critical() {
+        assert_eq!(chain_boosted_priority(400, 1.5), 600);
+        assert_eq!(chain_boosted_priority(900, 1.5), CHAIN_PRIORITY_CAP);
+        // Already past the cap: left where it was
+        assert_eq!(chain_boosted_priority(1200, 1.5), 1200);
+        assert_eq!(chain_boosted_priority(0, 1.5), 1);
+    }
+}
@@ -0,0 +1,322 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Dispatch loop watchdog
+//
+// If the BPF ring buffer wedges, or a chart takes forever, `dispatch_tasks`
+// stops coming back and tasks pile up silently. The dispatch loop beats a
+// heartbeat as each round begins; a separate thread watches it, along with the
+// count of tasks the BPF side has queued, and raises the alarm when the beats
+// stop while that count keeps growing. An idle machine queues nothing, so a
+// quiet loop alone is no stall. The warnings grow louder with every further
+// timeout, and past --watchdog-deadline the process exits: sched_ext then
+// hands every task back to the kernel's default scheduler rather than leave
+// the machine hanging.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+
+/// Exit status when the watchdog gives up on a stalled dispatch loop
+pub const WATCHDOG_EXIT_STATUS: i32 = 3;
+
+/// Reads how many tasks the BPF side has queued, from the watchdog thread
+pub type QueueDepth = Box<dyn Fn() -> u64 + Send>;
+
+/// A counter in the BPF program's memory-mapped .bss, read from another thread
+pub struct SharedCounter(*const u64);
+
+// SAFETY: the counter is only ever read, volatile, and whoever builds one keeps it alive for as long as it is read
+unsafe impl Send for SharedCounter {}
+
+impl SharedCounter {
+    /// # Safety
+    ///
+    /// `counter` must stay valid for as long as this is read.
+    pub unsafe fn new(counter: *const u64) -> Self {
+        Self(counter)
+    }
+
+    pub fn read(&self) -> u64 {
+        // SAFETY: valid by the contract of `new`; the BPF side updates it concurrently, hence volatile
+        unsafe { std::ptr::read_volatile(self.0) }
+    }
+}
+
+/// Check that a stall is noticed before the deadline to exit over it
+pub fn validate_deadline(timeout_secs: Option<u64>, deadline_secs: Option<u64>) -> Result<(), String> {
+    match (timeout_secs, deadline_secs) {
+        (Some(timeout), Some(deadline)) if deadline <= timeout => {
+            Err(format!("--watchdog-deadline ({deadline}) must be longer than --watchdog-timeout ({timeout})"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Timestamp of the start of the last dispatch iteration, shared across threads
+#[derive(Clone)]
+pub struct Heartbeat {
+    start: Instant,
+    last_beat_ms: Arc<AtomicU64>,  // Milliseconds since `start`
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self { start: Instant::now(), last_beat_ms: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Record progress, as a dispatch round begins
+    pub fn beat(&self) {
+        #[allow(clippy::cast_possible_truncation)]
+        let ms = self.start.elapsed().as_millis() as u64;
+        self.last_beat_ms.store(ms, Ordering::Relaxed);
+    }
+
+    pub fn last_beat(&self) -> Instant {
+        self.start + Duration::from_millis(self.last_beat_ms.load(Ordering::Relaxed))
+    }
+}
+
+/// A change in the dispatch loop's health, with how long it has been silent and how many tasks wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogEvent {
+    Stalled { silence: Duration, queued: u64 },
+    StillStalled { silence: Duration, queued: u64 },  // Once more per further timeout
+    Deadline { silence: Duration, queued: u64 },
+    Recovered,
+}
+
+/// Decides when silence becomes a stall, and when a stall has gone on too long
+pub struct StallDetector {
+    timeout: Duration,
+    deadline: Option<Duration>,
+    last_beat: Option<Instant>,
+    queued_at_beat: u64,  // Queue depth first seen after the last beat
+    timeouts: u32,        // Timeouts the current stall has lasted, as last reported
+    trips: u64,
+}
+
+impl StallDetector {
+    pub fn new(timeout: Duration, deadline: Option<Duration>) -> Self {
+        Self { timeout, deadline, last_beat: None, queued_at_beat: 0, timeouts: 0, trips: 0 }
+    }
+
+    /// Look at the loop's last beat and the queue depth at `now`
+    pub fn check(&mut self, last_beat: Instant, now: Instant, queued: u64) -> Option<WatchdogEvent> {
+        if self.last_beat != Some(last_beat) {
+            self.last_beat = Some(last_beat);
+            self.queued_at_beat = queued;
+            if self.timeouts > 0 {
+                self.timeouts = 0;
+                return Some(WatchdogEvent::Recovered);
+            }
+            return None;
+        }
+
+        let silence = now.saturating_duration_since(last_beat);
+        if silence < self.timeout || queued <= self.queued_at_beat {
+            return None;
+        }
+        if self.deadline.is_some_and(|deadline| silence >= deadline) {
+            return Some(WatchdogEvent::Deadline { silence, queued });
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let timeouts = (silence.as_millis() / self.timeout.as_millis().max(1)) as u32;
+        if timeouts <= self.timeouts {
+            return None;
+        }
+        let first = self.timeouts == 0;
+        self.timeouts = timeouts;
+        if first {
+            self.trips += 1;
+            Some(WatchdogEvent::Stalled { silence, queued })
+        } else {
+            Some(WatchdogEvent::StillStalled { silence, queued })
+        }
+    }
+
+    /// Stalls detected so far
+    pub fn trips(&self) -> u64 {
+        self.trips
+    }
+}
+
+/// Background thread watching a heartbeat
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+    restart: Arc<AtomicBool>,
+    trips: Arc<AtomicU64>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Start watching; with `restart_on_stall`, a stall also requests a scheduler restart
+    ///
+    /// Past `deadline` of silence with tasks still piling up, the process exits.
+    pub fn spawn(
+        heartbeat: Heartbeat,
+        queue: QueueDepth,
+        timeout: Duration,
+        deadline: Option<Duration>,
+        restart_on_stall: bool,
+    ) -> std::io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let restart = Arc::new(AtomicBool::new(false));
+        let trips = Arc::new(AtomicU64::new(0));
+        let poll = (timeout / 4).max(Duration::from_millis(10));
+
+        let handle = thread::Builder::new().name("horoscope-watchdog".to_string()).spawn({
+            let stop = Arc::clone(&stop);
+            let restart = Arc::clone(&restart);
+            let trips = Arc::clone(&trips);
+            move || {
+                let mut detector = StallDetector::new(timeout, deadline);
+                while !stop.load(Ordering::Relaxed) {
+                    thread::park_timeout(poll);
+                    match detector.check(heartbeat.last_beat(), Instant::now(), queue()) {
+                        Some(WatchdogEvent::Stalled { silence, queued }) => {
+                            trips.store(detector.trips(), Ordering::Relaxed);
+                            warn!(
+                                "🚨 WATCHDOG: no dispatch progress for {:.1}s with {queued} tasks queued - the BPF ring buffer may be wedged!",
+                                silence.as_secs_f64()
+                            );
+                            if restart_on_stall {
+                                warn!("🚨 WATCHDOG: requesting a scheduler restart");
+                                restart.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        Some(WatchdogEvent::StillStalled { silence, queued }) => {
+                            error!("🚨 WATCHDOG: still stalled after {:.1}s, {queued} tasks queued", silence.as_secs_f64());
+                        }
+                        Some(WatchdogEvent::Deadline { silence, queued }) => {
+                            error!(
+                                "🚨 WATCHDOG: stalled for {:.1}s with {queued} tasks queued ({} stalls this run) - exiting so the default scheduler takes over",
+                                silence.as_secs_f64(),
+                                detector.trips()
+                            );
+                            std::process::exit(WATCHDOG_EXIT_STATUS);
+                        }
+                        Some(WatchdogEvent::Recovered) => info!("Watchdog: dispatch loop is making progress again"),
+                        None => {}
+                    }
+                }
+            }
+        })?;
+
+        Ok(Self { stop, restart, trips, handle: Some(handle) })
+    }
+
+    /// Stalls detected so far
+    pub fn trips(&self) -> u64 {
+        self.trips.load(Ordering::Relaxed)
+    }
+
+    /// True once a stall has asked for the scheduler to be restarted
+    pub fn restart_requested(&self) -> bool {
+        self.restart.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_detection() {
+        let timeout = Duration::from_secs(5);
+        let mut detector = StallDetector::new(timeout, None);
+        let beat = Instant::now();
+        let at = |secs: u64| beat + Duration::from_secs(secs);
+
+        assert_eq!(detector.check(beat, at(1), 3), None);
+        assert_eq!(detector.check(beat, at(6), 9), Some(WatchdogEvent::Stalled { silence: Duration::from_secs(6), queued: 9 }));
+        // Reported once per timeout, not on every check
+        assert_eq!(detector.check(beat, at(9), 12), None);
+        assert_eq!(detector.check(beat, at(11), 15), Some(WatchdogEvent::StillStalled { silence: Duration::from_secs(11), queued: 15 }));
+        assert_eq!(detector.check(beat, at(12), 15), None);
+        assert_eq!(detector.trips(), 1);
+
+        let beat = at(20);
+        assert_eq!(detector.check(beat, beat + Duration::from_millis(100), 15), Some(WatchdogEvent::Recovered));
+        assert_eq!(detector.check(beat, beat + Duration::from_secs(2), 15), None);
+
+        // A second stall is a second trip
+        assert!(matches!(detector.check(beat, beat + Duration::from_secs(7), 16), Some(WatchdogEvent::Stalled { .. })));
+        assert_eq!(detector.trips(), 2);
+    }
+
+    #[test]
+    fn test_quiet_loop_with_nothing_queued_is_no_stall() {
+        let mut detector = StallDetector::new(Duration::from_secs(5), Some(Duration::from_secs(30)));
+        let beat = Instant::now();
+
+        // An idle machine: the loop sleeps, the queue stays empty
+        assert_eq!(detector.check(beat, beat, 0), None);
+        assert_eq!(detector.check(beat, beat + Duration::from_secs(60), 0), None);
+        // A queue that was already there and has not grown is not piling up either
+        let beat = beat + Duration::from_secs(61);
+        assert_eq!(detector.check(beat, beat, 4), None);
+        assert_eq!(detector.check(beat, beat + Duration::from_secs(60), 4), None);
+        assert_eq!(detector.trips(), 0);
+    }
+
+    #[test]
+    fn test_stall_deadline() {
+        let mut detector = StallDetector::new(Duration::from_secs(5), Some(Duration::from_secs(30)));
+        let beat = Instant::now();
+        assert_eq!(detector.check(beat, beat, 0), None);
+        assert!(matches!(detector.check(beat, beat + Duration::from_secs(5), 10), Some(WatchdogEvent::Stalled { .. })));
+        assert!(matches!(detector.check(beat, beat + Duration::from_secs(29), 40), Some(WatchdogEvent::StillStalled { .. })));
+        assert_eq!(
+            detector.check(beat, beat + Duration::from_secs(30), 50),
+            Some(WatchdogEvent::Deadline { silence: Duration::from_secs(30), queued: 50 })
+        );
+    }
+
+    #[test]
+    fn test_deadline_after_timeout() {
+        assert!(validate_deadline(Some(5), Some(30)).is_ok());
+        assert!(validate_deadline(Some(5), None).is_ok());
+        assert_eq!(
+            validate_deadline(Some(30), Some(30)),
+            Err("--watchdog-deadline (30) must be longer than --watchdog-timeout (30)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_moves_forward() {
+        let heartbeat = Heartbeat::new();
+        let before = heartbeat.last_beat();
+        thread::sleep(Duration::from_millis(5));
+        heartbeat.clone().beat();
+        assert!(heartbeat.last_beat() > before);
+    }
+
+    #[test]
+    fn test_watchdog_requests_restart() {
+        // Tasks keep arriving while nothing beats
+        let heartbeat = Heartbeat::new();
+        let queued = AtomicU64::new(0);
+        let queue: QueueDepth = Box::new(move || queued.fetch_add(1, Ordering::Relaxed));
+        let watchdog = Watchdog::spawn(heartbeat, queue, Duration::from_millis(20), None, true).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !watchdog.restart_requested() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(watchdog.restart_requested());
+        assert_eq!(watchdog.trips(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Runs the horoscope-query binary against a stand-in scheduler socket.
+
+#[allow(dead_code)]  // Only the server half is needed here
+#[path = "../src/ipc.rs"]
+mod ipc;
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use ipc::{IpcServer, Request, Response};
+
+/// A fake scheduler answering on a socket until dropped
+struct FakeScheduler {
+    path: PathBuf,
+    seen: Arc<Mutex<Vec<Request>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl FakeScheduler {
+    fn start(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("horoscope_query_{name}_{}.sock", std::process::id()));
+        let server = IpcServer::bind(&path).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (log, done) = (seen.clone(), stop.clone());
+        let thread = thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                server
+                    .serve_pending(|request| {
+                        log.lock().unwrap().push(request.clone());
+                        match request {
+                            Request::Explain { comm } => Response::ok(
+                                format!("{comm}: CPU-Intensive | Priority: 150"),
+                                &serde_json::json!({ "comm": comm, "priority": 150 }),
+                            ),
+                            Request::AddRule { task_type, .. } if task_type == "astral" => {
+                                Response::error("unknown task type 'astral'")
+                            }
+                            Request::Stats => Response::ok("⭐ Dispatches: user=7", &serde_json::json!({ "user": 7 })),
+                            _ => Response::ok("ok", &serde_json::Value::Null),
+                        }
+                    })
+                    .unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        Self { path, seen, stop, thread: Some(thread) }
+    }
+
+    fn seen(&self) -> Vec<Request> {
+        self.seen.lock().unwrap().clone()
+    }
+}
+
+impl Drop for FakeScheduler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn query(socket: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_horoscope-query"))
+        .arg("--socket-path")
+        .arg(socket)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn human_and_json_output() {
+    let scheduler = FakeScheduler::start("output");
+
+    let output = query(&scheduler.path, &["explain", "rustc"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "rustc: CPU-Intensive | Priority: 150\n");
+
+    let output = query(&scheduler.path, &["--json", "explain", "rustc"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["priority"], 150);
+
+    let output = query(&scheduler.path, &["add-rule", "rustc", "astral"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown task type"));
+
+    assert!(query(&scheduler.path, &["forecast", "--hours", "6"]).status.success());
+    assert_eq!(
+        scheduler.seen(),
+        [
+            Request::Explain { comm: "rustc".to_string() },
+            Request::Explain { comm: "rustc".to_string() },
+            Request::AddRule { comm: "rustc".to_string(), task_type: "astral".to_string() },
+            Request::Forecast { hours: 6 },
+        ]
+    );
+}
+
+#[test]
+fn every_query_reaches_the_scheduler() {
+    let scheduler = FakeScheduler::start("commands");
+    for args in [&["positions"][..], &["weather"], &["stats"], &["forecast"], &["add-rule", "make", "cpu"]] {
+        assert!(query(&scheduler.path, args).status.success(), "{args:?}");
+    }
+    assert_eq!(
+        scheduler.seen(),
+        [
+            Request::Positions,
+            Request::Weather,
+            Request::Stats,
+            Request::Forecast { hours: 24 },
+            Request::AddRule { comm: "make".to_string(), task_type: "cpu".to_string() },
+        ]
+    );
+
+    // Out-of-range arguments never reach the socket
+    assert!(!query(&scheduler.path, &["forecast", "--hours", "0"]).status.success());
+    assert_eq!(scheduler.seen().len(), 5);
+}
+
+#[test]
+fn watch_repeats_until_the_scheduler_goes_away() {
+    let scheduler = FakeScheduler::start("watch");
+    let socket = scheduler.path.clone();
+    let child = Command::new(env!("CARGO_BIN_EXE_horoscope-query"))
+        .arg("--socket-path")
+        .arg(&socket)
+        .args(["--json", "watch", "--interval", "1", "stats"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    thread::sleep(Duration::from_millis(1500));
+    drop(scheduler);
+    let output = child.wait_with_output().unwrap();
+
+    // Two refreshes, then a clean error once the socket is gone
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("\"user\": 7").count(), 2, "{stdout}");
+}
+
+#[test]
+fn no_scheduler_running() {
+    let missing = std::env::temp_dir().join(format!("horoscope_query_missing_{}.sock", std::process::id()));
+    let output = query(&missing, &["stats"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot query the scheduler"));
+}
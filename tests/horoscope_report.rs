@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// Runs the horoscope-report binary, which needs no scheduler and no BPF.
+
+use std::process::{Command, Output};
+
+fn report(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_horoscope-report")).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_text_report() {
+    let text = stdout(&report(&["--date", "2025-11-19", "--forecast-hours", "3"]));
+    assert!(text.contains("HOROSCOPE REPORT for 2025-11-19T00:00:00+00:00"));
+    assert!(text.contains("COSMIC WEATHER REPORT"));
+    assert!(text.contains("Mercury in Sagittarius"));
+    assert!(text.contains("ASPECTS"));
+    assert!(text.contains("FORECAST (next 3h)"));
+    assert!(text.contains("2025-11-19T02:00:00+00:00"));
+    // Neither was asked for
+    assert!(!text.contains("PLANETARY HOURS"));
+    assert!(!text.contains("RETROGRADE CALENDAR"));
+
+    let quiet = stdout(&report(&["--date", "2025-11-19", "--forecast-hours", "0"]));
+    assert!(!quiet.contains("FORECAST"));
+}
+
+#[test]
+fn test_hours_and_calendar() {
+    let text = stdout(&report(&[
+        "--date", "2025-11-19", "--latitude", "-33.9", "--longitude", "151.2", "--retrograde-calendar",
+    ]));
+    assert!(text.contains("PLANETARY HOURS"));
+    // A Wednesday opens with Mercury's hour
+    let first_hour = text.lines().skip_while(|line| !line.contains("PLANETARY HOURS")).nth(1).unwrap();
+    assert!(first_hour.contains("Mercury"), "{first_hour}");
+    assert!(text.contains("RETROGRADE CALENDAR (next 365 days)"));
+    assert!(text.contains("Mercury  2025-11-10 → 2025-11-30"));
+
+    // A location needs both halves
+    assert!(!report(&["--date", "2025-11-19", "--latitude", "51.5"]).status.success());
+}
+
+#[test]
+fn test_json_report() {
+    let json = stdout(&report(&[
+        "--date", "2025-11-19T12:00:00Z", "--forecast-hours", "2", "--output-format", "json", "--retrograde-calendar",
+    ]));
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["date"], "2025-11-19T12:00:00+00:00");
+    let positions = value["positions"].as_array().unwrap();
+    assert!(positions.iter().any(|pos| pos["planet"] == "Mercury" && pos["retrograde"] == true));
+    assert!(value["aspects"].as_array().is_some_and(|aspects| !aspects.is_empty()));
+    assert!(value["weather"].as_str().unwrap().contains("COSMIC WEATHER"));
+    assert_eq!(value["forecast"].as_array().unwrap().len(), 2);
+    assert!(value["planetary_hours"].is_null());
+    assert!(value["retrograde_calendar"].as_array().unwrap().iter().any(|spell| spell["planet"] == "Jupiter"));
+}
+
+#[test]
+fn test_bad_arguments() {
+    let output = report(&["--date", "the dawning of the age of Aquarius"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("YYYY-MM-DD"));
+    assert!(!report(&["--forecast-hours", "500"]).status.success());
+    assert!(!report(&["--latitude", "91", "--longitude", "0"]).status.success());
+}